@@ -38,6 +38,9 @@ export type {
   RpcCallMessage,
   RpcResponseMessage,
   RpcErrorMessage,
+  RpcHelloMessage,
+  RpcHelloAckMessage,
+  AuthContext,
   PendingRequest,
   // Security & Observability config types
   SecurityConfig,
@@ -12,6 +12,7 @@ import type {
   WsConnection,
 } from "./types.js";
 import { isAsyncIterable } from "./types.js";
+import { runWithAuth } from "./request-context.js";
 
 // Re-export types for backward compatibility
 export type { ZapRequest as IpcRequest } from "./types.js";
@@ -68,11 +69,28 @@ function deserializeMessage(data: Buffer): IpcMessage {
   return decode(data) as IpcMessage;
 }
 
+/**
+ * Transform applied to every frame payload on a connection, e.g. negotiated
+ * compression
+ */
+export interface FrameCodec {
+  /** Wrap a serialized message before it is framed */
+  encode(payload: Buffer): Buffer;
+  /** Unwrap a received frame into a serialized message */
+  decode(frame: Buffer): Buffer;
+}
+
 /**
  * Write a length-prefixed message to a socket
  */
-function writeFramedMessage(socket: Socket, msg: IpcMessage, encoding: IpcEncoding): void {
-  const payload = serializeMessage(msg, encoding);
+function writeFramedMessage(
+  socket: Socket,
+  msg: IpcMessage,
+  encoding: IpcEncoding,
+  codec: FrameCodec | null = null
+): void {
+  const serialized = serializeMessage(msg, encoding);
+  const payload = codec ? codec.encode(serialized) : serialized;
   const length = payload.length;
 
   // 4-byte big-endian length prefix
@@ -336,11 +354,13 @@ export class IpcServer {
 
       try {
         console.log(`[IPC] Invoking handler: ${handler_id} for ${request.method} ${request.path}`);
-        const result = handler(request);
+        // RPC calls made by the handler carry the request's identity
+        const result = runWithAuth(request.auth, () => handler(request));
 
         // Check if this is a streaming response (async iterable)
         if (isAsyncIterable<StreamChunk>(result)) {
-          await this.handleStreamingResponse(result, handler_id, socket);
+          // Generators resume in their caller's context, so iterate inside it too
+          await runWithAuth(request.auth, () => this.handleStreamingResponse(result, handler_id, socket));
         } else {
           // Regular response - await the promise
           const response = await result;
@@ -652,6 +672,7 @@ export class IpcClient extends EventEmitter {
   private connected: boolean = false;
   private frameReader: FrameReader | null = null;
   private encoding: IpcEncoding;
+  private frameCodec: FrameCodec | null = null;
 
   constructor(socketPath: string, encoding: IpcEncoding = "msgpack") {
     super();
@@ -706,7 +727,8 @@ export class IpcClient extends EventEmitter {
       // Set up frame reader for length-prefixed messages
       this.frameReader = new FrameReader((frame) => {
         try {
-          const message = deserializeMessage(frame);
+          const payload = this.frameCodec ? this.frameCodec.decode(frame) : frame;
+          const message = deserializeMessage(payload);
           this.emit("message", message);
         } catch (error) {
           this.emit("error", new Error(`Failed to deserialize message: ${error}`));
@@ -731,10 +753,19 @@ export class IpcClient extends EventEmitter {
 
     this.socket.on("close", () => {
       this.connected = false;
+      // A new connection starts without any negotiated framing
+      this.frameCodec = null;
       this.emit("close");
     });
   }
 
+  /**
+   * Apply `codec` to every frame sent and received on this connection
+   */
+  setFrameCodec(codec: FrameCodec | null): void {
+    this.frameCodec = codec;
+  }
+
   /**
    * Send a message to the server
    */
@@ -742,7 +773,7 @@ export class IpcClient extends EventEmitter {
     if (!this.socket || !this.connected) {
       throw new Error("IPC client not connected");
     }
-    writeFramedMessage(this.socket, message, this.encoding);
+    writeFramedMessage(this.socket, message, this.encoding, this.frameCodec);
   }
 
   /**
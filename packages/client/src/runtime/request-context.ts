@@ -0,0 +1,22 @@
+/**
+ * Per-request state that follows a handler through its async calls
+ */
+
+import { AsyncLocalStorage } from "async_hooks";
+import type { AuthContext } from "./types.js";
+
+const requestAuth = new AsyncLocalStorage<AuthContext | undefined>();
+
+/**
+ * Run a handler with the identity of the request it serves
+ */
+export function runWithAuth<T>(auth: AuthContext | undefined, fn: () => T): T {
+  return requestAuth.run(auth, fn);
+}
+
+/**
+ * Identity of the request currently being handled, if any
+ */
+export function currentAuth(): AuthContext | undefined {
+  return requestAuth.getStore();
+}
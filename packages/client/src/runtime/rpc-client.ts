@@ -2,21 +2,43 @@
  * RPC Client for calling Rust server functions from TypeScript
  */
 
+import { deflateSync, gunzipSync, gzipSync, inflateSync } from 'zlib';
 import { IpcClient } from './ipc-client.js';
-import type { RpcMessage, RpcCallMessage, PendingRequest } from './types.js';
-import { isRpcResponseMessage, isRpcErrorMessage } from './types.js';
+import type { FrameCodec } from './ipc-client.js';
+import { currentAuth } from './request-context.js';
+import type { RpcMessage, RpcCallMessage, RpcHelloMessage, PendingRequest } from './types.js';
+import { isRpcResponseMessage, isRpcErrorMessage, isRpcHelloAckMessage } from './types.js';
 
 let ipcClient: IpcClient | null = null;
 let requestCounter = 0;
 const pendingRequests = new Map<string, PendingRequest>();
 
+/** Compression negotiated on the current connection, once offered */
+let handshake: Promise<void> | null = null;
+
+/** Compression algorithms offered to the server, in order of preference */
+const COMPRESSION_OFFER = ['gzip', 'deflate'];
+
+/** Payloads above this size are compressed (the server's default threshold) */
+const COMPRESSION_THRESHOLD = 1024;
+
+/** How long to wait for the server to answer the compression offer */
+const HELLO_TIMEOUT_MS = 5000;
+
+/** Frame flag bytes once compression is negotiated */
+const FRAME_PLAIN = 0;
+const FRAME_DEFLATE = 1;
+const FRAME_GZIP = 2;
+
 /**
  * Custom error class for RPC errors
  */
 export class RpcError extends Error {
   constructor(
     public readonly errorType: string,
-    message: string
+    message: string,
+    public readonly code?: string,
+    public readonly status?: number
   ) {
     super(message);
     this.name = 'RpcError';
@@ -58,7 +80,9 @@ export async function initRpcClient(socketPath: string): Promise<void> {
         clearTimeout(pending.timeout);
         const error = new RpcError(
           msg.error_type || 'UnknownError',
-          msg.error || 'Unknown error'
+          msg.error || 'Unknown error',
+          msg.code,
+          msg.status
         );
         pending.reject(error);
         pendingRequests.delete(msg.request_id);
@@ -66,6 +90,11 @@ export async function initRpcClient(socketPath: string): Promise<void> {
     }
   });
 
+  // The server forgets negotiated compression when the connection drops
+  ipcClient.on('close', () => {
+    handshake = null;
+  });
+
   ipcClient.on('error', (error: Error) => {
     // Reject all pending requests on connection error
     for (const [, pending] of pendingRequests) {
@@ -82,6 +111,7 @@ export async function initRpcClient(socketPath: string): Promise<void> {
   for (let i = 0; i < maxRetries; i++) {
     try {
       await ipcClient.ensureConnected();
+      await negotiateCompression(ipcClient);
       console.log(`[RPC] Client connected to ${socketPath}`);
       return;
     } catch (err) {
@@ -96,6 +126,9 @@ export async function initRpcClient(socketPath: string): Promise<void> {
 
 /**
  * Call a Rust server function via RPC
+ *
+ * Inside a route handler the call carries the request's caller identity, so
+ * the function's `Context` reports the same user.
  */
 export async function rpcCall<T = unknown>(
   functionName: string,
@@ -106,8 +139,9 @@ export async function rpcCall<T = unknown>(
     throw new Error('RPC client not initialized. Call initRpcClient() first.');
   }
 
-  // Ensure connection before sending
+  // Ensure connection before sending; calls must not overtake the compression offer
   await ipcClient.ensureConnected();
+  await negotiateCompression(ipcClient);
 
   const requestId = `req_${Date.now()}_${requestCounter++}`;
 
@@ -116,6 +150,7 @@ export async function rpcCall<T = unknown>(
     function_name: functionName,
     params,
     request_id: requestId,
+    auth: currentAuth(),
   };
 
   return new Promise<T>((resolve, reject) => {
@@ -140,6 +175,96 @@ export async function rpcCall<T = unknown>(
   });
 }
 
+/**
+ * Offer compression once per connection
+ *
+ * Resolves once the server has answered; a connection whose server accepts
+ * nothing, or doesn't answer in time, stays uncompressed.
+ */
+function negotiateCompression(client: IpcClient): Promise<void> {
+  if (handshake) {
+    return handshake;
+  }
+
+  handshake = new Promise<void>((resolve) => {
+    const finish = () => {
+      clearTimeout(timeout);
+      client.removeListener('message', onMessage);
+      resolve();
+    };
+    const timeout = setTimeout(finish, HELLO_TIMEOUT_MS);
+
+    const onMessage = (message: unknown) => {
+      const msg = message as RpcMessage;
+      if (!msg || typeof msg !== 'object' || !isRpcHelloAckMessage(msg)) {
+        return;
+      }
+      const { compression } = msg.result;
+      const codec = compression ? compressionCodec(compression) : null;
+      if (codec) {
+        client.setFrameCodec(codec);
+      }
+      finish();
+    };
+    client.on('message', onMessage);
+
+    const hello: RpcHelloMessage = {
+      type: 'rpc_hello',
+      request_id: 'hello',
+      params: { compression: COMPRESSION_OFFER },
+    };
+    try {
+      client.send(hello);
+    } catch {
+      finish();
+    }
+  });
+  return handshake;
+}
+
+/**
+ * Frame codec for a negotiated algorithm: each payload gets a flag byte and is
+ * compressed when large enough for it to pay off
+ */
+function compressionCodec(algorithm: string): FrameCodec | null {
+  let flag: number;
+  let compress: (payload: Buffer) => Buffer;
+  if (algorithm === 'gzip') {
+    flag = FRAME_GZIP;
+    compress = (payload) => gzipSync(payload);
+  } else if (algorithm === 'deflate') {
+    flag = FRAME_DEFLATE;
+    compress = (payload) => deflateSync(payload);
+  } else {
+    return null;
+  }
+
+  return {
+    encode(payload: Buffer): Buffer {
+      if (payload.length > COMPRESSION_THRESHOLD) {
+        const compressed = compress(payload);
+        if (compressed.length < payload.length) {
+          return Buffer.concat([Buffer.from([flag]), compressed]);
+        }
+      }
+      return Buffer.concat([Buffer.from([FRAME_PLAIN]), payload]);
+    },
+    decode(frame: Buffer): Buffer {
+      const payload = frame.subarray(1);
+      switch (frame[0]) {
+        case FRAME_PLAIN:
+          return payload;
+        case FRAME_DEFLATE:
+          return inflateSync(payload);
+        case FRAME_GZIP:
+          return gunzipSync(payload);
+        default:
+          throw new Error(`Unknown RPC frame flag: ${frame[0]}`);
+      }
+    },
+  };
+}
+
 /**
  * Wait for a response from a specific request
  */
@@ -176,6 +301,7 @@ export async function closeRpcClient(): Promise<void> {
 
     await ipcClient.close();
     ipcClient = null;
+    handshake = null;
   }
 }
 
@@ -40,6 +40,16 @@ export interface ZapRequest<
   body: string;
   /** Parsed cookies */
   cookies: Record<string, string>;
+  /** Caller identity from verified JWT claims, absent for anonymous requests */
+  auth?: AuthContext;
+}
+
+/**
+ * Caller identity, forwarded on RPC calls made while handling a request
+ */
+export interface AuthContext {
+  user_id: string;
+  roles: string[];
 }
 
 // ============================================================================
@@ -240,6 +250,8 @@ export type IpcMessage =
   | RpcCallMessage
   | RpcResponseMessage
   | RpcErrorMessage
+  | RpcHelloMessage
+  | RpcHelloAckMessage
   // Streaming messages (Phase 8)
   | StreamStartMessage
   | StreamChunkMessage
@@ -267,6 +279,8 @@ export interface RpcCallMessage {
   function_name: string;
   params: Record<string, unknown>;
   request_id: string;
+  /** Identity of the request being handled, if any */
+  auth?: AuthContext;
 }
 
 /**
@@ -286,12 +300,45 @@ export interface RpcErrorMessage {
   request_id: string;
   error: string;
   error_type: string;
+  /** Semantic code of a typed error, e.g. `NOT_FOUND` */
+  code?: string;
+  /** HTTP status matching `code` */
+  status?: number;
+}
+
+/**
+ * Compression offer sent when an RPC connection opens
+ */
+export interface RpcHelloMessage {
+  type: 'rpc_hello';
+  request_id: string;
+  params: {
+    /** Algorithms in order of preference */
+    compression: string[];
+  };
+}
+
+/**
+ * Answer to an `rpc_hello`
+ */
+export interface RpcHelloAckMessage {
+  type: 'rpc_hello_ack';
+  request_id: string;
+  result: {
+    /** Accepted algorithm; later frames carry a flag byte when set */
+    compression: string | null;
+  };
 }
 
 /**
  * All RPC message types
  */
-export type RpcMessage = RpcCallMessage | RpcResponseMessage | RpcErrorMessage;
+export type RpcMessage =
+  | RpcCallMessage
+  | RpcResponseMessage
+  | RpcErrorMessage
+  | RpcHelloMessage
+  | RpcHelloAckMessage;
 
 // ============================================================================
 // Configuration Types
@@ -522,6 +569,13 @@ export function isRpcErrorMessage(msg: RpcMessage): msg is RpcErrorMessage {
   return msg.type === 'rpc_error';
 }
 
+/**
+ * Type guard for RpcHelloAckMessage
+ */
+export function isRpcHelloAckMessage(msg: RpcMessage): msg is RpcHelloAckMessage {
+  return msg.type === 'rpc_hello_ack';
+}
+
 // ============================================================================
 // Pending Request Type (for RPC client)
 // ============================================================================
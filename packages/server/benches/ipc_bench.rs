@@ -38,6 +38,7 @@ fn bench_ipc_serialization(c: &mut Criterion) {
             },
             body: String::new(),
             cookies: HashMap::new(),
+            auth: None,
         },
     };
 
@@ -200,6 +201,7 @@ fn bench_ipc_request_creation(c: &mut Criterion) {
                 headers: black_box(HashMap::new()),
                 body: black_box(String::new()),
                 cookies: black_box(HashMap::new()),
+                auth: None,
             };
             black_box(req)
         })
@@ -222,6 +224,7 @@ fn bench_ipc_request_creation(c: &mut Criterion) {
                 }),
                 body: black_box(r#"{"name":"John Doe","email":"john@example.com"}"#.to_string()),
                 cookies: black_box(HashMap::new()),
+                auth: None,
             };
             black_box(req)
         })
@@ -246,6 +249,7 @@ fn bench_ipc_request_creation(c: &mut Criterion) {
                 }),
                 body: black_box(String::new()),
                 cookies: black_box(HashMap::new()),
+                auth: None,
             };
             black_box(req)
         })
@@ -287,6 +291,7 @@ fn bench_ipc_round_trip(c: &mut Criterion) {
                 headers: HashMap::new(),
                 body: String::new(),
                 cookies: HashMap::new(),
+                auth: None,
             },
         }),
     ];
@@ -410,6 +415,7 @@ fn bench_ipc_message_types(c: &mut Criterion) {
             headers: HashMap::new(),
             body: String::new(),
             cookies: HashMap::new(),
+            auth: None,
         },
     };
     group.bench_function("invoke_handler", |b| {
@@ -5,7 +5,7 @@ use syn::{Attribute, Fields, FnArg, ItemFn, ItemStruct, Pat, ReturnType, Type, V
 use walkdir::WalkDir;
 
 /// Metadata about an exported function
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExportedFunction {
     pub name: String,
     pub namespace: Option<String>,
@@ -22,7 +22,7 @@ pub struct FunctionNamespace {
     pub functions: Vec<ExportedFunction>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExportedParam {
     pub name: String,
     pub ty: ExportedType,
@@ -55,6 +55,7 @@ pub enum ExportedType {
         name: String,
         generics: Vec<ExportedType>,
     },
+    Tuple(Vec<ExportedType>),
     Unit,
     Result {
         ok: Box<ExportedType>,
@@ -63,15 +64,21 @@ pub enum ExportedType {
 }
 
 /// Metadata about an exported struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExportedStruct {
     pub name: String,
+    /// Type parameters, e.g. `["T"]` for `Paginated<T>`
+    #[serde(default)]
+    pub generics: Vec<String>,
     pub fields: Vec<StructField>,
+    /// Element types of a tuple struct (`None` for structs with named fields)
+    #[serde(default)]
+    pub tuple_fields: Option<Vec<ExportedType>>,
     pub doc_comments: Vec<String>,
 }
 
 /// A field in an exported struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StructField {
     pub name: String,
     pub ty: ExportedType,
@@ -79,6 +86,30 @@ pub struct StructField {
     pub optional: bool,
 }
 
+/// Tagged union used for functions returning `Result<T, E>`
+const RPC_RESULT_TYPE: &str =
+    "export type RpcResult<T, E> = { ok: true; value: T } | { ok: false; error: E };\n\n";
+
+/// Runtime helper that turns typed RPC errors into the failure arm of `RpcResult`
+///
+/// The server reports `Err(e)` as an error message of the form
+/// `__TYPED_ERROR__:<json>`; anything else (transport failures, timeouts) is rethrown.
+const RPC_RESULT_HELPER: &str = r#"const TYPED_ERROR_PREFIX = '__TYPED_ERROR__:';
+
+async function rpcResult<T, E>(call: Promise<T>): Promise<RpcResult<T, E>> {
+  try {
+    return { ok: true, value: await call };
+  } catch (err) {
+    const message = err instanceof Error ? err.message : String(err);
+    if (message.startsWith(TYPED_ERROR_PREFIX)) {
+      return { ok: false, error: JSON.parse(message.slice(TYPED_ERROR_PREFIX.length)) as E };
+    }
+    throw err;
+  }
+}
+
+"#;
+
 impl ExportedType {
     /// Convert Rust type to TypeScript type string
     pub fn to_typescript(&self) -> String {
@@ -110,6 +141,14 @@ impl ExportedType {
                     value.to_typescript()
                 )
             }
+            ExportedType::Tuple(elems) => {
+                let elem_str = elems
+                    .iter()
+                    .map(|e| e.to_typescript())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("[{}]", elem_str)
+            }
             ExportedType::Unit => "void".to_string(),
             ExportedType::Result { ok, err } => {
                 // Generate union type: T | E
@@ -130,6 +169,19 @@ impl ExportedType {
         }
     }
 
+    /// TypeScript type a function call resolves to
+    ///
+    /// A top-level `Result<T, E>` becomes `RpcResult<T, E>` so callers can
+    /// discriminate success from failure on `ok`.
+    pub fn to_typescript_return(&self) -> String {
+        match self {
+            ExportedType::Result { ok, err } => {
+                format!("RpcResult<{}, {}>", ok.to_typescript(), err.to_typescript())
+            }
+            _ => self.to_typescript(),
+        }
+    }
+
     /// Convert parameter name to camelCase
     pub fn to_camel_case(snake_str: &str) -> String {
         let mut result = String::new();
@@ -165,6 +217,11 @@ fn collect_custom_types(ty: &ExportedType, types: &mut std::collections::HashSet
             collect_custom_types(key, types);
             collect_custom_types(value, types);
         }
+        ExportedType::Tuple(elems) => {
+            for e in elems {
+                collect_custom_types(e, types);
+            }
+        }
         ExportedType::Result { ok, err } => {
             collect_custom_types(ok, types);
             collect_custom_types(err, types);
@@ -173,22 +230,75 @@ fn collect_custom_types(ty: &ExportedType, types: &mut std::collections::HashSet
     }
 }
 
-/// Generate TypeScript type definitions
-pub fn generate_typescript_definitions(functions: &[ExportedFunction]) -> String {
-    let mut output = String::from("// Auto-generated TypeScript definitions\n");
-    output.push_str("// DO NOT EDIT MANUALLY\n\n");
-
-    // Collect all custom types used by functions
-    let mut custom_types: std::collections::HashSet<String> = std::collections::HashSet::new();
+/// Custom type names the generated bindings import from `./types`, sorted
+///
+/// Generic wrappers and their arguments are both included, so
+/// `Paginated<User>` imports `Paginated` and `User`. `unknown` (from
+/// `serde_json::Value`) is a TypeScript builtin and is never imported.
+fn imported_types(functions: &[ExportedFunction]) -> Vec<String> {
+    let mut custom_types = std::collections::HashSet::new();
     for func in functions {
         collect_custom_types(&func.return_type, &mut custom_types);
         for param in &func.params {
             collect_custom_types(&param.ty, &mut custom_types);
         }
     }
+    custom_types.remove("unknown");
 
     let mut sorted_types: Vec<_> = custom_types.into_iter().collect();
     sorted_types.sort();
+    sorted_types
+}
+
+/// Custom types referenced by `functions` that no scanned struct defines, sorted
+///
+/// The generated bindings import these from `./types`, so each one would fail
+/// to compile on the TypeScript side.
+pub fn unresolved_types(functions: &[ExportedFunction], structs: &[ExportedStruct]) -> Vec<String> {
+    let defined: std::collections::HashSet<&str> = structs.iter().map(|s| s.name.as_str()).collect();
+    imported_types(functions)
+        .into_iter()
+        .filter(|name| !defined.contains(name.as_str()))
+        .collect()
+}
+
+/// Check whether any function returns a `Result` and needs the `RpcResult` helpers
+fn uses_rpc_result(functions: &[ExportedFunction]) -> bool {
+    functions
+        .iter()
+        .any(|f| matches!(f.return_type, ExportedType::Result { .. }))
+}
+
+/// Generate the RPC call expression for a function, wrapping `Result` returns
+fn rpc_call_expr(return_type: &ExportedType, rpc_name: &str, params: &str) -> String {
+    match return_type {
+        ExportedType::Result { ok, err } => {
+            let ok_ts = ok.to_typescript();
+            format!(
+                "rpcResult<{}, {}>(rpcCall<{}>('{}', {}))",
+                ok_ts,
+                err.to_typescript(),
+                ok_ts,
+                rpc_name,
+                params
+            )
+        }
+        _ => format!(
+            "rpcCall<{}>('{}', {})",
+            return_type.to_typescript(),
+            rpc_name,
+            params
+        ),
+    }
+}
+
+/// Generate TypeScript type definitions
+pub fn generate_typescript_definitions(functions: &[ExportedFunction]) -> String {
+    let mut output = String::from("// Auto-generated TypeScript definitions\n");
+    output.push_str("// DO NOT EDIT MANUALLY\n\n");
+
+    // Collect all custom types used by functions
+    let sorted_types = imported_types(functions);
 
     // Import types for local usage
     output.push_str("import type {\n");
@@ -201,6 +311,10 @@ pub fn generate_typescript_definitions(functions: &[ExportedFunction]) -> String
     output.push_str("// Re-export types for consumers\n");
     output.push_str("export * from './types';\n\n");
 
+    if uses_rpc_result(functions) {
+        output.push_str(RPC_RESULT_TYPE);
+    }
+
     // Generate JSDoc and function signatures
     for func in functions {
         // Generate JSDoc comment
@@ -226,7 +340,7 @@ pub fn generate_typescript_definitions(functions: &[ExportedFunction]) -> String
             .collect::<Vec<_>>()
             .join(", ");
 
-        let return_type = func.return_type.to_typescript();
+        let return_type = func.return_type.to_typescript_return();
         let async_keyword = if func.is_async { "async " } else { "" };
 
         output.push_str(&format!(
@@ -251,7 +365,7 @@ pub fn generate_typescript_definitions(functions: &[ExportedFunction]) -> String
             .collect::<Vec<_>>()
             .join(", ");
 
-        let return_type = func.return_type.to_typescript();
+        let return_type = func.return_type.to_typescript_return();
 
         output.push_str(&format!(
             "  {}({}): Promise<{}>;\n",
@@ -275,16 +389,7 @@ pub fn generate_typescript_runtime(functions: &[ExportedFunction]) -> String {
     output.push_str("import { rpcCall } from './rpc-client';\n");
 
     // Collect all custom types used by functions
-    let mut custom_types: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for func in functions {
-        collect_custom_types(&func.return_type, &mut custom_types);
-        for param in &func.params {
-            collect_custom_types(&param.ty, &mut custom_types);
-        }
-    }
-
-    let mut sorted_types: Vec<_> = custom_types.into_iter().collect();
-    sorted_types.sort();
+    let sorted_types = imported_types(functions);
 
     // Import types for local usage AND re-export for consumers
     output.push_str("import type {\n");
@@ -297,6 +402,11 @@ pub fn generate_typescript_runtime(functions: &[ExportedFunction]) -> String {
     output.push_str("// Re-export types for consumers\n");
     output.push_str("export * from './types';\n\n");
 
+    if uses_rpc_result(functions) {
+        output.push_str(RPC_RESULT_TYPE);
+        output.push_str(RPC_RESULT_HELPER);
+    }
+
     // Generate backend object
     output.push_str("export const backend = {\n");
 
@@ -326,15 +436,20 @@ pub fn generate_typescript_runtime(functions: &[ExportedFunction]) -> String {
             .collect::<Vec<_>>()
             .join(", ");
 
-        let return_type = func.return_type.to_typescript();
+        let return_type = func.return_type.to_typescript_return();
+        let call = rpc_call_expr(
+            &func.return_type,
+            rust_name,
+            &format!("{{ {} }}", param_mapping),
+        );
 
         output.push_str(&format!(
             r#"  async {}({}): Promise<{}> {{
-    return rpcCall<{}>('{}', {{ {} }});
+    return {};
   }},
 
 "#,
-            fn_name, typed_params, return_type, return_type, rust_name, param_mapping
+            fn_name, typed_params, return_type, call
         ));
     }
 
@@ -373,16 +488,7 @@ pub fn generate_namespaced_server(functions: &[ExportedFunction]) -> String {
     output.push_str("import { rpcCall } from './rpc-client';\n");
 
     // Collect all custom types used by functions
-    let mut custom_types: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for func in functions {
-        collect_custom_types(&func.return_type, &mut custom_types);
-        for param in &func.params {
-            collect_custom_types(&param.ty, &mut custom_types);
-        }
-    }
-
-    let mut sorted_types: Vec<_> = custom_types.into_iter().collect();
-    sorted_types.sort();
+    let sorted_types = imported_types(functions);
 
     // Import types for local usage
     output.push_str("import type {\n");
@@ -395,6 +501,11 @@ pub fn generate_namespaced_server(functions: &[ExportedFunction]) -> String {
     output.push_str("// Re-export types for consumers\n");
     output.push_str("export * from './types';\n\n");
 
+    if uses_rpc_result(functions) {
+        output.push_str(RPC_RESULT_TYPE);
+        output.push_str(RPC_RESULT_HELPER);
+    }
+
     let namespaces = group_by_namespace(functions);
 
     // Generate server object with namespaces
@@ -432,7 +543,7 @@ pub fn generate_namespaced_server(functions: &[ExportedFunction]) -> String {
                 format!("params: {{ {} }}", params)
             };
 
-            let return_type = func.return_type.to_typescript();
+            let return_type = func.return_type.to_typescript_return();
 
             // Build RPC call params
             let rpc_params = if func.params.is_empty() {
@@ -455,8 +566,8 @@ pub fn generate_namespaced_server(functions: &[ExportedFunction]) -> String {
                 fn_name, typed_params, return_type
             ));
             output.push_str(&format!(
-                "      return rpcCall<{}>('{}', {});\n",
-                return_type, rpc_name, rpc_params
+                "      return {};\n",
+                rpc_call_expr(&func.return_type, &rpc_name, &rpc_params)
             ));
             output.push_str("    },\n");
         }
@@ -487,7 +598,23 @@ pub fn generate_typescript_interfaces(structs: &[ExportedStruct]) -> String {
             output.push_str(" */\n");
         }
 
-        output.push_str(&format!("export interface {} {{\n", s.name));
+        let name = if s.generics.is_empty() {
+            s.name.clone()
+        } else {
+            format!("{}<{}>", s.name, s.generics.join(", "))
+        };
+
+        // Newtypes serialize as their inner value, other tuple structs as arrays
+        if let Some(elems) = &s.tuple_fields {
+            let ty = match elems.as_slice() {
+                [inner] => inner.to_typescript(),
+                _ => ExportedType::Tuple(elems.clone()).to_typescript(),
+            };
+            output.push_str(&format!("export type {} = {};\n\n", name, ty));
+            continue;
+        }
+
+        output.push_str(&format!("export interface {} {{\n", name));
 
         for field in &s.fields {
             let ts_name = field.ts_name.as_ref().unwrap_or(&field.name);
@@ -557,8 +684,10 @@ fn parse_struct(item: &ItemStruct) -> Option<ExportedStruct> {
     }
 
     let name = item.ident.to_string();
+    let generics = item.generics.type_params().map(|p| p.ident.to_string()).collect();
     let doc_comments = extract_doc_comments(&item.attrs);
 
+    let mut tuple_fields = None;
     let fields = match &item.fields {
         Fields::Named(named) => named
             .named
@@ -579,57 +708,25 @@ fn parse_struct(item: &ItemStruct) -> Option<ExportedStruct> {
                 })
             })
             .collect(),
-        _ => return None, // Only support named structs
+        Fields::Unnamed(unnamed) => {
+            tuple_fields = Some(unnamed.unnamed.iter().map(|field| parse_type(&field.ty)).collect());
+            Vec::new()
+        }
+        Fields::Unit => return None,
     };
 
     Some(ExportedStruct {
         name,
+        generics,
         fields,
+        tuple_fields,
         doc_comments,
     })
 }
 
 /// Find all serializable structs in Rust source files
 pub fn find_exported_structs(project_dir: &Path) -> anyhow::Result<Vec<ExportedStruct>> {
-    let mut structs = Vec::new();
-
-    // Look for server/src directory first (standard ZapJS project structure)
-    let server_src = project_dir.join("server").join("src");
-    let search_dir = if server_src.exists() {
-        server_src
-    } else {
-        project_dir.to_path_buf()
-    };
-
-    for entry in WalkDir::new(&search_dir)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
-    {
-        let content = std::fs::read_to_string(entry.path())?;
-
-        // Parse the file
-        let syntax = match syn::parse_file(&content) {
-            Ok(syntax) => syntax,
-            Err(_) => continue,
-        };
-
-        // Find all structs with #[derive(Serialize)]
-        for item in syntax.items {
-            if let syn::Item::Struct(s) = item {
-                if let Some(exported) = parse_struct(&s) {
-                    eprintln!(
-                        "Found serializable struct: {} in {}",
-                        exported.name,
-                        entry.path().display()
-                    );
-                    structs.push(exported);
-                }
-            }
-        }
-    }
-
-    Ok(structs)
+    Ok(scan_project(project_dir).flat_map(|exports| exports.structs).collect())
 }
 
 /// Check if a function has the #[export] attribute
@@ -761,7 +858,13 @@ fn parse_type(ty: &Type) -> ExportedType {
             }
         }
         Type::Reference(type_ref) => parse_type(&type_ref.elem),
+        Type::Paren(paren) => parse_type(&paren.elem),
         Type::Tuple(tuple) if tuple.elems.is_empty() => ExportedType::Unit,
+        // Tuples serialize as JSON arrays: (A, B) -> [A, B]
+        Type::Tuple(tuple) => ExportedType::Tuple(tuple.elems.iter().map(parse_type).collect()),
+        // Fixed-size arrays and slices both serialize as plain arrays
+        Type::Array(array) => ExportedType::Vec(Box::new(parse_type(&array.elem))),
+        Type::Slice(slice) => ExportedType::Vec(Box::new(parse_type(&slice.elem))),
         _ => ExportedType::Custom {
             name: "unknown".to_string(),
             generics: vec![],
@@ -831,9 +934,39 @@ fn parse_function(func: &ItemFn) -> Option<ExportedFunction> {
 
 /// Find all exported functions in Rust source files
 pub fn find_exported_functions(project_dir: &Path) -> anyhow::Result<Vec<ExportedFunction>> {
-    let mut functions = Vec::new();
+    Ok(scan_project(project_dir).flat_map(|exports| exports.functions).collect())
+}
+
+/// Exported functions and serializable structs declared in one source file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceExports {
+    pub functions: Vec<ExportedFunction>,
+    pub structs: Vec<ExportedStruct>,
+}
+
+/// Scan a single Rust source file for `#[export]` functions and serializable structs
+pub fn scan_source_file(path: &Path) -> anyhow::Result<SourceExports> {
+    let content = std::fs::read_to_string(path)?;
+    let syntax = syn::parse_file(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut exports = SourceExports::default();
+    for item in syntax.items {
+        match item {
+            syn::Item::Fn(func) => exports.functions.extend(parse_function(&func)),
+            syn::Item::Struct(s) => exports.structs.extend(parse_struct(&s)),
+            _ => {}
+        }
+    }
+    Ok(exports)
+}
 
-    // Look for server/src directory first (standard ZapJS project structure)
+/// Scan every `.rs` file of a project with [`scan_source_file`], skipping
+/// files that fail to parse
+///
+/// Looks in `server/src` (the standard ZapJS project layout) when it exists,
+/// otherwise in `project_dir` itself.
+fn scan_project(project_dir: &Path) -> impl Iterator<Item = SourceExports> {
     let server_src = project_dir.join("server").join("src");
     let search_dir = if server_src.exists() {
         server_src
@@ -841,42 +974,17 @@ pub fn find_exported_functions(project_dir: &Path) -> anyhow::Result<Vec<Exporte
         project_dir.to_path_buf()
     };
 
-    for entry in WalkDir::new(&search_dir)
+    WalkDir::new(search_dir)
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
-    {
-        let content = std::fs::read_to_string(entry.path())?;
-
-        // Parse the file
-        let syntax = match syn::parse_file(&content) {
-            Ok(syntax) => syntax,
+        .filter_map(|entry| match scan_source_file(entry.path()) {
+            Ok(exports) => Some(exports),
             Err(e) => {
-                eprintln!(
-                    "Warning: Failed to parse {}: {}",
-                    entry.path().display(),
-                    e
-                );
-                continue;
-            }
-        };
-
-        // Find all functions with #[export] attribute
-        for item in syntax.items {
-            if let syn::Item::Fn(func) = item {
-                if let Some(exported) = parse_function(&func) {
-                    eprintln!(
-                        "Found exported function: {} in {}",
-                        exported.name,
-                        entry.path().display()
-                    );
-                    functions.push(exported);
-                }
+                eprintln!("Warning: {:#}", e);
+                None
             }
-        }
-    }
-
-    Ok(functions)
+        })
 }
 
 /// Convert Splice ExportMetadata to ExportedFunction
@@ -998,6 +1106,17 @@ fn parse_type_from_schema(schema: &serde_json::Value) -> anyhow::Result<Exported
     }
 }
 
+/// Write `contents` to `path` unless the file already holds exactly that
+///
+/// Returns whether the file was written. Leaving unchanged output untouched
+/// keeps its mtime, so TypeScript watchers don't rebuild for nothing.
+pub fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<bool> {
+    match std::fs::read(path) {
+        Ok(existing) if existing == contents.as_bytes() => Ok(false),
+        _ => std::fs::write(path, contents).map(|_| true),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1069,4 +1188,174 @@ mod tests {
         // Check RPC call uses namespaced name
         assert!(server.contains("'users.get'"));
     }
+
+    #[test]
+    fn test_parse_tuple_and_array_types() {
+        let ty: Type = syn::parse_quote!((String, u32));
+        assert_eq!(parse_type(&ty).to_typescript(), "[string, number]");
+
+        let ty: Type = syn::parse_quote!([u8; 16]);
+        assert_eq!(parse_type(&ty).to_typescript(), "number[]");
+
+        let ty: Type = syn::parse_quote!(&[String]);
+        assert_eq!(parse_type(&ty).to_typescript(), "string[]");
+
+        let ty: Type = syn::parse_quote!(Vec<(User, (bool, [f64; 2]))>);
+        assert_eq!(
+            parse_type(&ty).to_typescript(),
+            "[User, [boolean, number[]]][]"
+        );
+    }
+
+    #[test]
+    fn test_tuple_return_in_generated_output() {
+        let func: ItemFn = syn::parse_quote! {
+            #[export]
+            pub fn pair(id: u64) -> (String, u32) {
+                todo!()
+            }
+        };
+
+        let exported = parse_function(&func).unwrap();
+        let defs = generate_typescript_definitions(&[exported]);
+        assert!(defs.contains("pair(id: number): Promise<[string, number]>"));
+    }
+
+    #[test]
+    fn test_result_return_is_tagged_union() {
+        let func = ExportedFunction {
+            name: "get_user".to_string(),
+            namespace: Some("users".to_string()),
+            is_async: true,
+            params: vec![ExportedParam {
+                name: "id".to_string(),
+                ty: ExportedType::U64,
+            }],
+            return_type: ExportedType::Result {
+                ok: Box::new(ExportedType::Custom {
+                    name: "User".to_string(),
+                    generics: vec![],
+                }),
+                err: Box::new(ExportedType::Custom {
+                    name: "ApiError".to_string(),
+                    generics: vec![],
+                }),
+            },
+            doc_comments: vec![],
+        };
+
+        for output in [
+            generate_typescript_runtime(std::slice::from_ref(&func)),
+            generate_namespaced_server(std::slice::from_ref(&func)),
+        ] {
+            assert!(output.contains(
+                "export type RpcResult<T, E> = { ok: true; value: T } | { ok: false; error: E };"
+            ));
+            assert!(output.contains("Promise<RpcResult<User, ApiError>>"));
+            assert!(output.contains("return rpcResult<User, ApiError>(rpcCall<User>("));
+            assert!(!output.contains("User | ApiError"));
+        }
+
+        let defs = generate_typescript_definitions(&[func]);
+        assert!(defs.contains("getUser(id: number): Promise<RpcResult<User, ApiError>>;"));
+        // Declarations only need the type alias, not the runtime helper
+        assert!(!defs.contains("function rpcResult"));
+    }
+
+    #[test]
+    fn test_nested_generic_types_are_imported() {
+        let func: ItemFn = syn::parse_quote! {
+            #[export]
+            pub fn list_users(page: u32, filter: Option<serde_json::Value>) -> Paginated<User> {
+                todo!()
+            }
+        };
+        let exported = parse_function(&func).unwrap();
+
+        for output in [
+            generate_typescript_definitions(std::slice::from_ref(&exported)),
+            generate_typescript_runtime(std::slice::from_ref(&exported)),
+            generate_namespaced_server(std::slice::from_ref(&exported)),
+        ] {
+            let imports = &output[output.find("import type {").unwrap()..output.find("} from './types';").unwrap()];
+            assert!(imports.contains("  Paginated,\n"));
+            assert!(imports.contains("  User,\n"));
+            assert!(!imports.contains("unknown"));
+            assert!(output.contains("Promise<Paginated<User>>"));
+        }
+
+        let item: ItemStruct = syn::parse_quote! {
+            #[derive(Serialize)]
+            pub struct Paginated<T> {
+                pub items: Vec<T>,
+                pub total: u64,
+            }
+        };
+        let interfaces = generate_typescript_interfaces(&[parse_struct(&item).unwrap()]);
+        assert!(interfaces.contains("export interface Paginated<T> {\n  items: T[];\n  total: number;\n}"));
+    }
+
+    #[test]
+    fn test_tuple_structs_become_type_aliases() {
+        let items: [ItemStruct; 3] = [
+            syn::parse_quote! {
+                #[derive(Serialize)]
+                pub struct UserId(String);
+            },
+            syn::parse_quote! {
+                #[derive(Serialize, Deserialize)]
+                pub struct Point(pub f64, pub f64);
+            },
+            syn::parse_quote! {
+                #[derive(Serialize)]
+                pub struct Wrapper<T>(Vec<T>);
+            },
+        ];
+        let structs: Vec<_> = items.iter().map(|item| parse_struct(item).unwrap()).collect();
+        let output = generate_typescript_interfaces(&structs);
+
+        assert!(output.contains("export type UserId = string;\n"));
+        assert!(output.contains("export type Point = [number, number];\n"));
+        assert!(output.contains("export type Wrapper<T> = T[];\n"));
+        assert!(!output.contains("export interface"));
+    }
+
+    #[test]
+    fn test_write_if_changed_skips_identical_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("types.ts");
+
+        assert!(write_if_changed(&path, "export type A = string;\n").unwrap());
+        assert!(!write_if_changed(&path, "export type A = string;\n").unwrap());
+        assert!(write_if_changed(&path, "export type A = number;\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "export type A = number;\n");
+    }
+
+    #[test]
+    fn test_unresolved_types() {
+        let func = ExportedFunction {
+            name: "get_widget".to_string(),
+            namespace: None,
+            is_async: false,
+            params: vec![ExportedParam {
+                name: "owner".to_string(),
+                ty: ExportedType::Custom {
+                    name: "User".to_string(),
+                    generics: vec![],
+                },
+            }],
+            return_type: ExportedType::Custom {
+                name: "Widget".to_string(),
+                generics: vec![],
+            },
+            doc_comments: vec![],
+        };
+        let user: ItemStruct = syn::parse_quote! {
+            #[derive(Serialize, Deserialize)]
+            pub struct User { pub id: u64 }
+        };
+        let structs = vec![parse_struct(&user).unwrap()];
+
+        assert_eq!(unresolved_types(&[func], &structs), vec!["Widget".to_string()]);
+    }
 }
@@ -1,15 +1,19 @@
 use clap::Parser;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
 use zap_codegen::{
-    find_exported_functions, find_exported_structs, generate_namespaced_server,
-    generate_typescript_definitions, generate_typescript_interfaces, generate_typescript_runtime,
-    ExportedFunction,
+    generate_namespaced_server, generate_typescript_definitions, generate_typescript_interfaces,
+    generate_typescript_runtime, scan_source_file, unresolved_types, write_if_changed,
+    ExportedFunction, ExportedStruct, SourceExports,
 };
 use anyhow::{Context as _, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
 use tokio::net::UnixStream;
 use tokio_util::codec::Framed;
-use futures::StreamExt;
+use futures::{SinkExt, StreamExt};
 use splice::{Message, Role, SpliceCodec, PROTOCOL_VERSION, DEFAULT_MAX_FRAME_SIZE};
 
 #[derive(Parser, Debug)]
@@ -45,8 +49,19 @@ struct Args {
     /// Generate namespaced server client (server.users.get() style)
     #[arg(long, default_value_t = true)]
     server: bool,
+
+    /// Fail instead of warning when functions reference undefined types
+    #[arg(long)]
+    strict_types: bool,
+
+    /// Watch server/src and regenerate on .rs changes
+    #[arg(long)]
+    watch: bool,
 }
 
+/// Quiet period after a change before regenerating, so a burst of saves runs once
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
@@ -54,59 +69,255 @@ async fn main() -> anyhow::Result<()> {
     // Create output directory if it doesn't exist
     fs::create_dir_all(&args.output_dir)?;
 
-    // Load exported functions from Splice socket, input file, or scan Rust source
-    let functions = if let Some(socket_path) = args.splice_socket {
+    let source_dir = source_dir(&args.project_dir);
+    println!("Scanning {} for exports...", source_dir.display());
+    let mut index = SourceIndex::scan(&source_dir);
+
+    generate(&args, &index, Outputs::ALL).await?;
+
+    if args.watch {
+        watch(&args, &source_dir, &mut index).await?;
+    }
+    Ok(())
+}
+
+/// Directory holding the Rust sources: `server/src`, or the project itself
+///
+/// Canonicalized so scanned paths match the ones the file watcher reports.
+fn source_dir(project_dir: &Path) -> PathBuf {
+    let server_src = project_dir.join("server").join("src");
+    let dir = if server_src.is_dir() { server_src } else { project_dir.to_path_buf() };
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// Groups of generated files, by what they are generated from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Outputs {
+    /// `types.ts`, generated from structs
+    types: bool,
+    /// `backend.d.ts`, `backend.ts` and `server.ts`, generated from functions
+    bindings: bool,
+}
+
+impl Outputs {
+    const ALL: Self = Self { types: true, bindings: true };
+
+    fn any(self) -> bool {
+        self.types || self.bindings
+    }
+}
+
+/// Exports of every scanned source file, so a change rescans only that file
+/// and regenerates only the outputs its exports feed
+#[derive(Debug, Default)]
+struct SourceIndex {
+    files: BTreeMap<PathBuf, SourceExports>,
+}
+
+impl SourceIndex {
+    /// Scan every `.rs` file under `dir`, skipping files that fail to parse
+    fn scan(dir: &Path) -> Self {
+        let mut index = Self::default();
+        for entry in WalkDir::new(dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        {
+            match scan_source_file(entry.path()) {
+                Ok(exports) => {
+                    for function in &exports.functions {
+                        eprintln!("Found exported function: {} in {}", function.name, entry.path().display());
+                    }
+                    for s in &exports.structs {
+                        eprintln!("Found serializable struct: {} in {}", s.name, entry.path().display());
+                    }
+                    index.files.insert(entry.into_path(), exports);
+                }
+                Err(e) => eprintln!("Warning: {:#}", e),
+            }
+        }
+        index
+    }
+
+    /// Rescan one changed file and report which outputs its exports feed
+    ///
+    /// A deleted file drops its exports. A file that no longer parses (often
+    /// mid-edit) keeps its previous exports until it does.
+    fn update(&mut self, path: &Path) -> Outputs {
+        let exports = match scan_source_file(path) {
+            Ok(exports) => exports,
+            Err(_) if !path.exists() => SourceExports::default(),
+            Err(e) => {
+                eprintln!("Warning: {:#}", e);
+                return Outputs::default();
+            }
+        };
+        let previous = self.files.remove(path).unwrap_or_default();
+        let changed = Outputs {
+            types: exports.structs != previous.structs,
+            bindings: exports.functions != previous.functions,
+        };
+        if exports != SourceExports::default() {
+            self.files.insert(path.to_path_buf(), exports);
+        }
+        changed
+    }
+
+    fn functions(&self) -> Vec<ExportedFunction> {
+        self.files.values().flat_map(|f| f.functions.iter().cloned()).collect()
+    }
+
+    fn structs(&self) -> Vec<ExportedStruct> {
+        self.files.values().flat_map(|f| f.structs.iter().cloned()).collect()
+    }
+}
+
+/// Run a codegen pass, regenerating `outputs` and rewriting only files whose
+/// content changed
+async fn generate(args: &Args, index: &SourceIndex, outputs: Outputs) -> Result<()> {
+    // Load exported functions from Splice socket, input file, or the scanned sources
+    let functions = if let Some(socket_path) = &args.splice_socket {
         println!("Connecting to Splice at {}...", socket_path.display());
-        load_exports_from_splice(&socket_path).await?
-    } else if let Some(input_path) = args.input {
-        let json_content = fs::read_to_string(&input_path)?;
+        load_exports_from_splice(socket_path).await?
+    } else if let Some(input_path) = &args.input {
+        let json_content = fs::read_to_string(input_path)?;
         serde_json::from_str(&json_content)?
     } else {
-        // Scan Rust source files for #[export] functions
-        println!("Scanning {} for #[export] functions...", args.project_dir.display());
-        find_exported_functions(&args.project_dir)?
+        index.functions()
     };
+    let structs = index.structs();
 
-    // Scan for serializable structs
-    println!("Scanning {} for serializable structs...", args.project_dir.display());
-    let structs = find_exported_structs(&args.project_dir)?;
+    // Types with no struct definition would leave dangling imports in the output
+    if let Some(warning) = check_unresolved_types(&functions, &structs, args.strict_types)? {
+        eprintln!("warning: {}", warning);
+    }
 
     // Generate TypeScript interfaces from Rust structs
-    if !structs.is_empty() {
+    if outputs.types && !structs.is_empty() {
         let interfaces = generate_typescript_interfaces(&structs);
         let interfaces_path = args.output_dir.join("types.ts");
-        fs::write(&interfaces_path, interfaces)?;
-        println!("Generated: {} ({} types)", interfaces_path.display(), structs.len());
+        if write_output(&interfaces_path, &interfaces)? {
+            println!("Generated: {} ({} types)", interfaces_path.display(), structs.len());
+        }
     }
 
     // Generate TypeScript definitions
-    if args.definitions {
+    if outputs.bindings && args.definitions {
         let defs = generate_typescript_definitions(&functions);
         let defs_path = args.output_dir.join("backend.d.ts");
-        fs::write(&defs_path, defs)?;
-        println!("Generated: {}", defs_path.display());
+        write_output(&defs_path, &defs)?;
     }
 
     // Generate runtime bindings
-    if args.runtime {
+    if outputs.bindings && args.runtime {
         let runtime = generate_typescript_runtime(&functions);
         let runtime_path = args.output_dir.join("backend.ts");
-        fs::write(&runtime_path, runtime)?;
-        println!("Generated: {}", runtime_path.display());
+        write_output(&runtime_path, &runtime)?;
     }
 
     // Generate namespaced server client
-    if args.server {
+    if outputs.bindings && args.server {
         let server = generate_namespaced_server(&functions);
         let server_path = args.output_dir.join("server.ts");
-        fs::write(&server_path, server)?;
-        println!("Generated: {}", server_path.display());
+        write_output(&server_path, &server)?;
     }
 
     println!("Successfully generated TypeScript bindings for {} functions and {} types", functions.len(), structs.len());
     Ok(())
 }
 
+/// Check for types that exported functions use but no scanned struct defines
+///
+/// Fails in strict mode; otherwise returns the warning to report.
+fn check_unresolved_types(
+    functions: &[ExportedFunction],
+    structs: &[ExportedStruct],
+    strict: bool,
+) -> Result<Option<String>> {
+    let unresolved = unresolved_types(functions, structs);
+    if unresolved.is_empty() {
+        return Ok(None);
+    }
+    let message = unresolved_types_message(&unresolved);
+    if strict {
+        anyhow::bail!(message);
+    }
+    Ok(Some(message))
+}
+
+/// Describe types that exported functions use but no scanned struct defines
+fn unresolved_types_message(unresolved: &[String]) -> String {
+    format!(
+        "exported functions reference types with no #[derive(Serialize)] struct definition: {} \
+         (the generated bindings will import them from ./types and fail to compile)",
+        unresolved.join(", ")
+    )
+}
+
+/// Write a generated file if its content changed, reporting what happened
+fn write_output(path: &Path, contents: &str) -> Result<bool> {
+    let written = write_if_changed(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    if written {
+        println!("Generated: {}", path.display());
+    } else {
+        println!("Unchanged: {}", path.display());
+    }
+    Ok(written)
+}
+
+/// Regenerate bindings whenever a `.rs` file under `source_dir` changes
+///
+/// Only the changed files are rescanned, and only the outputs fed by exports
+/// that actually changed are regenerated. Functions loaded from Splice or an
+/// input file aren't tied to any source, so their bindings are regenerated on
+/// every change.
+async fn watch(args: &Args, source_dir: &Path, index: &mut SourceIndex) -> Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        // Access events fire when codegen itself reads the sources; ignore them
+        let Ok(event) = res else { return };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            return;
+        }
+        for path in event.paths {
+            if path.extension().is_some_and(|ext| ext == "rs") {
+                let _ = tx.send(path);
+            }
+        }
+    })
+    .context("Failed to create file watcher")?;
+    watcher
+        .watch(source_dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", source_dir.display()))?;
+
+    let external_functions = args.splice_socket.is_some() || args.input.is_some();
+
+    println!("Watching {} for changes...", source_dir.display());
+    while let Some(path) = rx.recv().await {
+        let mut changed = HashSet::from([path]);
+        while let Ok(Some(path)) = tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await {
+            changed.insert(path);
+        }
+
+        let mut outputs = Outputs { types: false, bindings: external_functions };
+        for path in &changed {
+            let affected = index.update(path);
+            outputs.types |= affected.types;
+            outputs.bindings |= affected.bindings;
+        }
+        if !outputs.any() {
+            continue;
+        }
+
+        println!("Change detected, regenerating...");
+        if let Err(e) = generate(args, index, outputs).await {
+            eprintln!("Codegen failed: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
 async fn load_exports_from_splice(socket_path: &PathBuf) -> Result<Vec<ExportedFunction>> {
     // 1. Connect to Unix socket
     let stream = UnixStream::connect(socket_path)
@@ -163,5 +374,106 @@ mod tests {
         let args = Args::parse_from(&["zap-codegen"]);
         assert_eq!(args.project_dir, PathBuf::from("."));
         assert_eq!(args.output_dir, PathBuf::from("./src/api"));
+        assert!(!args.watch);
+
+        let args = Args::parse_from(["zap-codegen", "--watch"]);
+        assert!(args.watch);
+    }
+
+    #[tokio::test]
+    async fn test_warns_about_undefined_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("lib.rs"),
+            "#[export]\npub fn get_widget(id: u64) -> Widget { todo!() }\n",
+        )
+        .unwrap();
+
+        let output_dir = dir.path().join("api");
+        let mut args = Args::parse_from([
+            "zap-codegen",
+            "--project-dir", dir.path().to_str().unwrap(),
+            "--output-dir", output_dir.to_str().unwrap(),
+        ]);
+        fs::create_dir_all(&args.output_dir).unwrap();
+        let index = SourceIndex::scan(&source_dir(&args.project_dir));
+
+        // Non-strict mode reports the type as a warning and still generates
+        let warning = check_unresolved_types(&index.functions(), &index.structs(), false)
+            .unwrap()
+            .expect("Widget should be reported");
+        assert!(warning.contains("no #[derive(Serialize)] struct definition: Widget"));
+        generate(&args, &index, Outputs::ALL).await.unwrap();
+        let runtime = fs::read_to_string(output_dir.join("backend.ts")).unwrap();
+        assert!(runtime.contains("getWidget"));
+        assert!(output_dir.join("backend.d.ts").exists());
+        assert!(output_dir.join("server.ts").exists());
+
+        args.strict_types = true;
+        let error = generate(&args, &index, Outputs::ALL).await.unwrap_err().to_string();
+        assert!(error.contains("no #[derive(Serialize)] struct definition: Widget"));
+    }
+
+    #[test]
+    fn test_source_index_maps_changes_to_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("models.rs");
+        let handlers = dir.path().join("handlers.rs");
+        fs::write(&models, "#[derive(Serialize)]\npub struct User { pub id: u64 }\n").unwrap();
+        fs::write(&handlers, "#[export]\npub fn get_user(id: u64) -> User { todo!() }\n").unwrap();
+
+        let mut index = SourceIndex::scan(dir.path());
+        assert_eq!(index.functions().len(), 1);
+        assert_eq!(index.structs().len(), 1);
+
+        // A new function only touches the bindings
+        fs::write(
+            &handlers,
+            "#[export]\npub fn get_user(id: u64) -> User { todo!() }\n#[export]\npub fn ping() -> bool { true }\n",
+        )
+        .unwrap();
+        assert_eq!(index.update(&handlers), Outputs { types: false, bindings: true });
+        assert_eq!(index.functions().len(), 2);
+
+        // A struct change only touches types.ts
+        fs::write(&models, "#[derive(Serialize)]\npub struct User { pub id: u64, pub name: String }\n").unwrap();
+        assert_eq!(index.update(&models), Outputs { types: true, bindings: false });
+
+        // Edits that leave the exports alone touch nothing, nor do unparsable saves
+        fs::write(&models, "// users\n#[derive(Serialize)]\npub struct User { pub id: u64, pub name: String }\n").unwrap();
+        assert_eq!(index.update(&models), Outputs::default());
+        fs::write(&models, "#[derive(Serialize)]\npub struct User {").unwrap();
+        assert_eq!(index.update(&models), Outputs::default());
+        assert_eq!(index.structs().len(), 1);
+
+        // Deleting a file drops its exports
+        fs::remove_file(&handlers).unwrap();
+        assert_eq!(index.update(&handlers), Outputs { types: false, bindings: true });
+        assert!(index.functions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_generate_writes_only_requested_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "#[derive(Serialize)]\npub struct User { pub id: u64 }\n#[export]\npub fn get_user(id: u64) -> User { todo!() }\n",
+        )
+        .unwrap();
+        let output_dir = dir.path().join("api");
+        fs::create_dir_all(&output_dir).unwrap();
+        let args = Args::parse_from([
+            "zap-codegen",
+            "--project-dir", dir.path().to_str().unwrap(),
+            "--output-dir", output_dir.to_str().unwrap(),
+        ]);
+
+        let index = SourceIndex::scan(&source_dir(&args.project_dir));
+        generate(&args, &index, Outputs { types: true, bindings: false }).await.unwrap();
+        assert!(output_dir.join("types.ts").exists());
+        assert!(!output_dir.join("backend.ts").exists());
+        assert!(!output_dir.join("server.ts").exists());
     }
 }
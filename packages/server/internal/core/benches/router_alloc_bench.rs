@@ -0,0 +1,91 @@
+//! Router Allocation Benchmarks
+//!
+//! Counts heap allocations per lookup to back the zero-allocation claims:
+//! - Static routes: 0 allocations with `at` and `at_into`
+//! - Dynamic routes: 0 allocations with a reused `at_into` buffer, versus
+//!   a fresh parameter map per lookup with `at`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zap_core::{Method, Params, Router};
+
+/// System allocator that counts allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const LOOKUPS: usize = 10_000;
+
+/// Average allocations per call of `f` over `LOOKUPS` calls
+fn allocations_per_lookup(mut f: impl FnMut()) -> f64 {
+    // Warm up so one-time growth isn't counted
+    f();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..LOOKUPS {
+        f();
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / LOOKUPS as f64
+}
+
+fn build_router() -> Router<&'static str> {
+    let mut router = Router::new();
+    router.insert(Method::GET, "/api/health", "health").unwrap();
+    router.insert(Method::GET, "/users/:id", "get_user").unwrap();
+    router.insert(Method::GET, "/users/:id/posts/:post_id", "get_post").unwrap();
+    router
+}
+
+/// Report allocation counts, then benchmark the buffer-filling lookup
+fn bench_router_allocations(c: &mut Criterion) {
+    let router = build_router();
+    let mut params = Params::with_capacity(4);
+
+    let cases = [
+        ("static", "/api/health"),
+        ("one_param", "/users/12345"),
+        ("two_params", "/users/123/posts/456"),
+    ];
+
+    for (name, path) in cases {
+        let at = allocations_per_lookup(|| {
+            black_box(router.at(Method::GET, black_box(path)));
+        });
+        let at_into = allocations_per_lookup(|| {
+            black_box(router.at_into(Method::GET, black_box(path), &mut params));
+        });
+        println!("{:<12} at: {:.2} allocs/lookup, at_into: {:.2} allocs/lookup", name, at, at_into);
+    }
+
+    let mut group = c.benchmark_group("router_at_into");
+
+    for (name, path) in cases {
+        group.bench_function(name, |b| {
+            b.iter(|| router.at_into(black_box(Method::GET), black_box(path), &mut params).is_some())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_router_allocations);
+criterion_main!(benches);
@@ -9,6 +9,7 @@
 use crate::method::Method;
 use memchr::memchr;
 use ahash::AHashMap;
+use std::hash::{Hash, Hasher};
 use std::str;
 
 /// HTTP request parser optimized for performance
@@ -17,6 +18,8 @@ pub struct HttpParser {
     max_header_size: usize,
     /// Maximum number of headers allowed
     max_headers: usize,
+    /// Maximum length of a single header value
+    max_header_value_size: usize,
 }
 
 impl HttpParser {
@@ -25,22 +28,87 @@ impl HttpParser {
         Self {
             max_header_size: 8 * 1024, // 8KB default
             max_headers: 100,
+            max_header_value_size: 4 * 1024, // 4KB default, half the total
         }
     }
 
     /// Create parser with custom limits
+    ///
+    /// A single header value may use the whole `max_header_size` unless
+    /// capped with [`max_header_value_size`](Self::max_header_value_size).
     pub fn with_limits(max_header_size: usize, max_headers: usize) -> Self {
         Self {
             max_header_size,
             max_headers,
+            max_header_value_size: max_header_size,
         }
     }
 
+    /// Cap the length of any single header value, e.g. a giant `Cookie`
+    pub fn max_header_value_size(mut self, bytes: usize) -> Self {
+        self.max_header_value_size = bytes;
+        self
+    }
+
     /// Parse HTTP request from bytes with zero-copy optimization
     pub fn parse_request<'a>(&self, input: &'a [u8]) -> Result<ParsedRequest<'a>, ParseError> {
-        let mut parser = RequestParser::new(input, self.max_header_size, self.max_headers);
+        let mut parser = RequestParser::new(input, self.max_header_size, self.max_headers, self.max_header_value_size);
         parser.parse()
     }
+
+    /// Build a request from already-parsed components
+    ///
+    /// For servers that receive requests pre-parsed (e.g. from hyper), this
+    /// skips reconstructing and re-parsing the raw bytes while enforcing the
+    /// same header limits as [`parse_request`](Self::parse_request). The body
+    /// is held separately, so `body_offset` is 0 and `total_size` is
+    /// `body_len`.
+    pub fn parse_parts<'a, I>(
+        &self,
+        method: Method,
+        path: &'a str,
+        version: &'a str,
+        headers: I,
+        body_len: usize,
+    ) -> Result<ParsedRequest<'a>, ParseError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a [u8])>,
+    {
+        if !version.starts_with("HTTP/") {
+            return Err(ParseError::InvalidVersion);
+        }
+
+        let mut parsed_headers = Headers::with_capacity(16);
+        // Count bytes as they'd appear on the wire ("name: value\r\n")
+        let mut headers_size = 0;
+
+        for (name, value) in headers {
+            if parsed_headers.len() >= self.max_headers {
+                return Err(ParseError::TooManyHeaders);
+            }
+
+            let value = str::from_utf8(value).map_err(|_| ParseError::InvalidHeader)?;
+            headers_size += name.len() + value.len() + 4;
+            if headers_size > self.max_header_size {
+                return Err(ParseError::HeadersTooLarge);
+            }
+
+            let value = value.trim();
+            if value.len() > self.max_header_value_size {
+                return Err(ParseError::HeaderValueTooLong);
+            }
+            parsed_headers.insert(name.trim(), value);
+        }
+
+        Ok(ParsedRequest {
+            method,
+            path,
+            version,
+            headers: parsed_headers,
+            body_offset: 0,
+            total_size: body_len,
+        })
+    }
 }
 
 impl Default for HttpParser {
@@ -66,13 +134,60 @@ pub struct ParsedRequest<'a> {
     pub total_size: usize,
 }
 
+impl<'a> ParsedRequest<'a> {
+    /// Check if the request was made with HTTP/1.0
+    #[inline]
+    pub fn is_http_1_0(&self) -> bool {
+        self.version == "HTTP/1.0"
+    }
+
+    /// Check if connection should be kept alive
+    ///
+    /// HTTP/1.0 connections close unless the client sends
+    /// `Connection: keep-alive`; HTTP/1.1 connections stay open unless it
+    /// sends `Connection: close`.
+    #[inline]
+    pub fn keep_alive(&self) -> bool {
+        if self.is_http_1_0() {
+            self.headers.connection_has("keep-alive")
+        } else {
+            self.headers.keep_alive()
+        }
+    }
+}
+
+/// Header name map key that hashes and compares ASCII case-insensitively,
+/// so lookups are normalized without allocating a lowercased copy
+#[derive(Debug, Clone, Copy)]
+struct HeaderKey<'a>(&'a str);
+
+impl PartialEq for HeaderKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(other.0)
+    }
+}
+
+impl Eq for HeaderKey<'_> {}
+
+impl Hash for HeaderKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for byte in self.0.bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+        state.write_u8(0xff);
+    }
+}
+
 /// Zero-copy header storage optimized for lookups
+///
+/// Repeated headers (e.g. several `X-Forwarded-For` lines) are all kept;
+/// [`Headers::get`] returns the first and [`Headers::get_all`] every value.
 #[derive(Debug)]
 pub struct Headers<'a> {
-    /// Fast lookup map for headers
-    map: AHashMap<&'a str, &'a str>,
-    /// Count of headers
-    count: usize,
+    /// Case-insensitive lookup map from name to its first entry
+    map: AHashMap<HeaderKey<'a>, usize>,
+    /// Every header in request order, including repeats
+    entries: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> Headers<'a> {
@@ -80,31 +195,30 @@ impl<'a> Headers<'a> {
     fn with_capacity(capacity: usize) -> Self {
         Self {
             map: AHashMap::with_capacity(capacity),
-            count: 0,
+            entries: Vec::with_capacity(capacity),
         }
     }
 
     /// Insert header (internal use)
     fn insert(&mut self, name: &'a str, value: &'a str) {
-        self.map.insert(name, value);
-        self.count += 1;
+        self.map.entry(HeaderKey(name)).or_insert(self.entries.len());
+        self.entries.push((name, value));
     }
 
     /// Get header value by name (case-insensitive)
     #[inline]
     pub fn get(&self, name: &str) -> Option<&'a str> {
-        // Fast path for exact case match
-        if let Some(value) = self.map.get(name) {
-            return Some(*value);
-        }
+        // Keys are covariant, so the map can be probed with a shorter-lived name
+        let map: &AHashMap<HeaderKey<'_>, usize> = &self.map;
+        map.get(&HeaderKey(name)).map(|&index| self.entries[index].1)
+    }
 
-        // Fallback to case-insensitive search
-        for (k, v) in &self.map {
-            if k.eq_ignore_ascii_case(name) {
-                return Some(*v);
-            }
-        }
-        None
+    /// Get every value of a repeated header, in request order (case-insensitive)
+    pub fn get_all<'h>(&'h self, name: &'h str) -> impl Iterator<Item = &'a str> + 'h {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
     }
 
     /// Get header value as specific type
@@ -123,29 +237,36 @@ impl<'a> Headers<'a> {
     }
 
     /// Check if connection should be kept alive
+    ///
+    /// Assumes HTTP/1.1 semantics; use [`ParsedRequest::keep_alive`] to
+    /// account for HTTP/1.0.
     #[inline]
     pub fn keep_alive(&self) -> bool {
-        match self.get("connection") {
-            Some(value) => !value.eq_ignore_ascii_case("close"),
-            None => true, // HTTP/1.1 default is keep-alive
-        }
+        !self.connection_has("close") // HTTP/1.1 default is keep-alive
+    }
+
+    /// Check if the Connection header lists `token` (case-insensitive)
+    pub fn connection_has(&self, token: &str) -> bool {
+        self.get("connection").is_some_and(|value| {
+            value.split(',').any(|option| option.trim().eq_ignore_ascii_case(token))
+        })
     }
 
     /// Get number of headers
     #[inline]
     pub fn len(&self) -> usize {
-        self.count
+        self.entries.len()
     }
 
     /// Check if headers are empty
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.count == 0
+        self.entries.is_empty()
     }
 
-    /// Iterate over all headers
+    /// Iterate over all headers in request order, including repeats
     pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
-        self.map.iter().map(|(k, v)| (*k, *v))
+        self.entries.iter().copied()
     }
 }
 
@@ -155,15 +276,17 @@ struct RequestParser<'a> {
     position: usize,
     max_header_size: usize,
     max_headers: usize,
+    max_header_value_size: usize,
 }
 
 impl<'a> RequestParser<'a> {
-    fn new(input: &'a [u8], max_header_size: usize, max_headers: usize) -> Self {
+    fn new(input: &'a [u8], max_header_size: usize, max_headers: usize, max_header_value_size: usize) -> Self {
         Self {
             input,
             position: 0,
             max_header_size,
             max_headers,
+            max_header_value_size,
         }
     }
 
@@ -248,13 +371,17 @@ impl<'a> RequestParser<'a> {
 
             // Parse single header
             let (name, value) = self.parse_header_line()?;
-            headers.insert(name, value);
             
             // Check header size limit after parsing (DoS protection)
             let headers_size = self.position - headers_start;
             if headers_size > self.max_header_size {
                 return Err(ParseError::HeadersTooLarge);
             }
+
+            if value.len() > self.max_header_value_size {
+                return Err(ParseError::HeaderValueTooLong);
+            }
+            headers.insert(name, value);
         }
 
         Ok(headers)
@@ -327,6 +454,8 @@ pub enum ParseError {
     TooManyHeaders,
     /// Headers too large (DoS protection)
     HeadersTooLarge,
+    /// A single header value is too long (DoS protection)
+    HeaderValueTooLong,
 }
 
 impl std::fmt::Display for ParseError {
@@ -340,6 +469,7 @@ impl std::fmt::Display for ParseError {
             ParseError::InvalidHeader => write!(f, "Invalid header format"),
             ParseError::TooManyHeaders => write!(f, "Too many headers"),
             ParseError::HeadersTooLarge => write!(f, "Headers too large"),
+            ParseError::HeaderValueTooLong => write!(f, "Header value too long"),
         }
     }
 }
@@ -403,6 +533,12 @@ mod tests {
         assert_eq!(parsed.headers.get("host"), Some("Example.Com"));
         assert_eq!(parsed.headers.get("Host"), Some("Example.Com"));
         assert_eq!(parsed.headers.get("HOST"), Some("Example.Com"));
+
+        // HTTP/2-style lowercase names are found by their canonical spelling
+        let request = b"GET / HTTP/1.1\r\ncontent-type: text/plain\r\n\r\n";
+        let parsed = parser.parse_request(request).unwrap();
+        assert_eq!(parsed.headers.get("Content-Type"), Some("text/plain"));
+        assert_eq!(parsed.headers.get("content-typ"), None);
     }
 
     #[test]
@@ -444,6 +580,75 @@ mod tests {
         let request4 = b"GET / HTTP/1.1\r\nConnection: CLOSE\r\n\r\n";
         let parsed4 = parser.parse_request(request4).unwrap();
         assert!(!parsed4.headers.keep_alive());
+
+        // Close listed among other connection options
+        let request5 = b"GET / HTTP/1.1\r\nConnection: Upgrade, close\r\n\r\n";
+        let parsed5 = parser.parse_request(request5).unwrap();
+        assert!(!parsed5.keep_alive());
+    }
+
+    #[test]
+    fn test_parse_parts_matches_parse_request() {
+        let parser = HttpParser::new();
+        let raw = b"POST /users/42?expand=true HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}";
+        let from_bytes = parser.parse_request(raw).unwrap();
+
+        let headers: [(&str, &[u8]); 3] = [
+            ("Host", b"localhost"),
+            ("Content-Type", b"application/json"),
+            ("Content-Length", b"2"),
+        ];
+        let from_parts = parser
+            .parse_parts(Method::POST, "/users/42?expand=true", "HTTP/1.1", headers, 2)
+            .unwrap();
+
+        assert_eq!(from_parts.method, from_bytes.method);
+        assert_eq!(from_parts.path, from_bytes.path);
+        assert_eq!(from_parts.version, from_bytes.version);
+        assert_eq!(from_parts.headers.len(), from_bytes.headers.len());
+        for (name, value) in from_bytes.headers.iter() {
+            assert_eq!(from_parts.headers.get(name), Some(value));
+        }
+        assert_eq!(from_parts.headers.content_length(), Some(2));
+        assert_eq!(from_parts.body_offset, 0);
+    }
+
+    #[test]
+    fn test_parse_parts_enforces_limits() {
+        let parser = HttpParser::with_limits(32, 2);
+
+        let headers: [(&str, &[u8]); 3] = [("a", b"1"), ("b", b"2"), ("c", b"3")];
+        let result = parser.parse_parts(Method::GET, "/", "HTTP/1.1", headers, 0);
+        assert!(matches!(result, Err(ParseError::TooManyHeaders)));
+
+        let long = [b'x'; 64];
+        let headers: [(&str, &[u8]); 1] = [("x-long", &long)];
+        let result = parser.parse_parts(Method::GET, "/", "HTTP/1.1", headers, 0);
+        assert!(matches!(result, Err(ParseError::HeadersTooLarge)));
+
+        let headers: [(&str, &[u8]); 1] = [("x-bad", &[0xff, 0xfe])];
+        let result = parser.parse_parts(Method::GET, "/", "HTTP/1.1", headers, 0);
+        assert!(matches!(result, Err(ParseError::InvalidHeader)));
+    }
+
+    #[test]
+    fn test_http_1_0_keep_alive_is_opt_in() {
+        let parser = HttpParser::new();
+
+        let request1 = b"GET / HTTP/1.0\r\nHost: localhost\r\n\r\n";
+        let parsed1 = parser.parse_request(request1).unwrap();
+        assert_eq!(parsed1.version, "HTTP/1.0");
+        assert!(parsed1.is_http_1_0());
+        assert!(!parsed1.keep_alive());
+
+        let request2 = b"GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n";
+        let parsed2 = parser.parse_request(request2).unwrap();
+        assert!(parsed2.keep_alive());
+
+        let request3 = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let parsed3 = parser.parse_request(request3).unwrap();
+        assert!(!parsed3.is_http_1_0());
+        assert!(parsed3.keep_alive());
     }
 
     #[test]
@@ -521,6 +726,24 @@ mod tests {
         assert!(matches!(result, Err(ParseError::HeadersTooLarge)));
     }
 
+    #[test]
+    fn test_header_value_length_limit() {
+        // One long value, well within the overall header budget
+        let parser = HttpParser::with_limits(8192, 100).max_header_value_size(64);
+        let cookie = "c".repeat(100);
+
+        let request = format!("GET / HTTP/1.1\r\nHost: example.com\r\nCookie: {}\r\n\r\n", cookie);
+        let result = parser.parse_request(request.as_bytes());
+        assert_eq!(result.unwrap_err(), ParseError::HeaderValueTooLong);
+
+        let headers: [(&str, &[u8]); 1] = [("cookie", cookie.as_bytes())];
+        let result = parser.parse_parts(Method::GET, "/", "HTTP/1.1", headers, 0);
+        assert_eq!(result.unwrap_err(), ParseError::HeaderValueTooLong);
+
+        let request = format!("GET / HTTP/1.1\r\nCookie: {}\r\n\r\n", &cookie[..64]);
+        assert!(parser.parse_request(request.as_bytes()).is_ok());
+    }
+
     #[test]
     fn test_path_with_query_string() {
         let request = b"GET /search?q=rust&limit=10 HTTP/1.1\r\nHost: example.com\r\n\r\n";
@@ -607,6 +830,19 @@ mod tests {
         assert_eq!(headers.get("Accept"), Some(&"*/*"));
     }
 
+    #[test]
+    fn test_repeated_headers_are_kept() {
+        let request = b"GET / HTTP/1.1\r\nX-Custom: one\r\nHost: example.com\r\nx-custom: two\r\n\r\n";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request).unwrap();
+
+        assert_eq!(parsed.headers.len(), 3);
+        assert_eq!(parsed.headers.get("X-Custom"), Some("one"));
+        assert_eq!(parsed.headers.get_all("X-CUSTOM").collect::<Vec<_>>(), vec!["one", "two"]);
+        let names: Vec<&str> = parsed.headers.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["X-Custom", "Host", "x-custom"]);
+    }
+
     #[test]
     fn test_header_get_parsed() {
         let request = b"GET / HTTP/1.1\r\nContent-Length: 42\r\nX-Custom-Number: 123\r\n\r\n";
@@ -38,34 +38,49 @@ pub mod csrf;
 pub mod request;
 pub mod response;
 pub mod security_headers;
+pub mod state;
 
 pub use method::Method;
 pub use params::{Params, ParamError};
-pub use radix::RadixTree;
+pub use radix::{RadixTree, DEFAULT_MAX_PARAMS};
 pub use http::{HttpParser, ParsedRequest, Headers, ParseError};
 pub use middleware::{
     Context, ResponseBuilder, Response as MiddlewareResponse, Extensions, MiddlewareResult,
-    Middleware, MiddlewareChain, MiddlewareError,
-    LoggerMiddleware, CorsMiddleware, CorsConfig, CorsError
+    Middleware, MiddlewareChain, MiddlewareError, MiddlewareFuture, ChainOutcome,
+    LoggerMiddleware, DebugBodyLogMiddleware, BodyLogSink, CorsMiddleware, CorsConfig, CorsError, AllowedMethods,
+    BasicAuthMiddleware, BasicAuthVerifier, AuthenticatedUser, constant_time_eq,
+    JwtMiddleware, JwtAlgorithm, JwtClaims, JwtError, RequestIdMiddleware, RequestId,
+    CacheMiddleware, EtagMiddleware, Session, SessionMiddleware
 };
 pub use csrf::{CsrfMiddleware, CsrfConfig, SameSitePolicy};
-pub use rate_limit::{RateLimitMiddleware, RateLimitConfig, RateLimitStore, InMemoryStore, RateLimitError};
-pub use request::{Request, FormParseError};
-pub use response::{Response, StatusCode, ResponseBody, CookieOptions};
+pub use rate_limit::{RateLimitMiddleware, RateLimitConfig, RateLimitStore, InMemoryStore, RateLimitError, RateLimitKeyFn};
+pub use request::{Request, FormParseError, decode_form_data, percent_decode, parse_cookie_header};
+pub use response::{Response, StatusCode, ResponseBody, CookieOptions, Vary};
 pub use security_headers::{SecurityHeadersMiddleware, SecurityHeadersConfig, HstsConfig};
+pub use state::AppState;
 
 /// Core router structure optimized for high-performance lookups
 pub struct Router<T> {
     /// Separate trees for each HTTP method for maximum performance
     trees: AHashMap<Method, RadixTree<T>>,
+    /// Cap on the parameters a single route may capture
+    max_params: usize,
 }
 
 impl<T> Router<T> {
     /// Create a new router instance
     #[inline]
     pub fn new() -> Self {
+        Self::with_max_params(radix::DEFAULT_MAX_PARAMS)
+    }
+
+    /// Create a router whose routes may capture at most `max_params` parameters
+    ///
+    /// Routes over the cap fail to insert with `RouterError::InvalidParameter`.
+    pub fn with_max_params(max_params: usize) -> Self {
         Self {
             trees: AHashMap::new(),
+            max_params,
         }
     }
 
@@ -80,7 +95,8 @@ impl<T> Router<T> {
             return Err(RouterError::InvalidPath(path.to_string()));
         }
 
-        let tree = self.trees.entry(method).or_default();
+        let max_params = self.max_params;
+        let tree = self.trees.entry(method).or_insert_with(|| RadixTree::with_max_params(max_params));
         tree.insert(path, handler)
     }
 
@@ -99,6 +115,24 @@ impl<T> Router<T> {
         self.trees.get(&method)?.find(path)
     }
 
+    /// Find a route handler, writing parameters into a reusable buffer
+    ///
+    /// # Performance
+    /// - Zero allocations for static paths
+    /// - Zero allocations for dynamic paths once `params` has enough capacity
+    ///
+    /// `params` is cleared before matching and left empty on a miss.
+    #[inline]
+    pub fn at_into<'a>(&'a self, method: Method, path: &'a str, params: &mut Params<'a>) -> Option<&'a T> {
+        match self.trees.get(&method) {
+            Some(tree) => tree.find_into(path, params),
+            None => {
+                params.clear();
+                None
+            }
+        }
+    }
+
     /// Get the number of routes for a specific method
     #[inline]
     pub fn len(&self, method: Method) -> usize {
@@ -120,6 +154,58 @@ impl<T> Router<T> {
     pub fn methods(&self) -> impl Iterator<Item = Method> + '_ {
         self.trees.keys().copied()
     }
+
+    /// Match a request, distinguishing an unknown path from a wrong method
+    ///
+    /// Independent of HTTP plumbing, so it can back other protocols and be
+    /// unit tested without a server.
+    pub fn match_request<'a>(&'a self, method: Method, path: &'a str) -> MatchResult<'a, T> {
+        if let Some((handler, params)) = self.at(method, path) {
+            return MatchResult::Matched { handler, params };
+        }
+        let allowed = self.allowed_methods(path);
+        if allowed.is_empty() {
+            MatchResult::NotFound
+        } else {
+            MatchResult::MethodNotAllowed { allowed }
+        }
+    }
+
+    /// Every route as `(method, pattern, handler)`, for introspection
+    pub fn routes(&self) -> Vec<(Method, String, &T)> {
+        self.trees
+            .iter()
+            .flat_map(|(method, tree)| {
+                tree.routes()
+                    .into_iter()
+                    .map(move |(pattern, handler)| (*method, pattern, handler))
+            })
+            .collect()
+    }
+
+    /// Consume the router, returning every route as `(method, pattern, handler)`
+    pub fn into_routes(self) -> Vec<(Method, String, T)> {
+        self.trees
+            .into_iter()
+            .flat_map(|(method, tree)| {
+                tree.into_routes()
+                    .into_iter()
+                    .map(move |(pattern, handler)| (method, pattern, handler))
+            })
+            .collect()
+    }
+
+    /// Methods with a route matching `path`, in `Method` declaration order
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let mut methods: Vec<Method> = self
+            .trees
+            .iter()
+            .filter(|(_, tree)| tree.find(path).is_some())
+            .map(|(method, _)| *method)
+            .collect();
+        methods.sort_by_key(|method| *method as u8);
+        methods
+    }
 }
 
 impl<T> Default for Router<T> {
@@ -128,6 +214,17 @@ impl<T> Default for Router<T> {
     }
 }
 
+/// Outcome of [`Router::match_request`]
+#[derive(Debug)]
+pub enum MatchResult<'a, T> {
+    /// A route matched the method and path
+    Matched { handler: &'a T, params: Params<'a> },
+    /// The path has routes, but none for this method (405)
+    MethodNotAllowed { allowed: Vec<Method> },
+    /// No route matches the path for any method (404)
+    NotFound,
+}
+
 /// Router errors
 #[derive(Debug, Clone, PartialEq)]
 pub enum RouterError {
@@ -135,6 +232,8 @@ pub enum RouterError {
     InvalidPath(String),
     /// Duplicate route registration
     DuplicateRoute(String),
+    /// Route would ambiguously match the same paths as an existing route
+    ConflictingRoute(String),
     /// Parameter parsing error
     InvalidParameter(String),
 }
@@ -144,6 +243,7 @@ impl std::fmt::Display for RouterError {
         match self {
             RouterError::InvalidPath(path) => write!(f, "Invalid path: {}", path),
             RouterError::DuplicateRoute(route) => write!(f, "Duplicate route: {}", route),
+            RouterError::ConflictingRoute(route) => write!(f, "Conflicting route: {}", route),
             RouterError::InvalidParameter(param) => write!(f, "Invalid parameter: {}", param),
         }
     }
@@ -168,6 +268,38 @@ mod tests {
         assert!(router.at(Method::GET, "/nonexistent").is_none());
     }
 
+    #[test]
+    fn test_allowed_methods() {
+        let mut router = Router::new();
+        router.insert(Method::DELETE, "/users/:id", "delete_user").unwrap();
+        router.insert(Method::GET, "/users/:id", "get_user").unwrap();
+        router.insert(Method::POST, "/users", "create_user").unwrap();
+
+        assert_eq!(router.allowed_methods("/users/7"), vec![Method::GET, Method::DELETE]);
+        assert_eq!(router.allowed_methods("/users"), vec![Method::POST]);
+        assert!(router.allowed_methods("/nope").is_empty());
+    }
+
+    #[test]
+    fn test_match_request() {
+        let mut router = Router::new();
+        router.insert(Method::GET, "/users/:id", "get_user").unwrap();
+        router.insert(Method::DELETE, "/users/:id", "delete_user").unwrap();
+
+        match router.match_request(Method::GET, "/users/7") {
+            MatchResult::Matched { handler, params } => {
+                assert_eq!(handler, &"get_user");
+                assert_eq!(params.get("id"), Some("7"));
+            }
+            other => panic!("expected a match, got {:?}", other),
+        }
+        assert!(matches!(
+            router.match_request(Method::POST, "/users/7"),
+            MatchResult::MethodNotAllowed { allowed } if allowed == vec![Method::GET, Method::DELETE]
+        ));
+        assert!(matches!(router.match_request(Method::GET, "/posts"), MatchResult::NotFound));
+    }
+
     #[test]
     fn test_parameter_routing() {
         let mut router = Router::new();
@@ -194,6 +326,33 @@ mod tests {
         assert_eq!(params.get("filepath"), Some("docs/readme.txt"));
     }
 
+    #[test]
+    fn test_specific_routes_beat_wildcard() {
+        let mut router = Router::new();
+        router.insert(Method::GET, "/files/*path", "wildcard").unwrap();
+        router.insert(Method::GET, "/files/meta/:id", "get meta").unwrap();
+        router.insert(Method::GET, "/files/:name/info", "file info").unwrap();
+
+        let (handler, params) = router.at(Method::GET, "/files/meta/7").unwrap();
+        assert_eq!(handler, &"get meta");
+        assert_eq!(params.get("id"), Some("7"));
+        assert_eq!(params.get("path"), None);
+
+        let (handler, params) = router.at(Method::GET, "/files/a/b.txt").unwrap();
+        assert_eq!(handler, &"wildcard");
+        assert_eq!(params.get("path"), Some("a/b.txt"));
+
+        let (handler, params) = router.at(Method::GET, "/files/report/info").unwrap();
+        assert_eq!(handler, &"file info");
+        assert_eq!(params.get("name"), Some("report"));
+
+        // Partial matches on the specific routes fall back to the wildcard
+        let (handler, params) = router.at(Method::GET, "/files/meta").unwrap();
+        assert_eq!(handler, &"wildcard");
+        assert_eq!(params.get("path"), Some("meta"));
+        assert_eq!(router.at(Method::GET, "/files/meta/7/x").unwrap().0, &"wildcard");
+    }
+
     #[test]
     fn test_method_separation() {
         let mut router = Router::new();
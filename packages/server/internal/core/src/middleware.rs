@@ -8,6 +8,12 @@
 
 use crate::http::{ParsedRequest, Headers};
 use crate::method::Method;
+use crate::request::parse_cookie_header;
+use crate::response::Vary;
+use base64::{Engine as _, engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}};
+use serde::{Deserialize, Serialize};
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha384, Sha512};
 use std::future::Future;
 use std::pin::Pin;
 
@@ -63,6 +69,26 @@ impl<'a> Context<'a> {
     pub fn body_string(&self) -> Result<&str, std::str::Utf8Error> {
         std::str::from_utf8(self.body)
     }
+
+    /// Authenticated user ID set by [`JwtMiddleware`] or [`BasicAuthMiddleware`]
+    pub fn user_id(&self) -> Option<&str> {
+        self.extensions
+            .get::<JwtClaims>()
+            .and_then(|claims| claims.user_id())
+            .or_else(|| self.extensions.get::<AuthenticatedUser>().map(|user| user.0.as_str()))
+    }
+
+    /// Request ID assigned by [`RequestIdMiddleware`]
+    pub fn request_id(&self) -> Option<&str> {
+        self.extensions.get::<RequestId>().map(|id| id.0.as_str())
+    }
+
+    /// Check if the JWT claims grant a role
+    pub fn has_role(&self, role: &str) -> bool {
+        self.extensions
+            .get::<JwtClaims>()
+            .is_some_and(|claims| claims.has_role(role))
+    }
 }
 
 /// Response builder for constructing HTTP responses
@@ -98,6 +124,22 @@ impl ResponseBuilder {
         self
     }
 
+    /// Note request headers the response varies on, merged with any `Vary`
+    /// already added into a single header
+    pub fn vary(mut self, names: &str) -> Self {
+        let mut vary = Vary::new();
+        self.headers.retain(|(key, value)| {
+            let is_vary = key.eq_ignore_ascii_case("vary");
+            if is_vary {
+                vary.add(value);
+            }
+            !is_vary
+        });
+        vary.add(names);
+        self.headers.push(("Vary".to_string(), vary.to_string()));
+        self
+    }
+
     /// Set response body from bytes
     pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
         self.body = body.into();
@@ -188,6 +230,14 @@ pub type MiddlewareFuture<'a> =
 pub trait Middleware: Send + Sync {
     /// Process request and return modified context and result
     fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a>;
+
+    /// Modify the outgoing response once it has been produced
+    ///
+    /// Runs in reverse registration order, so the first middleware sees the
+    /// response last. The default leaves the response untouched.
+    fn after(&self, _ctx: &Context<'_>, response: Response) -> Response {
+        response
+    }
 }
 
 /// Middleware chain for composing multiple middleware
@@ -210,23 +260,125 @@ impl MiddlewareChain {
         self
     }
 
+    /// Append another chain's middleware after this chain's
+    pub fn merge(mut self, other: MiddlewareChain) -> Self {
+        self.middleware.extend(other.middleware);
+        self
+    }
+
+    /// Restrict every middleware in the chain to requests at or below `prefix`
+    ///
+    /// Other requests pass through untouched: neither `call` nor `after` runs
+    /// for them. `prefix` matches whole segments, so `/admin` covers
+    /// `/admin/users` but not `/administrator`.
+    pub fn scoped(self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into().trim_end_matches('/').to_string();
+        Self {
+            middleware: self
+                .middleware
+                .into_iter()
+                .map(|inner| Box::new(PathScoped { prefix: prefix.clone(), inner }) as Box<dyn Middleware>)
+                .collect(),
+        }
+    }
+
+    /// Check if the chain has no middleware
+    pub fn is_empty(&self) -> bool {
+        self.middleware.is_empty()
+    }
+
     /// Execute middleware chain
-    pub async fn execute<'a>(&self, mut ctx: Context<'a>) -> Result<Response, MiddlewareError> {
-        for middleware in &self.middleware {
+    ///
+    /// Without a handler, the response built up by the middleware is the final
+    /// response; `after` hooks run on it in reverse order.
+    pub async fn execute<'a>(&'a self, ctx: Context<'a>) -> Result<Response, MiddlewareError> {
+        match self.run(ctx).await? {
+            ChainOutcome::Continue(ctx) => {
+                let response = ctx.response.clone().finish();
+                Ok(self.after(&ctx, response))
+            }
+            ChainOutcome::Response(response) => Ok(response),
+        }
+    }
+
+    /// Run middleware in order ahead of a handler
+    ///
+    /// Stops at the first middleware that returns a response, running the
+    /// `after` hooks of the middleware that were reached. Otherwise hands back
+    /// the context so the caller can dispatch to its handler and then call
+    /// [`MiddlewareChain::after`].
+    pub async fn run<'a>(&'a self, mut ctx: Context<'a>) -> Result<ChainOutcome<'a>, MiddlewareError> {
+        for (index, middleware) in self.middleware.iter().enumerate() {
             let (new_ctx, result) = middleware.call(ctx).await?;
             ctx = new_ctx;
-            
+
             match result {
                 MiddlewareResult::Continue => continue,
-                MiddlewareResult::Response(response) => return Ok(response),
+                MiddlewareResult::Response(response) => {
+                    let response = Self::run_after(&self.middleware[..=index], &ctx, response);
+                    return Ok(ChainOutcome::Response(response));
+                }
             }
         }
 
-        // If no middleware returned a response, return the built response
-        Ok(ctx.response.finish())
+        Ok(ChainOutcome::Continue(ctx))
+    }
+
+    /// Run every middleware's `after` hook in reverse order
+    pub fn after(&self, ctx: &Context<'_>, response: Response) -> Response {
+        Self::run_after(&self.middleware, ctx, response)
+    }
+
+    fn run_after(middleware: &[Box<dyn Middleware>], ctx: &Context<'_>, response: Response) -> Response {
+        middleware
+            .iter()
+            .rev()
+            .fold(response, |response, middleware| middleware.after(ctx, response))
+    }
+}
+
+/// Middleware applied only to requests under a path prefix
+struct PathScoped {
+    /// Prefix without a trailing slash; empty covers every path
+    prefix: String,
+    inner: Box<dyn Middleware>,
+}
+
+impl PathScoped {
+    fn applies(&self, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        path.strip_prefix(self.prefix.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
     }
 }
 
+impl Middleware for PathScoped {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        if self.applies(ctx.path()) {
+            self.inner.call(ctx)
+        } else {
+            Box::pin(async move { Ok((ctx, MiddlewareResult::Continue)) })
+        }
+    }
+
+    fn after(&self, ctx: &Context<'_>, response: Response) -> Response {
+        if self.applies(ctx.path()) {
+            self.inner.after(ctx, response)
+        } else {
+            response
+        }
+    }
+}
+
+/// Result of running a middleware chain ahead of a handler
+#[derive(Debug)]
+pub enum ChainOutcome<'a> {
+    /// Every middleware continued; the handler should run with this context
+    Continue(Context<'a>),
+    /// A middleware terminated early with this response
+    Response(Response),
+}
+
 impl Default for MiddlewareChain {
     fn default() -> Self {
         Self::new()
@@ -300,14 +452,183 @@ impl Middleware for LoggerMiddleware {
             let mut new_ctx = ctx;
             new_ctx.extensions.insert(start);
             
-            // Log the request
-            println!("{} {} - Starting", new_ctx.method(), new_ctx.path());
+            // Log the request, with its ID when RequestIdMiddleware ran first
+            match new_ctx.request_id() {
+                Some(id) => println!("{} {} [{}] - Starting", new_ctx.method(), new_ctx.path(), id),
+                None => println!("{} {} - Starting", new_ctx.method(), new_ctx.path()),
+            }
             
             Ok((new_ctx, MiddlewareResult::Continue))
         })
     }
 }
 
+/// Destination for lines written by [`DebugBodyLogMiddleware`]
+pub type BodyLogSink = fn(&str);
+
+/// Placeholder written in place of redacted values
+const REDACTED: &str = "[REDACTED]";
+
+/// Debug middleware that logs request and response headers and bodies
+///
+/// Meant for chasing integration issues, not for production traffic. Headers
+/// in the redaction list (`Authorization`, `Cookie`, ... by default) are never
+/// printed, and JSON or form fields named like an entry in the field list
+/// (`password`, `token`, ... by default, compared case-insensitively, at any
+/// depth) are replaced before logging. Bodies are cut off at
+/// `max_body_bytes`.
+pub struct DebugBodyLogMiddleware {
+    max_body_bytes: usize,
+    redact_headers: Vec<String>,
+    redact_fields: Vec<String>,
+    sink: BodyLogSink,
+}
+
+impl DebugBodyLogMiddleware {
+    /// Create middleware logging up to 4 KiB of each body to stdout
+    pub fn new() -> Self {
+        Self {
+            max_body_bytes: 4096,
+            redact_headers: ["authorization", "proxy-authorization", "cookie", "set-cookie"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            redact_fields: ["password", "secret", "token", "access_token", "refresh_token"]
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            sink: |line| println!("{}", line),
+        }
+    }
+
+    /// Log at most this many bytes of each body
+    pub fn max_body_bytes(mut self, bytes: usize) -> Self {
+        self.max_body_bytes = bytes;
+        self
+    }
+
+    /// Also redact this header
+    pub fn redact_header(mut self, name: impl Into<String>) -> Self {
+        self.redact_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Also redact JSON and form fields with this name
+    pub fn redact_field(mut self, name: impl Into<String>) -> Self {
+        self.redact_fields.push(name.into());
+        self
+    }
+
+    /// Write log lines somewhere other than stdout
+    pub fn sink(mut self, sink: BodyLogSink) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    fn header_line<'h>(&self, headers: impl Iterator<Item = (&'h str, &'h str)>) -> String {
+        headers
+            .map(|(name, value)| {
+                if self.redact_headers.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                    format!("{}: {}", name, REDACTED)
+                } else {
+                    format!("{}: {}", name, value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn is_redacted_field(&self, name: &str) -> bool {
+        self.redact_fields.iter().any(|f| f.eq_ignore_ascii_case(name))
+    }
+
+    fn redact_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.is_redacted_field(key) {
+                        *field = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_json(field);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.redact_json(item)),
+            _ => {}
+        }
+    }
+
+    /// Render a body for logging: redacted, lossily decoded and truncated
+    fn body_text(&self, body: &[u8], content_type: Option<&str>) -> String {
+        if body.is_empty() {
+            return "<empty>".to_string();
+        }
+
+        let text = if let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(body) {
+            self.redact_json(&mut json);
+            json.to_string()
+        } else if content_type.is_some_and(|ct| ct.starts_with("application/x-www-form-urlencoded")) {
+            String::from_utf8_lossy(body)
+                .split('&')
+                .map(|pair| match pair.split_once('=') {
+                    Some((key, _)) if self.is_redacted_field(key) => format!("{}={}", key, REDACTED),
+                    _ => pair.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("&")
+        } else {
+            String::from_utf8_lossy(body).into_owned()
+        };
+
+        if text.len() <= self.max_body_bytes {
+            return text;
+        }
+        let mut end = self.max_body_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}... ({} bytes truncated)", &text[..end], text.len() - end)
+    }
+}
+
+impl Default for DebugBodyLogMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for DebugBodyLogMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            (self.sink)(&format!(
+                "--> {} {} [{}] {}",
+                ctx.method(),
+                ctx.path(),
+                self.header_line(ctx.headers().iter()),
+                self.body_text(ctx.body(), ctx.headers().get("content-type")),
+            ));
+            Ok((ctx, MiddlewareResult::Continue))
+        })
+    }
+
+    fn after(&self, ctx: &Context<'_>, response: Response) -> Response {
+        let content_type = response
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str());
+        (self.sink)(&format!(
+            "<-- {} {} {} [{}] {}",
+            ctx.method(),
+            ctx.path(),
+            response.status,
+            self.header_line(response.headers.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+            self.body_text(&response.body, content_type),
+        ));
+        response
+    }
+}
+
 /// CORS configuration errors
 #[derive(Debug, Clone)]
 pub enum CorsError {
@@ -362,6 +683,14 @@ impl Default for CorsConfig {
     }
 }
 
+/// Methods the router serves for the request path
+///
+/// Set in the context [`Extensions`] for `OPTIONS` requests so
+/// [`CorsMiddleware`] can answer preflights with the methods that actually
+/// exist, rather than everything it is configured to allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedMethods(pub Vec<Method>);
+
 /// Built-in CORS middleware
 ///
 /// For production use, always use `CorsMiddleware::strict()` with explicit origins.
@@ -485,11 +814,12 @@ impl CorsMiddleware {
         })
     }
 
-    /// Build methods string for header
-    fn methods_string(&self) -> String {
+    /// Build methods string for header, limited to `routed` when known
+    fn methods_string(&self, routed: Option<&AllowedMethods>) -> String {
         self.methods
             .iter()
-            .map(|m| format!("{:?}", m))
+            .filter(|m| routed.is_none_or(|routed| routed.0.contains(m)))
+            .map(|m| m.as_str())
             .collect::<Vec<_>>()
             .join(", ")
     }
@@ -517,7 +847,10 @@ impl Middleware for CorsMiddleware {
                 if let Some(ref origin) = allowed_origin {
                     response_builder = response_builder
                         .header("Access-Control-Allow-Origin", origin)
-                        .header("Access-Control-Allow-Methods", &self.methods_string())
+                        .header(
+                            "Access-Control-Allow-Methods",
+                            self.methods_string(ctx.extensions.get::<AllowedMethods>()),
+                        )
                         .header("Access-Control-Allow-Headers", &self.headers_string());
 
                     if self.credentials {
@@ -563,7 +896,7 @@ impl Middleware for CorsMiddleware {
                 }
 
                 // Vary header for caching
-                new_ctx.response = new_ctx.response.header("Vary", "Origin");
+                new_ctx.response = new_ctx.response.vary("Origin");
             }
 
             Ok((new_ctx, MiddlewareResult::Continue))
@@ -571,6 +904,860 @@ impl Middleware for CorsMiddleware {
     }
 }
 
+/// Compare two byte strings in constant time (for equal-length inputs)
+///
+/// Only the length comparison short-circuits, so the time taken does not
+/// reveal how many leading bytes matched.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut result = 0u8;
+    for (byte_a, byte_b) in a.iter().zip(b.iter()) {
+        result |= byte_a ^ byte_b;
+    }
+    result == 0
+}
+
+/// Username of a request authenticated by [`BasicAuthMiddleware`]
+///
+/// Stored in the context [`Extensions`] on success.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedUser(pub String);
+
+/// Credential check used by [`BasicAuthMiddleware`]: `(username, password) -> valid`
+pub type BasicAuthVerifier = Box<dyn Fn(&str, &str) -> bool + Send + Sync>;
+
+/// HTTP Basic authentication middleware
+///
+/// Requests without valid `Authorization: Basic` credentials are answered with
+/// `401` and a `WWW-Authenticate` challenge for the configured realm.
+pub struct BasicAuthMiddleware {
+    realm: String,
+    verifier: BasicAuthVerifier,
+}
+
+impl BasicAuthMiddleware {
+    /// Create middleware that validates credentials with a custom verifier
+    ///
+    /// The verifier can check hashed or database-backed credentials; use
+    /// [`constant_time_eq`] when comparing secrets directly.
+    pub fn new<F>(realm: impl Into<String>, verifier: F) -> Self
+    where
+        F: Fn(&str, &str) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            realm: realm.into(),
+            verifier: Box::new(verifier),
+        }
+    }
+
+    /// Create middleware accepting a single fixed username and password
+    pub fn with_credentials(
+        realm: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        let username = username.into();
+        let password = password.into();
+        Self::new(realm, move |user, pass| {
+            // Evaluate both comparisons so timing doesn't reveal which one failed
+            let user_ok = constant_time_eq(user.as_bytes(), username.as_bytes());
+            let pass_ok = constant_time_eq(pass.as_bytes(), password.as_bytes());
+            user_ok & pass_ok
+        })
+    }
+
+    /// Decode `username:password` from an `Authorization` header value
+    fn parse_credentials(header: &str) -> Option<(String, String)> {
+        let (scheme, encoded) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("Basic") {
+            return None;
+        }
+
+        let decoded = STANDARD.decode(encoded.trim()).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some((username.to_string(), password.to_string()))
+    }
+
+    /// 401 response carrying the Basic challenge
+    fn challenge(&self) -> Response {
+        ResponseBuilder::new()
+            .status(401)
+            .header(
+                "WWW-Authenticate",
+                format!("Basic realm=\"{}\", charset=\"UTF-8\"", self.realm.replace('"', "\\\"")),
+            )
+            .text("Unauthorized")
+            .finish()
+    }
+}
+
+impl Middleware for BasicAuthMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let credentials = ctx
+                .headers()
+                .get("Authorization")
+                .and_then(Self::parse_credentials);
+
+            match credentials {
+                Some((username, password)) if (self.verifier)(&username, &password) => {
+                    let mut new_ctx = ctx;
+                    new_ctx.extensions.insert(AuthenticatedUser(username));
+                    Ok((new_ctx, MiddlewareResult::Continue))
+                }
+                _ => Ok((ctx, MiddlewareResult::Response(self.challenge()))),
+            }
+        })
+    }
+}
+
+/// Request ID stored in the context [`Extensions`] by [`RequestIdMiddleware`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Request ID propagation middleware
+///
+/// Reuses the incoming `X-Request-Id` (or a configured header) when present and
+/// well-formed, otherwise generates a UUID. The ID is stored in the context and
+/// echoed on the response.
+pub struct RequestIdMiddleware {
+    header_name: String,
+}
+
+impl RequestIdMiddleware {
+    /// Longest incoming ID accepted as-is
+    const MAX_ID_LEN: usize = 128;
+
+    /// Create middleware using the `X-Request-Id` header
+    pub fn new() -> Self {
+        Self {
+            header_name: "X-Request-Id".to_string(),
+        }
+    }
+
+    /// Use a different header name (e.g. `X-Correlation-Id`)
+    pub fn header_name(mut self, name: impl Into<String>) -> Self {
+        self.header_name = name.into();
+        self
+    }
+
+    /// Accept only short, printable IDs so clients can't inject junk into logs
+    fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id.len() <= Self::MAX_ID_LEN
+            && id.bytes().all(|b| b.is_ascii_graphic())
+    }
+}
+
+impl Default for RequestIdMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for RequestIdMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let request_id = ctx
+                .headers()
+                .get(&self.header_name)
+                .map(str::trim)
+                .filter(|id| Self::is_valid_id(id))
+                .map(str::to_string)
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+            let mut new_ctx = ctx;
+            new_ctx.response = new_ctx.response.header(self.header_name.as_str(), request_id.as_str());
+            new_ctx.extensions.insert(RequestId(request_id));
+            Ok((new_ctx, MiddlewareResult::Continue))
+        })
+    }
+}
+
+/// HMAC algorithms supported by [`JwtMiddleware`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    /// HMAC using SHA-256
+    HS256,
+    /// HMAC using SHA-384
+    HS384,
+    /// HMAC using SHA-512
+    HS512,
+}
+
+impl JwtAlgorithm {
+    /// Name used in the token header's `alg` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::HS384 => "HS384",
+            JwtAlgorithm::HS512 => "HS512",
+        }
+    }
+
+    /// Compute the HMAC signature of `message`
+    fn sign(&self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        match self {
+            JwtAlgorithm::HS256 => mac::<Hmac<Sha256>>(key, message).finalize().into_bytes().to_vec(),
+            JwtAlgorithm::HS384 => mac::<Hmac<Sha384>>(key, message).finalize().into_bytes().to_vec(),
+            JwtAlgorithm::HS512 => mac::<Hmac<Sha512>>(key, message).finalize().into_bytes().to_vec(),
+        }
+    }
+
+    /// Check `signature` against `message` in constant time
+    fn verify(&self, key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            JwtAlgorithm::HS256 => mac::<Hmac<Sha256>>(key, message).verify_slice(signature).is_ok(),
+            JwtAlgorithm::HS384 => mac::<Hmac<Sha384>>(key, message).verify_slice(signature).is_ok(),
+            JwtAlgorithm::HS512 => mac::<Hmac<Sha512>>(key, message).verify_slice(signature).is_ok(),
+        }
+    }
+}
+
+/// HMAC state keyed with `key` and fed `message`
+fn mac<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> M {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac
+}
+
+/// Decoded JWT claims, stored in the context [`Extensions`] by [`JwtMiddleware`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// Subject (user ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Roles granted to the subject
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Expiration time (seconds since Unix epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// Not-before time (seconds since Unix epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// Any other claims
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JwtClaims {
+    /// User ID from the `sub` claim
+    pub fn user_id(&self) -> Option<&str> {
+        self.sub.as_deref()
+    }
+
+    /// Check if the `roles` claim contains `role`
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// JWT verification errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JwtError {
+    /// Token is not three base64url segments of valid JSON
+    Malformed,
+    /// Header `alg` differs from the configured algorithm
+    AlgorithmMismatch,
+    /// Signature does not match
+    InvalidSignature,
+    /// `exp` is in the past
+    Expired,
+    /// `nbf` is in the future
+    NotYetValid,
+}
+
+impl std::fmt::Display for JwtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JwtError::Malformed => write!(f, "Malformed token"),
+            JwtError::AlgorithmMismatch => write!(f, "Unexpected signing algorithm"),
+            JwtError::InvalidSignature => write!(f, "Invalid token signature"),
+            JwtError::Expired => write!(f, "Token expired"),
+            JwtError::NotYetValid => write!(f, "Token not yet valid"),
+        }
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+/// JWT bearer token authentication middleware
+///
+/// Verifies `Authorization: Bearer <token>` against an HMAC key, checks the
+/// `exp`/`nbf` claims and stores the decoded [`JwtClaims`] in the context.
+/// Missing or invalid tokens are answered with `401`.
+pub struct JwtMiddleware {
+    key: Vec<u8>,
+    algorithm: JwtAlgorithm,
+    leeway_secs: u64,
+}
+
+impl JwtMiddleware {
+    /// Create middleware verifying tokens signed with `key`
+    pub fn new(key: impl Into<Vec<u8>>, algorithm: JwtAlgorithm) -> Self {
+        Self {
+            key: key.into(),
+            algorithm,
+            leeway_secs: 0,
+        }
+    }
+
+    /// Allow clock skew when checking `exp` and `nbf`
+    pub fn leeway(mut self, seconds: u64) -> Self {
+        self.leeway_secs = seconds;
+        self
+    }
+
+    /// Sign claims into a compact JWT using this middleware's key
+    pub fn sign(&self, claims: &JwtClaims) -> String {
+        let header = serde_json::json!({ "alg": self.algorithm.as_str(), "typ": "JWT" });
+        let header = URL_SAFE_NO_PAD.encode(header.to_string());
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).unwrap_or_default());
+
+        let signing_input = format!("{}.{}", header, payload);
+        let signature = self.algorithm.sign(&self.key, signing_input.as_bytes());
+        format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    /// Verify a compact JWT and return its claims
+    pub fn verify(&self, token: &str) -> Result<JwtClaims, JwtError> {
+        let mut parts = token.split('.');
+        let (header, payload, signature) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(h), Some(p), Some(s), None) => (h, p, s),
+            _ => return Err(JwtError::Malformed),
+        };
+
+        let header_json: serde_json::Value = URL_SAFE_NO_PAD
+            .decode(header)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(JwtError::Malformed)?;
+        // Never let the token choose its own algorithm (e.g. "none")
+        if header_json.get("alg").and_then(|alg| alg.as_str()) != Some(self.algorithm.as_str()) {
+            return Err(JwtError::AlgorithmMismatch);
+        }
+
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| JwtError::Malformed)?;
+        let signing_input = &token[..header.len() + 1 + payload.len()];
+        if !self.algorithm.verify(&self.key, signing_input.as_bytes(), &signature) {
+            return Err(JwtError::InvalidSignature);
+        }
+
+        let claims: JwtClaims = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(JwtError::Malformed)?;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if claims.exp.is_some_and(|exp| now >= exp.saturating_add(self.leeway_secs)) {
+            return Err(JwtError::Expired);
+        }
+        if claims.nbf.is_some_and(|nbf| now.saturating_add(self.leeway_secs) < nbf) {
+            return Err(JwtError::NotYetValid);
+        }
+
+        Ok(claims)
+    }
+
+    /// 401 response with a Bearer challenge describing the failure
+    fn unauthorized(message: &str) -> Response {
+        ResponseBuilder::new()
+            .status(401)
+            .header(
+                "WWW-Authenticate",
+                format!("Bearer error=\"invalid_token\", error_description=\"{}\"", message),
+            )
+            .text("Unauthorized")
+            .finish()
+    }
+}
+
+impl Middleware for JwtMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let token = ctx
+                .headers()
+                .get("Authorization")
+                .and_then(|value| value.trim().split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+                .map(|(_, token)| token.trim());
+
+            let Some(token) = token else {
+                let response = ResponseBuilder::new()
+                    .status(401)
+                    .header("WWW-Authenticate", "Bearer")
+                    .text("Unauthorized")
+                    .finish();
+                return Ok((ctx, MiddlewareResult::Response(response)));
+            };
+
+            match self.verify(token) {
+                Ok(claims) => {
+                    let mut new_ctx = ctx;
+                    new_ctx.extensions.insert(claims);
+                    Ok((new_ctx, MiddlewareResult::Continue))
+                }
+                Err(e) => Ok((ctx, MiddlewareResult::Response(Self::unauthorized(&e.to_string())))),
+            }
+        })
+    }
+}
+
+/// Marks a request whose response [`CacheMiddleware`] should store
+struct CacheKey(String);
+
+struct CachedResponse {
+    response: Response,
+    /// Request headers named by the response's `Vary`, with the values they had
+    vary: Vec<(String, Option<String>)>,
+    stored_at: std::time::Instant,
+    /// Access tick used for LRU eviction
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct ResponseCache {
+    entries: std::collections::HashMap<String, CachedResponse>,
+    tick: u64,
+}
+
+/// In-memory response cache for read-only routes
+///
+/// Successful `GET` responses are stored by method, path and query string for
+/// the configured TTL and replayed with `X-Cache: HIT`; other `GET`s run the
+/// handler and are marked `X-Cache: MISS`. A stored response is only replayed
+/// to requests that match it on every header its `Vary` names. Once
+/// `max_entries` is reached the least recently used entry is evicted.
+///
+/// Responses are never shared between users: requests carrying
+/// `Authorization` or `Cookie` bypass the cache, and responses that set a
+/// cookie, say `Cache-Control: private` or `no-store`, or `Vary: *` are not
+/// stored. Streamed responses reach the `after` hook without a body and are
+/// never cached.
+pub struct CacheMiddleware {
+    ttl: std::time::Duration,
+    max_entries: usize,
+    cache: parking_lot::Mutex<ResponseCache>,
+}
+
+impl CacheMiddleware {
+    /// Create a cache keeping responses for `ttl`, with up to 1000 entries
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: 1000,
+            cache: parking_lot::Mutex::new(ResponseCache::default()),
+        }
+    }
+
+    /// Limit the number of cached responses
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Number of cached responses, including expired ones not yet replaced
+    pub fn len(&self) -> usize {
+        self.cache.lock().entries.len()
+    }
+
+    /// Check if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached response
+    pub fn clear(&self) {
+        self.cache.lock().entries.clear();
+    }
+
+    fn lookup(&self, key: &str, headers: &Headers<'_>) -> Option<Response> {
+        let mut cache = self.cache.lock();
+        cache.tick += 1;
+        let tick = cache.tick;
+
+        match cache.entries.get_mut(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                let matches = entry
+                    .vary
+                    .iter()
+                    .all(|(name, value)| headers.get(name) == value.as_deref());
+                if !matches {
+                    return None;
+                }
+                entry.last_used = tick;
+                Some(entry.response.clone())
+            }
+            Some(_) => {
+                cache.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn store(&self, key: String, vary: Vec<(String, Option<String>)>, response: Response) {
+        let mut cache = self.cache.lock();
+        cache.tick += 1;
+        let tick = cache.tick;
+
+        if !cache.entries.contains_key(&key) && cache.entries.len() >= self.max_entries {
+            let oldest = cache
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.entries.remove(&oldest);
+            }
+        }
+
+        cache.entries.insert(key, CachedResponse {
+            response,
+            vary,
+            stored_at: std::time::Instant::now(),
+            last_used: tick,
+        });
+    }
+}
+
+impl Middleware for CacheMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            // Credentialed responses are per user; never share or replay them
+            let credentialed = ctx.headers().get("authorization").is_some()
+                || ctx.headers().get("cookie").is_some();
+            if ctx.method() != Method::GET || credentialed {
+                return Ok((ctx, MiddlewareResult::Continue));
+            }
+
+            // The request path includes the query string
+            let key = format!("{} {}", ctx.method(), ctx.path());
+            if let Some(mut response) = self.lookup(&key, ctx.headers()) {
+                response.headers.push(("X-Cache".to_string(), "HIT".to_string()));
+                return Ok((ctx, MiddlewareResult::Response(response)));
+            }
+
+            let mut new_ctx = ctx;
+            new_ctx.extensions.insert(CacheKey(key));
+            Ok((new_ctx, MiddlewareResult::Continue))
+        })
+    }
+
+    fn after(&self, ctx: &Context<'_>, mut response: Response) -> Response {
+        // Hits short-circuit in `call`, so only cacheable misses carry a key
+        let Some(CacheKey(key)) = ctx.extensions.get::<CacheKey>() else {
+            return response;
+        };
+
+        if response.status == 200 && !response.body.is_empty() {
+            if let Some(vary) = storable_vary(&response, ctx.headers()) {
+                self.store(key.clone(), vary, response.clone());
+            }
+        }
+        response.headers.push(("X-Cache".to_string(), "MISS".to_string()));
+        response
+    }
+}
+
+/// The `Vary` request headers and their values if `response` may be shared,
+/// or `None` for responses that must not be cached
+fn storable_vary(response: &Response, request_headers: &Headers<'_>) -> Option<Vec<(String, Option<String>)>> {
+    let mut vary = Vec::new();
+    for (name, value) in &response.headers {
+        if name.eq_ignore_ascii_case("set-cookie") {
+            return None;
+        }
+        if name.eq_ignore_ascii_case("cache-control")
+            && value.split(',').any(|directive| {
+                let directive = directive.trim();
+                directive.eq_ignore_ascii_case("private") || directive.eq_ignore_ascii_case("no-store")
+            })
+        {
+            return None;
+        }
+        if name.eq_ignore_ascii_case("vary") {
+            for field in value.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+                if field == "*" {
+                    return None;
+                }
+                let field = field.to_ascii_lowercase();
+                let current = request_headers.get(&field).map(str::to_string);
+                vary.push((field, current));
+            }
+        }
+    }
+    Some(vary)
+}
+
+/// Strong ETags for buffered responses
+///
+/// Hashes the final body of every `200` response to a `GET` or `HEAD` into an
+/// `ETag`, and turns the response into a bodiless `304 Not Modified` when the
+/// request's `If-None-Match` lists that tag. Responses that already carry an
+/// ETag, and streamed responses (which reach `after` without a body), are left
+/// alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EtagMiddleware;
+
+impl EtagMiddleware {
+    /// Create the middleware
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Middleware for EtagMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move { Ok((ctx, MiddlewareResult::Continue)) })
+    }
+
+    fn after(&self, ctx: &Context<'_>, mut response: Response) -> Response {
+        let applies = matches!(ctx.method(), Method::GET | Method::HEAD)
+            && response.status == 200
+            && !response.body.is_empty()
+            && !response.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("etag"));
+        if !applies {
+            return response;
+        }
+
+        let etag = format!("\"{:016x}\"", xxhash_rust::xxh3::xxh3_64(&response.body));
+        if ctx.headers().get("if-none-match").is_some_and(|value| if_none_match_lists(value, &etag)) {
+            response.status = 304;
+            response.body.clear();
+            response.headers.retain(|(name, _)| {
+                !name.eq_ignore_ascii_case("content-type") && !name.eq_ignore_ascii_case("content-length")
+            });
+        }
+        response.headers.push(("ETag".to_string(), etag));
+        response
+    }
+}
+
+/// Whether an `If-None-Match` value lists `etag`
+///
+/// Uses the weak comparison RFC 9110 prescribes for `If-None-Match`, so a
+/// `W/` prefix is ignored; `*` matches any tag.
+fn if_none_match_lists(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// Session values read by [`SessionMiddleware`], stored in the context [`Extensions`]
+///
+/// Clones share the same values, so a handle taken by later middleware or a
+/// handler sees and makes the same changes. Modified sessions are written back
+/// to the cookie once the response is built.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    inner: std::sync::Arc<parking_lot::Mutex<SessionData>>,
+}
+
+#[derive(Debug, Default)]
+struct SessionData {
+    values: std::collections::HashMap<String, String>,
+    modified: bool,
+}
+
+impl Session {
+    fn with_values(values: std::collections::HashMap<String, String>) -> Self {
+        Self {
+            inner: std::sync::Arc::new(parking_lot::Mutex::new(SessionData { values, modified: false })),
+        }
+    }
+
+    /// Get a value
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.inner.lock().values.get(key).cloned()
+    }
+
+    /// Set a value
+    pub fn insert(&self, key: impl Into<String>, value: impl Into<String>) {
+        let mut data = self.inner.lock();
+        data.values.insert(key.into(), value.into());
+        data.modified = true;
+    }
+
+    /// Remove a value, returning it
+    pub fn remove(&self, key: &str) -> Option<String> {
+        let mut data = self.inner.lock();
+        let removed = data.values.remove(key);
+        data.modified |= removed.is_some();
+        removed
+    }
+
+    /// Remove every value; the cookie is deleted with the response
+    pub fn clear(&self) {
+        let mut data = self.inner.lock();
+        data.values.clear();
+        data.modified = true;
+    }
+
+    /// Check if the session holds no values
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().values.is_empty()
+    }
+
+    /// Check if the session changed during this request
+    pub fn is_modified(&self) -> bool {
+        self.inner.lock().modified
+    }
+}
+
+/// Signed-cookie sessions
+///
+/// The cookie holds the session values as base64url JSON followed by an
+/// HMAC-SHA256 signature, so no server-side store is needed. Each request
+/// gets a [`Session`] in its [`Extensions`]; a missing, tampered or
+/// malformed cookie starts an empty one (and a bad cookie is deleted). A
+/// `Set-Cookie` is only sent when the session was modified. Values are
+/// readable by the client, so keep secrets out of them.
+pub struct SessionMiddleware {
+    key: Vec<u8>,
+    cookie_name: String,
+    path: String,
+    secure: bool,
+    same_site: crate::csrf::SameSitePolicy,
+    max_age: Option<u64>,
+}
+
+impl SessionMiddleware {
+    /// Create middleware signing cookies with `key`
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: key.into(),
+            cookie_name: "zap_session".to_string(),
+            path: "/".to_string(),
+            secure: true,
+            same_site: crate::csrf::SameSitePolicy::Lax,
+            max_age: None,
+        }
+    }
+
+    /// Use a different cookie name (default `zap_session`)
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    /// Limit the cookie to a path (default `/`)
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Set the `Secure` flag (default: on; turn off for plain-HTTP development)
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Set the `SameSite` policy (default: Lax)
+    pub fn same_site(mut self, same_site: crate::csrf::SameSitePolicy) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Keep the cookie for `seconds` (default: until the browser closes)
+    pub fn max_age(mut self, seconds: u64) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Sign session values into a cookie value
+    fn encode(&self, values: &std::collections::HashMap<String, String>) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(values).unwrap_or_default());
+        let signature = mac::<Hmac<Sha256>>(&self.key, payload.as_bytes()).finalize().into_bytes();
+        format!("{}.{}", payload, URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    /// Verify a cookie value and return its session values
+    fn decode(&self, cookie: &str) -> Option<std::collections::HashMap<String, String>> {
+        let (payload, signature) = cookie.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        mac::<Hmac<Sha256>>(&self.key, payload.as_bytes())
+            .verify_slice(&signature)
+            .ok()?;
+
+        let json = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// Value of the session cookie sent with the request
+    fn request_cookie<'a>(&self, ctx: &Context<'a>) -> Option<&'a str> {
+        parse_cookie_header(ctx.headers().get("Cookie")?)
+            .get(self.cookie_name.as_str())
+            .copied()
+    }
+
+    /// Build the `Set-Cookie` value, deleting the cookie for an empty session
+    fn set_cookie(&self, session: &Session) -> String {
+        let data = session.inner.lock();
+        let mut cookie = if data.values.is_empty() {
+            format!("{}=; Path={}; Max-Age=0", self.cookie_name, self.path)
+        } else {
+            let mut cookie = format!("{}={}; Path={}", self.cookie_name, self.encode(&data.values), self.path);
+            if let Some(max_age) = self.max_age {
+                cookie.push_str(&format!("; Max-Age={}", max_age));
+            }
+            cookie
+        };
+
+        cookie.push_str("; HttpOnly");
+        // SameSite=None is only honored on secure cookies
+        if self.secure || matches!(self.same_site, crate::csrf::SameSitePolicy::None) {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(match self.same_site {
+            crate::csrf::SameSitePolicy::Strict => "; SameSite=Strict",
+            crate::csrf::SameSitePolicy::Lax => "; SameSite=Lax",
+            crate::csrf::SameSitePolicy::None => "; SameSite=None",
+        });
+        cookie
+    }
+}
+
+impl Middleware for SessionMiddleware {
+    fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let session = match self.request_cookie(&ctx) {
+                Some(cookie) => match self.decode(cookie) {
+                    Some(values) => Session::with_values(values),
+                    None => {
+                        // Start over, and have the rejected cookie deleted
+                        let session = Session::default();
+                        session.clear();
+                        session
+                    }
+                },
+                None => Session::default(),
+            };
+
+            let mut new_ctx = ctx;
+            new_ctx.extensions.insert(session);
+            Ok((new_ctx, MiddlewareResult::Continue))
+        })
+    }
+
+    fn after(&self, ctx: &Context<'_>, mut response: Response) -> Response {
+        if let Some(session) = ctx.extensions.get::<Session>().filter(|session| session.is_modified()) {
+            response.headers.push(("Set-Cookie".to_string(), self.set_cookie(session)));
+        }
+        response
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -637,6 +1824,38 @@ mod tests {
         assert!(new_ctx.extensions.get::<std::time::Instant>().is_some());
     }
 
+    #[tokio::test]
+    async fn test_debug_body_log_redacts() {
+        static LINES: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+        let request_bytes = b"POST /login HTTP/1.1\r\nHost: example.com\r\nAuthorization: Bearer s3cret-token\r\nContent-Type: application/json\r\n\r\n{\"user\":\"ada\",\"password\":\"hunter2\",\"profile\":{\"Password\":\"nested\"}}";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let body = &request_bytes[parsed.body_offset..];
+
+        let logger = DebugBodyLogMiddleware::new()
+            .max_body_bytes(100)
+            .sink(|line| LINES.lock().unwrap().push(line.to_string()));
+        let (ctx, _) = logger.call(Context::new(&parsed, body)).await.unwrap();
+        let response = ResponseBuilder::new().text("x".repeat(150)).finish();
+        logger.after(&ctx, response);
+
+        let lines = LINES.lock().unwrap();
+        let request_line = &lines[0];
+        assert!(request_line.starts_with("--> POST /login"));
+        assert!(request_line.contains("Authorization: [REDACTED]"));
+        assert!(request_line.contains("\"password\":\"[REDACTED]\""));
+        assert!(request_line.contains("\"Password\":\"[REDACTED]\""));
+        assert!(request_line.contains("\"user\":\"ada\""));
+        assert!(!request_line.contains("s3cret-token"));
+        assert!(!request_line.contains("hunter2"));
+        assert!(!request_line.contains("nested"));
+
+        // Response bodies are cut off at the configured size
+        assert!(lines[1].starts_with("<-- POST /login 200"));
+        assert!(lines[1].ends_with(&format!("{}... (50 bytes truncated)", "x".repeat(100))));
+    }
+
     #[tokio::test]
     async fn test_cors_middleware_preflight() {
         let request_bytes = b"OPTIONS /api HTTP/1.1\r\nHost: example.com\r\n\r\n";
@@ -658,6 +1877,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_cors_merges_vary() {
+        let request_bytes = b"GET /api HTTP/1.1\r\nHost: example.com\r\nOrigin: https://app.example\r\n\r\n";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let body = &request_bytes[parsed.body_offset..];
+
+        let mut ctx = Context::new(&parsed, body);
+        ctx.response = ctx.response.header("Vary", "Accept-Encoding");
+        let cors = CorsMiddleware::strict(CorsConfig {
+            origins: vec!["https://app.example".to_string()],
+            ..CorsConfig::default()
+        })
+        .unwrap();
+
+        let (ctx, _) = cors.call(ctx).await.unwrap();
+        let vary: Vec<_> = ctx.response.headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("vary")).collect();
+        assert_eq!(vary, [&("Vary".to_string(), "Accept-Encoding, Origin".to_string())]);
+    }
+
     #[tokio::test]
     async fn test_middleware_chain() {
         let request_bytes = b"GET /test HTTP/1.1\r\nHost: example.com\r\n\r\n";
@@ -676,4 +1915,305 @@ mod tests {
         // Should have CORS headers added
         assert!(response.headers.iter().any(|(k, _)| k == "Access-Control-Allow-Origin"));
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_basic_auth_middleware() {
+        let auth = BasicAuthMiddleware::with_credentials("admin", "alice", "s3cret");
+
+        // "alice:s3cret"
+        let request_bytes = b"GET /admin HTTP/1.1\r\nAuthorization: Basic YWxpY2U6czNjcmV0\r\n\r\n";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let ctx = Context::new(&parsed, &request_bytes[parsed.body_offset..]);
+
+        let (new_ctx, result) = auth.call(ctx).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+        assert_eq!(
+            new_ctx.extensions.get::<AuthenticatedUser>(),
+            Some(&AuthenticatedUser("alice".to_string()))
+        );
+
+        // "alice:wrong", then no header at all
+        let invalid: [&[u8]; 2] = [
+            b"GET /admin HTTP/1.1\r\nAuthorization: Basic YWxpY2U6d3Jvbmc=\r\n\r\n",
+            b"GET /admin HTTP/1.1\r\nHost: example.com\r\n\r\n",
+        ];
+        for request_bytes in invalid {
+            let parsed = parser.parse_request(request_bytes).unwrap();
+            let ctx = Context::new(&parsed, &request_bytes[parsed.body_offset..]);
+
+            match auth.call(ctx).await.unwrap() {
+                (_, MiddlewareResult::Response(response)) => {
+                    assert_eq!(response.status, 401);
+                    assert!(response.headers.contains(&(
+                        "WWW-Authenticate".to_string(),
+                        "Basic realm=\"admin\", charset=\"UTF-8\"".to_string()
+                    )));
+                }
+                _ => panic!("Expected 401 for invalid credentials"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231 test case 2
+        let mac = JwtAlgorithm::HS256.sign(b"Jefe", b"what do ya want for nothing?");
+        let hex: String = mac.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[tokio::test]
+    async fn test_jwt_middleware() {
+        let jwt = JwtMiddleware::new("top-secret", JwtAlgorithm::HS256);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let token = jwt.sign(&JwtClaims {
+            sub: Some("user-42".to_string()),
+            roles: vec!["admin".to_string()],
+            exp: Some(now + 60),
+            ..Default::default()
+        });
+        let request = format!("GET /api HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", token);
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request.as_bytes()).unwrap();
+        let ctx = Context::new(&parsed, &[]);
+
+        let (new_ctx, result) = jwt.call(ctx).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+        assert_eq!(new_ctx.user_id(), Some("user-42"));
+        assert!(new_ctx.has_role("admin"));
+        assert!(!new_ctx.has_role("billing"));
+
+        // Expired, tampered and foreign-key tokens are all rejected
+        let expired = jwt.sign(&JwtClaims {
+            sub: Some("user-42".to_string()),
+            exp: Some(now - 10),
+            ..Default::default()
+        });
+        let foreign = JwtMiddleware::new("other-key", JwtAlgorithm::HS256).sign(&JwtClaims::default());
+        assert_eq!(jwt.verify(&expired), Err(JwtError::Expired));
+        assert_eq!(jwt.verify(&foreign), Err(JwtError::InvalidSignature));
+        assert_eq!(
+            JwtMiddleware::new("top-secret", JwtAlgorithm::HS512).verify(&token),
+            Err(JwtError::AlgorithmMismatch)
+        );
+
+        let request = format!("GET /api HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", expired);
+        let parsed = parser.parse_request(request.as_bytes()).unwrap();
+        match jwt.call(Context::new(&parsed, &[])).await.unwrap() {
+            (_, MiddlewareResult::Response(response)) => assert_eq!(response.status, 401),
+            _ => panic!("Expected 401 for expired token"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id_middleware() {
+        let parser = HttpParser::new();
+        let middleware = RequestIdMiddleware::new();
+
+        // Generated when absent
+        let request_bytes = b"GET /test HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let (ctx, result) = middleware.call(Context::new(&parsed, &[])).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+
+        let generated = ctx.request_id().unwrap().to_string();
+        assert_eq!(generated.len(), 36);
+        let response = ctx.response.finish();
+        assert!(response.headers.contains(&("X-Request-Id".to_string(), generated)));
+
+        // Propagated when present
+        let request_bytes = b"GET /test HTTP/1.1\r\nX-Request-Id: abc-123\r\n\r\n";
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let (ctx, _) = middleware.call(Context::new(&parsed, &[])).await.unwrap();
+        assert_eq!(ctx.request_id(), Some("abc-123"));
+    }
+
+    #[tokio::test]
+    async fn test_after_hooks_run_in_reverse_order() {
+        struct Tag(&'static str);
+
+        impl Middleware for Tag {
+            fn call<'a>(&'a self, ctx: Context<'a>) -> MiddlewareFuture<'a> {
+                Box::pin(async move { Ok((ctx, MiddlewareResult::Continue)) })
+            }
+
+            fn after(&self, _ctx: &Context<'_>, mut response: Response) -> Response {
+                response.headers.push(("X-After".to_string(), self.0.to_string()));
+                response
+            }
+        }
+
+        let request_bytes = b"GET /test HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request_bytes).unwrap();
+
+        let chain = MiddlewareChain::new()
+            .use_middleware(Tag("outer"))
+            .use_middleware(Tag("inner"));
+
+        let response = chain.execute(Context::new(&parsed, &[])).await.unwrap();
+        let tags: Vec<&str> = response
+            .headers
+            .iter()
+            .filter(|(k, _)| k == "X-After")
+            .map(|(_, v)| v.as_str())
+            .collect();
+        assert_eq!(tags, vec!["inner", "outer"]);
+    }
+
+    #[tokio::test]
+    async fn test_cache_middleware_serves_hits() {
+        async fn send(chain: &MiddlewareChain, raw: &[u8], counter: &std::sync::atomic::AtomicUsize) -> Response {
+            let parsed = HttpParser::new().parse_request(raw).unwrap();
+            match chain.run(Context::new(&parsed, &[])).await.unwrap() {
+                ChainOutcome::Continue(ctx) => {
+                    let count = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                    let response = ResponseBuilder::new().text(count.to_string()).finish();
+                    chain.after(&ctx, response)
+                }
+                ChainOutcome::Response(response) => response,
+            }
+        }
+
+        fn x_cache(response: &Response) -> Option<&str> {
+            response.headers.iter().find(|(k, _)| k == "X-Cache").map(|(_, v)| v.as_str())
+        }
+
+        let chain = MiddlewareChain::new()
+            .use_middleware(CacheMiddleware::new(std::time::Duration::from_secs(60)).max_entries(1));
+        let counter = std::sync::atomic::AtomicUsize::new(0);
+
+        let first = send(&chain, b"GET /report?year=2024 HTTP/1.1\r\n\r\n", &counter).await;
+        assert_eq!(x_cache(&first), Some("MISS"));
+        let second = send(&chain, b"GET /report?year=2024 HTTP/1.1\r\n\r\n", &counter).await;
+        assert_eq!(x_cache(&second), Some("HIT"));
+        assert_eq!(second.body, b"1");
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Different query is a separate entry and evicts the first (max 1)
+        let other = send(&chain, b"GET /report?year=2025 HTTP/1.1\r\n\r\n", &counter).await;
+        assert_eq!(x_cache(&other), Some("MISS"));
+        let evicted = send(&chain, b"GET /report?year=2024 HTTP/1.1\r\n\r\n", &counter).await;
+        assert_eq!(x_cache(&evicted), Some("MISS"));
+
+        // Non-GET requests always run the handler and are never marked
+        send(&chain, b"POST /report HTTP/1.1\r\n\r\n", &counter).await;
+        let post = send(&chain, b"POST /report HTTP/1.1\r\n\r\n", &counter).await;
+        assert_eq!(x_cache(&post), None);
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cache_middleware_never_shares_private_responses() {
+        async fn send(chain: &MiddlewareChain, raw: &[u8], response: Response) -> (Response, bool) {
+            let parsed = HttpParser::new().parse_request(raw).unwrap();
+            match chain.run(Context::new(&parsed, &[])).await.unwrap() {
+                ChainOutcome::Continue(ctx) => (chain.after(&ctx, response), true),
+                ChainOutcome::Response(response) => (response, false),
+            }
+        }
+
+        let chain = MiddlewareChain::new().use_middleware(CacheMiddleware::new(std::time::Duration::from_secs(60)));
+        let page = || ResponseBuilder::new().text("page").finish();
+
+        // Credentialed requests bypass the cache entirely
+        for raw in [
+            &b"GET /me HTTP/1.1\r\nAuthorization: Bearer alice\r\n\r\n"[..],
+            &b"GET /me HTTP/1.1\r\nCookie: sid=alice\r\n\r\n"[..],
+        ] {
+            for _ in 0..2 {
+                let (response, ran) = send(&chain, raw, page()).await;
+                assert!(ran);
+                assert!(!response.headers.iter().any(|(k, _)| k == "X-Cache"));
+            }
+        }
+
+        // Responses that set cookies or opt out of shared caching are not stored
+        let private = [
+            ("Set-Cookie", "sid=alice"),
+            ("Cache-Control", "max-age=60, private"),
+            ("Cache-Control", "no-store"),
+            ("Vary", "*"),
+        ];
+        for (i, (name, value)) in private.into_iter().enumerate() {
+            let raw = format!("GET /account/{} HTTP/1.1\r\n\r\n", i);
+            let response = ResponseBuilder::new().header(name, value).text("alice").finish();
+            send(&chain, raw.as_bytes(), response).await;
+            let (response, ran) = send(&chain, raw.as_bytes(), page()).await;
+            assert!(ran, "{}: {} was cached", name, value);
+            assert_eq!(response.body, b"page");
+        }
+
+        // Vary keeps variants apart
+        let english = ResponseBuilder::new().header("Vary", "Accept-Language").text("hello").finish();
+        send(&chain, b"GET /greet HTTP/1.1\r\nAccept-Language: en\r\n\r\n", english).await;
+        let (hit, ran) = send(&chain, b"GET /greet HTTP/1.1\r\nAccept-Language: en\r\n\r\n", page()).await;
+        assert!(!ran);
+        assert_eq!(hit.body, b"hello");
+        let (_, ran) = send(&chain, b"GET /greet HTTP/1.1\r\nAccept-Language: fr\r\n\r\n", page()).await;
+        assert!(ran);
+    }
+
+    #[tokio::test]
+    async fn test_session_round_trips_through_cookie() {
+        // Counts visits in the session, like a handler would
+        async fn visit(chain: &MiddlewareChain, cookie: Option<&str>) -> (Option<String>, Option<String>) {
+            let raw = match cookie {
+                Some(cookie) => format!("GET / HTTP/1.1\r\nCookie: theme=dark; {}\r\n\r\n", cookie),
+                None => "GET / HTTP/1.1\r\n\r\n".to_string(),
+            };
+            let parsed = HttpParser::new().parse_request(raw.as_bytes()).unwrap();
+            let ChainOutcome::Continue(ctx) = chain.run(Context::new(&parsed, &[])).await.unwrap() else {
+                panic!("session middleware never answers");
+            };
+
+            let session = ctx.extensions.get::<Session>().unwrap();
+            let seen = session.get("visits");
+            let visits = seen.as_deref().map_or(0, |v| v.parse::<u32>().unwrap()) + 1;
+            session.insert("visits", visits.to_string());
+
+            let response = chain.after(&ctx, ResponseBuilder::new().finish());
+            let set_cookie = response
+                .headers
+                .iter()
+                .find(|(k, _)| k == "Set-Cookie")
+                .map(|(_, v)| v.split(';').next().unwrap().to_string());
+            (seen, set_cookie)
+        }
+
+        let chain = MiddlewareChain::new().use_middleware(SessionMiddleware::new("secret").secure(false));
+
+        let (seen, cookie) = visit(&chain, None).await;
+        assert_eq!(seen, None);
+        let cookie = cookie.expect("modified session sets a cookie");
+        assert!(cookie.starts_with("zap_session="));
+
+        let (seen, _) = visit(&chain, Some(&cookie)).await;
+        assert_eq!(seen.as_deref(), Some("1"));
+
+        // A tampered payload is rejected and the session starts over
+        let forged = SessionMiddleware::new("secret").encode(&[("visits".to_string(), "99".to_string())].into());
+        let (payload, _) = forged.split_once('.').unwrap();
+        let (_, signature) = cookie.split_once('.').unwrap();
+        let tampered = format!("zap_session={}.{}", payload, signature);
+        let (seen, _) = visit(&chain, Some(&tampered)).await;
+        assert_eq!(seen, None);
+
+        // So is a cookie signed with another key
+        let other = SessionMiddleware::new("other").encode(&[("visits".to_string(), "5".to_string())].into());
+        let (seen, _) = visit(&chain, Some(&format!("zap_session={}", other))).await;
+        assert_eq!(seen, None);
+    }
+}
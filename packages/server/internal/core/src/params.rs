@@ -28,10 +28,29 @@ impl<'a> Params<'a> {
         }
     }
 
-    /// Insert a parameter (internal use only)
+    /// Insert a parameter, returning the value it replaced (internal use only)
     #[inline]
-    pub(crate) fn insert(&mut self, key: &'a str, value: &'a str) {
-        self.inner.insert(key, value);
+    pub(crate) fn insert(&mut self, key: &'a str, value: &'a str) -> Option<&'a str> {
+        self.inner.insert(key, value)
+    }
+
+    /// Undo an [`insert`](Self::insert) given the value it returned (internal use only)
+    #[inline]
+    pub(crate) fn restore(&mut self, key: &'a str, previous: Option<&'a str>) {
+        match previous {
+            Some(value) => {
+                self.inner.insert(key, value);
+            }
+            None => {
+                self.inner.remove(key);
+            }
+        }
+    }
+
+    /// Remove all parameters, keeping the allocated capacity
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
     }
 
     /// Get parameter value by name
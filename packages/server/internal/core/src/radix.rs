@@ -3,10 +3,14 @@
 use crate::params::Params;
 use memchr::memchr;
 
+/// Default cap on the parameters a single route may capture
+pub const DEFAULT_MAX_PARAMS: usize = 32;
+
 /// High-performance radix tree for route matching
 pub struct RadixTree<T> {
     root: Node<T>,
     size: usize,
+    max_params: usize,
 }
 
 /// Tree node optimized for routing
@@ -41,29 +45,89 @@ impl<T> Node<T> {
 impl<T> RadixTree<T> {
     /// Create new radix tree
     pub fn new() -> Self {
+        Self::with_max_params(DEFAULT_MAX_PARAMS)
+    }
+
+    /// Create a tree whose routes may capture at most `max_params` parameters
+    ///
+    /// Lookups only capture along the matched route, so this also bounds the
+    /// parameter storage `find` needs, however many segments a request has.
+    pub fn with_max_params(max_params: usize) -> Self {
         Self {
             root: Node::new(String::new()),
             size: 0,
+            max_params,
         }
     }
 
+    /// Maximum number of parameters a route may capture
+    pub fn max_params(&self) -> usize {
+        self.max_params
+    }
+
     /// Insert route into tree
+    ///
+    /// Patterns are made of static segments, `:name` parameters that capture
+    /// exactly one segment and may appear anywhere (`/files/:a/thumb/:b`), and
+    /// `*name` / `**name` wildcards that capture the rest of the path. A
+    /// wildcard must be the last segment; `/api/*version/users` fails with
+    /// `InvalidPath`, use `/api/:version/users` instead.
+    ///
+    /// Fails with `ConflictingRoute` when the pattern would match the same paths
+    /// as an existing one under a different name, e.g. `/users/:id` and
+    /// `/users/:name`, or `*path` and `**path` at the same position. Static
+    /// segments may overlap parameters since static matches take precedence.
     pub fn insert(&mut self, path: &str, handler: T) -> Result<(), crate::RouterError> {
         if path.is_empty() || !path.starts_with('/') {
             return Err(crate::RouterError::InvalidPath(path.to_string()));
         }
 
         let segments = parse_path(path);
-        self.insert_segments(&segments, handler)?;
+        let captures = segments.iter().filter(|s| !matches!(s, Segment::Static(_))).count();
+        if captures > self.max_params {
+            return Err(crate::RouterError::InvalidParameter(format!(
+                "{} captures {} parameters, the limit is {}",
+                path, captures, self.max_params
+            )));
+        }
+        // Wildcards consume the rest of the path, so nothing may follow them
+        for segment in &segments[..segments.len().saturating_sub(1)] {
+            let wildcard = match segment {
+                Segment::Wildcard(name) => format!("*{}", name),
+                Segment::CatchAll(name) => format!("**{}", name),
+                _ => continue,
+            };
+            return Err(crate::RouterError::InvalidPath(format!(
+                "{} (wildcard {} must be the last segment; use :{} for a single segment)",
+                path, wildcard, wildcard.trim_start_matches('*')
+            )));
+        }
+        self.insert_segments(path, &segments, handler)?;
         self.size += 1;
         Ok(())
     }
 
     /// Find handler for path with parameter extraction
+    ///
+    /// At each node, static children are tried before the parameter child, and
+    /// the parameter child before wildcards and catch-alls. If a more specific
+    /// branch fails to match further down, the search backtracks, so
+    /// `/files/meta/:id` wins over `/files/*path` for `/files/meta/7` while
+    /// `/files/meta` still falls back to the wildcard.
     pub fn find<'a>(&'a self, path: &'a str) -> Option<(&'a T, Params<'a>)> {
         let mut params = Params::new();
+        self.find_into(path, &mut params).map(|handler| (handler, params))
+    }
+
+    /// Find handler for path, writing parameters into a caller-owned buffer
+    ///
+    /// `params` is cleared first and keeps its capacity, so reusing one buffer
+    /// across lookups avoids allocating once it has grown to fit the routes.
+    /// On a miss the buffer is left empty.
+    pub fn find_into<'a>(&'a self, path: &'a str, params: &mut Params<'a>) -> Option<&'a T> {
+        params.clear();
         let clean_path = path.strip_prefix('/').unwrap_or(path);
-        Self::find_recursive_with_position(path, clean_path, &self.root, &mut params)
+        Self::find_recursive_with_position(path, clean_path, &self.root, params)
     }
 
     /// Get number of routes
@@ -76,22 +140,77 @@ impl<T> RadixTree<T> {
         self.size == 0
     }
 
+    /// Each route's pattern and handler, without consuming the tree
+    ///
+    /// Patterns are rebuilt as in [`RadixTree::into_routes`].
+    pub fn routes(&self) -> Vec<(String, &T)> {
+        let mut routes = Vec::with_capacity(self.size);
+        Self::collect_route_refs(&self.root, "", &mut routes);
+        routes
+    }
+
+    fn collect_route_refs<'a>(node: &'a Node<T>, prefix: &str, routes: &mut Vec<(String, &'a T)>) {
+        let path = if node.segment.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}/{}", prefix, node.segment)
+        };
+        if let Some(handler) = &node.handler {
+            let pattern = if path.is_empty() { "/".to_string() } else { path.clone() };
+            routes.push((pattern, handler));
+        }
+
+        let dynamic = [&node.param_child, &node.wildcard_child, &node.catchall_child];
+        for child in node.children.iter().chain(dynamic.into_iter().flatten().map(|(_, child)| child.as_ref())) {
+            Self::collect_route_refs(child, &path, routes);
+        }
+    }
+
+    /// Consume the tree, returning each route's pattern and handler
+    ///
+    /// Patterns are rebuilt from the tree's segments, so `/users/` comes back
+    /// as `/users`.
+    pub fn into_routes(self) -> Vec<(String, T)> {
+        let mut routes = Vec::with_capacity(self.size);
+        Self::collect_routes(self.root, "", &mut routes);
+        routes
+    }
+
+    fn collect_routes(node: Node<T>, prefix: &str, routes: &mut Vec<(String, T)>) {
+        let path = if node.segment.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}/{}", prefix, node.segment)
+        };
+        if let Some(handler) = node.handler {
+            let pattern = if path.is_empty() { "/".to_string() } else { path.clone() };
+            routes.push((pattern, handler));
+        }
+
+        let dynamic = [node.param_child, node.wildcard_child, node.catchall_child];
+        for child in node.children.into_iter().chain(dynamic.into_iter().flatten().map(|(_, child)| *child)) {
+            Self::collect_routes(child, &path, routes);
+        }
+    }
+
     fn insert_segments(
         &mut self,
+        path: &str,
         segments: &[Segment],
         handler: T,
     ) -> Result<(), crate::RouterError> {
-        Self::insert_segments_recursive(segments, handler, &mut self.root)
+        Self::insert_segments_recursive(path, segments, handler, &mut self.root)
     }
 
     fn insert_segments_recursive(
+        path: &str,
         segments: &[Segment],
         handler: T,
         node: &mut Node<T>,
     ) -> Result<(), crate::RouterError> {
         if segments.is_empty() {
             if node.handler.is_some() {
-                return Err(crate::RouterError::DuplicateRoute("Route exists".to_string()));
+                return Err(crate::RouterError::DuplicateRoute(path.to_string()));
             }
             node.handler = Some(handler);
             return Ok(());
@@ -105,37 +224,65 @@ impl<T> RadixTree<T> {
                 // Find or create static child
                 let child_pos = node.children.iter().position(|c| &c.segment == s);
                 if let Some(pos) = child_pos {
-                    Self::insert_segments_recursive(remaining, handler, &mut node.children[pos])
+                    Self::insert_segments_recursive(path, remaining, handler, &mut node.children[pos])
                 } else {
                     let mut child = Node::new(s.clone());
-                    Self::insert_segments_recursive(remaining, handler, &mut child)?;
+                    Self::insert_segments_recursive(path, remaining, handler, &mut child)?;
                     node.children.push(child);
                     Ok(())
                 }
             }
             Segment::Param(name) => {
+                if let Some((existing, _)) = &node.param_child {
+                    if existing != name {
+                        return Err(crate::RouterError::ConflictingRoute(format!(
+                            "{} (parameter :{} conflicts with existing :{})",
+                            path, name, existing
+                        )));
+                    }
+                }
                 if node.param_child.is_none() {
                     node.param_child = Some((name.clone(), Box::new(Node::new(format!(":{}", name)))));
                 }
                 if let Some((_, ref mut child)) = node.param_child {
-                    Self::insert_segments_recursive(remaining, handler, child)
+                    Self::insert_segments_recursive(path, remaining, handler, child)
                 } else {
                     unreachable!()
                 }
             }
             Segment::Wildcard(name) => {
+                if let Some((existing, _)) = &node.wildcard_child {
+                    if existing != name {
+                        return Err(crate::RouterError::ConflictingRoute(format!(
+                            "{} (wildcard *{} conflicts with existing *{})",
+                            path, name, existing
+                        )));
+                    }
+                }
+                if let Some((existing, _)) = &node.catchall_child {
+                    return Err(crate::RouterError::ConflictingRoute(format!(
+                        "{} (wildcard *{} conflicts with existing catch-all **{})",
+                        path, name, existing
+                    )));
+                }
                 if node.wildcard_child.is_none() {
                     node.wildcard_child = Some((name.clone(), Box::new(Node::new(format!("*{}", name)))));
                 }
                 if let Some((_, ref mut child)) = node.wildcard_child {
-                    Self::insert_segments_recursive(remaining, handler, child)
+                    Self::insert_segments_recursive(path, remaining, handler, child)
                 } else {
                     unreachable!()
                 }
             }
             Segment::CatchAll(name) => {
                 if node.catchall_child.is_some() {
-                    return Err(crate::RouterError::DuplicateRoute("Catch-all exists".to_string()));
+                    return Err(crate::RouterError::DuplicateRoute(path.to_string()));
+                }
+                if let Some((existing, _)) = &node.wildcard_child {
+                    return Err(crate::RouterError::ConflictingRoute(format!(
+                        "{} (catch-all **{} conflicts with existing wildcard *{})",
+                        path, name, existing
+                    )));
                 }
                 let mut child = Node::new(format!("**{}", name));
                 child.handler = Some(handler);
@@ -145,15 +292,20 @@ impl<T> RadixTree<T> {
         }
     }
 
+    /// Depth-first match with backtracking
+    ///
+    /// Parameters are written into `params` in place; a branch that fails to
+    /// match restores whatever it overwrote, so `params` only holds the
+    /// captures of the successful path.
     fn find_recursive_with_position<'a>(
         original_path: &'a str,
         current_path: &'a str,
         node: &'a Node<T>,
         params: &mut Params<'a>,
-    ) -> Option<(&'a T, Params<'a>)> {
+    ) -> Option<&'a T> {
         // Check if we've consumed the path
         if current_path.is_empty() {
-            return node.handler.as_ref().map(|h| (h, params.clone()));
+            return node.handler.as_ref();
         }
 
         // Find next segment
@@ -172,46 +324,44 @@ impl<T> RadixTree<T> {
             }
         }
 
-        // Try parameter child
+        // Try parameter child (only after every static child failed)
         if let Some((name, ref child)) = &node.param_child {
-            let mut new_params = params.clone();
-            new_params.insert(name, segment);
-            let result = Self::find_recursive_with_position(original_path, remaining, child, &mut new_params);
+            let previous = params.insert(name, segment);
+            let result = Self::find_recursive_with_position(original_path, remaining, child, params);
             if result.is_some() {
                 return result;
             }
+            params.restore(name, previous);
         }
 
         // Try wildcard child
         if let Some((name, ref child)) = &node.wildcard_child {
-            let mut new_params = params.clone();
-            // Calculate the wildcard value by finding the position in the original path
-            let clean_path_len = if original_path.starts_with('/') { 
-                original_path.len() - 1 
-            } else { 
-                original_path.len() 
-            };
-            let current_path_len = current_path.len();
-            let consumed_in_clean = clean_path_len - current_path_len;
-            
-            let wildcard_start = if original_path.starts_with('/') {
-                consumed_in_clean + 1 // Account for the leading slash
-            } else {
-                consumed_in_clean
-            };
-            
-            let wildcard_value = &original_path[wildcard_start..];
-            new_params.insert(name, wildcard_value);
-            
             // Wildcards consume the rest of the path, so check for handler directly
-            return child.handler.as_ref().map(|h| (h, new_params));
+            if let Some(h) = child.handler.as_ref() {
+                // Calculate the wildcard value by finding the position in the original path
+                let clean_path_len = if original_path.starts_with('/') {
+                    original_path.len() - 1
+                } else {
+                    original_path.len()
+                };
+                let consumed_in_clean = clean_path_len - current_path.len();
+
+                let wildcard_start = if original_path.starts_with('/') {
+                    consumed_in_clean + 1 // Account for the leading slash
+                } else {
+                    consumed_in_clean
+                };
+
+                params.insert(name, &original_path[wildcard_start..]);
+                return Some(h);
+            }
         }
 
-        // Try catch-all child
+        // Try catch-all child (least specific)
         if let Some((name, ref child)) = &node.catchall_child {
-            let mut new_params = params.clone();
-            new_params.insert(name, current_path);
-            return child.handler.as_ref().map(|h| (h, new_params));
+            let h = child.handler.as_ref()?;
+            params.insert(name, current_path);
+            return Some(h);
         }
 
         None
@@ -261,6 +411,60 @@ fn parse_path(path: &str) -> Vec<Segment> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_into_routes() {
+        let mut tree = RadixTree::new();
+        for path in ["/", "/users/", "/users/:id", "/users/:id/posts", "/files/*path", "/docs/**rest"] {
+            tree.insert(path, path).unwrap();
+        }
+
+        let mut routes = tree.into_routes();
+        routes.sort();
+        let patterns: Vec<&str> = routes.iter().map(|(pattern, _)| pattern.as_str()).collect();
+        assert_eq!(patterns, ["/", "/docs/**rest", "/files/*path", "/users", "/users/:id", "/users/:id/posts"]);
+        assert!(routes.iter().any(|(pattern, handler)| pattern == "/users" && *handler == "/users/"));
+
+        let mut tree = RadixTree::new();
+        tree.insert("/users/:id", 1).unwrap();
+        tree.insert("/files/*path", 2).unwrap();
+        let mut borrowed = tree.routes();
+        borrowed.sort();
+        assert_eq!(borrowed, [("/files/*path".to_string(), &2), ("/users/:id".to_string(), &1)]);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_find_into_reuses_buffer() {
+        let mut tree = RadixTree::new();
+        tree.insert("/users/:id", "user").unwrap();
+        tree.insert("/users/:id/posts/:post_id", "post").unwrap();
+        tree.insert("/users/:id/settings", "settings").unwrap();
+        tree.insert("/files/*path", "file").unwrap();
+
+        let mut params = Params::with_capacity(4);
+
+        assert_eq!(tree.find_into("/users/42/posts/7", &mut params), Some(&"post"));
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.get("post_id"), Some("7"));
+
+        // Previous captures are cleared before the next lookup
+        assert_eq!(tree.find_into("/users/9", &mut params), Some(&"user"));
+        assert_eq!(params.len(), 1);
+        assert_eq!(params.get("id"), Some("9"));
+
+        // A failed branch leaves nothing behind
+        assert_eq!(tree.find_into("/users/9/unknown", &mut params), None);
+        assert!(params.is_empty());
+
+        assert_eq!(tree.find_into("/files/a/b.txt", &mut params), Some(&"file"));
+        assert_eq!(params.get("path"), Some("a/b.txt"));
+
+        // Same results as the allocating lookup
+        let (handler, found) = tree.find("/users/42/posts/7").unwrap();
+        assert_eq!(handler, &"post");
+        assert_eq!(found.get("post_id"), Some("7"));
+    }
+
     #[test]
     fn test_static_routes() {
         let mut tree = RadixTree::new();
@@ -299,4 +503,101 @@ mod tests {
         assert_eq!(handler, &"catch_all");
         assert_eq!(params.get("path"), Some("v1/users/123"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_conflicting_param_names() {
+        let mut tree = RadixTree::new();
+        tree.insert("/users/:id", "by_id").unwrap();
+
+        let err = tree.insert("/users/:name", "by_name").unwrap_err();
+        assert!(matches!(err, crate::RouterError::ConflictingRoute(_)));
+
+        // Same name deeper in the tree is fine
+        tree.insert("/users/:id/posts", "posts").unwrap();
+        let err = tree.insert("/users/:user_id/comments", "comments").unwrap_err();
+        assert!(matches!(err, crate::RouterError::ConflictingRoute(_)));
+
+        // Failed inserts don't count or replace the original route
+        assert_eq!(tree.len(), 2);
+        let (handler, params) = tree.find("/users/42").unwrap();
+        assert_eq!(handler, &"by_id");
+        assert_eq!(params.get("id"), Some("42"));
+    }
+
+    #[test]
+    fn test_conflicting_wildcards() {
+        let mut tree = RadixTree::new();
+        tree.insert("/files/*path", "files").unwrap();
+        assert!(matches!(
+            tree.insert("/files/*rest", "other"),
+            Err(crate::RouterError::ConflictingRoute(_))
+        ));
+        assert!(matches!(
+            tree.insert("/files/**all", "catch_all"),
+            Err(crate::RouterError::ConflictingRoute(_))
+        ));
+        assert!(matches!(
+            tree.insert("/files/*path", "dup"),
+            Err(crate::RouterError::DuplicateRoute(_))
+        ));
+    }
+
+    #[test]
+    fn test_static_and_param_overlap_allowed() {
+        let mut tree = RadixTree::new();
+        tree.insert("/users/:id", "get_user").unwrap();
+        tree.insert("/users/me", "current_user").unwrap();
+
+        assert_eq!(tree.find("/users/me").unwrap().0, &"current_user");
+        assert_eq!(tree.find("/users/7").unwrap().0, &"get_user");
+    }
+
+    #[test]
+    fn test_params_anywhere_wildcards_last() {
+        let mut tree = RadixTree::new();
+        tree.insert("/files/:a/thumb/:b", "thumb").unwrap();
+
+        let (handler, params) = tree.find("/files/x/thumb/y").unwrap();
+        assert_eq!(handler, &"thumb");
+        assert_eq!(params.get("a"), Some("x"));
+        assert_eq!(params.get("b"), Some("y"));
+
+        // Multi-segment wildcards are only allowed at the end
+        for path in ["/api/*version/users", "/api/**rest/users"] {
+            assert!(matches!(
+                tree.insert(path, "bad"),
+                Err(crate::RouterError::InvalidPath(ref msg)) if msg.contains("must be the last segment")
+            ));
+        }
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_param_count_cap() {
+        let route = |count: usize| {
+            (0..count).map(|i| format!("/:p{}", i)).collect::<String>()
+        };
+
+        let mut tree = RadixTree::with_max_params(4);
+        tree.insert(&route(4), "at_cap").unwrap();
+        assert!(matches!(
+            tree.insert(&format!("/x{}", route(5)), "over_cap"),
+            Err(crate::RouterError::InvalidParameter(_))
+        ));
+        // Wildcards count towards the cap too
+        assert!(tree.insert("/y/:a/:b/:c/:d/*rest", "over_cap").is_err());
+        assert_eq!(tree.len(), 1);
+
+        let (handler, params) = tree.find("/a/b/c/d").unwrap();
+        assert_eq!(handler, &"at_cap");
+        assert_eq!(params.len(), 4);
+
+        // A long adversarial path captures nothing beyond the route's own params
+        let long_path = "/seg".repeat(10_000);
+        let mut params = Params::new();
+        assert_eq!(tree.find_into(&long_path, &mut params), None);
+        assert!(params.is_empty());
+
+        assert_eq!(RadixTree::<()>::new().max_params(), DEFAULT_MAX_PARAMS);
+    }
+}
@@ -176,13 +176,18 @@ impl RateLimitStore for InMemoryStore {
     }
 }
 
+/// Derives the bucket a request is counted against from its context
+pub type RateLimitKeyFn = Box<dyn Fn(&Context<'_>) -> String + Send + Sync>;
+
 /// Rate Limiting Middleware
 ///
-/// Limits requests based on client IP address.
+/// Limits requests based on client IP address by default; see
+/// [`RateLimitMiddleware::key_fn`] and [`RateLimitMiddleware::key_by_user`].
 /// Returns 429 Too Many Requests when limit is exceeded.
 pub struct RateLimitMiddleware {
     config: RateLimitConfig,
     store: Arc<dyn RateLimitStore>,
+    key_fn: Option<RateLimitKeyFn>,
 }
 
 impl RateLimitMiddleware {
@@ -190,12 +195,12 @@ impl RateLimitMiddleware {
     pub fn new(config: RateLimitConfig) -> Self {
         let store: Arc<dyn RateLimitStore> =
             Arc::new(InMemoryStore::new(config.window_secs));
-        Self { config, store }
+        Self { config, store, key_fn: None }
     }
 
     /// Create rate limit middleware with custom storage backend
     pub fn with_store(config: RateLimitConfig, store: Arc<dyn RateLimitStore>) -> Self {
-        Self { config, store }
+        Self { config, store, key_fn: None }
     }
 
     /// Create with default configuration (100 req/min)
@@ -227,6 +232,28 @@ impl RateLimitMiddleware {
         self
     }
 
+    /// Builder: Bucket requests by a custom key instead of client IP
+    ///
+    /// Counters are still kept per path, so the key only has to identify the caller.
+    pub fn key_fn<F>(mut self, key_fn: F) -> Self
+    where
+        F: Fn(&Context<'_>) -> String + Send + Sync + 'static,
+    {
+        self.key_fn = Some(Box::new(key_fn));
+        self
+    }
+
+    /// Builder: Bucket authenticated requests by user ID, anonymous ones by IP
+    ///
+    /// Users behind the same NAT no longer share a budget. The user ID comes from
+    /// [`Context::user_id`], so the auth middleware must run first.
+    pub fn key_by_user(self) -> Self {
+        self.key_fn(|ctx| match ctx.user_id() {
+            Some(user_id) => format!("user:{}", user_id),
+            None => format!("ip:{}", Self::extract_client_ip(ctx)),
+        })
+    }
+
     /// Extract client IP from request context
     fn extract_client_ip(ctx: &Context) -> String {
         // Check X-Forwarded-For first (for proxied requests)
@@ -270,8 +297,11 @@ impl Middleware for RateLimitMiddleware {
                 return Ok((ctx, MiddlewareResult::Continue));
             }
 
-            let client_ip = Self::extract_client_ip(&ctx);
-            let key = format!("{}:{}", ctx.path(), client_ip);
+            let client = match &self.key_fn {
+                Some(key_fn) => key_fn(&ctx),
+                None => Self::extract_client_ip(&ctx),
+            };
+            let key = format!("{}:{}", ctx.path(), client);
 
             match self.store.increment(&key, self.config.window_secs).await {
                 Ok((count, remaining_secs)) => {
@@ -419,6 +449,43 @@ mod tests {
         assert!(matches!(result, MiddlewareResult::Continue));
     }
 
+    #[tokio::test]
+    async fn test_key_by_user_separates_buckets() {
+        use crate::middleware::AuthenticatedUser;
+
+        let request_bytes = b"GET /api/test HTTP/1.1\r\nHost: example.com\r\nX-Forwarded-For: 203.0.113.7\r\n\r\n";
+        let parser = HttpParser::new();
+        let parsed = parser.parse_request(request_bytes).unwrap();
+        let body = &request_bytes[parsed.body_offset..];
+
+        let middleware = RateLimitMiddleware::new(RateLimitConfig {
+            max_requests: 1,
+            window_secs: 60,
+            ..Default::default()
+        })
+        .key_by_user();
+
+        let authenticated = |user: &str| {
+            let mut ctx = Context::new(&parsed, body);
+            ctx.extensions.insert(AuthenticatedUser(user.to_string()));
+            ctx
+        };
+
+        // Anonymous and authenticated callers behind the same IP get their own budgets
+        let (_, result) = middleware.call(Context::new(&parsed, body)).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+        let (_, result) = middleware.call(authenticated("alice")).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+        let (_, result) = middleware.call(authenticated("bob")).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Continue));
+
+        // Each bucket is now exhausted independently
+        let (_, result) = middleware.call(authenticated("alice")).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Response(ref r) if r.status == 429));
+        let (_, result) = middleware.call(Context::new(&parsed, body)).await.unwrap();
+        assert!(matches!(result, MiddlewareResult::Response(ref r) if r.status == 429));
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = RateLimitConfig::default();
@@ -6,8 +6,12 @@
 use crate::http::{ParsedRequest, Headers};
 use crate::params::Params;
 use crate::method::Method;
+use crate::middleware::{JwtClaims, Session};
+use crate::state::AppState;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::str;
+use std::sync::Arc;
 
 /// High-level HTTP request object
 #[derive(Debug)]
@@ -18,6 +22,16 @@ pub struct Request<'a> {
     body: &'a [u8],
     /// Route parameters (e.g., from "/users/:id")
     params: Params<'a>,
+    /// Resolved client IP (peer address or trusted forwarding header)
+    client_ip: Option<IpAddr>,
+    /// Shared application state registered on the server
+    state: Option<Arc<AppState>>,
+    /// Session loaded by `SessionMiddleware`
+    session: Option<Session>,
+    /// Claims verified by `JwtMiddleware`
+    claims: Option<JwtClaims>,
+    /// ID assigned by `RequestIdMiddleware`
+    request_id: Option<String>,
 }
 
 impl<'a> Request<'a> {
@@ -27,9 +41,77 @@ impl<'a> Request<'a> {
             parsed,
             body,
             params,
+            client_ip: None,
+            state: None,
+            session: None,
+            claims: None,
+            request_id: None,
         }
     }
 
+    /// Attach the resolved client IP address
+    pub fn with_client_ip(mut self, ip: IpAddr) -> Self {
+        self.client_ip = Some(ip);
+        self
+    }
+
+    /// Attach the server's shared application state
+    pub fn with_state(mut self, state: Arc<AppState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Attach the session loaded by `SessionMiddleware`
+    pub fn with_session(mut self, session: Session) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Get the session, if `SessionMiddleware` is installed
+    #[inline]
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Attach the claims verified by `JwtMiddleware`
+    pub fn with_claims(mut self, claims: JwtClaims) -> Self {
+        self.claims = Some(claims);
+        self
+    }
+
+    /// Get the verified JWT claims, if `JwtMiddleware` accepted a token
+    #[inline]
+    pub fn claims(&self) -> Option<&JwtClaims> {
+        self.claims.as_ref()
+    }
+
+    /// Attach the ID assigned by `RequestIdMiddleware`
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Get the request ID, if `RequestIdMiddleware` is installed
+    #[inline]
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Get the shared application state container, if attached
+    #[inline]
+    pub fn app_state(&self) -> Option<&Arc<AppState>> {
+        self.state.as_ref()
+    }
+
+    /// Get the resolved client IP address
+    ///
+    /// Set by the server from the socket peer address, honoring forwarding
+    /// headers only when the peer is a trusted proxy.
+    #[inline]
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
     /// Get HTTP method
     #[inline]
     pub fn method(&self) -> Method {
@@ -109,7 +191,7 @@ impl<'a> Request<'a> {
     /// Check if connection should be kept alive
     #[inline]
     pub fn keep_alive(&self) -> bool {
-        self.parsed.headers.keep_alive()
+        self.parsed.keep_alive()
     }
 
     /// Get User-Agent header
@@ -180,12 +262,12 @@ impl<'a> Request<'a> {
 
     /// Get cookie value by name
     pub fn cookie(&self, name: &str) -> Option<&'a str> {
-        parse_cookies(self.parsed.headers.get("Cookie")).get(name).copied()
+        self.cookies().get(name).copied()
     }
 
     /// Get all cookies
     pub fn cookies(&self) -> HashMap<&'a str, &'a str> {
-        parse_cookies(self.parsed.headers.get("Cookie"))
+        self.parsed.headers.get("Cookie").map(parse_cookie_header).unwrap_or_default()
     }
 }
 
@@ -209,39 +291,101 @@ fn parse_query_string(path: &str) -> HashMap<&str, &str> {
     params
 }
 
-/// Parse cookies from Cookie header
-fn parse_cookies(cookie_header: Option<&str>) -> HashMap<&str, &str> {
+/// Most cookies read from a single `Cookie` header
+pub const MAX_COOKIES: usize = 64;
+
+/// Bytes of a `Cookie` header that are parsed; anything beyond is ignored
+pub const MAX_COOKIE_HEADER_LEN: usize = 8 * 1024;
+
+/// Parse a `Cookie` header into a name -> value map
+///
+/// Reads at most [`MAX_COOKIES`] pairs from the first
+/// [`MAX_COOKIE_HEADER_LEN`] bytes. Pairs without `=` or with an invalid name
+/// are skipped, quoted values are unquoted, and names (including `__Host-` /
+/// `__Secure-` prefixes) are kept verbatim. The first occurrence of a name wins,
+/// as browsers send the most specific cookie first.
+pub fn parse_cookie_header(header: &str) -> HashMap<&str, &str> {
     let mut cookies = HashMap::new();
-    
-    if let Some(header) = cookie_header {
-        for cookie in header.split(';') {
-            let cookie = cookie.trim();
-            if let Some(eq_pos) = cookie.find('=') {
-                let name = cookie[..eq_pos].trim();
-                let value = cookie[eq_pos + 1..].trim();
-                cookies.insert(name, value);
-            }
+    let mut consumed = 0;
+
+    for pair in header.split(';') {
+        consumed += pair.len() + 1;
+        if consumed > MAX_COOKIE_HEADER_LEN + 1 || cookies.len() >= MAX_COOKIES {
+            break;
+        }
+
+        let Some((name, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.is_empty() || !name.bytes().all(is_cookie_name_byte) {
+            continue;
         }
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+
+        cookies.entry(name).or_insert(value);
     }
-    
+
     cookies
 }
 
+/// RFC 6265 cookie names are HTTP tokens
+fn is_cookie_name_byte(byte: u8) -> bool {
+    byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&byte)
+}
+
 /// Parse form data (application/x-www-form-urlencoded)
 fn parse_form_data(data: &str) -> HashMap<&str, &str> {
-    let mut params = HashMap::new();
-    
-    for pair in data.split('&') {
-        if let Some(eq_pos) = pair.find('=') {
-            let key = &pair[..eq_pos];
-            let value = &pair[eq_pos + 1..];
-            params.insert(key, value);
-        } else if !pair.is_empty() {
-            params.insert(pair, "");
+    form_pairs(data).collect()
+}
+
+/// Split form data into still-encoded key-value pairs, skipping empty pairs
+fn form_pairs(data: &str) -> impl Iterator<Item = (&str, &str)> {
+    data.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+/// Parse and decode form data, keeping every pair in body order
+///
+/// Keys and values are percent-decoded with `+` read as a space.
+pub fn decode_form_data(data: &str) -> Result<Vec<(String, String)>, FormParseError> {
+    form_pairs(data)
+        .map(|(key, value)| {
+            match (percent_decode(key, true), percent_decode(value, true)) {
+                (Some(key), Some(value)) => Ok((key, value)),
+                _ => Err(FormParseError::InvalidFormat),
+            }
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, and `+` as a space when `plus_as_space` is set
+/// (form bodies and query strings, but not paths)
+///
+/// Returns `None` on malformed escapes or invalid UTF-8.
+pub fn percent_decode(input: &str, plus_as_space: bool) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+                out.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                i += 2;
+            }
+            b'+' if plus_as_space => out.push(b' '),
+            byte => out.push(byte),
         }
+        i += 1;
     }
-    
-    params
+    String::from_utf8(out).ok()
 }
 
 /// Form parsing errors
@@ -330,6 +474,30 @@ mod tests {
         assert_eq!(cookies.len(), 3);
     }
 
+    #[test]
+    fn test_parse_cookie_header_skips_malformed_pairs() {
+        let cookies = parse_cookie_header(
+            r#"session=abc123; malformed; theme="dark mode"; __Host-id=42; bad name=x; session=shadowed"#,
+        );
+
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies["session"], "abc123");
+        assert_eq!(cookies["theme"], "dark mode");
+        assert_eq!(cookies["__Host-id"], "42");
+        assert!(!cookies.contains_key("malformed"));
+        assert!(!cookies.contains_key("bad name"));
+    }
+
+    #[test]
+    fn test_parse_cookie_header_limits() {
+        let many: Vec<String> = (0..MAX_COOKIES + 10).map(|i| format!("c{}=v", i)).collect();
+        assert_eq!(parse_cookie_header(&many.join("; ")).len(), MAX_COOKIES);
+
+        let long = format!("big={}; after=1", "x".repeat(MAX_COOKIE_HEADER_LEN));
+        let cookies = parse_cookie_header(&long);
+        assert!(!cookies.contains_key("after"));
+    }
+
     #[test]
     fn test_form_data() {
         let request_bytes = b"POST /submit HTTP/1.1\r\nHost: example.com\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: 43\r\n\r\nname=John+Doe&email=john@example.com&age=30";
@@ -348,6 +516,23 @@ mod tests {
         assert_eq!(form_data.get("age"), Some(&"30"));
     }
 
+    #[test]
+    fn test_decode_form_data() {
+        let pairs = decode_form_data("name=John+Doe&&tag=a%2Bb&tag=%E2%9C%93&flag").unwrap();
+        assert_eq!(pairs, vec![
+            ("name".to_string(), "John Doe".to_string()),
+            ("tag".to_string(), "a+b".to_string()),
+            ("tag".to_string(), "\u{2713}".to_string()),
+            ("flag".to_string(), String::new()),
+        ]);
+        assert_eq!(decode_form_data("a=%zz"), Err(FormParseError::InvalidFormat));
+        assert_eq!(decode_form_data("a=%+f"), Err(FormParseError::InvalidFormat));
+
+        assert_eq!(percent_decode("a+b%20c", false).as_deref(), Some("a+b c"));
+        assert_eq!(percent_decode("a+b%20c", true).as_deref(), Some("a b c"));
+        assert_eq!(percent_decode("%ff", false), None);
+    }
+
     #[test]
     fn test_remote_ip() {
         let request_bytes = b"GET / HTTP/1.1\r\nHost: example.com\r\nX-Forwarded-For: 192.168.1.100, 10.0.0.1\r\nX-Real-IP: 192.168.1.100\r\n\r\n";
@@ -211,6 +211,26 @@ impl Response {
     pub fn cache_control<V: Into<String>>(self, cache_control: V) -> Self {
         self.header("Cache-Control", cache_control)
     }
+
+    /// Note request headers the response varies on, merged into any `Vary`
+    /// already set so layers (CORS, compression, ...) don't overwrite each other
+    pub fn vary(mut self, names: &str) -> Self {
+        let mut vary = Vary::new();
+        let existing: Vec<String> = self.headers.keys()
+            .filter(|key| key.eq_ignore_ascii_case("vary"))
+            .cloned()
+            .collect();
+        for key in existing {
+            if let Some(value) = self.headers.remove(&key) {
+                vary.add(&value);
+            }
+        }
+        vary.add(names);
+        if !vary.is_empty() {
+            self.headers.insert("Vary".to_string(), vary.to_string());
+        }
+        self
+    }
     
     /// Set body from bytes
     pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
@@ -460,6 +480,49 @@ impl Response {
     }
 }
 
+/// Request header names a response varies on
+///
+/// Names are kept in insertion order without case-insensitive duplicates, and
+/// are rendered as a single `Vary` header; `*` swallows every other name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vary(Vec<String>);
+
+impl Vary {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add header names, accepting comma-separated lists like `Accept, Origin`
+    pub fn add(&mut self, names: &str) {
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if !self.0.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+                self.0.push(name.to_string());
+            }
+        }
+    }
+
+    /// Header names in the order they were first added
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Check if no names were added
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for Vary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.iter().any(|name| name == "*") {
+            f.write_str("*")
+        } else {
+            f.write_str(&self.0.join(", "))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -573,4 +636,17 @@ mod tests {
         let empty_response = Response::new();
         assert_eq!(empty_response.content_length(), Some(0));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_vary_merges_with_existing_header() {
+        let response = Response::new()
+            .header("vary", "Accept-Encoding")
+            .vary("Origin")
+            .vary("accept-encoding");
+        assert_eq!(response.headers.get("Vary").map(String::as_str), Some("Accept-Encoding, Origin"));
+        assert!(!response.headers.contains_key("vary"));
+
+        let response = Response::new().vary("Origin").vary("*");
+        assert_eq!(response.headers["Vary"], "*");
+    }
+}
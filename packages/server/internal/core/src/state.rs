@@ -0,0 +1,88 @@
+//! Type-keyed application state shared with handlers
+//!
+//! Each value is stored once per type and handed out as an `Arc`, so handlers
+//! can look up shared resources (database pools, config) by type instead of
+//! capturing them in every closure.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Container holding at most one value per type
+#[derive(Clone, Default)]
+pub struct AppState {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl AppState {
+    /// Create an empty state container
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a value, replacing any previous value of the same type
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Get the value registered for type `T`
+    ///
+    /// Returns `None` if no value of that type was registered.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    /// Number of registered values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no values are registered
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppState")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_insert_and_get_by_type() {
+        let mut state = AppState::new();
+        state.insert(Config { name: "zap" });
+        state.insert(42u32);
+
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.get::<Config>().unwrap().name, "zap");
+        assert_eq!(*state.get::<u32>().unwrap(), 42);
+        assert!(state.get::<String>().is_none());
+    }
+
+    #[test]
+    fn test_insert_replaces_same_type() {
+        let mut state = AppState::new();
+        state.insert(1u32);
+        state.insert(2u32);
+
+        assert_eq!(state.len(), 1);
+        assert_eq!(*state.get::<u32>().unwrap(), 2);
+    }
+}
@@ -5,7 +5,7 @@ use metadata::{FunctionMetadata, ParamMetadata};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{parse_macro_input, FnArg, ItemFn, PatType};
-use types::{parse_return_type, parse_type};
+use types::{borrowed_return_type, parse_return_type, parse_type, stream_item_type};
 
 /// Export a Rust function to be callable from TypeScript
 ///
@@ -27,7 +27,7 @@ pub fn export(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     // Validate the function signature
     if let Err(e) = validate_function(&input) {
-        return e;
+        return e.to_compile_error().into();
     }
 
     // Extract function metadata
@@ -42,8 +42,8 @@ pub fn export(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Generate inventory registration
     let registration = generate_registration(&metadata);
 
-    // Return the original function + wrapper + metadata + registration
-    let original = &input;
+    // Return the original function (minus our parameter attributes) + wrapper + metadata + registration
+    let original = strip_param_attributes(input);
     let output = quote! {
         #original
         #wrapper
@@ -55,21 +55,27 @@ pub fn export(_attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 /// Validate that the function is suitable for export
-fn validate_function(func: &ItemFn) -> Result<(), proc_macro::TokenStream> {
+fn validate_function(func: &ItemFn) -> syn::Result<()> {
     // Must be public
     if !matches!(func.vis, syn::Visibility::Public(_)) {
-        return Err(syn::Error::new_spanned(func, "exported function must be public")
-            .to_compile_error()
-            .into());
+        return Err(syn::Error::new_spanned(func, "exported function must be public"));
     }
 
     // Can't have generic parameters (for now)
     if !func.sig.generics.params.is_empty() {
-        return Err(
-            syn::Error::new_spanned(func, "exported functions cannot have generic parameters")
-                .to_compile_error()
-                .into(),
-        );
+        return Err(syn::Error::new_spanned(
+            func,
+            "exported functions cannot have generic parameters",
+        ));
+    }
+
+    // Results are serialized into owned JSON after the call, so they can't borrow
+    if let Some(borrowed) = borrowed_return_type(&func.sig.output) {
+        return Err(syn::Error::new_spanned(
+            borrowed,
+            "exported functions must return owned types (e.g. `String` instead of `&str`, \
+             `User` instead of `&User`); borrowed return values can't be serialized",
+        ));
     }
 
     Ok(())
@@ -93,6 +99,29 @@ fn is_context_type(ty: &syn::Type) -> bool {
     false
 }
 
+/// Get the `#[default = expr]` value attached to a parameter, if any
+fn param_default(pat_type: &PatType) -> Option<syn::Expr> {
+    pat_type.attrs.iter().find_map(|attr| {
+        if !attr.path().is_ident("default") {
+            return None;
+        }
+        match &attr.meta {
+            syn::Meta::NameValue(nv) => Some(nv.value.clone()),
+            _ => None,
+        }
+    })
+}
+
+/// Remove `#[default = ...]` attributes from parameters so the emitted function compiles
+fn strip_param_attributes(mut func: ItemFn) -> ItemFn {
+    for arg in func.sig.inputs.iter_mut() {
+        if let FnArg::Typed(pat_type) = arg {
+            pat_type.attrs.retain(|attr| !attr.path().is_ident("default"));
+        }
+    }
+    func
+}
+
 /// Extract metadata from a function signature
 fn extract_metadata(func: &ItemFn) -> FunctionMetadata {
     let name = func.sig.ident.to_string();
@@ -120,14 +149,17 @@ fn extract_metadata(func: &ItemFn) -> FunctionMetadata {
                 return None;
             }
 
-            if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
-                if let syn::Pat::Ident(pat_ident) = &**pat {
+            if let FnArg::Typed(pat_type) = arg {
+                if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
                     let param_name = pat_ident.ident.to_string();
-                    let param_type = parse_type(ty);
+                    let param_type = parse_type(&pat_type.ty);
+                    // Option<T> and #[default = ...] params may be omitted by the caller
+                    let is_optional = param_type.inner_option().is_some()
+                        || param_default(pat_type).is_some();
                     return Some(ParamMetadata {
                         name: param_name,
                         ty: param_type,
-                        is_optional: false,
+                        is_optional,
                     });
                 }
             }
@@ -135,8 +167,13 @@ fn extract_metadata(func: &ItemFn) -> FunctionMetadata {
         })
         .collect();
 
-    // Extract return type
-    let return_type = parse_return_type(&func.sig.output);
+    // Extract return type (streaming functions report their item type)
+    let stream_item = stream_item_type(&func.sig.output);
+    let is_streaming = stream_item.is_some();
+    let return_type = match stream_item {
+        Some(item) => parse_type(item),
+        None => parse_return_type(&func.sig.output),
+    };
 
     // Extract documentation
     let doc_comments: Vec<String> = func
@@ -163,6 +200,7 @@ fn extract_metadata(func: &ItemFn) -> FunctionMetadata {
         params,
         return_type,
         is_async,
+        is_streaming,
         has_context,
         doc_comments,
         line_number: 0, // Would need span info to get real line number
@@ -174,7 +212,7 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
     let fn_name = &func.sig.ident;
     let wrapper_name = format_ident!("__zap_wrapper_{}", fn_name);
 
-    // Get parameter types for proper deserialization (skip Context if present)
+    // Get parameter types and defaults for proper deserialization (skip Context if present)
     let param_types: Vec<_> = func.sig.inputs.iter()
         .enumerate()
         .filter_map(|(idx, arg)| {
@@ -182,8 +220,8 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
             if idx == 0 && metadata.has_context {
                 return None;
             }
-            if let FnArg::Typed(PatType { ty, .. }) = arg {
-                Some(ty.clone())
+            if let FnArg::Typed(pat_type) = arg {
+                Some((pat_type.ty.clone(), param_default(pat_type)))
             } else {
                 None
             }
@@ -194,22 +232,38 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
         .params
         .iter()
         .zip(param_types.iter())
-        .map(|(p, ty)| {
+        .map(|(p, (ty, default))| {
             let param_name = format_ident!("{}", p.name);
             let param_name_str = &p.name;
 
+            // What to use when the key is absent: the #[default] expression,
+            // None for Option<T>, otherwise an error
+            let missing = match default {
+                Some(expr) => quote! { #expr },
+                None if p.is_optional => quote! { None },
+                None => quote! {
+                    return Err(format!("Missing parameter: {}", #param_name_str))
+                },
+            };
+
             quote! {
-                let #param_name: #ty = {
-                    let value = params.get(#param_name_str)
-                        .ok_or_else(|| format!("Missing parameter: {}", #param_name_str))?
-                        .clone();
-                    serde_json::from_value(value)
-                        .map_err(|e| format!("Failed to deserialize parameter '{}': {}", #param_name_str, e))?
+                let #param_name: #ty = match params.get(#param_name_str) {
+                    Some(value) => serde_json::from_value(value.clone())
+                        .map_err(|e| format!("Failed to deserialize parameter '{}': {}", #param_name_str, e))?,
+                    None => #missing,
                 };
             }
         })
         .collect();
 
+    // Check the params against the function's schema before deserializing any of them
+    let schema_str = metadata.params_schema().to_string();
+    let param_validation = quote! {
+        static __ZAP_PARAMS_SCHEMA: ::std::sync::OnceLock<serde_json::Value> = ::std::sync::OnceLock::new();
+        let schema = __ZAP_PARAMS_SCHEMA.get_or_init(|| serde_json::from_str(#schema_str).unwrap_or_default());
+        ::zap_server::__private::check_params(schema, params)?;
+    };
+
     // Generate the call expression
     let param_names: Vec<_> = metadata
         .params
@@ -258,6 +312,47 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
         }
     };
 
+    // Streaming wrappers are always async so they fit the BoxFuture-based registry variants
+    if metadata.is_streaming {
+        let ctx_param = if metadata.has_context {
+            quote! { ctx: &::zap_server::__private::Context, }
+        } else {
+            quote! {}
+        };
+
+        // Serialize each item; Err items end the stream with a typed error
+        let item_handling = if metadata.return_type.is_result() {
+            quote! {
+                |item| match item {
+                    Ok(value) => serde_json::to_value(value).map_err(|e| e.to_string()),
+                    Err(e) => match serde_json::to_value(&e) {
+                        Ok(error_json) => Err(format!("__TYPED_ERROR__:{}", error_json)),
+                        Err(ser_err) => Err(format!("Failed to serialize error: {}", ser_err)),
+                    },
+                }
+            }
+        } else {
+            quote! {
+                |item| serde_json::to_value(item).map_err(|e| e.to_string())
+            }
+        };
+
+        return quote! {
+            #[doc(hidden)]
+            pub async fn #wrapper_name(
+                #ctx_param
+                params: &std::collections::HashMap<String, serde_json::Value>
+            ) -> Result<::zap_server::__private::ValueStream, String> {
+                #param_validation
+                #(#param_deserialize)*
+                let stream = #call_expr;
+                Ok(::std::boxed::Box::pin(
+                    ::zap_server::__private::futures::StreamExt::map(stream, #item_handling)
+                ))
+            }
+        };
+    }
+
     // Generate the full wrapper with conditional Context parameter
     if metadata.has_context {
         // Context-aware wrapper signature
@@ -268,6 +363,7 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
                     ctx: &::zap_server::__private::Context,
                     params: &std::collections::HashMap<String, serde_json::Value>
                 ) -> Result<serde_json::Value, String> {
+                    #param_validation
                     #(#param_deserialize)*
                     #result_handling
                 }
@@ -279,6 +375,7 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
                     ctx: &::zap_server::__private::Context,
                     params: &std::collections::HashMap<String, serde_json::Value>
                 ) -> Result<serde_json::Value, String> {
+                    #param_validation
                     #(#param_deserialize)*
                     #result_handling
                 }
@@ -292,6 +389,7 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
                 pub async fn #wrapper_name(
                     params: &std::collections::HashMap<String, serde_json::Value>
                 ) -> Result<serde_json::Value, String> {
+                    #param_validation
                     #(#param_deserialize)*
                     #result_handling
                 }
@@ -302,6 +400,7 @@ fn generate_wrapper(func: &ItemFn, metadata: &FunctionMetadata) -> proc_macro2::
                 pub fn #wrapper_name(
                     params: &std::collections::HashMap<String, serde_json::Value>
                 ) -> Result<serde_json::Value, String> {
+                    #param_validation
                     #(#param_deserialize)*
                     #result_handling
                 }
@@ -316,13 +415,19 @@ fn generate_metadata_emission(metadata: &FunctionMetadata) -> proc_macro2::Token
     let is_async = metadata.is_async;
 
     // Build a simple metadata string without serde (to avoid recursion issues)
-    let mut metadata_str = format!(r#"{{"name":"{}","is_async":{},"params":["#, fn_name, is_async);
+    let mut metadata_str = format!(
+        r#"{{"name":"{}","is_async":{},"is_streaming":{},"params":["#,
+        fn_name, is_async, metadata.is_streaming
+    );
 
     for (i, param) in metadata.params.iter().enumerate() {
         if i > 0 {
             metadata_str.push(',');
         }
-        metadata_str.push_str(&format!(r#"{{"name":"{}"}}"#, param.name));
+        metadata_str.push_str(&format!(
+            r#"{{"name":"{}","optional":{}}}"#,
+            param.name, param.is_optional
+        ));
     }
 
     metadata_str.push_str(r#"],"return_type":"unit"}"#);
@@ -344,48 +449,77 @@ fn generate_registration(metadata: &FunctionMetadata) -> proc_macro2::TokenStrea
     let wrapper_name = format_ident!("__zap_wrapper_{}", fn_name);
     let is_async = metadata.is_async;
     let has_context = metadata.has_context;
+    let params_schema = metadata.params_schema().to_string();
 
-    // Determine which FunctionWrapper variant to use based on (is_async, has_context)
-    let wrapper_variant = match (is_async, has_context) {
-        (false, false) => {
-            // Sync, no context
-            quote! {
-                ::zap_server::__private::FunctionWrapper::Sync(#wrapper_name)
-            }
-        }
-        (true, false) => {
-            // Async, no context
+    // Determine which FunctionWrapper variant to use based on (is_async, has_context);
+    // streaming functions always use the Stream variants
+    let wrapper_variant = if metadata.is_streaming {
+        if has_context {
             quote! {
-                ::zap_server::__private::FunctionWrapper::Async(
-                    |params| {
+                ::zap_server::__private::FunctionWrapper::StreamCtx(
+                    |ctx, params| {
+                        let ctx_owned = ctx.clone();
                         let params_owned = params.clone();
                         ::std::boxed::Box::pin(async move {
-                            #wrapper_name(&params_owned).await
+                            #wrapper_name(&ctx_owned, &params_owned).await
                         })
                     }
                 )
             }
-        }
-        (false, true) => {
-            // Sync with context
-            quote! {
-                ::zap_server::__private::FunctionWrapper::SyncCtx(#wrapper_name)
-            }
-        }
-        (true, true) => {
-            // Async with context (clone context for 'static future)
+        } else {
             quote! {
-                ::zap_server::__private::FunctionWrapper::AsyncCtx(
-                    |ctx, params| {
-                        let ctx_owned = ctx.clone();
+                ::zap_server::__private::FunctionWrapper::Stream(
+                    |params| {
                         let params_owned = params.clone();
                         ::std::boxed::Box::pin(async move {
-                            #wrapper_name(&ctx_owned, &params_owned).await
+                            #wrapper_name(&params_owned).await
                         })
                     }
                 )
             }
         }
+    } else {
+        match (is_async, has_context) {
+            (false, false) => {
+                // Sync, no context
+                quote! {
+                    ::zap_server::__private::FunctionWrapper::Sync(#wrapper_name)
+                }
+            }
+            (true, false) => {
+                // Async, no context
+                quote! {
+                    ::zap_server::__private::FunctionWrapper::Async(
+                        |params| {
+                            let params_owned = params.clone();
+                            ::std::boxed::Box::pin(async move {
+                                #wrapper_name(&params_owned).await
+                            })
+                        }
+                    )
+                }
+            }
+            (false, true) => {
+                // Sync with context
+                quote! {
+                    ::zap_server::__private::FunctionWrapper::SyncCtx(#wrapper_name)
+                }
+            }
+            (true, true) => {
+                // Async with context (clone context for 'static future)
+                quote! {
+                    ::zap_server::__private::FunctionWrapper::AsyncCtx(
+                        |ctx, params| {
+                            let ctx_owned = ctx.clone();
+                            let params_owned = params.clone();
+                            ::std::boxed::Box::pin(async move {
+                                #wrapper_name(&ctx_owned, &params_owned).await
+                            })
+                        }
+                    )
+                }
+            }
+        }
     };
 
     // Use linkme distributed_slice instead of inventory
@@ -403,6 +537,7 @@ fn generate_registration(metadata: &FunctionMetadata) -> proc_macro2::TokenStrea
                 name: #fn_name,
                 is_async: #is_async,
                 has_context: #has_context,
+                params_schema: #params_schema,
                 wrapper: #wrapper_variant,
             };
     }
@@ -430,4 +565,105 @@ mod tests {
         assert_eq!(metadata.params[0].name, "id");
         assert!(metadata.return_type.is_result());
     }
+
+    #[test]
+    fn test_option_param_tolerates_missing_key() {
+        let code = quote! {
+            pub fn list_items(limit: Option<u32>) -> Vec<u32> {
+                todo!()
+            }
+        };
+
+        let func: ItemFn = syn::parse2(code).unwrap();
+        let metadata = extract_metadata(&func);
+        assert!(metadata.params[0].is_optional);
+
+        let wrapper = generate_wrapper(&func, &metadata).to_string();
+        assert!(wrapper.contains("None => None"));
+        assert!(!wrapper.contains("Missing parameter"));
+    }
+
+    #[test]
+    fn test_default_param_attribute() {
+        let code = quote! {
+            pub fn list_items(id: u64, #[default = 20] page_size: u32) -> Vec<u32> {
+                todo!()
+            }
+        };
+
+        let func: ItemFn = syn::parse2(code).unwrap();
+        let metadata = extract_metadata(&func);
+        assert!(!metadata.params[0].is_optional);
+        assert!(metadata.params[1].is_optional);
+
+        let wrapper = generate_wrapper(&func, &metadata).to_string();
+        assert!(wrapper.contains("None => 20"));
+        // Required params still error when absent
+        assert!(wrapper.contains("Missing parameter"));
+
+        // The attribute must not leak into the emitted function
+        let original = strip_param_attributes(func);
+        assert!(!quote!(#original).to_string().contains("default"));
+    }
+
+    #[test]
+    fn test_streaming_function_registers_stream_variant() {
+        let code = quote! {
+            pub fn tail_log(ctx: &Context, from: u64) -> impl Stream<Item = Result<String, String>> {
+                todo!()
+            }
+        };
+
+        let func: ItemFn = syn::parse2(code).unwrap();
+        let metadata = extract_metadata(&func);
+        assert!(metadata.is_streaming);
+        assert!(metadata.has_context);
+        assert!(metadata.return_type.is_result());
+
+        let registration = generate_registration(&metadata).to_string();
+        assert!(registration.contains("FunctionWrapper :: StreamCtx"));
+
+        // Sync streaming functions still get an async wrapper
+        let wrapper = generate_wrapper(&func, &metadata).to_string();
+        assert!(wrapper.contains("pub async fn __zap_wrapper_tail_log"));
+        assert!(wrapper.contains("ValueStream"));
+    }
+
+    #[test]
+    fn test_zap_stream_without_context() {
+        let code = quote! {
+            pub async fn countdown(from: u32) -> ZapStream<u32> {
+                todo!()
+            }
+        };
+
+        let func: ItemFn = syn::parse2(code).unwrap();
+        let metadata = extract_metadata(&func);
+        assert!(metadata.is_streaming);
+
+        let registration = generate_registration(&metadata).to_string();
+        assert!(registration.contains("FunctionWrapper :: Stream ("));
+    }
+
+    #[test]
+    fn test_borrowed_return_type_is_rejected() {
+        let func: ItemFn = syn::parse_quote! {
+            pub fn f() -> &str {
+                "borrowed"
+            }
+        };
+        let err = validate_function(&func).unwrap_err();
+        assert!(err.to_string().contains("must return owned types"));
+        assert!(err.to_string().contains("instead of `&str`"));
+
+        // Nested borrows are caught too, owned returns pass
+        for func in [
+            syn::parse_quote! { pub fn f(users: Vec<User>) -> Option<&'static User> { todo!() } },
+            syn::parse_quote! { pub fn f() -> impl Stream<Item = Result<&'static str, String>> { todo!() } },
+        ] {
+            assert!(validate_function(&func).is_err());
+        }
+        let owned: ItemFn = syn::parse_quote! { pub fn f(name: &str) -> Result<String, String> { todo!() } };
+        assert!(validate_function(&owned).is_ok());
+    }
 }
@@ -7,10 +7,12 @@ pub struct FunctionMetadata {
     pub name: String,
     /// Function parameters
     pub params: Vec<ParamMetadata>,
-    /// Return type
+    /// Return type (the item type for streaming functions)
     pub return_type: TypeMetadata,
     /// Whether the function is async
     pub is_async: bool,
+    /// Whether the function returns a stream of items
+    pub is_streaming: bool,
     /// Whether the function requires Context parameter
     pub has_context: bool,
     /// Documentation comments
@@ -19,6 +21,31 @@ pub struct FunctionMetadata {
     pub line_number: usize,
 }
 
+impl FunctionMetadata {
+    /// JSON Schema of the params object the wrapper receives
+    ///
+    /// Optional and `#[default]` parameters may be omitted; extra keys are
+    /// allowed since wrappers ignore them.
+    pub fn params_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self
+            .params
+            .iter()
+            .map(|p| (p.name.clone(), p.ty.to_json_schema()))
+            .collect();
+        let required: Vec<&str> = self
+            .params
+            .iter()
+            .filter(|p| !p.is_optional)
+            .map(|p| p.name.as_str())
+            .collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
 /// Metadata about a function parameter
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ParamMetadata {
@@ -129,13 +156,41 @@ impl TypeMetadata {
         }
     }
 
+    /// JSON Schema describing values of this type
+    ///
+    /// User-defined types aren't visible to the macro, so their schema accepts
+    /// anything and serde reports the details.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        match self {
+            TypeMetadata::String => json!({ "type": "string" }),
+            TypeMetadata::Bool => json!({ "type": "boolean" }),
+            TypeMetadata::I8 => json!({ "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX }),
+            TypeMetadata::I16 => json!({ "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX }),
+            TypeMetadata::I32 => json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+            TypeMetadata::I64 | TypeMetadata::I128 => json!({ "type": "integer" }),
+            TypeMetadata::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+            TypeMetadata::U16 => json!({ "type": "integer", "minimum": 0, "maximum": u16::MAX }),
+            TypeMetadata::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            TypeMetadata::U64 | TypeMetadata::U128 => json!({ "type": "integer", "minimum": 0 }),
+            TypeMetadata::F32 | TypeMetadata::F64 => json!({ "type": "number" }),
+            TypeMetadata::Option(inner) => json!({ "anyOf": [inner.to_json_schema(), { "type": "null" }] }),
+            TypeMetadata::Vec(inner) => json!({ "type": "array", "items": inner.to_json_schema() }),
+            TypeMetadata::HashMap { value, .. } => {
+                json!({ "type": "object", "additionalProperties": value.to_json_schema() })
+            }
+            TypeMetadata::Unit => json!({ "type": "null" }),
+            TypeMetadata::Custom { .. } | TypeMetadata::Result { .. } => json!({}),
+        }
+    }
+
     /// Check if this type is wrapped in a Result
     pub fn is_result(&self) -> bool {
         matches!(self, TypeMetadata::Result { .. })
     }
 
     /// Get the inner type if this is an Option
-    #[allow(dead_code)]
     pub fn inner_option(&self) -> Option<&TypeMetadata> {
         match self {
             TypeMetadata::Option(inner) => Some(inner),
@@ -172,4 +227,34 @@ mod tests {
         };
         assert_eq!(result_str.to_typescript(), "Promise<string>");
     }
+
+    #[test]
+    fn test_params_schema() {
+        let metadata = FunctionMetadata {
+            name: "search".to_string(),
+            params: vec![
+                ParamMetadata { name: "id".to_string(), ty: TypeMetadata::U32, is_optional: false },
+                ParamMetadata {
+                    name: "tags".to_string(),
+                    ty: TypeMetadata::Option(Box::new(TypeMetadata::Vec(Box::new(TypeMetadata::String)))),
+                    is_optional: true,
+                },
+            ],
+            return_type: TypeMetadata::Unit,
+            is_async: false,
+            is_streaming: false,
+            has_context: false,
+            doc_comments: vec![],
+            line_number: 0,
+        };
+
+        let schema = metadata.params_schema();
+        assert_eq!(schema["required"], serde_json::json!(["id"]));
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["id"]["maximum"], u32::MAX);
+        assert_eq!(
+            schema["properties"]["tags"]["anyOf"][0],
+            serde_json::json!({ "type": "array", "items": { "type": "string" } })
+        );
+    }
 }
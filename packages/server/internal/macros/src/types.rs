@@ -1,5 +1,6 @@
 use syn::{
-    GenericArgument, PathArguments, PathSegment, ReturnType, Type, TypePath, TypeTuple,
+    GenericArgument, PathArguments, PathSegment, ReturnType, Type, TypeParamBound, TypePath,
+    TypeTuple,
 };
 
 use crate::metadata::TypeMetadata;
@@ -140,6 +141,96 @@ pub fn parse_return_type(rt: &ReturnType) -> TypeMetadata {
     }
 }
 
+/// Get the item type of a streaming return type
+///
+/// Recognizes `impl Stream<Item = T>` and `ZapStream<T>`.
+pub fn stream_item_type(rt: &ReturnType) -> Option<&Type> {
+    let ty = match rt {
+        ReturnType::Default => return None,
+        ReturnType::Type(_, ty) => &**ty,
+    };
+
+    match ty {
+        Type::ImplTrait(impl_trait) => impl_trait.bounds.iter().find_map(|bound| {
+            let TypeParamBound::Trait(trait_bound) = bound else {
+                return None;
+            };
+            let segment = trait_bound.path.segments.last()?;
+            if segment.ident != "Stream" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return None;
+            };
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+                _ => None,
+            })
+        }),
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "ZapStream" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return None;
+            };
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Find a borrowed type anywhere in a return type
+///
+/// Returns the first reference, including ones nested in generics
+/// (`Option<&User>`) or a stream's item type.
+pub fn borrowed_return_type(rt: &ReturnType) -> Option<&Type> {
+    match rt {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => find_reference(ty),
+    }
+}
+
+fn find_reference(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(_) => Some(ty),
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .iter()
+            .find_map(|segment| find_reference_in_args(&segment.arguments)),
+        Type::ImplTrait(impl_trait) => impl_trait.bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(trait_bound) => trait_bound
+                .path
+                .segments
+                .iter()
+                .find_map(|segment| find_reference_in_args(&segment.arguments)),
+            _ => None,
+        }),
+        Type::Tuple(tuple) => tuple.elems.iter().find_map(find_reference),
+        Type::Array(array) => find_reference(&array.elem),
+        Type::Slice(slice) => find_reference(&slice.elem),
+        Type::Paren(paren) => find_reference(&paren.elem),
+        Type::Group(group) => find_reference(&group.elem),
+        _ => None,
+    }
+}
+
+fn find_reference_in_args(args: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = args else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => find_reference(ty),
+        GenericArgument::AssocType(assoc) => find_reference(&assoc.ty),
+        _ => None,
+    })
+}
+
 /// Parse a tuple type
 fn parse_tuple(tuple: &TypeTuple) -> TypeMetadata {
     if tuple.elems.is_empty() {
@@ -196,6 +287,21 @@ mod tests {
         assert_eq!(parse_type(&result_ty), expected);
     }
 
+    #[test]
+    fn test_stream_item_type() {
+        let impl_stream: ReturnType = parse_quote!(-> impl Stream<Item = u32> + Send);
+        let item = stream_item_type(&impl_stream).unwrap();
+        assert_eq!(parse_type(item), TypeMetadata::U32);
+
+        let zap_stream: ReturnType = parse_quote!(-> zap_server::ZapStream<String>);
+        let item = stream_item_type(&zap_stream).unwrap();
+        assert_eq!(parse_type(item), TypeMetadata::String);
+
+        let plain: ReturnType = parse_quote!(-> Vec<u32>);
+        assert!(stream_item_type(&plain).is_none());
+        assert!(stream_item_type(&ReturnType::Default).is_none());
+    }
+
     #[test]
     fn test_parse_custom_type() {
         let custom_ty: Type = parse_quote!(User);
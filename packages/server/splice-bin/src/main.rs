@@ -37,6 +37,12 @@ struct Cli {
     timeout: u64,
 }
 
+/// Publish the supervisor's view of the worker for `HealthCheck` replies
+fn report_health(supervisor: &Supervisor, router: &Router) {
+    let (health, retry_in) = supervisor.health();
+    router.set_worker_health(health, retry_in);
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
@@ -56,6 +62,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_concurrent_requests: cli.max_concurrency,
         max_concurrent_per_function: 256, // Increased to handle test load
         default_timeout: Duration::from_secs(cli.timeout),
+        ..RouterConfig::default()
     };
 
     let worker_socket = cli.socket.parent()
@@ -124,6 +131,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }).await?;
 
         supervisor.update_state(WorkerState::Ready);
+        report_health(&supervisor, &router);
         info!("Worker handshake complete");
     } else {
         error!("Invalid worker handshake");
@@ -244,6 +252,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                     }
                                                 }
                                             }
+                                            Message::HealthCheck => {
+                                                let _ = host_framed.send(router_for_task.health_status().await).await;
+                                            }
                                             Message::Shutdown => {
                                                 let _ = host_framed.send(Message::ShutdownAck).await;
                                                 break;
@@ -263,12 +274,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             // Health check interval
             _ = tokio::time::sleep(Duration::from_secs(5)) => {
-                if !supervisor.is_ready() {
+                if let Some(retry_in) = supervisor.crash_loop_backoff() {
+                    warn!("Worker is crash looping ({:?}), next restart in {:?}", supervisor.state(), retry_in);
+                } else if !supervisor.is_ready() {
                     warn!("Worker not ready, attempting restart");
                     if let Err(e) = supervisor.restart().await {
                         error!("Failed to restart worker: {}", e);
                     }
                 }
+                report_health(&supervisor, &router);
             }
 
             // Hot reload check
@@ -278,6 +292,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     if let Err(e) = reload_manager.perform_reload(&mut supervisor, Duration::from_secs(30)).await {
                         error!("Hot reload failed: {}", e);
                     }
+                    report_health(&supervisor, &router);
                 }
             }
         }
@@ -4,4 +4,6 @@ pub mod router;
 pub mod reload;
 pub mod metrics;
 
-pub use protocol::{Message, Role, ErrorKind};
+pub use protocol::{
+    ErrorKind, ExportMetadata, Message, Role, SpliceCodec, DEFAULT_MAX_FRAME_SIZE, PROTOCOL_VERSION,
+};
@@ -70,6 +70,18 @@ pub enum ErrorKind {
     Cancelled = 4,
 }
 
+/// Worker health as reported in `HealthStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum WorkerHealth {
+    #[default]
+    Healthy = 1,
+    Starting = 2,
+    Unhealthy = 3,
+    /// Restarts are suspended after a crash loop
+    CrashLoopBackoff = 4,
+}
+
 // Error codes
 pub const ERR_INVALID_REQUEST: u16 = 1000;
 pub const ERR_INVALID_PARAMS: u16 = 1001;
@@ -197,6 +209,11 @@ pub enum Message {
         uptime_ms: u64,
         active_requests: u32,
         total_requests: u64,
+        #[serde(default)]
+        health: WorkerHealth,
+        /// Time left before the supervisor retries a crash-looping worker
+        #[serde(default)]
+        retry_in_ms: Option<u64>,
     },
 }
 
@@ -435,6 +452,8 @@ mod tests {
                     uptime_ms: 1000,
                     active_requests: 0,
                     total_requests: 100,
+                    health: WorkerHealth::Healthy,
+                    retry_in_ms: None,
                 },
             ]
         }
@@ -600,6 +619,8 @@ mod tests {
             uptime_ms: 1000,
             active_requests: 0,
             total_requests: 100,
+            health: WorkerHealth::Healthy,
+            retry_in_ms: None,
         };
         assert_eq!(msg.message_type(), MSG_HEALTH_STATUS);
     }
@@ -1005,6 +1026,8 @@ mod tests {
             uptime_ms: 123456789,
             active_requests: 10,
             total_requests: 1000000,
+            health: WorkerHealth::CrashLoopBackoff,
+            retry_in_ms: Some(5000),
         };
 
         codec.encode(original.clone(), &mut buf).unwrap();
@@ -1012,12 +1035,14 @@ mod tests {
 
         match (original, decoded) {
             (
-                Message::HealthStatus { uptime_ms: u1, active_requests: a1, total_requests: t1 },
-                Message::HealthStatus { uptime_ms: u2, active_requests: a2, total_requests: t2 },
+                Message::HealthStatus { uptime_ms: u1, active_requests: a1, total_requests: t1, health: h1, retry_in_ms: r1 },
+                Message::HealthStatus { uptime_ms: u2, active_requests: a2, total_requests: t2, health: h2, retry_in_ms: r2 },
             ) => {
                 assert_eq!(u1, u2);
                 assert_eq!(a1, a2);
                 assert_eq!(t1, t2);
+                assert_eq!(h1, h2);
+                assert_eq!(r1, r2);
             }
             _ => panic!("Message type mismatch"),
         }
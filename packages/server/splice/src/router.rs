@@ -1,10 +1,11 @@
-use crate::protocol::{Message, ErrorKind, ExportMetadata, ERR_TIMEOUT, ERR_OVERLOADED, ERR_CANCELLED};
+use crate::protocol::{Message, ErrorKind, ExportMetadata, WorkerHealth, ERR_TIMEOUT, ERR_OVERLOADED, ERR_CANCELLED};
 use bytes::Bytes;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, RwLock, Semaphore};
 use tokio::time::timeout;
 use tracing::{debug, warn};
 
@@ -30,22 +31,52 @@ pub enum RouterError {
 pub struct RouterConfig {
     pub max_concurrent_requests: usize,
     pub max_concurrent_per_function: usize,
+    /// Calls that may wait for a slot once a function is saturated
+    /// (default 0: queueing is opt-in and saturated calls are rejected immediately)
+    pub max_queued_per_function: usize,
+    /// How long a queued call waits for a slot before it's rejected
+    pub queue_timeout: Duration,
+    /// Per-function weights scaling both limits above (default 1)
+    pub function_weights: HashMap<String, usize>,
     pub default_timeout: Duration,
 }
 
+impl RouterConfig {
+    /// Weight for `function_name`, giving hot functions a larger share
+    fn weight(&self, function_name: &str) -> usize {
+        self.function_weights.get(function_name).copied().unwrap_or(1).max(1)
+    }
+}
+
 impl Default for RouterConfig {
     fn default() -> Self {
         Self {
             max_concurrent_requests: 1024,
             max_concurrent_per_function: 100,
+            max_queued_per_function: 0,
+            queue_timeout: Duration::from_millis(500),
+            function_weights: HashMap::new(),
             default_timeout: Duration::from_secs(30),
         }
     }
 }
 
+/// Concurrency slots for one function, plus a bounded wait queue
+struct FunctionSlots {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+    max_queued: usize,
+}
+
+/// Worker health last reported by the supervisor
+#[derive(Debug, Clone, Copy)]
+struct WorkerStatus {
+    health: WorkerHealth,
+    retry_at: Option<Instant>,
+}
+
 #[derive(Debug)]
 struct PendingRequest {
-    function_name: String,
     started_at: Instant,
     response_tx: oneshot::Sender<Message>,
 }
@@ -54,9 +85,12 @@ pub struct Router {
     config: RouterConfig,
     exports: Arc<RwLock<HashMap<String, ExportMetadata>>>,
     pending: Arc<RwLock<HashMap<u64, PendingRequest>>>,
-    function_counts: Arc<RwLock<HashMap<String, usize>>>,
+    function_slots: Arc<RwLock<HashMap<String, Arc<FunctionSlots>>>>,
     next_request_id: Arc<RwLock<u64>>,
     worker_tx: Option<mpsc::Sender<Message>>,
+    worker_status: Mutex<WorkerStatus>,
+    started_at: Instant,
+    total_requests: AtomicU64,
 }
 
 impl Router {
@@ -65,9 +99,15 @@ impl Router {
             config,
             exports: Arc::new(RwLock::new(HashMap::new())),
             pending: Arc::new(RwLock::new(HashMap::new())),
-            function_counts: Arc::new(RwLock::new(HashMap::new())),
+            function_slots: Arc::new(RwLock::new(HashMap::new())),
             next_request_id: Arc::new(RwLock::new(1)),
             worker_tx: None,
+            worker_status: Mutex::new(WorkerStatus {
+                health: WorkerHealth::Starting,
+                retry_at: None,
+            }),
+            started_at: Instant::now(),
+            total_requests: AtomicU64::new(0),
         }
     }
 
@@ -87,6 +127,30 @@ impl Router {
         self.exports.read().await.values().cloned().collect()
     }
 
+    /// Record the worker's health, as seen by the supervisor
+    ///
+    /// `retry_in` is the time left on a crash-loop backoff, if any.
+    pub fn set_worker_health(&self, health: WorkerHealth, retry_in: Option<Duration>) {
+        *self.worker_status.lock().unwrap() = WorkerStatus {
+            health,
+            retry_at: retry_in.map(|retry_in| Instant::now() + retry_in),
+        };
+    }
+
+    /// `HealthStatus` reply for a host's `HealthCheck`
+    pub async fn health_status(&self) -> Message {
+        let status = *self.worker_status.lock().unwrap();
+        Message::HealthStatus {
+            uptime_ms: self.started_at.elapsed().as_millis() as u64,
+            active_requests: self.pending.read().await.len() as u32,
+            total_requests: self.total_requests.load(Ordering::Relaxed),
+            health: status.health,
+            retry_in_ms: status
+                .retry_at
+                .map(|at| at.saturating_duration_since(Instant::now()).as_millis() as u64),
+        }
+    }
+
     pub async fn invoke(
         &self,
         function_name: String,
@@ -104,18 +168,8 @@ impl Router {
             return Err(RouterError::Overloaded);
         }
 
-        // Check per-function concurrency limit
-        {
-            let counts = self.function_counts.read().await;
-            let func_count = counts.get(&function_name).copied().unwrap_or(0);
-            if func_count >= self.config.max_concurrent_per_function {
-                warn!(
-                    "Function concurrency limit exceeded for '{}': {}/{}",
-                    function_name, func_count, self.config.max_concurrent_per_function
-                );
-                return Err(RouterError::Overloaded);
-            }
-        }
+        // Take a per-function slot, held until the call completes
+        let _slot = self.acquire_slot(&function_name).await?;
 
         // Allocate request ID
         let request_id = {
@@ -124,6 +178,7 @@ impl Router {
             *next_id = next_id.wrapping_add(1);
             id
         };
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
 
         // Create response channel
         let (response_tx, response_rx) = oneshot::channel();
@@ -134,19 +189,12 @@ impl Router {
             pending.insert(
                 request_id,
                 PendingRequest {
-                    function_name: function_name.clone(),
                     started_at: Instant::now(),
                     response_tx,
                 },
             );
         }
 
-        // Increment function counter
-        {
-            let mut counts = self.function_counts.write().await;
-            *counts.entry(function_name.clone()).or_insert(0) += 1;
-        }
-
         // Send invoke message to worker
         let worker_tx = self.worker_tx.as_ref()
             .ok_or(RouterError::WorkerUnavailable)?;
@@ -220,14 +268,71 @@ impl Router {
     }
 
     async fn cleanup_request(&self, request_id: u64) {
-        if let Some(pending) = self.pending.write().await.remove(&request_id) {
-            let mut counts = self.function_counts.write().await;
-            if let Some(count) = counts.get_mut(&pending.function_name) {
-                *count = count.saturating_sub(1);
+        self.pending.write().await.remove(&request_id);
+    }
+
+    /// Number of calls waiting for a slot on `function_name`
+    pub async fn queued_calls(&self, function_name: &str) -> usize {
+        self.function_slots
+            .read()
+            .await
+            .get(function_name)
+            .map_or(0, |slots| slots.queued.load(Ordering::SeqCst))
+    }
+
+    /// Wait for a concurrency slot on `function_name`
+    ///
+    /// A saturated function queues up to `max_queued_per_function` callers
+    /// (scaled by its weight) for `queue_timeout`; anyone beyond that, or
+    /// still waiting when the timeout expires, is rejected as overloaded.
+    async fn acquire_slot(&self, function_name: &str) -> Result<OwnedSemaphorePermit, RouterError> {
+        let slots = self.slots_for(function_name).await;
+
+        if let Ok(permit) = slots.permits.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        if slots.queued.fetch_add(1, Ordering::SeqCst) >= slots.max_queued {
+            slots.queued.fetch_sub(1, Ordering::SeqCst);
+            warn!(
+                "Function concurrency limit exceeded for '{}' and queue is full ({} waiting)",
+                function_name, slots.max_queued
+            );
+            return Err(RouterError::Overloaded);
+        }
+
+        let permit = timeout(self.config.queue_timeout, slots.permits.clone().acquire_owned()).await;
+        slots.queued.fetch_sub(1, Ordering::SeqCst);
+
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => {
+                warn!("Queued call to '{}' timed out waiting for a slot", function_name);
+                Err(RouterError::Overloaded)
             }
         }
     }
 
+    async fn slots_for(&self, function_name: &str) -> Arc<FunctionSlots> {
+        if let Some(slots) = self.function_slots.read().await.get(function_name) {
+            return slots.clone();
+        }
+
+        let weight = self.config.weight(function_name);
+        self.function_slots
+            .write()
+            .await
+            .entry(function_name.to_string())
+            .or_insert_with(|| {
+                Arc::new(FunctionSlots {
+                    permits: Arc::new(Semaphore::new(self.config.max_concurrent_per_function * weight)),
+                    queued: AtomicUsize::new(0),
+                    max_queued: self.config.max_queued_per_function * weight,
+                })
+            })
+            .clone()
+    }
+
     pub async fn drain(&self, timeout_duration: Duration) {
         let start = Instant::now();
 
@@ -257,5 +362,130 @@ mod tests {
         let config = RouterConfig::default();
         assert_eq!(config.max_concurrent_requests, 1024);
         assert_eq!(config.max_concurrent_per_function, 100);
+        assert_eq!(config.max_queued_per_function, 0);
+    }
+
+    /// Router whose worker channel is answered by the returned receiver
+    fn test_router(config: RouterConfig) -> (Arc<Router>, mpsc::Receiver<Message>) {
+        let (worker_tx, worker_rx) = mpsc::channel(16);
+        let mut router = Router::new(config);
+        router.set_worker_tx(worker_tx);
+        (Arc::new(router), worker_rx)
+    }
+
+    fn spawn_invoke(router: &Arc<Router>, function_name: &str) -> tokio::task::JoinHandle<Result<Bytes, RouterError>> {
+        let router = router.clone();
+        let function_name = function_name.to_string();
+        tokio::spawn(async move {
+            let context = crate::protocol::RequestContext {
+                trace_id: 0,
+                span_id: 0,
+                headers: Vec::new(),
+                auth: None,
+            };
+            router.invoke(function_name, Bytes::new(), 0, context).await
+        })
+    }
+
+    async fn reply(router: &Router, worker_rx: &mut mpsc::Receiver<Message>) {
+        match worker_rx.recv().await {
+            Some(Message::Invoke { request_id, .. }) => {
+                router
+                    .handle_worker_message(Message::InvokeResult {
+                        request_id,
+                        result: Bytes::from_static(b"ok"),
+                        duration_us: 0,
+                    })
+                    .await
+            }
+            other => panic!("expected invoke, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_saturated_function_queues_then_rejects() {
+        let (router, mut worker_rx) = test_router(RouterConfig {
+            max_concurrent_per_function: 2,
+            max_queued_per_function: 2,
+            queue_timeout: Duration::from_secs(5),
+            ..RouterConfig::default()
+        });
+
+        // Two calls run, two wait in the queue
+        let calls: Vec<_> = (0..4).map(|_| spawn_invoke(&router, "hot")).collect();
+        while router.queued_calls("hot").await < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        // The queue is full, so the next call is turned away immediately
+        let rejected = spawn_invoke(&router, "hot").await.unwrap();
+        assert!(matches!(rejected, Err(RouterError::Overloaded)));
+
+        // Other functions aren't affected
+        let cold = spawn_invoke(&router, "cold");
+        reply(&router, &mut worker_rx).await;
+        reply(&router, &mut worker_rx).await;
+        reply(&router, &mut worker_rx).await;
+
+        // Finished calls free slots for the queued ones
+        reply(&router, &mut worker_rx).await;
+        reply(&router, &mut worker_rx).await;
+
+        assert_eq!(cold.await.unwrap().unwrap(), Bytes::from_static(b"ok"));
+        for call in calls {
+            assert_eq!(call.await.unwrap().unwrap(), Bytes::from_static(b"ok"));
+        }
+        assert_eq!(router.queued_calls("hot").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_health_status_reports_worker_health() {
+        let (router, mut worker_rx) = test_router(RouterConfig::default());
+
+        router.set_worker_health(WorkerHealth::Healthy, None);
+        let call = spawn_invoke(&router, "f");
+        reply(&router, &mut worker_rx).await;
+        call.await.unwrap().unwrap();
+
+        match router.health_status().await {
+            Message::HealthStatus { active_requests, total_requests, health, retry_in_ms, .. } => {
+                assert_eq!(active_requests, 0);
+                assert_eq!(total_requests, 1);
+                assert_eq!(health, WorkerHealth::Healthy);
+                assert_eq!(retry_in_ms, None);
+            }
+            other => panic!("expected health status, got {:?}", other),
+        }
+
+        // A crash-looping worker is reported along with the time until its next restart
+        router.set_worker_health(WorkerHealth::CrashLoopBackoff, Some(Duration::from_secs(30)));
+        match router.health_status().await {
+            Message::HealthStatus { health, retry_in_ms, .. } => {
+                assert_eq!(health, WorkerHealth::CrashLoopBackoff);
+                assert!(matches!(retry_in_ms, Some(ms) if ms > 29_000 && ms <= 30_000));
+            }
+            other => panic!("expected health status, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_function_weight_scales_limits() {
+        let (router, _worker_rx) = test_router(RouterConfig {
+            max_concurrent_per_function: 1,
+            max_queued_per_function: 0,
+            function_weights: HashMap::from([("hot".to_string(), 3)]),
+            ..RouterConfig::default()
+        });
+
+        let _running: Vec<_> = (0..3).map(|_| spawn_invoke(&router, "hot")).collect();
+        let _cold = spawn_invoke(&router, "cold");
+        while router.pending.read().await.len() < 4 {
+            tokio::task::yield_now().await;
+        }
+
+        let rejected = spawn_invoke(&router, "hot").await.unwrap();
+        assert!(matches!(rejected, Err(RouterError::Overloaded)));
+        let rejected = spawn_invoke(&router, "cold").await.unwrap();
+        assert!(matches!(rejected, Err(RouterError::Overloaded)));
     }
 }
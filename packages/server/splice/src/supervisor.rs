@@ -1,4 +1,5 @@
-use crate::protocol::{Message, Role, PROTOCOL_VERSION, CAP_STREAMING, CAP_CANCELLATION};
+use crate::protocol::{Message, Role, WorkerHealth, PROTOCOL_VERSION, CAP_STREAMING, CAP_CANCELLATION};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::{Duration, Instant};
@@ -23,6 +24,9 @@ pub enum SupervisorError {
 
     #[error("Circuit breaker open")]
     CircuitBreakerOpen,
+
+    #[error("Worker crash loop detected, retrying in {0:?}")]
+    CrashLoop(Duration),
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +36,12 @@ pub struct SupervisorConfig {
     pub health_check_interval: Duration,
     pub drain_timeout: Duration,
     pub connect_timeout: Duration,
+    /// Restarts within `crash_loop_window` that count as a crash loop
+    pub crash_loop_threshold: usize,
+    pub crash_loop_window: Duration,
+    /// First crash-loop backoff, doubled on each consecutive loop
+    pub crash_loop_backoff: Duration,
+    pub max_crash_loop_backoff: Duration,
 }
 
 impl Default for SupervisorConfig {
@@ -48,6 +58,10 @@ impl Default for SupervisorConfig {
             health_check_interval: Duration::from_secs(5),
             drain_timeout: Duration::from_secs(30),
             connect_timeout: Duration::from_secs(10),
+            crash_loop_threshold: 5,
+            crash_loop_window: Duration::from_secs(60),
+            crash_loop_backoff: Duration::from_secs(1),
+            max_crash_loop_backoff: Duration::from_secs(60),
         }
     }
 }
@@ -78,6 +92,11 @@ pub struct Supervisor {
     worker: Option<Child>,
     worker_info: Option<WorkerInfo>,
     circuit_breaker_until: Option<Instant>,
+    /// Recent restart times, oldest first, within `crash_loop_window`
+    restart_times: VecDeque<Instant>,
+    /// Consecutive crash loops, for exponential backoff
+    crash_loops: u32,
+    crash_loop_until: Option<Instant>,
 }
 
 impl Supervisor {
@@ -93,6 +112,9 @@ impl Supervisor {
             worker: None,
             worker_info: None,
             circuit_breaker_until: None,
+            restart_times: VecDeque::new(),
+            crash_loops: 0,
+            crash_loop_until: None,
         }
     }
 
@@ -170,6 +192,11 @@ impl Supervisor {
             let _ = child.kill().await;
         }
 
+        if let Some(retry_in) = self.check_crash_loop() {
+            self.update_state(WorkerState::Failed);
+            return Err(SupervisorError::CrashLoop(retry_in));
+        }
+
         let restart_count = self
             .worker_info
             .as_ref()
@@ -179,6 +206,63 @@ impl Supervisor {
         self.spawn_worker(restart_count).await
     }
 
+    /// Record a restart attempt and return the remaining backoff if the
+    /// worker is crash looping
+    ///
+    /// `crash_loop_threshold` restarts within `crash_loop_window` start a
+    /// backoff during which restarts are refused. Each consecutive loop
+    /// doubles the backoff, up to `max_crash_loop_backoff`.
+    fn check_crash_loop(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+
+        if let Some(until) = self.crash_loop_until {
+            if now < until {
+                return Some(until - now);
+            }
+            // A full quiet window after the last backoff ends the streak
+            if now.duration_since(until) > self.config.crash_loop_window {
+                self.crash_loop_until = None;
+                self.crash_loops = 0;
+            }
+        }
+
+        while self
+            .restart_times
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > self.config.crash_loop_window)
+        {
+            self.restart_times.pop_front();
+        }
+        self.restart_times.push_back(now);
+
+        if self.restart_times.len() < self.config.crash_loop_threshold {
+            return None;
+        }
+
+        let backoff = self
+            .config
+            .crash_loop_backoff
+            .saturating_mul(2u32.saturating_pow(self.crash_loops))
+            .min(self.config.max_crash_loop_backoff);
+        error!(
+            "Worker restarted {} times within {:?}, backing off for {:?}",
+            self.restart_times.len(),
+            self.config.crash_loop_window,
+            backoff
+        );
+
+        self.crash_loops = self.crash_loops.saturating_add(1);
+        self.crash_loop_until = Some(now + backoff);
+        self.restart_times.clear();
+        Some(backoff)
+    }
+
+    /// Remaining crash-loop backoff, if restarts are currently refused
+    pub fn crash_loop_backoff(&self) -> Option<Duration> {
+        self.crash_loop_until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+    }
+
     pub async fn graceful_shutdown(&mut self, timeout: Duration) -> Result<(), SupervisorError> {
         if let Some(ref mut child) = self.worker {
             info!("Initiating graceful shutdown");
@@ -226,6 +310,23 @@ impl Supervisor {
         }
     }
 
+    pub fn state(&self) -> Option<WorkerState> {
+        self.worker_info.as_ref().map(|w| w.state)
+    }
+
+    /// Health to report to hosts, with the time left on any crash-loop backoff
+    pub fn health(&self) -> (WorkerHealth, Option<Duration>) {
+        if let Some(retry_in) = self.crash_loop_backoff() {
+            return (WorkerHealth::CrashLoopBackoff, Some(retry_in));
+        }
+        let health = match self.state() {
+            Some(WorkerState::Ready) => WorkerHealth::Healthy,
+            Some(WorkerState::Starting) => WorkerHealth::Starting,
+            _ => WorkerHealth::Unhealthy,
+        };
+        (health, None)
+    }
+
     pub fn is_ready(&self) -> bool {
         self.worker_info
             .as_ref()
@@ -243,5 +344,69 @@ mod tests {
         let config = SupervisorConfig::default();
         assert_eq!(config.max_restarts, 10);
         assert_eq!(config.restart_backoff.len(), 5);
+        assert_eq!(config.crash_loop_threshold, 5);
+    }
+
+    #[tokio::test]
+    async fn test_rapid_restarts_enter_crash_loop_backoff() {
+        let config = SupervisorConfig {
+            restart_backoff: vec![Duration::ZERO],
+            crash_loop_threshold: 3,
+            crash_loop_backoff: Duration::from_millis(200),
+            ..SupervisorConfig::default()
+        };
+        // A worker that exits immediately, like a broken binary
+        let mut supervisor = Supervisor::new(config, PathBuf::from("true"), PathBuf::from("/tmp/unused.sock"));
+
+        supervisor.start().await.unwrap();
+        supervisor.restart().await.unwrap();
+        let last = supervisor.restart().await.unwrap();
+
+        // The third restart within the window trips the crash-loop detector
+        let err = supervisor.restart().await.unwrap_err();
+        assert!(matches!(err, SupervisorError::CrashLoop(backoff) if backoff == Duration::from_millis(200)));
+        assert_eq!(supervisor.state(), Some(WorkerState::Failed));
+        assert!(!supervisor.is_ready());
+        assert!(matches!(supervisor.health(), (WorkerHealth::CrashLoopBackoff, Some(_))));
+
+        // During the backoff, restarts are refused without spawning
+        assert!(matches!(supervisor.restart().await, Err(SupervisorError::CrashLoop(_))));
+        assert_eq!(supervisor.worker_info().unwrap().pid, last.pid);
+        assert!(supervisor.crash_loop_backoff().is_some());
+
+        // Once it expires the worker is restarted again
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(supervisor.crash_loop_backoff().is_none());
+        assert_eq!(supervisor.health(), (WorkerHealth::Unhealthy, None));
+        let info = supervisor.restart().await.unwrap();
+        assert_eq!(info.state, WorkerState::Starting);
+        assert_eq!(supervisor.health(), (WorkerHealth::Starting, None));
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_crash_loops_double_backoff() {
+        let config = SupervisorConfig {
+            restart_backoff: vec![Duration::ZERO],
+            crash_loop_threshold: 1,
+            crash_loop_backoff: Duration::from_millis(10),
+            max_crash_loop_backoff: Duration::from_millis(30),
+            ..SupervisorConfig::default()
+        };
+        let mut supervisor = Supervisor::new(config, PathBuf::from("true"), PathBuf::from("/tmp/unused.sock"));
+        supervisor.start().await.unwrap();
+
+        let mut backoffs = Vec::new();
+        for _ in 0..3 {
+            match supervisor.restart().await {
+                Err(SupervisorError::CrashLoop(backoff)) => backoffs.push(backoff),
+                other => panic!("expected crash loop, got {:?}", other.map(|info| info.pid)),
+            }
+            tokio::time::sleep(backoffs.last().copied().unwrap() + Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(
+            backoffs,
+            vec![Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(30)]
+        );
     }
 }
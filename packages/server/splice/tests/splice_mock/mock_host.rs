@@ -231,6 +231,7 @@ impl MockHost {
                 uptime_ms,
                 active_requests,
                 total_requests,
+                ..
             })) => Ok((uptime_ms, active_requests, total_requests)),
             Ok(Some(msg)) => Err(format!("Expected HealthStatus, got {:?}", msg)),
             Ok(None) => Err("Channel closed".to_string()),
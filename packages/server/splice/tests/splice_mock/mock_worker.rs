@@ -7,7 +7,7 @@ use tokio::time::timeout;
 
 // Import protocol types
 pub use splice::protocol::{
-    Message, ExportMetadata, Role, ErrorKind, RequestContext, AuthContext,
+    Message, ExportMetadata, Role, ErrorKind, RequestContext, AuthContext, WorkerHealth,
     PROTOCOL_VERSION, DEFAULT_MAX_FRAME_SIZE, CAP_STREAMING, CAP_CANCELLATION,
     ERR_INVALID_PARAMS, ERR_EXECUTION_FAILED,
 };
@@ -286,6 +286,8 @@ impl MockWorker {
                         uptime_ms: 0, // Simplified for mock
                         active_requests: self.pending_requests.len() as u32,
                         total_requests: 0, // Simplified for mock
+                        health: WorkerHealth::Healthy,
+                        retry_in_ms: None,
                     })
                     .await?;
 
@@ -5,10 +5,12 @@
 //! - Environment variables
 //! - CLI argument overrides
 
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
 use crate::error::{ZapError, ZapResult};
+use crate::response::JsonOptions;
 
 /// User-provided RPC dispatch function
 ///
@@ -66,6 +68,10 @@ pub struct ZapConfig {
     #[serde(default)]
     pub metrics_path: Option<String>,
 
+    /// Proxy networks whose X-Forwarded-For headers are trusted
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNet>,
+
     /// RPC dispatch function (code-only, not in JSON config)
     /// Enables TypeScript handlers to call Rust functions via IPC
     #[serde(skip)]
@@ -87,6 +93,7 @@ impl std::fmt::Debug for ZapConfig {
             .field("middleware", &self.middleware)
             .field("health_check_path", &self.health_check_path)
             .field("metrics_path", &self.metrics_path)
+            .field("trusted_proxies", &self.trusted_proxies)
             .field("rpc_dispatch", &self.rpc_dispatch.as_ref().map(|_| "<function>"))
             .finish()
     }
@@ -166,6 +173,7 @@ impl Default for ZapConfig {
             middleware: MiddlewareConfig::default(),
             health_check_path: "/health".to_string(),
             metrics_path: None,
+            trusted_proxies: Vec::new(),
             rpc_dispatch: None,
         }
     }
@@ -236,7 +244,28 @@ pub struct ServerConfig {
     pub keep_alive_timeout: Duration,
     pub max_request_body_size: usize,
     pub max_headers: usize,
+    /// Maximum combined size of request headers in bytes
+    pub max_header_size: usize,
+    /// Maximum length of a single request header value in bytes; defaults to
+    /// 4 KiB so one value (e.g. a huge `Cookie`) can't fill the whole header budget
+    pub max_header_value_size: usize,
     pub request_timeout: Duration,
+    /// Peers allowed to set X-Forwarded-For (empty = never trust the header)
+    pub trusted_proxies: Vec<IpNet>,
+    /// Honor X-HTTP-Method-Override / `_method` on POST requests
+    pub method_override: bool,
+    /// How handler JSON responses are serialized
+    pub json: JsonOptions,
+    /// Close keep-alive connections after this many requests (None = unlimited)
+    pub max_requests_per_connection: Option<usize>,
+    /// Try the next ports when the configured one is busy (off = fail with `AddrInUse`)
+    pub port_cascade: bool,
+    /// Set `TCP_NODELAY` on accepted connections (disables Nagle's algorithm)
+    pub tcp_nodelay: bool,
+    /// Set `SO_REUSEADDR` on the listening socket
+    pub reuse_address: bool,
+    /// Maximum queue of pending connections passed to `listen`
+    pub backlog: u32,
 }
 
 impl Default for ServerConfig {
@@ -247,7 +276,17 @@ impl Default for ServerConfig {
             keep_alive_timeout: Duration::from_secs(75),
             max_request_body_size: 16 * 1024 * 1024,
             max_headers: 100,
+            max_header_size: 8 * 1024,
+            max_header_value_size: 4 * 1024,
             request_timeout: Duration::from_secs(30),
+            trusted_proxies: Vec::new(),
+            method_override: false,
+            json: JsonOptions::default(),
+            max_requests_per_connection: None,
+            port_cascade: true,
+            tcp_nodelay: false,
+            reuse_address: true,
+            backlog: 1024,
         }
     }
 }
@@ -282,11 +321,61 @@ impl ServerConfig {
         self
     }
 
+    pub fn max_header_size(mut self, bytes: usize) -> Self {
+        self.max_header_size = bytes;
+        self
+    }
+
+    pub fn max_header_value_size(mut self, bytes: usize) -> Self {
+        self.max_header_value_size = bytes;
+        self
+    }
+
     pub fn request_timeout(mut self, timeout: Duration) -> Self {
         self.request_timeout = timeout;
         self
     }
 
+    pub fn trusted_proxies(mut self, proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = proxies;
+        self
+    }
+
+    pub fn method_override(mut self, enabled: bool) -> Self {
+        self.method_override = enabled;
+        self
+    }
+
+    pub fn json_options(mut self, options: JsonOptions) -> Self {
+        self.json = options;
+        self
+    }
+
+    pub fn max_requests_per_connection(mut self, max: usize) -> Self {
+        self.max_requests_per_connection = Some(max);
+        self
+    }
+
+    pub fn port_cascade(mut self, enabled: bool) -> Self {
+        self.port_cascade = enabled;
+        self
+    }
+
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp_nodelay = enabled;
+        self
+    }
+
+    pub fn reuse_address(mut self, enabled: bool) -> Self {
+        self.reuse_address = enabled;
+        self
+    }
+
+    pub fn backlog(mut self, backlog: u32) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
     pub fn socket_addr(&self) -> String {
         format!("{}:{}", self.hostname, self.port)
     }
@@ -8,15 +8,17 @@
 //! - Pool of N persistent connections (default: 4)
 //! - Health checks before use
 //! - Automatic reconnection on failure
+//! - Health-aware eviction: broken connections leave rotation and are
+//!   lazily replaced on later successful calls
 //! - Connection timeout handling
 //! - Fair connection distribution
 
 use crate::error::{ZapError, ZapResult};
 use crate::ipc::{IpcClient, IpcEncoding, IpcMessage};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{Mutex, Semaphore};
+use tokio::sync::{Mutex, MutexGuard, Semaphore, SemaphorePermit};
 use tracing::{debug, error, warn};
 
 /// Default number of connections in the pool
@@ -47,6 +49,13 @@ impl PooledConnection {
     fn is_valid(&self) -> bool {
         self.client.is_some() && self.healthy
     }
+
+    /// Hold a freshly connected client
+    fn install(&mut self, client: IpcClient) {
+        self.client = Some(client);
+        self.healthy = true;
+        self.last_used = std::time::Instant::now();
+    }
 }
 
 /// Configuration for the connection pool
@@ -104,6 +113,18 @@ impl PoolConfig {
     }
 }
 
+/// Connect a client to the pool's socket, giving up after `connect_timeout`
+async fn connect(config: &PoolConfig) -> ZapResult<IpcClient> {
+    let timeout = config.connect_timeout;
+
+    tokio::time::timeout(
+        timeout,
+        IpcClient::connect_with_encoding(&config.socket_path, config.encoding),
+    )
+    .await
+    .map_err(|_| ZapError::timeout("Connection pool connect timeout", timeout.as_millis() as u64))?
+}
+
 /// IPC Connection Pool
 ///
 /// Manages a pool of persistent connections to the TypeScript IPC server.
@@ -113,6 +134,12 @@ pub struct ConnectionPool {
     config: PoolConfig,
     /// Pooled connections (each wrapped in Mutex for exclusive access)
     connections: Vec<Arc<Mutex<PooledConnection>>>,
+    /// Lock-free mirror of each slot's health, used to skip evicted slots
+    slot_healthy: Arc<Vec<AtomicBool>>,
+    /// Whether a background reconnect of an evicted slot is running
+    replenishing: Arc<AtomicBool>,
+    /// Total connections evicted after failures
+    evictions: AtomicU64,
     /// Semaphore to limit concurrent connection acquisition
     semaphore: Arc<Semaphore>,
     /// Round-robin index for fair distribution
@@ -131,6 +158,9 @@ impl ConnectionPool {
         Self {
             semaphore: Arc::new(Semaphore::new(config.size)),
             connections,
+            slot_healthy: Arc::new((0..config.size).map(|_| AtomicBool::new(false)).collect()),
+            replenishing: Arc::new(AtomicBool::new(false)),
+            evictions: AtomicU64::new(0),
             config,
             next_index: AtomicUsize::new(0),
             initialized: std::sync::atomic::AtomicBool::new(false),
@@ -155,9 +185,7 @@ impl ConnectionPool {
             let mut conn = conn_mutex.lock().await;
             match self.create_connection().await {
                 Ok(client) => {
-                    conn.client = Some(client);
-                    conn.healthy = true;
-                    conn.last_used = std::time::Instant::now();
+                    self.mark_healthy(i, &mut conn, client);
                     init_count += 1;
                     debug!("Connection {} initialized", i);
                 }
@@ -178,30 +206,126 @@ impl ConnectionPool {
         Ok(())
     }
 
+    /// Eagerly connect up to `n` slots so the first requests find warm connections
+    ///
+    /// Meant for server startup. Failures are logged rather than returned: warm-up
+    /// stops at the first failed connect and the remaining slots are filled lazily.
+    /// Returns the number of healthy connections afterwards.
+    pub async fn warm_up(&self, n: usize) -> usize {
+        let target = n.min(self.config.size);
+        let mut warm = 0;
+
+        for (index, conn_mutex) in self.connections.iter().enumerate() {
+            if warm >= target {
+                break;
+            }
+            let mut conn = conn_mutex.lock().await;
+            if conn.is_valid() {
+                warm += 1;
+                continue;
+            }
+            match self.create_connection().await {
+                Ok(client) => {
+                    self.mark_healthy(index, &mut conn, client);
+                    warm += 1;
+                }
+                Err(e) => {
+                    warn!("Pool warm-up stopped after {}/{} connections: {}", warm, target, e);
+                    break;
+                }
+            }
+        }
+
+        if warm > 0 {
+            self.initialized.store(true, Ordering::Release);
+        }
+        debug!("Connection pool warmed up with {}/{} connections", warm, target);
+
+        self.healthy_count()
+    }
+
     /// Create a new IPC connection
     async fn create_connection(&self) -> ZapResult<IpcClient> {
-        let timeout = self.config.connect_timeout;
+        connect(&self.config).await
+    }
 
-        tokio::time::timeout(
-            timeout,
-            IpcClient::connect_with_encoding(&self.config.socket_path, self.config.encoding),
-        )
-        .await
-        .map_err(|_| ZapError::timeout("Connection pool connect timeout", timeout.as_millis() as u64))?
+    /// Install a fresh client in a slot and return it to rotation
+    fn mark_healthy(&self, index: usize, conn: &mut PooledConnection, client: IpcClient) {
+        conn.install(client);
+        self.slot_healthy[index].store(true, Ordering::Release);
+    }
+
+    /// Drop a broken connection and remove its slot from rotation
+    fn evict(&self, index: usize, conn: &mut PooledConnection) {
+        conn.client = None;
+        conn.healthy = false;
+        if self.slot_healthy[index].swap(false, Ordering::AcqRel) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
-    /// Get a connection from the pool, reconnecting if necessary
+    /// Get a connection from the pool, preferring healthy slots
+    ///
+    /// Round-robins over slots that are currently healthy. If every slot has
+    /// been evicted, falls back to plain round-robin so the caller reconnects.
     async fn get_connection_index(&self) -> ZapResult<usize> {
-        // Round-robin selection with wrap-around
-        let index = self.next_index.fetch_add(1, Ordering::Relaxed) % self.config.size;
-        Ok(index)
+        let size = self.config.size;
+        let start = self.next_index.fetch_add(1, Ordering::Relaxed) % size;
+
+        let healthy = (0..size)
+            .map(|offset| (start + offset) % size)
+            .find(|&i| self.slot_healthy[i].load(Ordering::Acquire));
+
+        // Before initialization nothing is marked healthy; keep plain round-robin
+        Ok(healthy.unwrap_or(start))
+    }
+
+    /// Reconnect one evicted slot in the background, restoring the pool
+    /// toward its max size
+    ///
+    /// Only called after a successful round-trip (the server is reachable).
+    /// The connect runs on its own task holding the slot's lock, so the
+    /// request that triggered it doesn't wait; busy slots are skipped and
+    /// only one reconnect runs at a time.
+    fn replenish(&self) {
+        if self.replenishing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let evicted = self.connections.iter().enumerate().find_map(|(index, conn_mutex)| {
+            if self.slot_healthy[index].load(Ordering::Acquire) {
+                return None;
+            }
+            let conn = Arc::clone(conn_mutex).try_lock_owned().ok()?;
+            (!conn.is_valid()).then_some((index, conn))
+        });
+        let Some((index, mut conn)) = evicted else {
+            self.replenishing.store(false, Ordering::Release);
+            return;
+        };
+
+        let config = self.config.clone();
+        let slot_healthy = Arc::clone(&self.slot_healthy);
+        let replenishing = Arc::clone(&self.replenishing);
+        tokio::spawn(async move {
+            match connect(&config).await {
+                Ok(client) => {
+                    debug!("Connection {} replenished", index);
+                    conn.install(client);
+                    slot_healthy[index].store(true, Ordering::Release);
+                }
+                Err(e) => debug!("Failed to replenish connection {}: {}", index, e),
+            }
+            replenishing.store(false, Ordering::Release);
+        });
     }
 
     /// Execute a request-response operation using a pooled connection
     ///
     /// This method handles:
     /// - Connection acquisition from pool
-    /// - Automatic reconnection on failure
+    /// - Eviction and automatic reconnection on failure
+    /// - Lazy replacement of previously evicted connections
     /// - Connection release back to pool
     pub async fn send_recv(&self, message: IpcMessage) -> ZapResult<IpcMessage> {
         // Acquire semaphore permit (limits concurrent usage)
@@ -211,6 +335,49 @@ impl ConnectionPool {
 
         // Get a connection index
         let index = self.get_connection_index().await?;
+        let result = self.send_recv_on(index, message).await;
+
+        if result.is_ok() && self.healthy_count() < self.config.size {
+            self.replenish();
+        }
+
+        result
+    }
+
+    /// Lease a pooled connection for an exchange spanning several messages
+    ///
+    /// The connection stays checked out until the lease is dropped, so a
+    /// streamed response can be read to its end. Callers that leave the
+    /// connection in an unknown state (a timeout or I/O error mid-exchange)
+    /// must [`evict`](PooledClient::evict) it.
+    pub async fn acquire(&self) -> ZapResult<PooledClient<'_>> {
+        let permit = self.semaphore.acquire().await.map_err(|_| {
+            ZapError::ipc("Connection pool semaphore closed")
+        })?;
+
+        let index = self.get_connection_index().await?;
+        let mut conn = self.connections[index].lock().await;
+        if !conn.is_valid() {
+            debug!("Connection {} invalid, reconnecting", index);
+            match self.create_connection().await {
+                Ok(client) => self.mark_healthy(index, &mut conn, client),
+                Err(e) => {
+                    self.evict(index, &mut conn);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(PooledClient {
+            pool: self,
+            index,
+            conn,
+            _permit: permit,
+        })
+    }
+
+    /// Send on a specific slot, evicting and retrying once on failure
+    async fn send_recv_on(&self, index: usize, message: IpcMessage) -> ZapResult<IpcMessage> {
         let conn_mutex = &self.connections[index];
 
         // Try with the existing connection first
@@ -220,12 +387,9 @@ impl ConnectionPool {
         if !conn.is_valid() {
             debug!("Connection {} invalid, reconnecting", index);
             match self.create_connection().await {
-                Ok(client) => {
-                    conn.client = Some(client);
-                    conn.healthy = true;
-                }
+                Ok(client) => self.mark_healthy(index, &mut conn, client),
                 Err(e) => {
-                    conn.healthy = false;
+                    self.evict(index, &mut conn);
                     return Err(e);
                 }
             }
@@ -239,19 +403,16 @@ impl ConnectionPool {
                     Ok(response)
                 }
                 Err(e) => {
-                    // Connection failed, mark as unhealthy
-                    warn!("Connection {} failed: {}, marking unhealthy", index, e);
-                    conn.healthy = false;
-                    conn.client = None;
+                    // Connection failed, evict it from rotation
+                    warn!("Connection {} failed: {}, evicting", index, e);
+                    self.evict(index, &mut conn);
 
                     // Try to reconnect and retry once
                     match self.create_connection().await {
                         Ok(mut new_client) => {
                             match new_client.send_recv(message).await {
                                 Ok(response) => {
-                                    conn.client = Some(new_client);
-                                    conn.healthy = true;
-                                    conn.last_used = std::time::Instant::now();
+                                    self.mark_healthy(index, &mut conn, new_client);
                                     Ok(response)
                                 }
                                 Err(retry_err) => {
@@ -272,6 +433,14 @@ impl ConnectionPool {
         }
     }
 
+    /// Number of slots currently holding a healthy connection
+    pub fn healthy_count(&self) -> usize {
+        self.slot_healthy
+            .iter()
+            .filter(|h| h.load(Ordering::Acquire))
+            .count()
+    }
+
     /// Perform health check on all connections
     pub async fn health_check(&self) -> (usize, usize) {
         let mut healthy = 0;
@@ -292,10 +461,11 @@ impl ConnectionPool {
     pub async fn close(&self) {
         debug!("Closing connection pool");
 
-        for conn_mutex in &self.connections {
+        for (index, conn_mutex) in self.connections.iter().enumerate() {
             let mut conn = conn_mutex.lock().await;
             conn.client = None;
             conn.healthy = false;
+            self.slot_healthy[index].store(false, Ordering::Release);
         }
 
         self.initialized.store(false, Ordering::Release);
@@ -311,15 +481,45 @@ impl ConnectionPool {
         PoolStats {
             size: self.config.size,
             initialized: self.initialized.load(Ordering::Acquire),
+            healthy: self.healthy_count(),
+            evictions: self.evictions.load(Ordering::Relaxed),
         }
     }
 }
 
+/// A connection checked out of a [`ConnectionPool`] by [`ConnectionPool::acquire`]
+pub struct PooledClient<'a> {
+    pool: &'a ConnectionPool,
+    index: usize,
+    conn: MutexGuard<'a, PooledConnection>,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl PooledClient<'_> {
+    /// The leased IPC client
+    pub fn client(&mut self) -> &mut IpcClient {
+        self.conn.last_used = std::time::Instant::now();
+        self.conn
+            .client
+            .as_mut()
+            .expect("leased connections are connected")
+    }
+
+    /// Drop the connection and remove its slot from rotation
+    pub fn evict(mut self) {
+        self.pool.evict(self.index, &mut self.conn);
+    }
+}
+
 /// Pool statistics
 #[derive(Debug, Clone)]
 pub struct PoolStats {
     pub size: usize,
     pub initialized: bool,
+    /// Connections currently in rotation
+    pub healthy: usize,
+    /// Total connections evicted after failures
+    pub evictions: u64,
 }
 
 /// Global connection pool singleton
@@ -389,6 +589,8 @@ mod tests {
 
         assert_eq!(stats.size, DEFAULT_POOL_SIZE);
         assert!(!stats.initialized);
+        assert_eq!(stats.healthy, 0);
+        assert_eq!(stats.evictions, 0);
     }
 
     #[tokio::test]
@@ -401,4 +603,112 @@ mod tests {
             assert_eq!(index, expected % 4);
         }
     }
+
+    /// Spawn a frame-echo IPC server. When `drop_next` is set, the next
+    /// connection to receive a message closes without replying.
+    fn spawn_test_server(path: &std::path::Path, drop_next: Arc<AtomicBool>) {
+        use crate::ipc::{deserialize_message, serialize_message};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::UnixListener::bind(path).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let drop_next = drop_next.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let mut len_buf = [0u8; 4];
+                        if stream.read_exact(&mut len_buf).await.is_err() {
+                            return;
+                        }
+                        let mut payload = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+                        if stream.read_exact(&mut payload).await.is_err() {
+                            return;
+                        }
+                        if drop_next.swap(false, Ordering::SeqCst) {
+                            return;
+                        }
+                        let _ = deserialize_message(&payload).unwrap();
+                        let reply = serialize_message(&IpcMessage::HealthCheckResponse, IpcEncoding::MessagePack).unwrap();
+                        let mut frame = (reply.len() as u32).to_be_bytes().to_vec();
+                        frame.extend_from_slice(&reply);
+                        if stream.write_all(&frame).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("warm.sock");
+        spawn_test_server(&socket, Arc::new(AtomicBool::new(false)));
+
+        let pool = ConnectionPool::new(
+            PoolConfig::new(socket.to_string_lossy().into_owned())
+                .size(4)
+                .connect_timeout(Duration::from_secs(1)),
+        );
+        assert_eq!(pool.warm_up(2).await, 2);
+        let stats = pool.stats();
+        assert!(stats.initialized);
+        assert_eq!(stats.healthy, 2);
+
+        // Asking for more than the pool holds fills it without error
+        assert_eq!(pool.warm_up(10).await, 4);
+
+        // An unreachable socket degrades to a cold pool instead of failing
+        let cold = ConnectionPool::new(
+            PoolConfig::new(dir.path().join("missing.sock").to_string_lossy().into_owned())
+                .connect_timeout(Duration::from_secs(1)),
+        );
+        assert_eq!(cold.warm_up(4).await, 0);
+        assert!(!cold.stats().initialized);
+    }
+
+    #[tokio::test]
+    async fn test_pool_recovers_after_dead_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("pool.sock");
+        let parked = dir.path().join("parked.sock");
+        let drop_next = Arc::new(AtomicBool::new(false));
+        spawn_test_server(&socket, drop_next.clone());
+
+        let pool = ConnectionPool::new(
+            PoolConfig::new(socket.to_string_lossy().into_owned())
+                .size(3)
+                .connect_timeout(Duration::from_secs(1)),
+        );
+        pool.initialize().await.unwrap();
+        assert_eq!(pool.stats().healthy, 3);
+
+        // Kill one connection while new connects are impossible: the slot is evicted
+        std::fs::rename(&socket, &parked).unwrap();
+        drop_next.store(true, Ordering::SeqCst);
+        assert!(pool.send_recv(IpcMessage::HealthCheck).await.is_err());
+
+        let stats = pool.stats();
+        assert_eq!(stats.healthy, 2);
+        assert_eq!(stats.evictions, 1);
+
+        // Traffic keeps flowing on the remaining connections
+        for _ in 0..4 {
+            assert!(pool.send_recv(IpcMessage::HealthCheck).await.is_ok());
+        }
+        assert_eq!(pool.stats().healthy, 2);
+
+        // Once the server is reachable again, the pool refills in the background
+        std::fs::rename(&parked, &socket).unwrap();
+        assert!(pool.send_recv(IpcMessage::HealthCheck).await.is_ok());
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while pool.stats().healthy < 3 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("evicted slot was not replenished");
+        assert_eq!(pool.health_check().await, (3, 3));
+    }
 }
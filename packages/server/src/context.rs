@@ -3,7 +3,8 @@
 //! This module provides the `Context` type that gives user-exported functions
 //! access to request metadata like trace IDs, headers, and authentication information.
 
-use splice::protocol::{RequestContext, AuthContext};
+use splice::protocol::{RequestContext, AuthContext, CAP_CANCELLATION, CAP_STREAMING};
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
 /// Request execution context available to exported functions
@@ -16,6 +17,7 @@ use tokio_util::sync::CancellationToken;
 ///
 /// The context includes a cancellation token that allows long-running functions to
 /// cooperatively handle cancellation (e.g., due to timeout or client disconnect).
+/// When the caller supplied a deadline, passing it also counts as cancellation.
 ///
 /// # Example
 /// ```ignore
@@ -50,6 +52,9 @@ use tokio_util::sync::CancellationToken;
 pub struct Context {
     inner: RequestContext,
     cancellation_token: CancellationToken,
+    deadline: Option<Instant>,
+    capabilities: u32,
+    received_at: Instant,
 }
 
 impl Context {
@@ -63,6 +68,9 @@ impl Context {
         Self {
             inner,
             cancellation_token: CancellationToken::new(),
+            deadline: None,
+            capabilities: 0,
+            received_at: Instant::now(),
         }
     }
 
@@ -75,9 +83,98 @@ impl Context {
         Self {
             inner,
             cancellation_token: token,
+            deadline: None,
+            capabilities: 0,
+            received_at: Instant::now(),
         }
     }
 
+    /// Attach the caller's deadline
+    ///
+    /// Used by the worker to propagate `deadline_ms` from the Splice `Invoke` message.
+    #[doc(hidden)]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Attach a deadline relative to now, from a Splice `deadline_ms` value
+    ///
+    /// A value of 0 means "no deadline" and leaves the context unchanged.
+    #[doc(hidden)]
+    pub fn with_deadline_ms(self, deadline_ms: u32) -> Self {
+        if deadline_ms == 0 {
+            return self;
+        }
+        self.with_deadline(Instant::now() + Duration::from_millis(deadline_ms as u64))
+    }
+
+    /// Attach the capabilities negotiated in the Splice handshake
+    ///
+    /// Contexts start with none; the worker sets the `CAP_*` bits it agreed on.
+    #[doc(hidden)]
+    pub fn with_capabilities(mut self, capabilities: u32) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Set when the request was received
+    ///
+    /// Contexts default to their creation time; the worker passes the moment
+    /// the Splice `Invoke` message arrived.
+    #[doc(hidden)]
+    pub fn with_received_at(mut self, received_at: Instant) -> Self {
+        self.received_at = received_at;
+        self
+    }
+
+    /// Get when the request was received
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
+
+    /// Get the time spent on this request so far
+    ///
+    /// # Example
+    /// ```ignore
+    /// if ctx.elapsed() > Duration::from_millis(50) {
+    ///     return cached_result();
+    /// }
+    /// ```
+    pub fn elapsed(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+
+    /// Get the negotiated Splice capability bits (`CAP_*` flags)
+    pub fn capabilities(&self) -> u32 {
+        self.capabilities
+    }
+
+    /// Check whether results can be streamed back chunk by chunk
+    ///
+    /// When streaming wasn't negotiated, a streaming export's items are
+    /// collected into a single response, so it may prefer to yield fewer of them.
+    ///
+    /// # Example
+    /// ```ignore
+    /// #[export]
+    /// pub fn progress(ctx: &Context, steps: u32) -> ZapStream<u32> {
+    ///     if ctx.supports_streaming() {
+    ///         ZapStream::iter(1..=steps)
+    ///     } else {
+    ///         ZapStream::iter([steps])
+    ///     }
+    /// }
+    /// ```
+    pub fn supports_streaming(&self) -> bool {
+        self.capabilities & CAP_STREAMING != 0
+    }
+
+    /// Check whether the host can cancel in-flight calls
+    pub fn supports_cancellation(&self) -> bool {
+        self.capabilities & CAP_CANCELLATION != 0
+    }
+
     /// Get the distributed trace ID for this request
     ///
     /// Useful for correlating logs and spans across services in a distributed system.
@@ -166,9 +263,32 @@ impl Context {
             .unwrap_or(false)
     }
 
+    /// Get the point in time after which the caller no longer wants the result
+    ///
+    /// Returns `None` if the caller did not set a deadline.
+    ///
+    /// # Example
+    /// ```ignore
+    /// if let Some(deadline) = ctx.deadline() {
+    ///     let budget = deadline.saturating_duration_since(std::time::Instant::now());
+    ///     tracing::debug!("{:?} left to finish", budget);
+    /// }
+    /// ```
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Check whether the caller's deadline has passed
+    ///
+    /// Always `false` when no deadline was set.
+    pub fn is_deadline_exceeded(&self) -> bool {
+        self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+
     /// Check if this request has been cancelled
     ///
-    /// Returns `true` if the request was cancelled (e.g., due to timeout or client disconnect).
+    /// Returns `true` if the request was cancelled (e.g., due to timeout or client disconnect)
+    /// or its deadline has passed.
     /// Long-running functions should periodically check this and return early if cancelled.
     ///
     /// Cancellation is **cooperative** - functions must explicitly check and respond to it.
@@ -190,13 +310,14 @@ impl Context {
     /// }
     /// ```
     pub fn is_cancelled(&self) -> bool {
-        self.cancellation_token.is_cancelled()
+        self.cancellation_token.is_cancelled() || self.is_deadline_exceeded()
     }
 
     /// Get a future that completes when the request is cancelled
     ///
     /// This can be used with `tokio::select!` for automatic cancellation handling.
-    /// The returned future will complete when the cancellation token is triggered.
+    /// The returned future will complete when the cancellation token is triggered
+    /// or the deadline passes, whichever comes first.
     ///
     /// # Example
     /// ```ignore
@@ -209,6 +330,14 @@ impl Context {
     /// }
     /// ```
     pub async fn cancelled(&self) {
-        self.cancellation_token.cancelled().await
+        match self.deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = self.cancellation_token.cancelled() => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => self.cancellation_token.cancelled().await,
+        }
     }
 }
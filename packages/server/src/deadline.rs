@@ -0,0 +1,98 @@
+//! Caller-supplied request deadlines
+//!
+//! Clients can bound how long a request may take with `X-Deadline-Ms: <millis>`
+//! or gRPC's `Grpc-Timeout: <value><unit>`, where the unit is one of `H`, `M`,
+//! `S`, `m` (milliseconds), `u` (microseconds) or `n` (nanoseconds). The budget
+//! can shorten but never extend the server's request timeout, and is handed on
+//! to exported functions as their deadline.
+
+use std::time::Duration;
+
+/// Deadline budget in milliseconds
+pub const DEADLINE_MS_HEADER: &str = "x-deadline-ms";
+
+/// gRPC-style timeout, e.g. `250m` or `2S`
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Parse an `X-Deadline-Ms` value; zero or malformed values are ignored
+pub fn parse_deadline_ms(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(0) | Err(_) => None,
+        Ok(ms) => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// Parse a `Grpc-Timeout` value (at most 8 digits followed by a unit)
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let digits = &value[..value.len() - unit.len_utf8()];
+    if digits.is_empty() || digits.len() > 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+
+    let timeout = match unit {
+        'H' => Duration::from_secs(amount * 3600),
+        'M' => Duration::from_secs(amount * 60),
+        'S' => Duration::from_secs(amount),
+        'm' => Duration::from_millis(amount),
+        'u' => Duration::from_micros(amount),
+        'n' => Duration::from_nanos(amount),
+        _ => return None,
+    };
+    (!timeout.is_zero()).then_some(timeout)
+}
+
+/// Budget requested by a request's headers, if any
+///
+/// Header names are matched case-insensitively; `X-Deadline-Ms` wins over
+/// `Grpc-Timeout` when both are present.
+pub fn requested_timeout<'a, I>(headers: I) -> Option<Duration>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut grpc = None;
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case(DEADLINE_MS_HEADER) {
+            if let Some(timeout) = parse_deadline_ms(value) {
+                return Some(timeout);
+            }
+        } else if name.eq_ignore_ascii_case(GRPC_TIMEOUT_HEADER) && grpc.is_none() {
+            grpc = parse_grpc_timeout(value);
+        }
+    }
+    grpc
+}
+
+/// Express a remaining budget as a Splice `deadline_ms`, where 0 means "none"
+///
+/// Rounds up so a sub-millisecond budget still carries a deadline.
+pub fn to_deadline_ms(budget: Duration) -> u32 {
+    let millis = budget.as_nanos().div_ceil(1_000_000);
+    u32::try_from(millis).unwrap_or(u32::MAX).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_timeout() {
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("2S"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("123456789m"), None);
+        assert_eq!(parse_grpc_timeout("10x"), None);
+        assert_eq!(parse_grpc_timeout("m"), None);
+        assert_eq!(parse_deadline_ms("0"), None);
+
+        let headers = [("Grpc-Timeout", "1S"), ("X-Deadline-Ms", "50")];
+        assert_eq!(requested_timeout(headers), Some(Duration::from_millis(50)));
+        assert_eq!(requested_timeout([("grpc-timeout", "1S")]), Some(Duration::from_secs(1)));
+        assert_eq!(requested_timeout([("x-deadline-ms", "soon")]), None);
+
+        assert_eq!(to_deadline_ms(Duration::from_micros(10)), 1);
+        assert_eq!(to_deadline_ms(Duration::from_millis(1500)), 1500);
+    }
+}
@@ -16,6 +16,9 @@ use std::io;
 use thiserror::Error;
 use uuid::Uuid;
 
+/// Content type for [`ZapError::to_problem_json`] documents
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
 /// Zap error type covering all possible failure modes
 #[derive(Debug, Error)]
 pub enum ZapError {
@@ -31,6 +34,15 @@ pub enum ZapError {
     #[error("Route not found: {path}")]
     RouteNotFound { path: String },
 
+    /// Path is routed, but not for this method (405)
+    #[error("Method {method} not allowed for {path}")]
+    MethodNotAllowed {
+        method: String,
+        path: String,
+        /// Methods the path does handle, sent as the `Allow` header
+        allowed: Vec<String>,
+    },
+
     /// Handler execution errors
     #[error("Handler error: {message}")]
     Handler {
@@ -73,6 +85,14 @@ pub enum ZapError {
     #[error("Timeout: {message}")]
     Timeout { message: String, timeout_ms: u64 },
 
+    /// Request body exceeds the allowed size (413)
+    #[error("Payload too large: limit is {limit} bytes")]
+    PayloadTooLarge { limit: usize },
+
+    /// Request headers too many or too large (431)
+    #[error("Request header fields too large: {message}")]
+    HeadersTooLarge { message: String },
+
     /// Rate limit exceeded (429)
     #[error("Rate limit exceeded")]
     RateLimited { retry_after_secs: u64 },
@@ -88,6 +108,14 @@ pub enum ZapError {
     /// WebSocket errors
     #[error("WebSocket error: {message}")]
     WebSocket { message: String },
+
+    /// Requested functionality the server doesn't provide (501)
+    #[error("Not implemented: {message}")]
+    NotImplemented { message: String },
+
+    /// Malformed request the server cannot interpret (400)
+    #[error("Bad request: {message}")]
+    BadRequest { message: String },
 }
 
 impl ZapError {
@@ -96,6 +124,7 @@ impl ZapError {
         match self {
             ZapError::Http { .. } => "HTTP_ERROR",
             ZapError::RouteNotFound { .. } => "ROUTE_NOT_FOUND",
+            ZapError::MethodNotAllowed { .. } => "METHOD_NOT_ALLOWED",
             ZapError::Handler { .. } => "HANDLER_ERROR",
             ZapError::Ipc { .. } => "IPC_ERROR",
             ZapError::Config { .. } => "CONFIG_ERROR",
@@ -105,10 +134,14 @@ impl ZapError {
             ZapError::Unauthorized { .. } => "UNAUTHORIZED",
             ZapError::Forbidden { .. } => "FORBIDDEN",
             ZapError::Timeout { .. } => "TIMEOUT",
+            ZapError::PayloadTooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            ZapError::HeadersTooLarge { .. } => "HEADERS_TOO_LARGE",
             ZapError::RateLimited { .. } => "RATE_LIMITED",
             ZapError::InvalidState(_) => "INVALID_STATE",
             ZapError::Internal(_) => "INTERNAL_ERROR",
             ZapError::WebSocket { .. } => "WEBSOCKET_ERROR",
+            ZapError::NotImplemented { .. } => "NOT_IMPLEMENTED",
+            ZapError::BadRequest { .. } => "BAD_REQUEST",
         }
     }
 
@@ -117,6 +150,7 @@ impl ZapError {
         match self {
             ZapError::Http { .. } => 500,
             ZapError::RouteNotFound { .. } => 404,
+            ZapError::MethodNotAllowed { .. } => 405,
             ZapError::Handler { .. } => 500,
             ZapError::Ipc { .. } => 502,
             ZapError::Config { .. } => 500,
@@ -126,10 +160,14 @@ impl ZapError {
             ZapError::Unauthorized { .. } => 401,
             ZapError::Forbidden { .. } => 403,
             ZapError::Timeout { .. } => 504,
+            ZapError::PayloadTooLarge { .. } => 413,
+            ZapError::HeadersTooLarge { .. } => 431,
             ZapError::RateLimited { .. } => 429,
             ZapError::InvalidState(_) => 500,
             ZapError::Internal(_) => 500,
             ZapError::WebSocket { .. } => 500,
+            ZapError::NotImplemented { .. } => 501,
+            ZapError::BadRequest { .. } => 400,
         }
     }
 
@@ -147,6 +185,44 @@ impl ZapError {
         }
     }
 
+    /// Convert to an RFC 7807 problem details document
+    ///
+    /// `type` identifies the variant (`urn:zap:error:validation-error`),
+    /// `title` is the status reason phrase and `detail` the error message.
+    /// Server errors (5xx) get a generic `detail` so internal messages aren't
+    /// exposed. The error `code` and any details are added as extension members.
+    /// Serve it with the [`PROBLEM_JSON_CONTENT_TYPE`] content type.
+    pub fn to_problem_json(&self) -> serde_json::Value {
+        let status = self.status_code();
+        let title = hyper::StatusCode::from_u16(status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("Error");
+        let detail = if status >= 500 {
+            "The server encountered an error while processing the request".to_string()
+        } else {
+            self.to_string()
+        };
+
+        let mut problem = serde_json::json!({
+            "type": format!("urn:zap:error:{}", self.code().to_ascii_lowercase().replace('_', "-")),
+            "title": title,
+            "status": status,
+            "detail": detail,
+            "code": self.code(),
+        });
+
+        if let (Some(serde_json::Value::Object(details)), Some(object)) =
+            (self.details(), problem.as_object_mut())
+        {
+            for (key, value) in details {
+                object.entry(key).or_insert(value);
+            }
+        }
+
+        problem
+    }
+
     /// Get additional error-specific details
     fn details(&self) -> Option<serde_json::Value> {
         match self {
@@ -156,10 +232,12 @@ impl ZapError {
             ZapError::RateLimited { retry_after_secs } => {
                 Some(serde_json::json!({ "retryAfter": retry_after_secs }))
             }
+            ZapError::PayloadTooLarge { limit } => Some(serde_json::json!({ "limit": limit })),
             ZapError::Timeout { timeout_ms, .. } => {
                 Some(serde_json::json!({ "timeoutMs": timeout_ms }))
             }
             ZapError::RouteNotFound { path } => Some(serde_json::json!({ "path": path })),
+            ZapError::MethodNotAllowed { allowed, .. } => Some(serde_json::json!({ "allowed": allowed })),
             ZapError::Handler { handler_id, .. } => {
                 handler_id.as_ref().map(|id| serde_json::json!({ "handlerId": id }))
             }
@@ -182,6 +260,19 @@ impl ZapError {
         ZapError::RouteNotFound { path: path.into() }
     }
 
+    /// Create a method not allowed error for a path routed only for `allowed`
+    pub fn method_not_allowed(
+        method: impl Into<String>,
+        path: impl Into<String>,
+        allowed: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        ZapError::MethodNotAllowed {
+            method: method.into(),
+            path: path.into(),
+            allowed: allowed.into_iter().map(Into::into).collect(),
+        }
+    }
+
     /// Create a handler error
     pub fn handler(message: impl Into<String>) -> Self {
         ZapError::Handler {
@@ -250,6 +341,18 @@ impl ZapError {
         }
     }
 
+    /// Create a payload too large error
+    pub fn payload_too_large(limit: usize) -> Self {
+        ZapError::PayloadTooLarge { limit }
+    }
+
+    /// Create a request header fields too large error
+    pub fn headers_too_large(message: impl Into<String>) -> Self {
+        ZapError::HeadersTooLarge {
+            message: message.into(),
+        }
+    }
+
     /// Create a rate limited error
     pub fn rate_limited(retry_after_secs: u64) -> Self {
         ZapError::RateLimited { retry_after_secs }
@@ -261,6 +364,35 @@ impl ZapError {
             message: message.into(),
         }
     }
+
+    /// Create a not implemented error
+    pub fn not_implemented(message: impl Into<String>) -> Self {
+        ZapError::NotImplemented {
+            message: message.into(),
+        }
+    }
+
+    /// Create a bad request error
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ZapError::BadRequest {
+            message: message.into(),
+        }
+    }
+}
+
+impl From<zap_core::MiddlewareError> for ZapError {
+    fn from(err: zap_core::MiddlewareError) -> Self {
+        use zap_core::MiddlewareError;
+
+        match err {
+            MiddlewareError::BadRequest(message) => Self::validation(message),
+            MiddlewareError::Unauthorized(message) => Self::unauthorized(message),
+            MiddlewareError::NotFound(path) => Self::route_not_found(path),
+            MiddlewareError::InternalError(_) | MiddlewareError::InternalServerError(_) => {
+                Self::Internal(err.to_string())
+            }
+        }
+    }
 }
 
 impl From<String> for ZapError {
@@ -346,10 +478,12 @@ mod tests {
     #[test]
     fn test_status_codes() {
         assert_eq!(ZapError::route_not_found("/test").status_code(), 404);
+        assert_eq!(ZapError::method_not_allowed("PUT", "/test", ["GET"]).status_code(), 405);
         assert_eq!(ZapError::validation("test").status_code(), 400);
         assert_eq!(ZapError::unauthorized("test").status_code(), 401);
         assert_eq!(ZapError::forbidden("test").status_code(), 403);
         assert_eq!(ZapError::rate_limited(60).status_code(), 429);
+        assert_eq!(ZapError::payload_too_large(1024).status_code(), 413);
         assert_eq!(ZapError::timeout("test", 5000).status_code(), 504);
     }
 
@@ -368,6 +502,24 @@ mod tests {
         assert_eq!(details["field"], "email");
     }
 
+    #[test]
+    fn test_problem_json() {
+        let problem = ZapError::validation_field("Invalid email", "email").to_problem_json();
+
+        assert_eq!(problem["type"], "urn:zap:error:validation-error");
+        assert_eq!(problem["title"], "Bad Request");
+        assert_eq!(problem["status"], 400);
+        assert_eq!(problem["detail"], "Validation error: Invalid email");
+        assert_eq!(problem["code"], "VALIDATION_ERROR");
+        assert_eq!(problem["field"], "email");
+
+        // Internal messages stay out of server error documents
+        let problem = ZapError::Internal("db password rejected".to_string()).to_problem_json();
+        assert_eq!(problem["status"], 500);
+        assert_eq!(problem["title"], "Internal Server Error");
+        assert!(!problem["detail"].as_str().unwrap().contains("password"));
+    }
+
     #[test]
     fn test_error_response_json() {
         let response = ErrorResponse::new("TEST_ERROR", "Test message", 500);
@@ -0,0 +1,112 @@
+//! Client IP resolution behind reverse proxies
+//!
+//! `X-Forwarded-For` is only trustworthy when it was appended by a proxy we
+//! control. Any client can send the header, so it is honored only when the
+//! socket peer belongs to one of the configured trusted proxy networks.
+//!
+//! The chain is walked right-to-left (closest hop first), skipping trusted
+//! proxies; the first untrusted address is the client.
+
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// Standard header name for the forwarded client chain
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Check whether an address belongs to any trusted proxy network
+#[inline]
+pub fn is_trusted(ip: &IpAddr, trusted_proxies: &[IpNet]) -> bool {
+    trusted_proxies.iter().any(|net| net.contains(ip))
+}
+
+/// Resolve the originating client IP for a request
+///
+/// Returns `peer` unchanged unless it is a trusted proxy. Otherwise walks the
+/// `X-Forwarded-For` chain right-to-left and returns the first hop that is not
+/// a trusted proxy. Parsing stops at the first malformed entry, in which case
+/// the last valid hop is used.
+pub fn resolve_client_ip(
+    peer: IpAddr,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpNet],
+) -> IpAddr {
+    if !is_trusted(&peer, trusted_proxies) {
+        return peer;
+    }
+
+    let chain = match forwarded_for {
+        Some(value) => value,
+        None => return peer,
+    };
+
+    let mut client = peer;
+    for hop in chain.rsplit(',') {
+        match hop.trim().parse::<IpAddr>() {
+            Ok(ip) => {
+                client = ip;
+                if !is_trusted(&ip, trusted_proxies) {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nets(list: &[&str]) -> Vec<IpNet> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_spoofed_header() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let peer: IpAddr = "203.0.113.7".parse().unwrap();
+
+        let ip = resolve_client_ip(peer, Some("1.2.3.4"), &trusted);
+        assert_eq!(ip, peer);
+    }
+
+    #[test]
+    fn test_trusted_proxy_header_honored() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+
+        let ip = resolve_client_ip(peer, Some("198.51.100.20"), &trusted);
+        assert_eq!(ip, "198.51.100.20".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_chain_skips_trusted_hops() {
+        let trusted = nets(&["10.0.0.0/8", "192.168.0.0/16"]);
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+
+        // Leftmost entry is client-controlled and must not win
+        let ip = resolve_client_ip(
+            peer,
+            Some("6.6.6.6, 198.51.100.20, 192.168.1.5"),
+            &trusted,
+        );
+        assert_eq!(ip, "198.51.100.20".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_malformed_hop_stops_walk() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+
+        let ip = resolve_client_ip(peer, Some("198.51.100.20, garbage, 10.0.0.2"), &trusted);
+        assert_eq!(ip, "10.0.0.2".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_no_trusted_proxies_uses_peer() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        assert_eq!(resolve_client_ip(peer, Some("1.2.3.4"), &[]), peer);
+    }
+}
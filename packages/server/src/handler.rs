@@ -3,11 +3,20 @@
 use std::future::Future;
 use std::pin::Pin;
 
+use bytes::Bytes;
+use futures::Stream;
+
 use crate::error::ZapError;
 use crate::response::ZapResponse;
 use zap_core::Request;
 use crate::request::RequestData;
 
+/// Request body delivered chunk by chunk to streaming handlers
+///
+/// Yields a 413 `PayloadTooLarge` error once the route's body limit is
+/// exceeded.
+pub type BodyStream = Pin<Box<dyn Stream<Item = Result<Bytes, ZapError>> + Send>>;
+
 /// Handler trait for request processing
 pub trait Handler {
     /// Handle the request and return a response
@@ -15,6 +24,40 @@ pub trait Handler {
         &'a self,
         req: Request<'a>,
     ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>>;
+
+    /// Maximum request body size for this handler's route
+    ///
+    /// `None` falls back to the server's global `max_request_body_size`.
+    fn body_limit(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether this handler reads the request body as a [`BodyStream`]
+    ///
+    /// When true the server skips buffering the body and calls
+    /// [`Handler::handle_stream`] instead of [`Handler::handle`].
+    fn streams_body(&self) -> bool {
+        false
+    }
+
+    /// Whether requests are proxied to a TypeScript handler over IPC
+    ///
+    /// Only used for diagnostics such as [`crate::Zap::debug_routes`].
+    fn is_typescript(&self) -> bool {
+        false
+    }
+
+    /// Handle a request whose body is streamed rather than buffered
+    ///
+    /// `req.body` is empty; the body arrives through `body`.
+    fn handle_stream<'a>(
+        &'a self,
+        req: RequestData,
+        body: BodyStream,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        let _ = (req, body);
+        Box::pin(async { Err(ZapError::handler("Handler does not accept a streamed request body")) })
+    }
 }
 
 /// Implement Handler for simple closures that return strings
@@ -85,5 +128,118 @@ where
     }
 }
 
+/// Async handler wrapper for fallible handlers
+///
+/// An `Err` is answered by the server's error response, e.g. a 400 problem
+/// document for `ZapError::Validation`.
+pub struct TryAsyncHandler<F> {
+    func: F,
+}
+
+impl<F> TryAsyncHandler<F> {
+    pub fn new(func: F) -> Self {
+        Self { func }
+    }
+}
+
+impl<F, Fut> Handler for TryAsyncHandler<F>
+where
+    F: Fn(RequestData) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<ZapResponse, ZapError>> + Send,
+{
+    fn handle<'a>(
+        &'a self,
+        req: Request<'a>,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        let req_data = RequestData::from_request(&req);
+        Box::pin(async move { (self.func)(req_data).await })
+    }
+}
+
+/// Async handler that receives the request body as a stream
+///
+/// Lets large uploads be written out incrementally instead of held in memory.
+pub struct StreamingHandler<F> {
+    func: F,
+}
+
+impl<F> StreamingHandler<F> {
+    pub fn new(func: F) -> Self {
+        Self { func }
+    }
+}
+
+impl<F, Fut> Handler for StreamingHandler<F>
+where
+    F: Fn(RequestData, BodyStream) -> Fut + Send + Sync,
+    Fut: Future<Output = ZapResponse> + Send,
+{
+    /// Called with an already-buffered body, which is streamed as one chunk
+    fn handle<'a>(
+        &'a self,
+        req: Request<'a>,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        let mut req_data = RequestData::from_request(&req);
+        let body = Bytes::from(std::mem::take(&mut req_data.body));
+        self.handle_stream(req_data, Box::pin(futures::stream::once(async move { Ok(body) })))
+    }
+
+    fn streams_body(&self) -> bool {
+        true
+    }
+
+    fn handle_stream<'a>(
+        &'a self,
+        req: RequestData,
+        body: BodyStream,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        Box::pin(async move { Ok((self.func)(req, body).await) })
+    }
+}
+
+/// Handler wrapper that overrides the request body size limit for its route
+pub struct BodyLimit<H> {
+    inner: H,
+    limit: usize,
+}
+
+impl<H> BodyLimit<H> {
+    pub fn new(inner: H, limit: usize) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl<H> Handler for BodyLimit<H>
+where
+    H: Handler + Send + Sync,
+{
+    fn handle<'a>(
+        &'a self,
+        req: Request<'a>,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        self.inner.handle(req)
+    }
+
+    fn body_limit(&self) -> Option<usize> {
+        Some(self.limit)
+    }
+
+    fn streams_body(&self) -> bool {
+        self.inner.streams_body()
+    }
+
+    fn is_typescript(&self) -> bool {
+        self.inner.is_typescript()
+    }
+
+    fn handle_stream<'a>(
+        &'a self,
+        req: RequestData,
+        body: BodyStream,
+    ) -> Pin<Box<dyn Future<Output = Result<ZapResponse, ZapError>> + Send + 'a>> {
+        self.inner.handle_stream(req, body)
+    }
+}
+
 /// Type alias for boxed async handlers
 pub type BoxedHandler = Box<dyn Handler + Send + Sync>; 
\ No newline at end of file
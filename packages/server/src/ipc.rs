@@ -185,6 +185,11 @@ pub struct IpcRequest {
 
     /// Cookies parsed from headers
     pub cookies: HashMap<String, String>,
+
+    /// Caller identity from verified JWT claims, echoed on the handler's RPC
+    /// calls so exported functions see it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<splice::protocol::AuthContext>,
 }
 
 /// IPC Server - receives requests from Rust, forwards to TypeScript
@@ -371,6 +376,7 @@ mod tests {
             headers: HashMap::new(),
             body: String::new(),
             cookies: HashMap::new(),
+            auth: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -397,6 +403,7 @@ mod tests {
             headers: HashMap::new(),
             body: String::new(),
             cookies: HashMap::new(),
+            auth: None,
         };
 
         let msg = IpcMessage::InvokeHandler {
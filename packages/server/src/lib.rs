@@ -90,7 +90,9 @@ extern crate self as zap;
 pub mod config;
 pub mod connection_pool;
 pub mod context;
+pub mod deadline;
 pub mod error;
+pub mod forwarded;
 pub mod handler;
 pub mod ipc;
 pub mod metrics;
@@ -101,43 +103,54 @@ pub mod request;
 pub mod request_id;
 pub mod response;
 pub mod rpc;
+pub mod schema;
 pub mod server;
 pub mod shutdown;
 pub mod splice_client;
 pub mod splice_worker;
 pub mod r#static;
+pub mod stream;
 pub mod utils;
 pub mod websocket;
+pub mod ws;
 
 // Re-export main types for convenient use
 pub use config::{ServerConfig, ZapConfig};
-pub use connection_pool::{ConnectionPool, PoolConfig, PoolStats};
+pub use connection_pool::{ConnectionPool, PoolConfig, PoolStats, PooledClient};
 pub use context::Context;
-pub use error::{ZapError, ZapResult, ErrorResponse};
-pub use handler::{AsyncHandler, BoxedHandler, Handler, SimpleHandler};
+pub use error::{ZapError, ZapResult, ErrorResponse, PROBLEM_JSON_CONTENT_TYPE};
+pub use handler::{AsyncHandler, BodyLimit, BodyStream, BoxedHandler, Handler, SimpleHandler, StreamingHandler, TryAsyncHandler};
 pub use ipc::{IpcMessage, IpcRequest, IpcServer, IpcClient, IpcEncoding};
 pub use proxy::ProxyHandler;
 pub use request::RequestData;
-pub use response::{Json, ZapResponse};
-pub use rpc::{RpcServerHandle, RpcDispatchFn, RpcCallMessage, RpcResponseMessage, RpcErrorMessage};
-pub use server::Zap;
-pub use shutdown::{GracefulShutdown, ShutdownConfig, ConnectionGuard};
-pub use r#static::{ETagStrategy, StaticHandler, StaticOptions, handle_static_files_with_headers};
+pub use response::{
+    Json, JsonOptions, MultipartBody, MultipartPart, PrettyJson, ResponseSize, StreamedBody, Vary, ZapBody, ZapResponse,
+};
+pub use rpc::{
+    RpcServerHandle, RpcShutdown, RpcDispatchFn, RpcCallMessage, RpcResponseMessage, RpcErrorMessage,
+    RpcCodec, JsonCodec, MessagePackCodec, CborCodec, RpcError, status_for_code, TYPED_ERROR_PREFIX,
+};
+pub use schema::{parse_validation_error, validate_params, ParamViolation};
+pub use server::{BoundServer, ResponseHook, ResponseInfo, RewriteAction, RewriteFn, UpgradeHandler, UpgradedConnection, Zap};
+pub use shutdown::{GracefulShutdown, ShutdownConfig, ShutdownHook, ConnectionGuard};
+pub use r#static::{ETagStrategy, StaticHandler, StaticOptions, handle_static_files_with_headers, handle_static_files_with_method};
 pub use websocket::{WsConfig, WsHandler, handle_websocket_connection, is_websocket_upgrade};
+pub use ws::{BroadcastHub, HubClient};
 pub use reliability::{
-    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState,
-    HealthChecker, HealthCheckResponse, HealthStatus, ComponentHealth,
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState, CircuitStateHook,
+    HealthChecker, HealthCheckResponse, HealthStatus, ComponentHealth, HealthPing,
     ResilientIpc, RetryConfig,
 };
 
 // Re-export important types from core crate for convenience
-pub use zap_core::{Method, StatusCode};
+pub use zap_core::{Method, RouterError, StatusCode};
 
 // Re-export macros for #[zap::export] syntax
 pub use zap_macros::export;
 
 // Re-export registry function for building RPC dispatchers
-pub use registry::build_rpc_dispatcher;
+pub use registry::{build_context_dispatcher, build_rpc_dispatcher, find_export, ContextDispatchFn};
+pub use stream::{ValueStream, ZapStream};
 
 // Re-export Splice protocol types for user worker code
 pub use splice::protocol::{Message, Role, ExportMetadata, RequestContext, AuthContext};
@@ -146,9 +159,12 @@ pub use splice_worker::run as splice_worker_run;
 // Internal types for macro use - not part of public API
 #[doc(hidden)]
 pub mod __private {
+    pub use futures;
     pub use linkme;
     pub use crate::context::Context;
     pub use crate::registry::{ExportedFunction, FunctionWrapper, EXPORTS};
+    pub use crate::schema::check_params;
+    pub use crate::stream::ValueStream;
 }
 
 #[cfg(test)]
@@ -250,8 +266,8 @@ mod tests {
         let path = "/api/users/123?include=profile&format=json".to_string();
         let headers = {
             let mut h = HashMap::new();
-            h.insert("Content-Type".to_string(), "application/json".to_string());
-            h.insert("Authorization".to_string(), "Bearer token123".to_string());
+            h.insert("content-type".to_string(), vec!["application/json".to_string()]);
+            h.insert("authorization".to_string(), vec!["Bearer token123".to_string()]);
             h
         };
         let params = {
@@ -281,6 +297,11 @@ mod tests {
             params,
             query,
             cookies,
+            client_ip: None,
+            app_state: None,
+            session: None,
+            claims: None,
+            request_id: None,
         };
         
         assert_eq!(req_data.method, Method::POST);
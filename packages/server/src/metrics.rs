@@ -7,10 +7,11 @@
 
 use lazy_static::lazy_static;
 use prometheus::{
-    CounterVec, Encoder, Gauge, HistogramOpts, HistogramVec, Opts, Registry,
+    Counter, CounterVec, Encoder, Gauge, HistogramOpts, HistogramVec, Opts, Registry,
     TextEncoder,
 };
-use std::sync::Once;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Once};
 
 static INIT: Once = Once::new();
 
@@ -45,6 +46,28 @@ lazy_static! {
         "Number of HTTP requests currently being processed"
     ).expect("metric can be created");
 
+    // ========================================================================
+    // Connection Metrics
+    // ========================================================================
+
+    /// Number of TCP connections currently open
+    pub static ref HTTP_CONNECTIONS_ACTIVE: Gauge = Gauge::new(
+        "zap_http_connections_active",
+        "Number of HTTP connections currently open"
+    ).expect("metric can be created");
+
+    /// Total number of accepted TCP connections
+    pub static ref HTTP_CONNECTIONS_ACCEPTED_TOTAL: Counter = Counter::new(
+        "zap_http_connections_accepted_total",
+        "Total number of accepted HTTP connections"
+    ).expect("metric can be created");
+
+    /// Total number of TCP connections that have closed
+    pub static ref HTTP_CONNECTIONS_COMPLETED_TOTAL: Counter = Counter::new(
+        "zap_http_connections_completed_total",
+        "Total number of completed HTTP connections"
+    ).expect("metric can be created");
+
     // ========================================================================
     // IPC Metrics
     // ========================================================================
@@ -82,6 +105,9 @@ lazy_static! {
         &["version"]
     ).expect("metric can be created");
 
+    /// Monotonic server start time, used for uptime
+    static ref STARTED_AT: std::time::Instant = std::time::Instant::now();
+
     /// Server start time (unix timestamp)
     pub static ref SERVER_START_TIME: Gauge = Gauge::new(
         "zap_server_start_time_seconds",
@@ -103,6 +129,17 @@ pub fn init_metrics() {
             .register(Box::new(HTTP_REQUESTS_IN_FLIGHT.clone()))
             .expect("HTTP_REQUESTS_IN_FLIGHT can be registered");
 
+        // Connection metrics
+        REGISTRY
+            .register(Box::new(HTTP_CONNECTIONS_ACTIVE.clone()))
+            .expect("HTTP_CONNECTIONS_ACTIVE can be registered");
+        REGISTRY
+            .register(Box::new(HTTP_CONNECTIONS_ACCEPTED_TOTAL.clone()))
+            .expect("HTTP_CONNECTIONS_ACCEPTED_TOTAL can be registered");
+        REGISTRY
+            .register(Box::new(HTTP_CONNECTIONS_COMPLETED_TOTAL.clone()))
+            .expect("HTTP_CONNECTIONS_COMPLETED_TOTAL can be registered");
+
         // IPC metrics
         REGISTRY
             .register(Box::new(IPC_INVOKE_DURATION_SECONDS.clone()))
@@ -228,6 +265,115 @@ pub fn dec_in_flight() {
     HTTP_REQUESTS_IN_FLIGHT.dec();
 }
 
+/// Tracks an accepted connection until dropped
+///
+/// Created by [`track_connection`] or [`ConnectionCounters::track`];
+/// dropping it marks the connection completed.
+pub struct ConnectionMetricsGuard {
+    counters: Option<Arc<ConnectionCounters>>,
+}
+
+impl Drop for ConnectionMetricsGuard {
+    fn drop(&mut self) {
+        HTTP_CONNECTIONS_ACTIVE.dec();
+        HTTP_CONNECTIONS_COMPLETED_TOTAL.inc();
+        if let Some(counters) = &self.counters {
+            counters.active.fetch_sub(1, Ordering::Relaxed);
+            counters.completed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Record an accepted connection
+pub fn track_connection() -> ConnectionMetricsGuard {
+    HTTP_CONNECTIONS_ACCEPTED_TOTAL.inc();
+    HTTP_CONNECTIONS_ACTIVE.inc();
+    ConnectionMetricsGuard { counters: None }
+}
+
+/// Connection counts of a single server
+///
+/// The process-wide metrics add up every server in the process; these count
+/// only the connections one server accepted.
+#[derive(Debug, Default)]
+pub struct ConnectionCounters {
+    active: AtomicU64,
+    accepted: AtomicU64,
+    completed: AtomicU64,
+}
+
+impl ConnectionCounters {
+    /// Record an accepted connection here and in the process-wide metrics
+    pub fn track(self: &Arc<Self>) -> ConnectionMetricsGuard {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        self.active.fetch_add(1, Ordering::Relaxed);
+        let mut guard = track_connection();
+        guard.counters = Some(Arc::clone(self));
+        guard
+    }
+
+    /// Read the current counts
+    pub fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            active: self.active.load(Ordering::Relaxed),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Snapshot of connection metrics
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct ConnectionStats {
+    /// Connections currently open
+    pub active: u64,
+    /// Connections accepted since startup
+    pub accepted: u64,
+    /// Connections closed since startup
+    pub completed: u64,
+}
+
+/// Read current connection metrics
+pub fn connection_stats() -> ConnectionStats {
+    ConnectionStats {
+        active: HTTP_CONNECTIONS_ACTIVE.get().max(0.0) as u64,
+        accepted: HTTP_CONNECTIONS_ACCEPTED_TOTAL.get() as u64,
+        completed: HTTP_CONNECTIONS_COMPLETED_TOTAL.get() as u64,
+    }
+}
+
+/// Record the server start time used by [`uptime_secs`]
+///
+/// Only the first call has an effect.
+pub fn record_start() {
+    lazy_static::initialize(&STARTED_AT);
+}
+
+/// Seconds since the server started (or since first asked, if never started)
+pub fn uptime_secs() -> u64 {
+    STARTED_AT.elapsed().as_secs()
+}
+
+/// Resident set size of this process in bytes
+///
+/// Read from `/proc/self/status` on Linux; `None` on other platforms or if
+/// the file can't be read.
+pub fn memory_rss_bytes() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        // Reported as e.g. "VmRSS:	   12345 kB"
+        let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kib * 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,5 +406,12 @@ mod tests {
         init_metrics();
         let output = encode_metrics();
         assert!(output.contains("zap_"));
+        assert!(output.contains("zap_http_connections_active"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_memory_rss_bytes() {
+        assert!(memory_rss_bytes().unwrap() > 0);
     }
 }
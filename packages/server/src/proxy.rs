@@ -32,7 +32,7 @@ pub struct ProxyHandler {
     /// Request timeout in seconds
     timeout_secs: u64,
 
-    /// Optional connection pool (if None, uses global pool or creates per-request connections)
+    /// Optional connection pool (if None, creates per-request connections)
     connection_pool: Option<Arc<ConnectionPool>>,
 }
 
@@ -104,10 +104,12 @@ impl ProxyHandler {
             request,
         };
 
-        // For streaming support, we need a dedicated connection that we can keep reading from
-        // We can't use the connection pool for this because streaming needs multiple reads
-        // So we create a dedicated connection for the entire request lifecycle
-        let response = self.invoke_with_streaming_support(msg).await?;
+        // Streaming needs several reads, so the whole exchange runs on one
+        // connection: a leased pooled one when available, else a dedicated one
+        let response = match &self.connection_pool {
+            Some(pool) => self.invoke_with_pool(pool, msg).await?,
+            None => self.invoke_with_streaming_support(msg).await?,
+        };
 
         debug!("📥 Received response from TypeScript handler");
 
@@ -128,6 +130,29 @@ impl ProxyHandler {
             e
         })?;
 
+        self.exchange(&mut client, msg).await
+    }
+
+    /// Invoke handler on a connection leased from the pool
+    ///
+    /// The lease is held until any streamed response ends. A connection that
+    /// failed mid-exchange may still have a reply in flight, so it is evicted;
+    /// errors reported by the handler itself leave it in rotation.
+    async fn invoke_with_pool(&self, pool: &ConnectionPool, msg: IpcMessage) -> ZapResult<ZapResponse> {
+        let mut lease = pool.acquire().await.map_err(|e| {
+            error!("Failed to acquire pooled IPC connection: {}", e);
+            e
+        })?;
+
+        let result = self.exchange(lease.client(), msg).await;
+        if matches!(result, Err(ref e) if !matches!(e, ZapError::Handler { .. })) {
+            lease.evict();
+        }
+        result
+    }
+
+    /// Send an invocation and read its response, following a stream to its end
+    async fn exchange(&self, client: &mut IpcClient, msg: IpcMessage) -> ZapResult<ZapResponse> {
         // Send the invocation
         client.send_message(msg).await.map_err(|e| {
             error!("Failed to send IPC message: {}", e);
@@ -189,7 +214,7 @@ impl ProxyHandler {
                 headers,
             } => {
                 info!("Starting streaming response: {} (status: {})", stream_id, status);
-                self.handle_streaming_response(&mut client, stream_id, status, headers)
+                self.handle_streaming_response(client, stream_id, status, headers)
                     .await
             }
 
@@ -332,32 +357,6 @@ impl ProxyHandler {
             }
         }
     }
-
-    /// Invoke handler using connection pool (for non-streaming responses)
-    #[allow(dead_code)]
-    async fn invoke_with_pool(
-        &self,
-        pool: &ConnectionPool,
-        msg: IpcMessage,
-    ) -> ZapResult<IpcMessage> {
-        let timeout_duration = std::time::Duration::from_secs(self.timeout_secs);
-
-        tokio::time::timeout(timeout_duration, pool.send_recv(msg))
-            .await
-            .map_err(|_| {
-                warn!(
-                    "Handler {} timed out after {}s",
-                    self.handler_id, self.timeout_secs
-                );
-                ZapError::timeout(
-                    format!(
-                        "Handler {} did not respond within {}s",
-                        self.handler_id, self.timeout_secs
-                    ),
-                    self.timeout_secs * 1000,
-                )
-            })?
-    }
 }
 
 impl Handler for ProxyHandler {
@@ -371,13 +370,16 @@ impl Handler for ProxyHandler {
             let body_string = String::from_utf8_lossy(body_bytes).to_string();
 
             // Use the request data that's already been parsed
-            // Get or generate request ID for correlation
+            // Correlate with RequestIdMiddleware's ID, or the incoming header
             let headers_map: std::collections::HashMap<String, String> = req
                 .headers()
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect();
-            let request_id = request_id::get_or_generate(&headers_map);
+            let request_id = match req.request_id() {
+                Some(id) => id.to_string(),
+                None => request_id::get_or_generate(&headers_map),
+            };
 
             let ipc_request = IpcRequest {
                 request_id,
@@ -401,12 +403,22 @@ impl Handler for ProxyHandler {
                     .iter()
                     .map(|(k, v)| (k.to_string(), v.to_string()))
                     .collect(),
+                auth: req.claims().and_then(|claims| {
+                    Some(splice::protocol::AuthContext {
+                        user_id: claims.sub.clone()?,
+                        roles: claims.roles.clone(),
+                    })
+                }),
             };
 
             // Invoke TypeScript handler via IPC (handles both regular and streaming responses)
             self.invoke_handler(ipc_request).await
         })
     }
+
+    fn is_typescript(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -433,4 +445,78 @@ mod tests {
         assert_eq!(handler.handler_id, "handler_1");
         assert_eq!(handler.timeout_secs, 60);
     }
+
+    #[tokio::test]
+    async fn test_pooled_proxy_reuses_warm_connection() {
+        use crate::connection_pool::PoolConfig;
+        use crate::ipc::{deserialize_message, serialize_message};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("proxy.sock");
+        let accepted = Arc::new(AtomicUsize::new(0));
+
+        // A TypeScript stand-in answering every invocation with 200 "ok"
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    loop {
+                        let mut len_buf = [0u8; 4];
+                        if stream.read_exact(&mut len_buf).await.is_err() {
+                            return;
+                        }
+                        let mut payload = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+                        if stream.read_exact(&mut payload).await.is_err() {
+                            return;
+                        }
+                        let IpcMessage::InvokeHandler { handler_id, .. } = deserialize_message(&payload).unwrap() else {
+                            return;
+                        };
+                        let reply = IpcMessage::HandlerResponse {
+                            handler_id,
+                            status: 200,
+                            headers: Default::default(),
+                            body: "ok".to_string(),
+                        };
+                        let reply = serialize_message(&reply, IpcEncoding::MessagePack).unwrap();
+                        let mut frame = (reply.len() as u32).to_be_bytes().to_vec();
+                        frame.extend_from_slice(&reply);
+                        if stream.write_all(&frame).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        let socket_path = socket.to_string_lossy().into_owned();
+        let pool = Arc::new(ConnectionPool::new(PoolConfig::new(socket_path.clone()).size(1)));
+        assert_eq!(pool.warm_up(1).await, 1);
+
+        let handler = ProxyHandler::with_timeout_and_pool("handler_0".to_string(), socket_path, 5, pool.clone());
+        for _ in 0..3 {
+            let request = IpcRequest {
+                request_id: "req".to_string(),
+                method: "GET".to_string(),
+                path: "/".to_string(),
+                path_only: "/".to_string(),
+                query: Default::default(),
+                params: Default::default(),
+                headers: Default::default(),
+                body: String::new(),
+                cookies: Default::default(),
+                auth: None,
+            };
+            let response = handler.invoke_handler(request).await.unwrap();
+            assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 200));
+        }
+
+        // Every request ran on the connection opened during warm-up
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.stats().healthy, 1);
+    }
 }
@@ -6,15 +6,23 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use serde_json::Value;
 use futures::future::BoxFuture;
+use futures::stream::StreamExt;
 use crate::context::Context;
+use crate::deadline::requested_timeout;
+use crate::splice_client::DEFAULT_SPLICE_DEADLINE_MS;
+use crate::stream::ValueStream;
+
+/// Future resolving to the item stream of a streaming function
+pub type StreamFuture = BoxFuture<'static, Result<ValueStream, String>>;
 
 /// Wrapper around sync or async functions callable via RPC
 ///
 /// This enum allows the registry to handle both synchronous and asynchronous
 /// functions with a uniform interface. Supports both legacy (no Context) and
-/// context-aware functions.
+/// context-aware functions, plus streaming functions that yield multiple values.
 pub enum FunctionWrapper {
     /// Synchronous function handler (legacy - no Context)
     Sync(fn(&HashMap<String, Value>) -> Result<Value, String>),
@@ -24,11 +32,24 @@ pub enum FunctionWrapper {
     SyncCtx(fn(&Context, &HashMap<String, Value>) -> Result<Value, String>),
     /// Asynchronous function handler with Context support
     AsyncCtx(fn(&Context, &HashMap<String, Value>) -> BoxFuture<'static, Result<Value, String>>),
+    /// Streaming function handler (legacy - no Context)
+    Stream(fn(&HashMap<String, Value>) -> StreamFuture),
+    /// Streaming function handler with Context support
+    StreamCtx(fn(&Context, &HashMap<String, Value>) -> StreamFuture),
 }
 
 impl FunctionWrapper {
+    /// Whether this wrapper yields a stream of values
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, FunctionWrapper::Stream(_) | FunctionWrapper::StreamCtx(_))
+    }
+
     /// Call the wrapped function (async-safe for both sync and async functions)
     ///
+    /// Streaming functions are drained and their items returned as a JSON array,
+    /// stopping at the first error. Use [`FunctionWrapper::call_stream`] to
+    /// consume items as they are produced.
+    ///
     /// # Arguments
     /// * `context` - Optional request context (required for SyncCtx/AsyncCtx variants)
     /// * `params` - Function parameters as JSON HashMap
@@ -54,6 +75,35 @@ impl FunctionWrapper {
                 )?;
                 f(ctx, params).await
             }
+            FunctionWrapper::Stream(_) | FunctionWrapper::StreamCtx(_) => {
+                let mut stream = self.call_stream(context, params).await?;
+                let mut items = Vec::new();
+                while let Some(item) = stream.next().await {
+                    items.push(item?);
+                }
+                Ok(Value::Array(items))
+            }
+        }
+    }
+
+    /// Call a streaming function and return its item stream
+    ///
+    /// Returns an error for non-streaming wrappers.
+    pub async fn call_stream(
+        &self,
+        context: Option<&Context>,
+        params: &HashMap<String, Value>
+    ) -> Result<ValueStream, String> {
+        match self {
+            FunctionWrapper::Stream(f) => f(params).await,
+            FunctionWrapper::StreamCtx(f) => {
+                let ctx = context.ok_or_else(||
+                    "Function requires context but none provided. \
+                     Ensure the function is called through the Splice protocol.".to_string()
+                )?;
+                f(ctx, params).await
+            }
+            _ => Err("Function does not return a stream".to_string()),
         }
     }
 }
@@ -69,6 +119,8 @@ pub struct ExportedFunction {
     pub is_async: bool,
     /// Whether the function requires Context parameter
     pub has_context: bool,
+    /// JSON Schema of the parameters object, checked before deserializing
+    pub params_schema: &'static str,
     /// The wrapper function that handles deserialization and execution
     pub wrapper: FunctionWrapper,
 }
@@ -90,6 +142,17 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Look up a registered export by name
+pub fn find_export(name: &str) -> Option<&'static ExportedFunction> {
+    EXPORTS.iter().find(|func| func.name == name)
+}
+
+/// Dispatch function that receives a fully-built [`Context`]
+///
+/// Unlike [`crate::rpc::RpcDispatchFn`], the caller constructs the `Context`, so it can
+/// carry a cancellation token and deadline tied to the originating request.
+pub type ContextDispatchFn = Arc<dyn Fn(String, Value, Option<Context>) -> Result<Value, String> + Send + Sync>;
+
 /// Build RPC dispatcher from all registered functions
 ///
 /// This function iterates through all functions registered via the `#[zap::export]` macro
@@ -111,6 +174,34 @@ lazy_static::lazy_static! {
 /// // Use with RpcServerHandle::new()
 /// ```
 pub fn build_rpc_dispatcher() -> crate::rpc::RpcDispatchFn {
+    let dispatch = build_context_dispatcher();
+    Arc::new(move |function_name: String, params: Value, context_data: Option<splice::protocol::RequestContext>| {
+        let received_at = Instant::now();
+        dispatch(
+            function_name,
+            params,
+            context_data.map(|inner| context_with_requested_deadline(inner, received_at)),
+        )
+    })
+}
+
+/// Wrap a protocol context, with the deadline its headers ask for counted from
+/// `received_at` and capped like calls forwarded to Splice
+fn context_with_requested_deadline(inner: splice::protocol::RequestContext, received_at: Instant) -> Context {
+    let budget = requested_timeout(inner.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        .map(|t| t.min(Duration::from_millis(DEFAULT_SPLICE_DEADLINE_MS as u64)));
+    let ctx = Context::new(inner).with_received_at(received_at);
+    match budget {
+        Some(budget) => ctx.with_deadline(received_at + budget),
+        None => ctx,
+    }
+}
+
+/// Build a dispatcher that accepts a caller-constructed [`Context`]
+///
+/// Used by the Splice worker so exported functions observe the request's
+/// cancellation token and deadline. See [`build_rpc_dispatcher`] for details.
+pub fn build_context_dispatcher() -> ContextDispatchFn {
     use tracing::{info, debug, error};
 
     let mut registry: HashMap<String, &'static ExportedFunction> = HashMap::new();
@@ -138,7 +229,7 @@ pub fn build_rpc_dispatcher() -> crate::rpc::RpcDispatchFn {
     info!("RPC registry: {} functions registered", registry.len());
 
     // Return dispatcher closure with context support
-    Arc::new(move |function_name: String, params: Value, context_data: Option<splice::protocol::RequestContext>| {
+    Arc::new(move |function_name: String, params: Value, context: Option<Context>| {
         // Convert params to HashMap for wrapper functions
         let params_map: HashMap<String, Value> = match params {
             Value::Object(map) => map.into_iter().collect(),
@@ -159,9 +250,6 @@ pub fn build_rpc_dispatcher() -> crate::rpc::RpcDispatchFn {
 
         match registry.get(&function_name) {
             Some(func) => {
-                // Convert RequestContext to Context wrapper if provided
-                let context = context_data.map(|c| Context::new(c));
-
                 // Check if we're in an async context
                 match tokio::runtime::Handle::try_current() {
                     Ok(handle) => {
@@ -184,3 +272,35 @@ pub fn build_rpc_dispatcher() -> crate::rpc::RpcDispatchFn {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context_with_deadline_header(value: &str) -> splice::protocol::RequestContext {
+        splice::protocol::RequestContext {
+            trace_id: 0,
+            span_id: 0,
+            headers: vec![("x-deadline-ms".to_string(), value.to_string())],
+            auth: None,
+        }
+    }
+
+    #[test]
+    fn test_requested_deadline_counts_from_receipt() {
+        let received_at = Instant::now() - Duration::from_millis(200);
+        let ctx = context_with_requested_deadline(context_with_deadline_header("500"), received_at);
+
+        assert_eq!(ctx.received_at(), received_at);
+        assert_eq!(ctx.deadline(), Some(received_at + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_requested_deadline_is_capped() {
+        let received_at = Instant::now();
+        let ctx = context_with_requested_deadline(context_with_deadline_header("3600000"), received_at);
+
+        let cap = Duration::from_millis(DEFAULT_SPLICE_DEADLINE_MS as u64);
+        assert_eq!(ctx.deadline(), Some(received_at + cap));
+    }
+}
@@ -24,10 +24,13 @@
 use crate::connection_pool::ConnectionPool;
 use crate::error::{ZapError, ZapResult};
 use crate::ipc::IpcMessage;
-use std::sync::atomic::{AtomicU64, Ordering};
+use futures::future::BoxFuture;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
 // ============================================================================
@@ -138,6 +141,9 @@ impl std::fmt::Display for CircuitState {
     }
 }
 
+/// Callback fired on every circuit transition with `(old, new)`, e.g. to alert
+pub type CircuitStateHook = Arc<dyn Fn(CircuitState, CircuitState) + Send + Sync>;
+
 /// Circuit breaker configuration
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
@@ -147,7 +153,7 @@ pub struct CircuitBreakerConfig {
     pub reset_timeout: Duration,
     /// Number of successes in HALF_OPEN to close circuit
     pub success_threshold: usize,
-    /// Time window for counting failures
+    /// Trailing window in which `failure_threshold` failures open the circuit
     pub failure_window: Duration,
 }
 
@@ -196,9 +202,10 @@ impl CircuitBreakerConfig {
 /// Circuit breaker internal state
 struct CircuitBreakerState {
     state: CircuitState,
-    failure_count: usize,
+    /// Ring buffer of the most recent failure times, oldest first; holds at
+    /// most `failure_threshold` entries, all inside `failure_window`
+    recent_failures: VecDeque<Instant>,
     success_count: usize,
-    last_failure_time: Option<Instant>,
     opened_at: Option<Instant>,
 }
 
@@ -212,6 +219,8 @@ pub struct CircuitBreaker {
     total_successes: AtomicU64,
     /// Times circuit opened (for metrics)
     times_opened: AtomicU64,
+    /// Callbacks run after each state transition
+    state_hooks: Vec<CircuitStateHook>,
 }
 
 impl CircuitBreaker {
@@ -226,22 +235,53 @@ impl CircuitBreaker {
             config,
             state: RwLock::new(CircuitBreakerState {
                 state: CircuitState::Closed,
-                failure_count: 0,
+                recent_failures: VecDeque::new(),
                 success_count: 0,
-                last_failure_time: None,
                 opened_at: None,
             }),
             total_failures: AtomicU64::new(0),
             total_successes: AtomicU64::new(0),
             times_opened: AtomicU64::new(0),
+            state_hooks: Vec::new(),
+        }
+    }
+
+    /// Register a callback fired whenever the circuit changes state
+    ///
+    /// Hooks run in registration order after the transition, outside the
+    /// breaker's lock, so they should be quick (e.g. queue an alert).
+    ///
+    /// # Example
+    /// ```ignore
+    /// CircuitBreaker::new().on_state_change(|old, new| {
+    ///     if new == CircuitState::Open {
+    ///         alert(format!("IPC circuit {} -> {}", old, new));
+    ///     }
+    /// })
+    /// ```
+    pub fn on_state_change<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(CircuitState, CircuitState) + Send + Sync + 'static,
+    {
+        self.state_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Run the state hooks if the circuit moved from `old` to `new`
+    fn notify_transition(&self, old: CircuitState, new: CircuitState) {
+        if old != new {
+            for hook in &self.state_hooks {
+                hook(old, new);
+            }
         }
     }
 
     /// Check if a request is allowed to proceed
     pub async fn allow_request(&self) -> bool {
         let mut state = self.state.write().await;
+        let old = state.state;
 
-        match state.state {
+        let allowed = match state.state {
             CircuitState::Closed => true,
             CircuitState::Open => {
                 // Check if reset timeout has elapsed
@@ -262,7 +302,12 @@ impl CircuitBreaker {
                 // Allow request in half-open state
                 true
             }
-        }
+        };
+
+        let new = state.state;
+        drop(state);
+        self.notify_transition(old, new);
+        allowed
     }
 
     /// Record a successful request
@@ -270,6 +315,7 @@ impl CircuitBreaker {
         self.total_successes.fetch_add(1, Ordering::Relaxed);
 
         let mut state = self.state.write().await;
+        let old = state.state;
 
         match state.state {
             CircuitState::HalfOpen => {
@@ -280,23 +326,22 @@ impl CircuitBreaker {
                         state.success_count
                     );
                     state.state = CircuitState::Closed;
-                    state.failure_count = 0;
+                    state.recent_failures.clear();
                     state.success_count = 0;
                     state.opened_at = None;
                 }
             }
             CircuitState::Closed => {
-                // Reset failure count on success (sliding window behavior)
-                if let Some(last_failure) = state.last_failure_time {
-                    if last_failure.elapsed() > self.config.failure_window {
-                        state.failure_count = 0;
-                    }
-                }
+                self.expire_failures(&mut state.recent_failures, Instant::now());
             }
             CircuitState::Open => {
                 // Shouldn't happen, but handle gracefully
             }
         }
+
+        let new = state.state;
+        drop(state);
+        self.notify_transition(old, new);
     }
 
     /// Record a failed request
@@ -304,25 +349,26 @@ impl CircuitBreaker {
         self.total_failures.fetch_add(1, Ordering::Relaxed);
 
         let mut state = self.state.write().await;
+        let old = state.state;
 
         match state.state {
             CircuitState::Closed => {
-                // Check if we should reset the failure window
-                if let Some(last_failure) = state.last_failure_time {
-                    if last_failure.elapsed() > self.config.failure_window {
-                        state.failure_count = 0;
-                    }
+                // Only failures inside the trailing window count
+                let now = Instant::now();
+                self.expire_failures(&mut state.recent_failures, now);
+                state.recent_failures.push_back(now);
+                if state.recent_failures.len() > self.config.failure_threshold {
+                    state.recent_failures.pop_front();
                 }
 
-                state.failure_count += 1;
-                state.last_failure_time = Some(Instant::now());
-
-                if state.failure_count >= self.config.failure_threshold {
+                if state.recent_failures.len() >= self.config.failure_threshold {
                     warn!(
-                        "Circuit breaker OPENING after {} failures",
-                        state.failure_count
+                        "Circuit breaker OPENING after {} failures within {:?}",
+                        state.recent_failures.len(),
+                        self.config.failure_window
                     );
                     state.state = CircuitState::Open;
+                    state.recent_failures.clear();
                     state.opened_at = Some(Instant::now());
                     self.times_opened.fetch_add(1, Ordering::Relaxed);
                 }
@@ -336,10 +382,23 @@ impl CircuitBreaker {
                 self.times_opened.fetch_add(1, Ordering::Relaxed);
             }
             CircuitState::Open => {
-                // Already open, update failure time
-                state.last_failure_time = Some(Instant::now());
+                // Already open; failures only count again once closed
             }
         }
+
+        let new = state.state;
+        drop(state);
+        self.notify_transition(old, new);
+    }
+
+    /// Drop failures that have slid out of the failure window
+    fn expire_failures(&self, failures: &mut VecDeque<Instant>, now: Instant) {
+        while failures
+            .front()
+            .is_some_and(|failed_at| now.duration_since(*failed_at) > self.config.failure_window)
+        {
+            failures.pop_front();
+        }
     }
 
     /// Get current circuit state
@@ -352,7 +411,11 @@ impl CircuitBreaker {
         let state = self.state.read().await;
         CircuitBreakerStats {
             state: state.state,
-            failure_count: state.failure_count,
+            failure_count: state
+                .recent_failures
+                .iter()
+                .filter(|failed_at| failed_at.elapsed() <= self.config.failure_window)
+                .count(),
             success_count: state.success_count,
             total_failures: self.total_failures.load(Ordering::Relaxed),
             total_successes: self.total_successes.load(Ordering::Relaxed),
@@ -364,14 +427,17 @@ impl CircuitBreaker {
     pub async fn force_state(&self, new_state: CircuitState) {
         let mut state = self.state.write().await;
         info!("Force-setting circuit breaker to {}", new_state);
+        let old = state.state;
         state.state = new_state;
         if new_state == CircuitState::Open {
             state.opened_at = Some(Instant::now());
         } else {
             state.opened_at = None;
         }
-        state.failure_count = 0;
+        state.recent_failures.clear();
         state.success_count = 0;
+        drop(state);
+        self.notify_transition(old, new_state);
     }
 }
 
@@ -508,8 +574,10 @@ impl ResilientIpc {
 fn is_non_retryable_error(error: &ZapError) -> bool {
     match error {
         ZapError::Validation { .. } => true,
+        ZapError::BadRequest { .. } => true,
         ZapError::Unauthorized { .. } => true,
         ZapError::Forbidden { .. } => true,
+        ZapError::MethodNotAllowed { .. } => true,
         ZapError::RateLimited { .. } => true,
         _ => false,
     }
@@ -592,12 +660,23 @@ impl HealthCheckResponse {
     }
 }
 
+/// Probe that checks the worker/RPC backend can actually execute work
+///
+/// Resolves to `true` when the backend responded correctly. Being a closure,
+/// it can hold whatever client or pool it needs to reach the backend.
+pub type HealthPing = Arc<dyn Fn() -> BoxFuture<'static, bool> + Send + Sync>;
+
+/// How long the readiness check waits for a [`HealthPing`] (2 seconds)
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Health checker for the ZapJS server
 pub struct HealthChecker {
     start_time: Instant,
     version: String,
     pool: Option<Arc<ConnectionPool>>,
     circuit_breaker: Option<Arc<CircuitBreaker>>,
+    ping: Option<HealthPing>,
+    draining: Option<Arc<AtomicBool>>,
 }
 
 impl HealthChecker {
@@ -608,6 +687,8 @@ impl HealthChecker {
             version,
             pool: None,
             circuit_breaker: None,
+            ping: None,
+            draining: None,
         }
     }
 
@@ -623,6 +704,27 @@ impl HealthChecker {
         self
     }
 
+    /// Set a backend ping run on every readiness check
+    ///
+    /// A ping that returns `false` or takes longer than 2 seconds marks the
+    /// server unhealthy, catching workers that are connected but stuck.
+    pub fn with_ping<F>(mut self, ping: F) -> Self
+    where
+        F: Fn() -> BoxFuture<'static, bool> + Send + Sync + 'static,
+    {
+        self.ping = Some(Arc::new(ping));
+        self
+    }
+
+    /// Report not-ready once `flag` is set (e.g. when shutdown starts)
+    ///
+    /// Load balancers then stop routing new traffic while in-flight requests
+    /// finish. Liveness is unaffected.
+    pub fn with_draining_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.draining = Some(flag);
+        self
+    }
+
     /// Liveness probe: Is the process alive?
     /// This should always return true if the server can respond at all.
     pub fn liveness(&self) -> HealthCheckResponse {
@@ -640,11 +742,24 @@ impl HealthChecker {
     }
 
     /// Readiness probe: Can the server handle requests?
-    /// Checks connection pool and circuit breaker state.
+    /// Checks connection pool and circuit breaker state, and pings the backend.
     pub async fn readiness(&self) -> HealthCheckResponse {
         let mut components = Vec::new();
         let mut overall_status = HealthStatus::Healthy;
 
+        // A draining server must not receive new traffic
+        if let Some(draining) = &self.draining {
+            if draining.load(Ordering::SeqCst) {
+                overall_status = HealthStatus::Unhealthy;
+                components.push(ComponentHealth {
+                    name: "shutdown".to_string(),
+                    status: HealthStatus::Unhealthy,
+                    message: Some("Server is draining connections".to_string()),
+                    latency_ms: None,
+                });
+            }
+        }
+
         // Check connection pool
         if let Some(pool) = &self.pool {
             let start = Instant::now();
@@ -694,6 +809,32 @@ impl HealthChecker {
             });
         }
 
+        // Ping the backend
+        if let Some(ping) = &self.ping {
+            let start = Instant::now();
+            let result = tokio::time::timeout(PING_TIMEOUT, ping()).await;
+            let latency = start.elapsed().as_millis() as u64;
+
+            let (ping_status, message) = match result {
+                Ok(true) => (HealthStatus::Healthy, "Backend responded".to_string()),
+                Ok(false) => (HealthStatus::Unhealthy, "Backend ping failed".to_string()),
+                Err(_) => (
+                    HealthStatus::Unhealthy,
+                    format!("Backend ping timed out after {}ms", PING_TIMEOUT.as_millis()),
+                ),
+            };
+            if ping_status == HealthStatus::Unhealthy {
+                overall_status = HealthStatus::Unhealthy;
+            }
+
+            components.push(ComponentHealth {
+                name: "backend".to_string(),
+                status: ping_status,
+                message: Some(message),
+                latency_ms: Some(latency),
+            });
+        }
+
         // If no components configured, assume healthy
         if components.is_empty() {
             components.push(ComponentHealth {
@@ -855,6 +996,59 @@ mod tests {
         assert_eq!(cb.state().await, CircuitState::Closed);
     }
 
+    #[tokio::test]
+    async fn test_circuit_breaker_sliding_window() {
+        tokio::time::pause();
+        let config = CircuitBreakerConfig::new()
+            .failure_threshold(3)
+            .failure_window(Duration::from_millis(300));
+        let cb = CircuitBreaker::with_config(config);
+
+        // Spaced failures: the first has slid out of the window by the third
+        cb.record_failure().await;
+        tokio::time::advance(Duration::from_millis(200)).await;
+        cb.record_failure().await;
+        tokio::time::advance(Duration::from_millis(200)).await;
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Closed);
+        assert_eq!(cb.stats().await.failure_count, 2);
+
+        // A fourth failure makes three inside the trailing window
+        tokio::time::advance(Duration::from_millis(20)).await;
+        cb.record_failure().await;
+        assert_eq!(cb.state().await, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_state_change_hook() {
+        let transitions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&transitions);
+
+        let config = CircuitBreakerConfig::new()
+            .failure_threshold(2)
+            .success_threshold(1)
+            .reset_timeout(Duration::from_millis(10));
+        let cb = CircuitBreaker::with_config(config)
+            .on_state_change(move |old, new| recorded.lock().unwrap().push((old, new)));
+
+        cb.record_failure().await;
+        assert!(transitions.lock().unwrap().is_empty());
+        cb.record_failure().await;
+        assert_eq!(*transitions.lock().unwrap(), vec![(CircuitState::Closed, CircuitState::Open)]);
+
+        tokio::time::sleep(Duration::from_millis(15)).await;
+        assert!(cb.allow_request().await);
+        cb.record_success().await;
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_circuit_breaker_stats() {
         let cb = CircuitBreaker::new();
@@ -894,6 +1088,38 @@ mod tests {
         assert_eq!(response.status, HealthStatus::Healthy);
     }
 
+    #[tokio::test]
+    async fn test_health_checker_readiness_failed_ping() {
+        // The ping reaches the backend through state it captures
+        let backend_up = Arc::new(AtomicBool::new(false));
+        let up = backend_up.clone();
+        let checker = HealthChecker::new("1.0.0".to_string())
+            .with_ping(move || -> BoxFuture<'static, bool> {
+                let up = up.load(Ordering::SeqCst);
+                Box::pin(async move { up })
+            });
+
+        let response = checker.readiness().await;
+        assert_eq!(response.status, HealthStatus::Unhealthy);
+        let backend = response.components.iter().find(|c| c.name == "backend").unwrap();
+        assert_eq!(backend.status, HealthStatus::Unhealthy);
+
+        backend_up.store(true, Ordering::SeqCst);
+        let response = checker.readiness().await;
+        assert_eq!(response.status, HealthStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_draining_flag_fails_readiness_only() {
+        let draining = Arc::new(AtomicBool::new(false));
+        let checker = HealthChecker::new("1.0.0".to_string()).with_draining_flag(draining.clone());
+        assert_eq!(checker.readiness().await.status, HealthStatus::Healthy);
+
+        draining.store(true, Ordering::SeqCst);
+        assert_eq!(checker.readiness().await.status, HealthStatus::Unhealthy);
+        assert_eq!(checker.liveness().status, HealthStatus::Healthy);
+    }
+
     #[test]
     fn test_health_response_json() {
         let response = HealthCheckResponse {
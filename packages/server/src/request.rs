@@ -1,7 +1,17 @@
 //! Request types and utilities for ZapServer
 
+use crate::error::{ZapError, ZapResult};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use zap_core::{Request, Method};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use zap_core::{AppState, JwtClaims, Request, Method, Session};
+
+pub use zap_core::request::{parse_cookie_header, MAX_COOKIES, MAX_COOKIE_HEADER_LEN};
+
+/// Content type of HTML form posts read by [`RequestData::form_urlencoded`]
+pub const FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
 
 /// Request data that can be owned and moved between threads
 #[derive(Debug, Clone)]
@@ -10,11 +20,20 @@ pub struct RequestData {
     pub path: String,
     pub path_only: String,
     pub version: String,
-    pub headers: HashMap<String, String>,
+    /// Header values keyed by lowercased name, repeats kept in request order
+    pub headers: HashMap<String, Vec<String>>,
     pub body: Vec<u8>,
     pub params: HashMap<String, String>,
     pub query: HashMap<String, String>,
     pub cookies: HashMap<String, String>,
+    pub client_ip: Option<IpAddr>,
+    pub app_state: Option<Arc<AppState>>,
+    /// Session from `SessionMiddleware`; changes are saved with the response
+    pub session: Option<Session>,
+    /// Claims verified by `JwtMiddleware`
+    pub claims: Option<JwtClaims>,
+    /// ID assigned by `RequestIdMiddleware`, also echoed on the response
+    pub request_id: Option<String>,
 }
 
 impl RequestData {
@@ -25,11 +44,16 @@ impl RequestData {
             path: req.path().to_string(),
             path_only: req.path_only().to_string(),
             version: req.version().to_string(),
-            headers: req.headers().iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            headers: collect_headers(req.headers().iter()),
             body: req.body().to_vec(),
             params: req.params().iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
             query: req.query_params().into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
             cookies: req.cookies().into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            client_ip: req.client_ip(),
+            app_state: req.app_state().cloned(),
+            session: req.session().cloned(),
+            claims: req.claims().cloned(),
+            request_id: req.request_id().map(str::to_string),
         }
     }
     
@@ -38,14 +62,54 @@ impl RequestData {
         self.params.get(name).map(|s| s.as_str())
     }
     
+    /// Get a path parameter parsed as `T`
+    ///
+    /// A missing or unparseable segment is a validation error (400) naming
+    /// the parameter.
+    ///
+    /// ```ignore
+    /// let id: u64 = req.param_parse("id")?;
+    /// ```
+    pub fn param_parse<T: FromStr>(&self, name: &str) -> ZapResult<T> {
+        let value = self.param(name).ok_or_else(|| {
+            ZapError::validation_field(format!("Missing path parameter '{}'", name), name)
+        })?;
+
+        value.parse().map_err(|_| {
+            ZapError::validation_field(
+                format!(
+                    "Invalid path parameter '{}': expected {}, got '{}'",
+                    name,
+                    std::any::type_name::<T>(),
+                    value
+                ),
+                name,
+            )
+        })
+    }
+    
     /// Get query parameter by name
     pub fn query(&self, name: &str) -> Option<&str> {
         self.query.get(name).map(|s| s.as_str())
     }
     
-    /// Get header by name
+    /// Get the first value of a header (case-insensitive)
     pub fn header(&self, name: &str) -> Option<&str> {
-        self.headers.get(name).map(|s| s.as_str())
+        self.headers_all(name).first().map(|s| s.as_str())
+    }
+
+    /// Get every value of a repeated header, in request order (case-insensitive)
+    ///
+    /// Empty when the header is absent. Keys are normally lowercase already;
+    /// maps built by hand with other casing are matched by a scan.
+    pub fn headers_all(&self, name: &str) -> &[String] {
+        let values = self.headers.get(name).or_else(|| {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, values)| values)
+        });
+        values.map_or(&[], Vec::as_slice)
     }
     
     /// Get cookie by name
@@ -53,8 +117,225 @@ impl RequestData {
         self.cookies.get(name).map(|s| s.as_str())
     }
     
+    /// Get the resolved client IP address
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+    
+    /// Get shared state registered with `Zap::with_state`
+    ///
+    /// Returns `None` if no value of type `T` was registered.
+    pub fn state<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.app_state.as_ref().and_then(|state| state.get::<T>())
+    }
+    
+    /// Get the session, if `SessionMiddleware` is installed
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Get the verified JWT claims, if `JwtMiddleware` accepted a token
+    pub fn claims(&self) -> Option<&JwtClaims> {
+        self.claims.as_ref()
+    }
+
+    /// Get the request ID, if `RequestIdMiddleware` is installed
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// Caller identity for Splice invocations, from the verified JWT claims
+    ///
+    /// `None` without claims or without a `sub` claim.
+    pub fn auth_context(&self) -> Option<splice::protocol::AuthContext> {
+        let claims = self.claims.as_ref()?;
+        Some(splice::protocol::AuthContext {
+            user_id: claims.sub.clone()?,
+            roles: claims.roles.clone(),
+        })
+    }
+
     /// Get body as string
-    pub fn body_string(&self) -> Result<String, std::string::FromUtf8Error> {
-        String::from_utf8(self.body.clone())
+    ///
+    /// Strict: a body that isn't valid UTF-8 is a validation error naming the
+    /// byte offset of the first invalid sequence. Use [`body_string_lossy`]
+    /// when replacement characters are acceptable (e.g. for logging).
+    ///
+    /// [`body_string_lossy`]: RequestData::body_string_lossy
+    pub fn body_string(&self) -> ZapResult<String> {
+        match std::str::from_utf8(&self.body) {
+            Ok(body) => Ok(body.to_string()),
+            Err(e) => Err(ZapError::validation_field(
+                format!("Request body is not valid UTF-8 (invalid byte at offset {})", e.valid_up_to()),
+                "body",
+            )),
+        }
+    }
+
+    /// Get body as string, replacing invalid UTF-8 with U+FFFD
+    pub fn body_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Parse an `application/x-www-form-urlencoded` body
+    ///
+    /// Keys and values are percent-decoded with `+` read as a space; when a
+    /// key repeats, the last value wins. Another content type is a 400, as is
+    /// a malformed escape. Use [`form_urlencoded_multi`] to keep every value.
+    ///
+    /// [`form_urlencoded_multi`]: RequestData::form_urlencoded_multi
+    pub fn form_urlencoded(&self) -> ZapResult<HashMap<String, String>> {
+        let fields = self.form_urlencoded_multi()?;
+        Ok(fields
+            .into_iter()
+            .filter_map(|(key, mut values)| values.pop().map(|value| (key, value)))
+            .collect())
+    }
+
+    /// Parse an `application/x-www-form-urlencoded` body, keeping every value
+    /// of a repeated key in body order
+    pub fn form_urlencoded_multi(&self) -> ZapResult<HashMap<String, Vec<String>>> {
+        let content_type = self.header("content-type").unwrap_or_default();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if !essence.eq_ignore_ascii_case(FORM_URLENCODED) {
+            return Err(ZapError::bad_request(format!(
+                "Expected a {} body, got content type '{}'",
+                FORM_URLENCODED, content_type
+            )));
+        }
+
+        let body = std::str::from_utf8(&self.body).map_err(|e| {
+            ZapError::validation_field(
+                format!("Form body is not valid UTF-8 (invalid byte at offset {})", e.valid_up_to()),
+                "body",
+            )
+        })?;
+        parse_form_urlencoded(body)
+    }
+}
+
+/// Group a decoded urlencoded body into key -> values
+fn parse_form_urlencoded(body: &str) -> ZapResult<HashMap<String, Vec<String>>> {
+    let pairs = zap_core::decode_form_data(body)
+        .map_err(|e| ZapError::validation_field(e.to_string(), "body"))?;
+    let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in pairs {
+        fields.entry(key).or_default().push(value);
+    }
+    Ok(fields)
+}
+
+/// Group headers by lowercased name, keeping repeated values in order
+fn collect_headers<'a>(headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> HashMap<String, Vec<String>> {
+    let mut collected: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, value) in headers {
+        collected.entry(name.to_ascii_lowercase()).or_default().push(value.to_string());
+    }
+    collected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_with_body(body: &[u8]) -> RequestData {
+        RequestData {
+            method: Method::POST,
+            path: "/".to_string(),
+            path_only: "/".to_string(),
+            version: "HTTP/1.1".to_string(),
+            headers: HashMap::new(),
+            body: body.to_vec(),
+            params: HashMap::new(),
+            query: HashMap::new(),
+            cookies: HashMap::new(),
+            client_ip: None,
+            app_state: None,
+            session: None,
+            claims: None,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_body_string_invalid_utf8() {
+        let req = request_with_body(b"caf\xc3(ok\xff");
+
+        let err = req.body_string().unwrap_err();
+        assert_eq!(err.status_code(), 400);
+        assert!(err.to_string().contains("offset 3"), "{}", err);
+
+        assert_eq!(req.body_string_lossy(), "caf\u{FFFD}(ok\u{FFFD}");
+        assert!(matches!(request_with_body(b"ok").body_string_lossy(), Cow::Borrowed("ok")));
+    }
+
+    #[test]
+    fn test_repeated_headers_preserved() {
+        let raw = b"GET / HTTP/1.1\r\nHost: localhost\r\nX-Custom: first\r\nX-Custom: second\r\n\r\n";
+        let parsed = zap_core::HttpParser::new().parse_request(raw).unwrap();
+        let req = RequestData::from_request(&Request::new(&parsed, b"", zap_core::Params::new()));
+
+        assert_eq!(req.header("X-Custom"), Some("first"));
+        assert_eq!(req.headers_all("X-Custom"), ["first", "second"]);
+        assert_eq!(req.headers_all("x-custom").len(), 2);
+        assert_eq!(req.header("host"), Some("localhost"));
+        assert!(req.headers_all("X-Missing").is_empty());
+    }
+
+    #[test]
+    fn test_header_lookup_ignores_case() {
+        let mut req = request_with_body(b"");
+        req.headers.insert("content-type".to_string(), vec!["application/json".to_string()]);
+        req.headers.insert("X-Api-Key".to_string(), vec!["secret".to_string()]);
+
+        assert_eq!(req.header("Content-Type"), Some("application/json"));
+        assert_eq!(req.header("CONTENT-TYPE"), Some("application/json"));
+        assert_eq!(req.header("x-api-key"), Some("secret"));
+        assert_eq!(req.header("Content-Length"), None);
+    }
+
+    #[test]
+    fn test_param_parse() {
+        let mut router = zap_core::Router::new();
+        router.insert(Method::GET, "/users/:id", ()).unwrap();
+        let request_for = |path: &str| {
+            let (_, params) = router.at(Method::GET, path).unwrap();
+            let mut req = request_with_body(b"");
+            req.params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            req
+        };
+
+        assert_eq!(request_for("/users/42").param_parse::<u64>("id").unwrap(), 42);
+
+        let err = request_for("/users/abc").param_parse::<u64>("id").unwrap_err();
+        assert!(matches!(err, ZapError::Validation { .. }));
+        assert_eq!(err.status_code(), 400);
+        assert!(err.to_string().contains("'id'"), "{}", err);
+
+        let err = request_for("/users/42").param_parse::<u64>("post_id").unwrap_err();
+        assert_eq!(err.status_code(), 400);
+        assert!(err.to_string().contains("Missing path parameter 'post_id'"), "{}", err);
+    }
+
+    #[test]
+    fn test_form_urlencoded() {
+        let mut req = request_with_body(b"a=1&b=hello+world&a=2&name=J%C3%BCrgen%20K&flag");
+        req.headers.insert("content-type".to_string(), vec!["application/x-www-form-urlencoded; charset=utf-8".to_string()]);
+
+        let form = req.form_urlencoded().unwrap();
+        assert_eq!(form["a"], "2");
+        assert_eq!(form["b"], "hello world");
+        assert_eq!(form["name"], "Jürgen K");
+        assert_eq!(form["flag"], "");
+
+        let multi = req.form_urlencoded_multi().unwrap();
+        assert_eq!(multi["a"], ["1", "2"]);
+
+        let mut bad = request_with_body(b"a=%zz");
+        bad.headers = req.headers.clone();
+        assert_eq!(bad.form_urlencoded().unwrap_err().status_code(), 400);
+
+        let err = request_with_body(b"a=1").form_urlencoded().unwrap_err();
+        assert!(matches!(err, ZapError::BadRequest { .. }));
     }
 } 
\ No newline at end of file
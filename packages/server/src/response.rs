@@ -1,12 +1,33 @@
 //! Response types and utilities for ZapServer
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use bytes::Bytes;
+use futures::{Stream, TryStreamExt};
+use http_body_util::{combinators::BoxBody, BodyExt, Full, StreamBody};
+use hyper::body::Frame;
 use serde::Serialize;
+use tokio_util::io::ReaderStream;
 
 use zap_core::{Response, StatusCode, ResponseBody};
+pub use zap_core::Vary;
+
+/// Chunk size used when streaming files from disk
+pub(crate) const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Body type produced by [`ZapResponse::to_hyper_response`]
+///
+/// In-memory responses are sent as a single frame; file responses are
+/// streamed from disk in chunks, and [`StreamedBody`] responses frame by
+/// frame as the handler produces them.
+pub type ZapBody = BoxBody<Bytes, std::io::Error>;
+
+/// Build a body from an in-memory buffer
+pub fn full_body(data: impl Into<Bytes>) -> ZapBody {
+    Full::new(data.into()).map_err(|never| match never {}).boxed()
+}
 
 /// Streaming response data
 #[derive(Debug)]
@@ -50,6 +71,188 @@ impl StreamingResponse {
     }
 }
 
+/// Response whose body is handed to hyper as-is, without buffering
+///
+/// Sent with chunked transfer encoding unless a `Content-Length` header is
+/// set. The body can only be sent once; converting the same response again
+/// yields an empty body.
+pub struct StreamedBody {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    pub headers: Vec<(String, String)>,
+    body: Mutex<Option<ZapBody>>,
+}
+
+impl StreamedBody {
+    /// Wrap an existing body with status 200
+    pub fn new(body: ZapBody) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: Mutex::new(Some(body)),
+        }
+    }
+
+    /// Send each item of `stream` as a body chunk
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, std::io::Error>> + Send + Sync + 'static,
+    {
+        Self::new(StreamBody::new(stream.map_ok(Frame::data)).boxed())
+    }
+
+    /// Set the status code
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    fn take_body(&self) -> ZapBody {
+        let mut body = self.body.lock().unwrap_or_else(|e| e.into_inner());
+        body.take().unwrap_or_else(|| full_body(Bytes::new()))
+    }
+}
+
+impl std::fmt::Debug for StreamedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamedBody")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One part of a [`MultipartBody`]
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    /// Part headers, e.g. `Content-Type` and `Content-Disposition`
+    pub headers: Vec<(String, String)>,
+    /// Part body
+    pub body: Bytes,
+}
+
+impl MultipartPart {
+    /// Create a part with the given content type
+    pub fn new(content_type: impl Into<String>, body: impl Into<Bytes>) -> Self {
+        Self {
+            headers: vec![("Content-Type".to_string(), content_type.into())],
+            body: body.into(),
+        }
+    }
+
+    /// Add a part header
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Delimiter, headers and body of this part as sent on the wire
+    fn encode(&self, boundary: &str) -> Bytes {
+        let mut encoded = format!("--{}\r\n", boundary);
+        for (key, value) in &self.headers {
+            encoded.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        encoded.push_str("\r\n");
+
+        let mut encoded = encoded.into_bytes();
+        encoded.extend_from_slice(&self.body);
+        encoded.extend_from_slice(b"\r\n");
+        Bytes::from(encoded)
+    }
+}
+
+/// `multipart/mixed` response streamed part by part
+///
+/// Each part is written as it is produced, so large exports are never held in
+/// memory at once. Like [`StreamedBody`], the parts can only be sent once.
+pub struct MultipartBody {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers, besides the generated `Content-Type`
+    pub headers: Vec<(String, String)>,
+    boundary: String,
+    parts: Mutex<Option<MultipartStream>>,
+}
+
+/// Parts of a [`MultipartBody`]
+type MultipartStream = std::pin::Pin<Box<dyn Stream<Item = Result<MultipartPart, std::io::Error>> + Send + Sync>>;
+
+impl MultipartBody {
+    /// Send each item of `parts` as one part, with a random boundary
+    pub fn from_stream<S>(parts: S) -> Self
+    where
+        S: Stream<Item = Result<MultipartPart, std::io::Error>> + Send + Sync + 'static,
+    {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            boundary: format!("zap-{}", uuid::Uuid::new_v4().simple()),
+            parts: Mutex::new(Some(Box::pin(parts))),
+        }
+    }
+
+    /// Send parts that are already in memory
+    pub fn from_parts(parts: Vec<MultipartPart>) -> Self {
+        Self::from_stream(futures::stream::iter(parts.into_iter().map(Ok)))
+    }
+
+    /// Use `boundary` instead of a random one
+    ///
+    /// It must not occur in any part, or clients will split the body there.
+    pub fn boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = boundary.into();
+        self
+    }
+
+    /// Set the status code
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// `Content-Type` announcing the boundary
+    pub fn content_type(&self) -> String {
+        format!("multipart/mixed; boundary={}", self.boundary)
+    }
+
+    fn take_body(&self) -> ZapBody {
+        let parts = self.parts.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let Some(parts) = parts else {
+            return full_body(Bytes::new());
+        };
+
+        let boundary = self.boundary.clone();
+        let closing = Bytes::from(format!("--{}--\r\n", boundary));
+        let parts = parts.map_ok(move |part| part.encode(&boundary));
+        let frames = futures::StreamExt::chain(parts, futures::stream::once(async move { Ok(closing) }))
+            .map_ok(Frame::data);
+        StreamBody::new(frames).boxed()
+    }
+}
+
+impl std::fmt::Debug for MultipartBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartBody")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("boundary", &self.boundary)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Zap response types with auto-serialization
 #[derive(Debug)]
 pub enum ZapResponse {
@@ -61,10 +264,19 @@ pub enum ZapResponse {
     Json(serde_json::Value),
     /// JSON response with custom status code
     JsonWithStatus(serde_json::Value, u16),
+    /// Pretty-printed JSON response
+    JsonPretty(serde_json::Value),
     /// Binary response
     Bytes(Bytes),
-    /// File response
+    /// File response, streamed from disk without reading it fully into memory
     File(PathBuf),
+    /// Streamed file response with explicit headers
+    ///
+    /// A `Content-Type` in `headers` replaces the one guessed from the path.
+    FileWithHeaders {
+        path: PathBuf,
+        headers: Vec<(String, String)>,
+    },
     /// Custom response with full control
     Custom(Response),
     /// Redirect response
@@ -73,12 +285,28 @@ pub enum ZapResponse {
     Status(StatusCode),
     /// Streaming response (collected chunks)
     Stream(StreamingResponse),
+    /// Body streamed to the client as it is produced
+    Streamed(StreamedBody),
+    /// `multipart/mixed` body streamed part by part
+    Multipart(MultipartBody),
+    /// Response with request headers it varies on, added by [`ZapResponse::vary`]
+    Varying {
+        response: Box<ZapResponse>,
+        vary: Vary,
+    },
 }
 
 /// JSON response wrapper for auto-serialization
 #[derive(Debug)]
 pub struct Json<T>(pub T);
 
+impl<T> Json<T> {
+    /// Pretty-print the JSON instead of serializing it compactly
+    pub fn pretty(self) -> PrettyJson<T> {
+        PrettyJson(self.0)
+    }
+}
+
 impl<T: Serialize> From<Json<T>> for ZapResponse {
     fn from(json: Json<T>) -> Self {
         match serde_json::to_value(json.0) {
@@ -90,44 +318,144 @@ impl<T: Serialize> From<Json<T>> for ZapResponse {
     }
 }
 
+/// Pretty-printed JSON response wrapper, created by [`Json::pretty`]
+#[derive(Debug)]
+pub struct PrettyJson<T>(pub T);
+
+impl<T: Serialize> From<PrettyJson<T>> for ZapResponse {
+    fn from(json: PrettyJson<T>) -> Self {
+        match serde_json::to_value(json.0) {
+            Ok(value) => ZapResponse::JsonPretty(value),
+            Err(_) => ZapResponse::Custom(
+                Response::internal_server_error("Failed to serialize JSON"),
+            ),
+        }
+    }
+}
+
+/// Server-wide JSON serialization options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Serialize integers outside ±(2^53 - 1) as strings so JavaScript
+    /// clients don't lose precision
+    pub large_numbers_as_strings: bool,
+}
+
+/// Largest integer a JavaScript number represents exactly
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+impl JsonOptions {
+    /// Serialize a JSON value according to these options
+    fn serialize(&self, value: &serde_json::Value, pretty: bool) -> String {
+        let converted;
+        let value = if self.large_numbers_as_strings {
+            converted = stringify_large_numbers(value);
+            &converted
+        } else {
+            value
+        };
+
+        let result = if pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        };
+        result.unwrap_or_else(|_| r#"{"error": "Failed to serialize JSON"}"#.to_string())
+    }
+}
+
+/// Replace integers JavaScript can't represent exactly with their string form
+fn stringify_large_numbers(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Number(n) => {
+            let unsafe_int = n.as_u64().is_some_and(|v| v > MAX_SAFE_INTEGER)
+                || n.as_i64().is_some_and(|v| v.unsigned_abs() > MAX_SAFE_INTEGER);
+            if unsafe_int {
+                Value::String(n.to_string())
+            } else {
+                value.clone()
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(stringify_large_numbers).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), stringify_large_numbers(v)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
 impl ZapResponse {
-    /// Convert ZapResponse to hyper Response
-    pub fn to_hyper_response(&self) -> hyper::Response<String> {
+    /// Mark the response as varying on the request header `name`
+    ///
+    /// Compression, CORS and content negotiation can each add the headers
+    /// they looked at; the names are merged with any `Vary` the response
+    /// already sets into one deduplicated header.
+    pub fn vary(self, name: &str) -> Self {
         match self {
+            ZapResponse::Varying { response, mut vary } => {
+                vary.add(name);
+                ZapResponse::Varying { response, vary }
+            }
+            response => {
+                let mut vary = Vary::new();
+                vary.add(name);
+                ZapResponse::Varying { response: Box::new(response), vary }
+            }
+        }
+    }
+
+    /// Convert ZapResponse to hyper Response
+    pub fn to_hyper_response(&self) -> hyper::Response<ZapBody> {
+        self.to_hyper_response_with(&JsonOptions::default())
+    }
+
+    /// Convert ZapResponse to hyper Response using the given JSON options
+    ///
+    /// The serialized body length is recorded as a [`ResponseSize`] extension.
+    pub fn to_hyper_response_with(&self, json_options: &JsonOptions) -> hyper::Response<ZapBody> {
+        let mut response = match self {
             ZapResponse::Text(text) => hyper::Response::builder()
                 .status(200)
                 .header("Content-Type", "text/plain; charset=utf-8")
-                .body(text.clone())
+                .body(full_body(text.clone()))
                 .unwrap(),
             ZapResponse::Html(html) => hyper::Response::builder()
                 .status(200)
                 .header("Content-Type", "text/html; charset=utf-8")
-                .body(html.clone())
+                .body(full_body(html.clone()))
                 .unwrap(),
             ZapResponse::Json(json) => {
-                let body = serde_json::to_string(json).unwrap_or_else(|_| {
-                    r#"{"error": "Failed to serialize JSON"}"#.to_string()
-                });
+                let body = json_options.serialize(json, false);
                 hyper::Response::builder()
                     .status(200)
                     .header("Content-Type", "application/json")
-                    .body(body)
+                    .body(full_body(body))
                     .unwrap()
             }
             ZapResponse::JsonWithStatus(json, status) => {
-                let body = serde_json::to_string(json).unwrap_or_else(|_| {
-                    r#"{"error": "Failed to serialize JSON"}"#.to_string()
-                });
+                let body = json_options.serialize(json, false);
                 hyper::Response::builder()
                     .status(*status)
                     .header("Content-Type", "application/json")
-                    .body(body)
+                    .body(full_body(body))
+                    .unwrap()
+            }
+            ZapResponse::JsonPretty(json) => {
+                let body = json_options.serialize(json, true);
+                hyper::Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(full_body(body))
                     .unwrap()
             }
             ZapResponse::Bytes(bytes) => hyper::Response::builder()
                 .status(200)
                 .header("Content-Type", "application/octet-stream")
-                .body(String::from_utf8_lossy(bytes).to_string())
+                .body(full_body(bytes.clone()))
                 .unwrap(),
             ZapResponse::Custom(response) => {
                 let status = response.status.as_u16();
@@ -138,32 +466,24 @@ impl ZapResponse {
                 }
                 
                 let body = match &response.body {
-                    ResponseBody::Empty => String::new(),
-                    ResponseBody::Text(text) => text.clone(),
-                    ResponseBody::Bytes(bytes) => {
-                        String::from_utf8_lossy(bytes).to_string()
-                    }
+                    ResponseBody::Empty => Bytes::new(),
+                    ResponseBody::Text(text) => Bytes::from(text.clone()),
+                    ResponseBody::Bytes(bytes) => Bytes::from(bytes.clone()),
                 };
                 
-                builder.body(body).unwrap()
+                builder.body(full_body(body)).unwrap()
             }
             ZapResponse::Redirect(location) => hyper::Response::builder()
                 .status(302)
                 .header("Location", location)
-                .body(String::new())
+                .body(full_body(Bytes::new()))
                 .unwrap(),
             ZapResponse::Status(status) => hyper::Response::builder()
                 .status(status.as_u16())
-                .body(String::new())
+                .body(full_body(Bytes::new()))
                 .unwrap(),
-            ZapResponse::File(_path) => {
-                // File serving would be implemented here
-                // For now, return not implemented
-                hyper::Response::builder()
-                    .status(501)
-                    .body("File serving not yet implemented".to_string())
-                    .unwrap()
-            }
+            ZapResponse::File(path) => file_response(path, &[]),
+            ZapResponse::FileWithHeaders { path, headers } => file_response(path, headers),
             ZapResponse::Stream(stream_response) => {
                 let mut builder = hyper::Response::builder()
                     .status(stream_response.status);
@@ -174,9 +494,289 @@ impl ZapResponse {
                 }
 
                 // Convert chunks to body
-                let body = stream_response.body_string();
-                builder.body(body).unwrap()
+                builder.body(full_body(stream_response.body_bytes())).unwrap()
             }
+            ZapResponse::Streamed(streamed) => {
+                let mut builder = hyper::Response::builder().status(streamed.status);
+                for (key, value) in &streamed.headers {
+                    builder = builder.header(key, value);
+                }
+                builder.body(streamed.take_body()).unwrap_or_else(|_| {
+                    hyper::Response::builder()
+                        .status(500)
+                        .header("Content-Type", "text/plain; charset=utf-8")
+                        .body(full_body("Invalid response header"))
+                        .unwrap()
+                })
+            }
+            ZapResponse::Multipart(multipart) => {
+                let mut builder = hyper::Response::builder()
+                    .status(multipart.status)
+                    .header("Content-Type", multipart.content_type());
+                for (key, value) in &multipart.headers {
+                    builder = builder.header(key, value);
+                }
+                builder.body(multipart.take_body()).unwrap_or_else(|_| {
+                    hyper::Response::builder()
+                        .status(500)
+                        .header("Content-Type", "text/plain; charset=utf-8")
+                        .body(full_body("Invalid response header"))
+                        .unwrap()
+                })
+            }
+            ZapResponse::Varying { response, vary } => {
+                let mut response = response.to_hyper_response_with(json_options);
+                apply_vary(vary, response.headers_mut());
+                response
+            }
+        };
+
+        if let Some(size) = ResponseSize::of(&response) {
+            response.extensions_mut().insert(size);
+        }
+        response
+    }
+}
+
+/// Serialized body length of a response, in bytes
+///
+/// Recorded in the response extensions by [`ZapResponse::to_hyper_response`]
+/// so billing, quotas and access logs can account for the bytes sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseSize(pub u64);
+
+impl ResponseSize {
+    /// Body length of `response`, if known without consuming the body
+    ///
+    /// Uses a recorded `ResponseSize`, then the body's exact size hint, then
+    /// a `Content-Length` header (streamed files).
+    pub fn of<B: hyper::body::Body>(response: &hyper::Response<B>) -> Option<Self> {
+        if let Some(size) = response.extensions().get::<ResponseSize>() {
+            return Some(*size);
+        }
+        response
+            .body()
+            .size_hint()
+            .exact()
+            .or_else(|| {
+                response
+                    .headers()
+                    .get(hyper::header::CONTENT_LENGTH)?
+                    .to_str()
+                    .ok()?
+                    .parse()
+                    .ok()
+            })
+            .map(ResponseSize)
+    }
+}
+
+/// Replace the `Vary` headers in `headers` with one header listing their
+/// names merged with `vary`
+fn apply_vary(vary: &Vary, headers: &mut hyper::HeaderMap) {
+    let mut merged = Vary::new();
+    for value in headers.get_all(hyper::header::VARY) {
+        if let Ok(value) = value.to_str() {
+            merged.add(value);
+        }
+    }
+    for name in vary.names() {
+        merged.add(name);
+    }
+
+    if let Ok(value) = hyper::header::HeaderValue::from_str(&merged.to_string()) {
+        if !merged.is_empty() {
+            headers.insert(hyper::header::VARY, value);
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Stream a file from disk in `FILE_CHUNK_SIZE` chunks
+///
+/// The file is opened on the body's first poll, so building the response
+/// never touches the disk. Headers go out before the open, which means the
+/// length is unknown (the body is sent chunked) and a missing or unreadable
+/// file aborts the body rather than producing a 404; callers that need the
+/// status check the file first, as `StaticHandler` does. `headers` are added
+/// to the response; a `Content-Type` among them replaces the guessed one.
+fn file_response(path: &Path, headers: &[(String, String)]) -> hyper::Response<ZapBody> {
+    let owned_path = path.to_path_buf();
+    let reader = futures::stream::once(async move {
+        let file = tokio::fs::File::open(&owned_path).await?;
+        if !file.metadata().await?.is_file() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "not a regular file"));
+        }
+        Ok(ReaderStream::with_capacity(file, FILE_CHUNK_SIZE))
+    })
+    .try_flatten();
+    let body = StreamBody::new(reader.map_ok(Frame::data)).boxed();
+
+    let mut builder = hyper::Response::builder().status(200);
+    if !headers.iter().any(|(key, _)| key.eq_ignore_ascii_case("content-type")) {
+        builder = builder.header(
+            "Content-Type",
+            crate::r#static::content_type_for(path, "application/octet-stream"),
+        );
+    }
+    for (key, value) in headers {
+        builder = builder.header(key, value);
+    }
+
+    builder.body(body).unwrap_or_else(|_| {
+        hyper::Response::builder()
+            .status(500)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(full_body("Invalid response header"))
+            .unwrap()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_string(response: hyper::Response<ZapBody>) -> String {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_multipart_streams_parts_in_order() {
+        let parts = futures::stream::iter([
+            Ok(MultipartPart::new("application/json", r#"{"id":1}"#)),
+            Ok(MultipartPart::new("text/csv", "id,name\n1,report\n")
+                .header("Content-Disposition", "attachment; filename=\"report.csv\"")),
+        ]);
+        let response = ZapResponse::Multipart(MultipartBody::from_stream(parts).boundary("b0undary"));
+
+        let response = response.to_hyper_response();
+        assert_eq!(response.headers()["content-type"], "multipart/mixed; boundary=b0undary");
+        assert_eq!(response.extensions().get::<ResponseSize>(), None);
+        assert_eq!(
+            body_string(response).await,
+            "--b0undary\r\n\
+             Content-Type: application/json\r\n\r\n\
+             {\"id\":1}\r\n\
+             --b0undary\r\n\
+             Content-Type: text/csv\r\n\
+             Content-Disposition: attachment; filename=\"report.csv\"\r\n\r\n\
+             id,name\n1,report\n\r\n\
+             --b0undary--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_vary_layers_merge_into_one_header() {
+        // Compression, then CORS, each note what they looked at
+        let compressed = ZapResponse::Text("hello".to_string()).vary("Accept-Encoding");
+        let response = compressed.vary("Origin").vary("accept-encoding").to_hyper_response();
+        let vary: Vec<_> = response.headers().get_all("vary").iter().collect();
+        assert_eq!(vary, ["Accept-Encoding, Origin"]);
+
+        // Names already on the response are kept
+        let custom = Response::ok("negotiated").header("Vary", "Accept, Origin");
+        let response = ZapResponse::Custom(custom).vary("Accept-Encoding").to_hyper_response();
+        assert_eq!(response.headers()["vary"], "Accept, Origin, Accept-Encoding");
+
+        let response = ZapResponse::Status(StatusCode::OK).vary("Origin").vary("*").to_hyper_response();
+        assert_eq!(response.headers()["vary"], "*");
+    }
+
+    #[tokio::test]
+    async fn test_json_pretty_and_large_numbers() {
+        let value = serde_json::json!({ "id": u64::MAX, "small": 42, "neg": i64::MIN });
+
+        let pretty: ZapResponse = Json(value.clone()).pretty().into();
+        let body = body_string(pretty.to_hyper_response()).await;
+        assert!(body.contains('\n'));
+        assert!(body.contains("\"id\": 18446744073709551615"));
+
+        let compact = ZapResponse::Json(value);
+        let options = JsonOptions { large_numbers_as_strings: true };
+        let body = body_string(compact.to_hyper_response_with(&options)).await;
+        assert!(!body.contains('\n'));
+        assert!(body.contains("\"id\":\"18446744073709551615\""));
+        assert!(body.contains("\"neg\":\"-9223372036854775808\""));
+        assert!(body.contains("\"small\":42"));
+    }
+
+    #[tokio::test]
+    async fn test_file_response_streams_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        // Larger than one chunk, with non-UTF-8 bytes
+        let contents: Vec<u8> = (0..FILE_CHUNK_SIZE * 2 + 123).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &contents).unwrap();
+
+        let response = ZapResponse::File(path).to_hyper_response();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], "application/octet-stream");
+        // The file isn't opened until the body is polled, so its length is unknown
+        assert!(response.headers().get("content-length").is_none());
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), contents.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_file_response_content_type_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("page.html");
+        std::fs::write(&path, "<p>hi</p>").unwrap();
+
+        let response = ZapResponse::File(path).to_hyper_response();
+        assert_eq!(response.headers()["content-type"], "text/html; charset=utf-8");
+
+        // Headers are already out when the open fails, so the body errors instead
+        let missing = ZapResponse::File(dir.path().join("nope.txt")).to_hyper_response();
+        let err = missing.into_body().collect().await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        let directory = ZapResponse::File(dir.path().to_path_buf()).to_hyper_response();
+        assert!(directory.into_body().collect().await.is_err());
+    }
+
+    #[test]
+    fn test_response_size_recorded() {
+        let response = ZapResponse::Text("hello world".into()).to_hyper_response();
+        assert_eq!(response.extensions().get::<ResponseSize>(), Some(&ResponseSize(11)));
+
+        let json = ZapResponse::Json(serde_json::json!({ "ok": true })).to_hyper_response();
+        assert_eq!(ResponseSize::of(&json), Some(ResponseSize(11)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, vec![0u8; 1000]).unwrap();
+        let file = ZapResponse::FileWithHeaders {
+            path,
+            headers: vec![("Content-Length".to_string(), "1000".to_string())],
+        }
+        .to_hyper_response();
+        assert_eq!(file.extensions().get::<ResponseSize>(), Some(&ResponseSize(1000)));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_is_not_buffered() {
+        let text = ZapResponse::Text("hello".to_string()).to_hyper_response();
+        assert_eq!(body_string(text).await, "hello");
+
+        let (tx, rx) = futures::channel::mpsc::unbounded::<Result<Bytes, std::io::Error>>();
+        let response = ZapResponse::Streamed(
+            StreamedBody::from_stream(rx).header("Content-Type", "text/event-stream"),
+        )
+        .to_hyper_response();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        // Length is unknown up front, so hyper sends it chunked
+        assert_eq!(ResponseSize::of(&response), None);
+
+        // Each chunk is readable before the stream has ended
+        let mut body = response.into_body();
+        tx.unbounded_send(Ok(Bytes::from_static(b"first"))).unwrap();
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), "first");
+
+        tx.unbounded_send(Ok(Bytes::from_static(b"second"))).unwrap();
+        drop(tx);
+        assert_eq!(body.collect().await.unwrap().to_bytes(), "second");
+    }
+}
@@ -8,7 +8,9 @@
 //!
 //! - **Socket Path**: `{ipc_socket_path}.rpc` (e.g., `/tmp/zap-dev-123.sock.rpc`)
 //! - **Protocol**: Length-prefixed MessagePack/JSON messages
-//! - **Encoding**: MessagePack by default, JSON fallback for debugging
+//! - **Encoding**: MessagePack by default, JSON fallback for debugging, CBOR opt-in via
+//!   [`RpcServerHandle::with_codec`]; each message's codec is detected from its first byte
+//!   and responses use the caller's encoding
 //! - **Concurrency**: Each connection handled in separate Tokio task
 //!
 //! ## Message Format
@@ -41,13 +43,36 @@
 //!   "error_type": "RpcError"
 //! }
 //! ```
-
+//!
+//! Typed errors (`__TYPED_ERROR__:<json>`) whose JSON has a string `code`, such as
+//! [`RpcError`], also carry that `code` and the matching HTTP `status`, e.g.
+//! `"code": "NOT_FOUND", "status": 404`.
+//!
+//! ## Compression
+//!
+//! A client may open with an `rpc_hello` offering compression algorithms:
+//! ```json
+//! { "type": "rpc_hello", "request_id": "hello", "params": { "compression": ["gzip", "deflate"] } }
+//! ```
+//! The server answers with an `rpc_hello_ack` whose `result.compression` names the
+//! accepted algorithm (the first offered one it supports: `gzip`, or `deflate` for
+//! zlib-wrapped DEFLATE), or is `null`. Once one is accepted, every later payload in
+//! either direction starts with a flag byte: `0` for a plain payload, `1` for a
+//! deflate-compressed one, `2` for a gzip-compressed one. Responses above the
+//! compression threshold (default 1KB) are compressed; connections that never say
+//! hello are unchanged.
+
+use std::borrow::Cow;
 use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
 use tracing::{debug, error, info, warn};
 
 use crate::error::{ZapError, ZapResult};
+use crate::schema::ParamViolation;
 
 /// User-provided RPC dispatch function
 ///
@@ -64,6 +89,10 @@ pub struct RpcCallMessage {
     pub function_name: String,
     pub params: serde_json::Value,
     pub request_id: String,
+    /// Identity of the HTTP request the calling handler is serving, as sent
+    /// to it in `IpcRequest::auth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<splice::protocol::AuthContext>,
 }
 
 /// RPC success response to TypeScript
@@ -83,6 +112,96 @@ pub struct RpcErrorMessage {
     pub request_id: String,
     pub error: String,
     pub error_type: String,
+    /// Semantic code of a typed error, e.g. `NOT_FOUND`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// HTTP status matching `code`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+/// Prefix of a dispatch error that carries a serialized error value
+pub const TYPED_ERROR_PREFIX: &str = "__TYPED_ERROR__:";
+
+/// Error with a semantic code, for functions whose callers map codes to
+/// HTTP statuses
+///
+/// Serializes as `{ "error": ..., "code": ... }`, plus `"violations": [...]`
+/// for params rejected by schema validation. Exported functions can return it
+/// directly; hand-written dispatch functions convert it into their error
+/// string with `.into()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub error: String,
+    pub code: String,
+    /// Parameters that failed schema validation, for `VALIDATION_ERROR`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<ParamViolation>,
+}
+
+impl RpcError {
+    /// Create an error with the given code and message
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { error: message.into(), code: code.into(), violations: Vec::new() }
+    }
+
+    /// `VALIDATION_ERROR` (400) listing every parameter that failed the schema
+    pub fn invalid_params(violations: Vec<ParamViolation>) -> Self {
+        let details = violations
+            .iter()
+            .map(|violation| format!("{}: {}", violation.path, violation.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self {
+            violations,
+            ..Self::validation(format!("Invalid parameters: {}", details))
+        }
+    }
+
+    /// `NOT_FOUND` (404)
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new("NOT_FOUND", message)
+    }
+
+    /// `VALIDATION_ERROR` (400)
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new("VALIDATION_ERROR", message)
+    }
+
+    /// HTTP status for this error's code
+    pub fn status(&self) -> u16 {
+        status_for_code(&self.code)
+    }
+}
+
+impl From<RpcError> for String {
+    fn from(error: RpcError) -> Self {
+        let json = serde_json::to_string(&error).unwrap_or_default();
+        format!("{}{}", TYPED_ERROR_PREFIX, json)
+    }
+}
+
+/// HTTP status for a semantic error code; unknown codes are server errors
+pub fn status_for_code(code: &str) -> u16 {
+    match code {
+        "BAD_REQUEST" | "VALIDATION_ERROR" => 400,
+        "UNAUTHORIZED" => 401,
+        "FORBIDDEN" => 403,
+        "NOT_FOUND" | "ROUTE_NOT_FOUND" => 404,
+        "CONFLICT" => 409,
+        "PAYLOAD_TOO_LARGE" => 413,
+        "RATE_LIMITED" => 429,
+        "NOT_IMPLEMENTED" => 501,
+        "TIMEOUT" => 504,
+        _ => 500,
+    }
+}
+
+/// Code carried by a dispatch error, if it is a typed error
+fn error_code(error: &str) -> Option<String> {
+    let json = error.strip_prefix(TYPED_ERROR_PREFIX)?;
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    value.get("code")?.as_str().map(str::to_string)
 }
 
 /// Internal RPC message enum for type-safe handling
@@ -92,10 +211,266 @@ enum RpcMessage {
     Error(RpcErrorMessage),
 }
 
+/// Default cap on a single RPC message (100MB)
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 100 * 1024 * 1024;
+
+/// Default time allowed to receive a message body once its length arrived
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time allowed to write a response before the client is dropped
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time shutdown waits for in-flight calls before removing the socket
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default size above which responses on a compressing connection are compressed (1KB)
+const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Message type a client uses to negotiate compression
+const RPC_HELLO: &str = "rpc_hello";
+
+/// Compression algorithm offered in `rpc_hello`: zlib-wrapped DEFLATE
+pub const RPC_COMPRESSION_DEFLATE: &str = "deflate";
+
+/// Compression algorithm offered in `rpc_hello`: gzip
+pub const RPC_COMPRESSION_GZIP: &str = "gzip";
+
+/// Flag byte of an uncompressed payload on a compressing connection
+const FRAME_PLAIN: u8 = 0;
+
+/// Flag byte of a zlib-compressed payload on a compressing connection
+const FRAME_DEFLATE: u8 = 1;
+
+/// Flag byte of a gzip-compressed payload on a compressing connection
+const FRAME_GZIP: u8 = 2;
+
+/// Compression algorithm negotiated for a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Deflate,
+    Gzip,
+}
+
+impl Compression {
+    /// Algorithm offered in `rpc_hello` under `name`
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            RPC_COMPRESSION_DEFLATE => Some(Self::Deflate),
+            RPC_COMPRESSION_GZIP => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    /// Algorithm of a payload with flag byte `flag`
+    fn from_flag(flag: u8) -> Option<Self> {
+        match flag {
+            FRAME_DEFLATE => Some(Self::Deflate),
+            FRAME_GZIP => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Deflate => RPC_COMPRESSION_DEFLATE,
+            Self::Gzip => RPC_COMPRESSION_GZIP,
+        }
+    }
+
+    fn flag(self) -> u8 {
+        match self {
+            Self::Deflate => FRAME_DEFLATE,
+            Self::Gzip => FRAME_GZIP,
+        }
+    }
+
+    fn compress(self, payload: &[u8]) -> std::io::Result<Vec<u8>> {
+        use std::io::Write;
+
+        let level = flate2::Compression::new(6);
+        match self {
+            Self::Deflate => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+                encoder.write_all(payload)?;
+                encoder.finish()
+            }
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+                encoder.write_all(payload)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    /// Decompress `payload`, failing if it inflates past `max_size` bytes
+    fn decompress(self, payload: &[u8], max_size: usize) -> std::io::Result<Vec<u8>> {
+        use std::io::Read;
+
+        let limit = max_size.saturating_add(1) as u64;
+        let mut decompressed = Vec::new();
+        match self {
+            Self::Deflate => flate2::read::ZlibDecoder::new(payload).take(limit).read_to_end(&mut decompressed)?,
+            Self::Gzip => flate2::read::GzDecoder::new(payload).take(limit).read_to_end(&mut decompressed)?,
+        };
+        if decompressed.len() > max_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("decompressed size exceeds {} bytes", max_size),
+            ));
+        }
+        Ok(decompressed)
+    }
+}
+
+/// Compression offer a client may open a connection with
+#[derive(Debug, Deserialize)]
+struct RpcHelloMessage {
+    request_id: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+/// A decoded incoming message
+#[derive(Debug)]
+enum RpcIncoming {
+    Call(RpcCallMessage),
+    Hello(RpcHelloMessage),
+}
+
+/// Per-connection framing limits
+#[derive(Debug, Clone, Copy)]
+struct RpcLimits {
+    max_message_size: usize,
+    read_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    write_timeout: Duration,
+    compression_threshold: usize,
+}
+
+impl Default for RpcLimits {
+    fn default() -> Self {
+        Self {
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            idle_timeout: None,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+}
+
+/// Wire format for RPC messages
+///
+/// Each incoming message is matched against the server's codecs by its first
+/// bytes, and the response is encoded with the codec that matched.
+pub trait RpcCodec: Send + Sync {
+    /// Short name used in logs and errors
+    fn name(&self) -> &'static str;
+
+    /// Whether `data` looks like a message in this encoding
+    fn matches(&self, data: &[u8]) -> bool;
+
+    /// Encode an outgoing response or error message
+    fn encode(&self, message: &serde_json::Value) -> ZapResult<Vec<u8>>;
+
+    /// Decode an incoming message (a call or a hello)
+    fn decode(&self, data: &[u8]) -> ZapResult<serde_json::Value>;
+}
+
+impl std::fmt::Debug for dyn RpcCodec + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// JSON codec, for debugging; messages are objects starting with `{`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl RpcCodec for JsonCodec {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn matches(&self, data: &[u8]) -> bool {
+        data.first() == Some(&b'{')
+    }
+
+    fn encode(&self, message: &serde_json::Value) -> ZapResult<Vec<u8>> {
+        serde_json::to_vec(message)
+            .map_err(|e| ZapError::ipc(format!("Failed to serialize RPC message to JSON: {}", e)))
+    }
+
+    fn decode(&self, data: &[u8]) -> ZapResult<serde_json::Value> {
+        serde_json::from_slice(data)
+            .map_err(|e| ZapError::ipc(format!("Failed to deserialize JSON RPC message: {}", e)))
+    }
+}
+
+/// MessagePack codec, the default; messages are maps (fixmap, map16 or map32)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+impl RpcCodec for MessagePackCodec {
+    fn name(&self) -> &'static str {
+        "MessagePack"
+    }
+
+    fn matches(&self, data: &[u8]) -> bool {
+        matches!(data.first(), Some(0x80..=0x8f | 0xde | 0xdf))
+    }
+
+    fn encode(&self, message: &serde_json::Value) -> ZapResult<Vec<u8>> {
+        // Use named fields for compatibility with TypeScript @msgpack/msgpack
+        rmp_serde::to_vec_named(message)
+            .map_err(|e| ZapError::ipc(format!("Failed to serialize RPC message to MessagePack: {}", e)))
+    }
+
+    fn decode(&self, data: &[u8]) -> ZapResult<serde_json::Value> {
+        rmp_serde::from_slice(data)
+            .map_err(|e| ZapError::ipc(format!("Failed to deserialize MessagePack RPC message: {}", e)))
+    }
+}
+
+/// CBOR codec (RFC 8949) for constrained clients; messages are maps,
+/// optionally behind the self-describe tag
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+impl RpcCodec for CborCodec {
+    fn name(&self) -> &'static str {
+        "CBOR"
+    }
+
+    fn matches(&self, data: &[u8]) -> bool {
+        matches!(data, [0xa0..=0xbf, ..] | [0xd9, 0xd9, 0xf7, ..])
+    }
+
+    fn encode(&self, message: &serde_json::Value) -> ZapResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(message, &mut bytes)
+            .map_err(|e| ZapError::ipc(format!("Failed to serialize RPC message to CBOR: {}", e)))?;
+        Ok(bytes)
+    }
+
+    fn decode(&self, data: &[u8]) -> ZapResult<serde_json::Value> {
+        ciborium::from_reader(data)
+            .map_err(|e| ZapError::ipc(format!("Failed to deserialize CBOR RPC message: {}", e)))
+    }
+}
+
+/// Codecs accepted by default: JSON and MessagePack
+fn default_codecs() -> Vec<Arc<dyn RpcCodec>> {
+    vec![Arc::new(JsonCodec), Arc::new(MessagePackCodec)]
+}
+
 /// Handle to the RPC server (for lifecycle management)
 pub struct RpcServerHandle {
     socket_path: String,
     dispatch_fn: RpcDispatchFn,
+    limits: RpcLimits,
+    drain_timeout: Duration,
+    codecs: Vec<Arc<dyn RpcCodec>>,
 }
 
 impl RpcServerHandle {
@@ -104,19 +479,71 @@ impl RpcServerHandle {
         Self {
             socket_path,
             dispatch_fn,
+            limits: RpcLimits::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            codecs: default_codecs(),
         }
     }
 
+    /// Accept messages in an additional encoding, e.g. [`CborCodec`]
+    ///
+    /// Codecs added later are tried first when detecting a message's encoding.
+    pub fn with_codec<C: RpcCodec + 'static>(mut self, codec: C) -> Self {
+        self.codecs.insert(0, Arc::new(codec));
+        self
+    }
+
+    /// Reject messages larger than `bytes` (default 100MB)
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.limits.max_message_size = bytes;
+        self
+    }
+
+    /// Close connections that take longer than `timeout` to send a message
+    /// body after its length prefix (default 30s)
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.limits.read_timeout = timeout;
+        self
+    }
+
+    /// Close connections that send nothing for `timeout` between calls
+    /// (default: never, since clients keep one connection open)
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.limits.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Close connections that take longer than `timeout` to accept a
+    /// response (default 30s)
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.limits.write_timeout = timeout;
+        self
+    }
+
+    /// Compress responses larger than `bytes` on connections that negotiated
+    /// compression (default 1KB)
+    pub fn compression_threshold(mut self, bytes: usize) -> Self {
+        self.limits.compression_threshold = bytes;
+        self
+    }
+
+    /// Give in-flight calls up to `timeout` to finish on shutdown (default 30s)
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
     /// Start the RPC server in the background
     ///
     /// Creates a Unix domain socket at `{socket_path}.rpc` and spawns a background
-    /// task to accept connections and handle RPC calls.
+    /// task to accept connections and handle RPC calls. The returned
+    /// [`RpcShutdown`] stops it again.
     ///
     /// # Errors
     ///
     /// Returns an error if the socket cannot be bound (e.g., permission denied,
     /// address already in use).
-    pub async fn start(self) -> ZapResult<()> {
+    pub async fn start(self) -> ZapResult<RpcShutdown> {
         let rpc_socket_path = format!("{}.rpc", self.socket_path);
 
         // Remove existing socket file if it exists
@@ -129,15 +556,30 @@ impl RpcServerHandle {
         info!("🔧 RPC server listening on {}", rpc_socket_path);
 
         let dispatch_fn = self.dispatch_fn;
+        let limits = self.limits;
+        let drain_timeout = self.drain_timeout;
+        let codecs: Arc<[Arc<dyn RpcCodec>]> = self.codecs.into();
+        let shutdown = RpcShutdown {
+            signal: CancellationToken::new(),
+            stopped: CancellationToken::new(),
+        };
+        let (signal, stopped) = (shutdown.signal.clone(), shutdown.stopped.clone());
 
         // Spawn background task to accept connections
         tokio::spawn(async move {
+            let connections = TaskTracker::new();
             loop {
-                match listener.accept().await {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    _ = signal.cancelled() => break,
+                };
+                match accepted {
                     Ok((stream, _addr)) => {
                         let dispatch_fn = dispatch_fn.clone();
-                        tokio::spawn(async move {
-                            if let Err(e) = handle_rpc_connection(stream, dispatch_fn).await {
+                        let codecs = codecs.clone();
+                        let signal = signal.clone();
+                        connections.spawn(async move {
+                            if let Err(e) = handle_rpc_connection(stream, dispatch_fn, limits, codecs, signal).await {
                                 error!("RPC connection error: {}", e);
                             }
                         });
@@ -148,26 +590,74 @@ impl RpcServerHandle {
                     }
                 }
             }
+
+            // Refuse new connections, then let in-flight calls finish
+            drop(listener);
+            let _ = std::fs::remove_file(&rpc_socket_path);
+            connections.close();
+            if tokio::time::timeout(drain_timeout, connections.wait()).await.is_err() {
+                warn!("RPC drain timeout reached with {} connection(s) still active", connections.len());
+            }
+            info!("🔧 RPC server on {} stopped", rpc_socket_path);
+            stopped.cancel();
         });
 
-        Ok(())
+        Ok(shutdown)
+    }
+}
+
+/// Stops a running RPC server, returned by [`RpcServerHandle::start`]
+#[derive(Debug, Clone)]
+pub struct RpcShutdown {
+    signal: CancellationToken,
+    stopped: CancellationToken,
+}
+
+impl RpcShutdown {
+    /// Stop accepting connections and wait until the server has stopped
+    ///
+    /// The socket file is removed right away. Idle connections are closed,
+    /// while calls already being handled finish and get their response,
+    /// waiting at most the drain timeout.
+    pub async fn shutdown(&self) {
+        self.signal.cancel();
+        self.stopped.cancelled().await;
+    }
+
+    /// Check if the server has stopped
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.is_cancelled()
     }
 }
 
 /// Handle a single RPC client connection
 ///
 /// Reads messages from the Unix socket, dispatches RPC calls, and sends responses.
-/// Runs in a loop until the client disconnects or an error occurs.
+/// Runs in a loop until the client disconnects, `shutdown` is cancelled between
+/// calls, or an error occurs. Empty, oversized, or too slowly delivered
+/// messages close the connection, as do idle clients and clients that stop
+/// reading responses.
 async fn handle_rpc_connection(
     stream: tokio::net::UnixStream,
     dispatch_fn: RpcDispatchFn,
+    limits: RpcLimits,
+    codecs: Arc<[Arc<dyn RpcCodec>]>,
+    shutdown: CancellationToken,
 ) -> ZapResult<()> {
     let mut stream = stream;
+    let mut compression = None;
 
     loop {
         // Read 4-byte big-endian length prefix
         let mut len_buf = [0u8; 4];
-        match stream.read_exact(&mut len_buf).await {
+        let read = tokio::select! {
+            read = read_idle(&mut stream, &mut len_buf, limits.idle_timeout) => read?,
+            _ = shutdown.cancelled() => {
+                debug!("RPC server shutting down, closing idle connection");
+                return Ok(());
+            }
+        };
+        match read {
             Ok(_) => {}
             Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
                 debug!("RPC client disconnected");
@@ -180,26 +670,56 @@ async fn handle_rpc_connection(
 
         let len = u32::from_be_bytes(len_buf) as usize;
 
-        // Sanity check: reject messages larger than 100MB
-        if len > 100 * 1024 * 1024 {
-            return Err(ZapError::ipc(format!("Message too large: {} bytes", len)));
+        if len == 0 {
+            return Err(ZapError::ipc("Empty RPC frame"));
         }
 
-        // Read payload
+        // Sanity check: reject messages above the configured cap
+        if len > limits.max_message_size {
+            return Err(ZapError::ipc(format!(
+                "Message too large: {} bytes (limit {})",
+                len, limits.max_message_size
+            )));
+        }
+
+        // Read payload, giving up on clients that advertise a length and stall
         let mut buffer = vec![0u8; len];
-        stream
-            .read_exact(&mut buffer)
+        tokio::time::timeout(limits.read_timeout, stream.read_exact(&mut buffer))
             .await
+            .map_err(|_| {
+                ZapError::ipc(format!(
+                    "Timed out reading {}-byte message payload after {:?}",
+                    len, limits.read_timeout
+                ))
+            })?
             .map_err(|e| ZapError::ipc(format!("Failed to read message payload: {}", e)))?;
 
-        // Deserialize RPC call (auto-detect MessagePack or JSON)
-        let call = deserialize_rpc_message(&buffer)?;
+        // Strip the compression flag once negotiated; the reply to a hello is
+        // framed the way the hello was
+        let framed = compression;
+        let payload = if framed.is_some() {
+            decode_frame(&buffer, limits.max_message_size)?
+        } else {
+            Cow::Borrowed(buffer.as_slice())
+        };
 
-        // Dispatch RPC call to user function
-        let response_msg = dispatch_rpc_call(&call, &dispatch_fn);
+        // Deserialize RPC call (encoding detected from the first bytes)
+        let (message, codec) = deserialize_rpc_message(&payload, &codecs)?;
 
-        // Serialize response
-        let response_bytes = serialize_rpc_message(&response_msg)?;
+        // Dispatch RPC call to user function, or answer a compression offer
+        let response_msg = match message {
+            RpcIncoming::Call(call) => dispatch_rpc_call(&call, &dispatch_fn),
+            RpcIncoming::Hello(hello) => {
+                compression = negotiate_compression(&hello.params);
+                hello_ack(&hello, compression)
+            }
+        };
+
+        // Serialize response in the encoding the client used
+        let mut response_bytes = serialize_rpc_message(&response_msg, codec)?;
+        if let Some(algorithm) = framed {
+            response_bytes = encode_frame(response_bytes, algorithm, limits.compression_threshold);
+        }
 
         // Write length prefix + payload (atomic frame)
         let frame_len = response_bytes.len() as u32;
@@ -207,18 +727,99 @@ async fn handle_rpc_connection(
         frame.extend_from_slice(&frame_len.to_be_bytes());
         frame.extend_from_slice(&response_bytes);
 
-        stream
-            .write_all(&frame)
+        let write = async {
+            stream
+                .write_all(&frame)
+                .await
+                .map_err(|e| ZapError::ipc(format!("Failed to write response: {}", e)))?;
+
+            stream
+                .flush()
+                .await
+                .map_err(|e| ZapError::ipc(format!("Failed to flush response: {}", e)))
+        };
+        tokio::time::timeout(limits.write_timeout, write)
             .await
-            .map_err(|e| ZapError::ipc(format!("Failed to write response: {}", e)))?;
+            .map_err(|_| {
+                ZapError::ipc(format!(
+                    "Timed out writing {}-byte response after {:?}",
+                    frame.len(), limits.write_timeout
+                ))
+            })??;
+    }
+}
 
-        stream
-            .flush()
+/// Read the next length prefix, failing once the connection has been idle
+/// for `idle_timeout`
+async fn read_idle(
+    stream: &mut tokio::net::UnixStream,
+    len_buf: &mut [u8; 4],
+    idle_timeout: Option<Duration>,
+) -> ZapResult<std::io::Result<usize>> {
+    match idle_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, stream.read_exact(len_buf))
             .await
-            .map_err(|e| ZapError::ipc(format!("Failed to flush response: {}", e)))?;
+            .map_err(|_| ZapError::ipc(format!("Closing RPC connection idle for {:?}", timeout))),
+        None => Ok(stream.read_exact(len_buf).await),
     }
 }
 
+/// The first algorithm listed in an `rpc_hello` that the server supports
+fn negotiate_compression(params: &serde_json::Value) -> Option<Compression> {
+    params["compression"]
+        .as_array()?
+        .iter()
+        .find_map(|algorithm| algorithm.as_str().and_then(Compression::from_name))
+}
+
+/// Answer an `rpc_hello` with the accepted compression algorithm, if any
+fn hello_ack(hello: &RpcHelloMessage, compression: Option<Compression>) -> RpcMessage {
+    let compression = compression.map(Compression::name);
+    debug!("RPC: negotiated compression {:?}", compression);
+
+    RpcMessage::Response(RpcResponseMessage {
+        msg_type: "rpc_hello_ack".to_string(),
+        request_id: hello.request_id.clone(),
+        result: serde_json::json!({ "compression": compression }),
+    })
+}
+
+/// Strip the flag byte from a payload on a compressing connection,
+/// decompressing it when compressed (to at most `max_size` bytes)
+fn decode_frame(frame: &[u8], max_size: usize) -> ZapResult<Cow<'_, [u8]>> {
+    match frame.split_first() {
+        Some((&FRAME_PLAIN, payload)) => Ok(Cow::Borrowed(payload)),
+        Some((&flag, payload)) => match Compression::from_flag(flag) {
+            Some(algorithm) => algorithm
+                .decompress(payload, max_size)
+                .map(Cow::Owned)
+                .map_err(|e| ZapError::ipc(format!("Failed to decompress RPC message: {}", e))),
+            None => Err(ZapError::ipc(format!("Unknown RPC frame flag: {}", flag))),
+        },
+        None => Err(ZapError::ipc("Empty RPC frame")),
+    }
+}
+
+/// Prefix a payload with its flag byte, compressing it with `compression`
+/// when larger than `threshold` and compression actually saves space
+fn encode_frame(payload: Vec<u8>, compression: Compression, threshold: usize) -> Vec<u8> {
+    if payload.len() > threshold {
+        if let Ok(compressed) = compression.compress(&payload) {
+            if compressed.len() < payload.len() {
+                let mut frame = Vec::with_capacity(1 + compressed.len());
+                frame.push(compression.flag());
+                frame.extend_from_slice(&compressed);
+                return frame;
+            }
+        }
+    }
+
+    let mut frame = Vec::with_capacity(1 + payload.len());
+    frame.push(FRAME_PLAIN);
+    frame.extend_from_slice(&payload);
+    frame
+}
+
 /// Dispatch an RPC call to the user's dispatch function
 fn dispatch_rpc_call(call: &RpcCallMessage, dispatch_fn: &RpcDispatchFn) -> RpcMessage {
     debug!(
@@ -228,8 +829,41 @@ fn dispatch_rpc_call(call: &RpcCallMessage, dispatch_fn: &RpcDispatchFn) -> RpcM
 
     let start = std::time::Instant::now();
 
-    // RPC calls from TypeScript don't have context (only Splice calls do)
-    match dispatch_fn(call.function_name.clone(), call.params.clone(), None) {
+    // Calls made while serving an authenticated request carry its identity;
+    // anything else runs without a context.
+    let context = call.auth.clone().map(|auth| splice::protocol::RequestContext {
+        trace_id: 0,
+        span_id: 0,
+        headers: vec![],
+        auth: Some(auth),
+    });
+
+    // A panicking function must still answer, or the client waits forever.
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        dispatch_fn(call.function_name.clone(), call.params.clone(), context)
+    }));
+
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            error!(
+                "RPC: {} panicked: {} (request_id: {})",
+                call.function_name, message, call.request_id
+            );
+
+            return RpcMessage::Error(RpcErrorMessage {
+                msg_type: "rpc_error".to_string(),
+                request_id: call.request_id.clone(),
+                error: format!("Function '{}' panicked: {}", call.function_name, message),
+                error_type: "Panic".to_string(),
+                code: None,
+                status: None,
+            });
+        }
+    };
+
+    match outcome {
         Ok(result) => {
             let duration = start.elapsed();
             debug!(
@@ -250,38 +884,62 @@ fn dispatch_rpc_call(call: &RpcCallMessage, dispatch_fn: &RpcDispatchFn) -> RpcM
                 call.function_name, duration, error, call.request_id
             );
 
+            let code = error_code(&error);
+            let status = code.as_deref().map(status_for_code);
+
             RpcMessage::Error(RpcErrorMessage {
                 msg_type: "rpc_error".to_string(),
                 request_id: call.request_id.clone(),
                 error,
                 error_type: "RpcError".to_string(),
+                code,
+                status,
             })
         }
     }
 }
 
-/// Deserialize RPC message with auto-detection of MessagePack or JSON
-fn deserialize_rpc_message(data: &[u8]) -> ZapResult<RpcCallMessage> {
+/// Extract the message from a panic payload (`panic!` yields `&str` or `String`)
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+/// Deserialize an RPC message with the first codec that recognizes it
+///
+/// Unrecognized payloads are decoded as MessagePack, the default encoding.
+/// Returns the codec used so the response can be encoded the same way.
+fn deserialize_rpc_message<'c>(
+    data: &[u8],
+    codecs: &'c [Arc<dyn RpcCodec>],
+) -> ZapResult<(RpcIncoming, &'c dyn RpcCodec)> {
     if data.is_empty() {
         return Err(ZapError::ipc("Empty RPC message"));
     }
 
-    // Auto-detect encoding based on first byte
-    let first_byte = data[0];
+    let codec = codecs
+        .iter()
+        .find(|codec| codec.matches(data))
+        .map_or(&MessagePackCodec as &dyn RpcCodec, |codec| codec.as_ref());
+    let value = codec.decode(data)?;
 
-    if first_byte == b'{' {
-        // JSON format (starts with '{')
-        serde_json::from_slice(data)
-            .map_err(|e| ZapError::ipc(format!("Failed to deserialize JSON RPC message: {}", e)))
+    let message = if value["type"] == RPC_HELLO {
+        serde_json::from_value(value)
+            .map(RpcIncoming::Hello)
+            .map_err(|e| ZapError::ipc(format!("Invalid RPC hello: {}", e)))?
     } else {
-        // MessagePack format (binary)
-        rmp_serde::from_slice(data)
-            .map_err(|e| ZapError::ipc(format!("Failed to deserialize MessagePack RPC message: {}", e)))
-    }
+        serde_json::from_value(value)
+            .map(RpcIncoming::Call)
+            .map_err(|e| ZapError::ipc(format!("Invalid {} RPC call: {}", codec.name(), e)))?
+    };
+    Ok((message, codec))
 }
 
-/// Serialize RPC message to MessagePack format
-fn serialize_rpc_message(msg: &RpcMessage) -> ZapResult<Vec<u8>> {
+/// Serialize an RPC response or error message with `codec`
+fn serialize_rpc_message(msg: &RpcMessage, codec: &dyn RpcCodec) -> ZapResult<Vec<u8>> {
     let serializable = match msg {
         RpcMessage::Response(resp) => serde_json::to_value(resp)
             .map_err(|e| ZapError::ipc(format!("Failed to convert response to JSON value: {}", e)))?,
@@ -289,9 +947,7 @@ fn serialize_rpc_message(msg: &RpcMessage) -> ZapResult<Vec<u8>> {
             .map_err(|e| ZapError::ipc(format!("Failed to convert error to JSON value: {}", e)))?,
     };
 
-    // Use MessagePack with named fields for compatibility with TypeScript @msgpack/msgpack
-    rmp_serde::to_vec_named(&serializable)
-        .map_err(|e| ZapError::ipc(format!("Failed to serialize RPC message to MessagePack: {}", e)))
+    codec.encode(&serializable)
 }
 
 #[cfg(test)]
@@ -310,6 +966,7 @@ mod tests {
             function_name: "get_benchmarks".to_string(),
             params: json!({"limit": 10, "offset": 0}),
             request_id: "req_123".to_string(),
+            auth: None,
         };
 
         let json_bytes = serde_json::to_vec(&call).unwrap();
@@ -346,6 +1003,8 @@ mod tests {
             request_id: "req_789".to_string(),
             error: "Function not found".to_string(),
             error_type: "NotFound".to_string(),
+            code: None,
+            status: None,
         };
 
         let json_bytes = serde_json::to_vec(&error).unwrap();
@@ -373,7 +1032,7 @@ mod tests {
             }),
         });
 
-        let msgpack_bytes = serialize_rpc_message(&response).unwrap();
+        let msgpack_bytes = serialize_rpc_message(&response, &MessagePackCodec).unwrap();
         let json_bytes = serde_json::to_vec(&json!({
             "type": "rpc_response",
             "request_id": "req_benchmark_001",
@@ -398,6 +1057,13 @@ mod tests {
         );
     }
 
+    fn decode_call(data: &[u8]) -> RpcCallMessage {
+        match deserialize_rpc_message(data, &default_codecs()).unwrap().0 {
+            RpcIncoming::Call(call) => call,
+            other => panic!("expected a call, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_deserialize_json_rpc_call() {
         let json_data = r#"{
@@ -407,7 +1073,7 @@ mod tests {
             "request_id": "req_test_001"
         }"#;
 
-        let call = deserialize_rpc_message(json_data.as_bytes()).unwrap();
+        let call = decode_call(json_data.as_bytes());
 
         assert_eq!(call.msg_type, "rpc_call");
         assert_eq!(call.function_name, "list_users");
@@ -422,13 +1088,14 @@ mod tests {
             function_name: "get_user".to_string(),
             params: json!({"id": "user_123"}),
             request_id: "req_msgpack_001".to_string(),
+            auth: None,
         };
 
         // Serialize to MessagePack
         let msgpack_bytes = rmp_serde::to_vec_named(&call).unwrap();
 
         // Deserialize back
-        let decoded = deserialize_rpc_message(&msgpack_bytes).unwrap();
+        let decoded = decode_call(&msgpack_bytes);
 
         assert_eq!(decoded.function_name, "get_user");
         assert_eq!(decoded.params["id"], "user_123");
@@ -443,24 +1110,242 @@ mod tests {
             function_name: "test".to_string(),
             params: json!({}),
             request_id: "req_001".to_string(),
+            auth: None,
         };
         let json_bytes = serde_json::to_vec(&json_call).unwrap();
         assert_eq!(json_bytes[0], b'{');
 
-        let decoded_json = deserialize_rpc_message(&json_bytes).unwrap();
+        let decoded_json = decode_call(&json_bytes);
         assert_eq!(decoded_json.function_name, "test");
 
         // MessagePack starts with binary format marker
         let msgpack_bytes = rmp_serde::to_vec_named(&json_call).unwrap();
         assert!(msgpack_bytes[0] != b'{'); // Not JSON
 
-        let decoded_msgpack = deserialize_rpc_message(&msgpack_bytes).unwrap();
+        let decoded_msgpack = decode_call(&msgpack_bytes);
         assert_eq!(decoded_msgpack.function_name, "test");
     }
 
+    #[tokio::test]
+    async fn test_response_matches_request_encoding() {
+        let dispatch: RpcDispatchFn = Arc::new(|_func, _params, _context| Ok(json!({"pong": true})));
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        tokio::spawn(handle_rpc_connection(server, dispatch, RpcLimits::default(), default_codecs().into(), CancellationToken::new()));
+
+        async fn round_trip(client: &mut tokio::net::UnixStream, payload: &[u8]) -> Vec<u8> {
+            client.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+            client.write_all(payload).await.unwrap();
+
+            let mut len_buf = [0u8; 4];
+            client.read_exact(&mut len_buf).await.unwrap();
+            let mut response = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+            client.read_exact(&mut response).await.unwrap();
+            response
+        }
+
+        let call = RpcCallMessage {
+            msg_type: "rpc_call".to_string(),
+            function_name: "ping".to_string(),
+            params: json!({}),
+            request_id: "req_enc_001".to_string(),
+            auth: None,
+        };
+
+        let json_response = round_trip(&mut client, &serde_json::to_vec(&call).unwrap()).await;
+        assert_eq!(json_response[0], b'{');
+        let decoded: RpcResponseMessage = serde_json::from_slice(&json_response).unwrap();
+        assert_eq!(decoded.request_id, "req_enc_001");
+
+        let msgpack_response = round_trip(&mut client, &rmp_serde::to_vec_named(&call).unwrap()).await;
+        assert_ne!(msgpack_response[0], b'{');
+        let decoded: RpcResponseMessage = rmp_serde::from_slice(&msgpack_response).unwrap();
+        assert_eq!(decoded.result["pong"], true);
+    }
+
+    #[tokio::test]
+    async fn test_cbor_codec_round_trip() {
+        let dispatch: RpcDispatchFn = Arc::new(|func, params, _context| {
+            Ok(json!({ "function": func, "sum": params["a"].as_i64().unwrap() + params["b"].as_i64().unwrap() }))
+        });
+        let handle = RpcServerHandle::new("/tmp/test-rpc-cbor.sock".to_string(), dispatch.clone())
+            .with_codec(CborCodec);
+        assert_eq!(handle.codecs[0].name(), "CBOR");
+
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        tokio::spawn(handle_rpc_connection(server, dispatch, RpcLimits::default(), handle.codecs.into(), CancellationToken::new()));
+
+        let call = RpcCallMessage {
+            msg_type: "rpc_call".to_string(),
+            function_name: "add".to_string(),
+            params: json!({ "a": 2, "b": 40 }),
+            request_id: "req_cbor_001".to_string(),
+            auth: None,
+        };
+        let mut payload = Vec::new();
+        ciborium::into_writer(&call, &mut payload).unwrap();
+        assert!(CborCodec.matches(&payload));
+        assert!(!MessagePackCodec.matches(&payload));
+
+        client.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+        client.write_all(&payload).await.unwrap();
+        let mut len_buf = [0u8; 4];
+        client.read_exact(&mut len_buf).await.unwrap();
+        let mut response = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+        client.read_exact(&mut response).await.unwrap();
+
+        let decoded: RpcResponseMessage = ciborium::from_reader(response.as_slice()).unwrap();
+        assert_eq!(decoded.msg_type, "rpc_response");
+        assert_eq!(decoded.request_id, "req_cbor_001");
+        assert_eq!(decoded.result, json!({ "function": "add", "sum": 42 }));
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_compression_round_trip() {
+        let expected = json!({ "posts": [{ "id": 1, "content": "lorem ipsum dolor sit amet ".repeat(4096) }] });
+        let result = expected.clone();
+        let dispatch: RpcDispatchFn = Arc::new(move |_func, _params, _context| Ok(result.clone()));
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        tokio::spawn(handle_rpc_connection(server, dispatch, RpcLimits::default(), default_codecs().into(), CancellationToken::new()));
+
+        async fn round_trip(client: &mut tokio::net::UnixStream, payload: &[u8]) -> Vec<u8> {
+            client.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+            client.write_all(payload).await.unwrap();
+
+            let mut len_buf = [0u8; 4];
+            client.read_exact(&mut len_buf).await.unwrap();
+            let mut response = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+            client.read_exact(&mut response).await.unwrap();
+            response
+        }
+
+        // The hello and its ack are exchanged without flag bytes
+        let hello = json!({ "type": "rpc_hello", "request_id": "hello", "params": { "compression": ["gzip", "deflate"] } });
+        let ack = round_trip(&mut client, &serde_json::to_vec(&hello).unwrap()).await;
+        let ack: RpcResponseMessage = serde_json::from_slice(&ack).unwrap();
+        assert_eq!(ack.msg_type, "rpc_hello_ack");
+        assert_eq!(ack.result["compression"], "gzip");
+
+        // Requests may be compressed too
+        let call = RpcCallMessage {
+            msg_type: "rpc_call".to_string(),
+            function_name: "list_posts".to_string(),
+            params: json!({}),
+            request_id: "req_zip_001".to_string(),
+            auth: None,
+        };
+        let mut request = vec![FRAME_GZIP];
+        request.extend(Compression::Gzip.compress(&rmp_serde::to_vec_named(&call).unwrap()).unwrap());
+        let response = round_trip(&mut client, &request).await;
+
+        let uncompressed = serialize_rpc_message(
+            &RpcMessage::Response(RpcResponseMessage {
+                msg_type: "rpc_response".to_string(),
+                request_id: "req_zip_001".to_string(),
+                result: expected.clone(),
+            }),
+            &MessagePackCodec,
+        )
+        .unwrap();
+        assert_eq!(response[0], FRAME_GZIP);
+        assert!(response.len() < uncompressed.len() / 10, "{} vs {}", response.len(), uncompressed.len());
+
+        let decoded: RpcResponseMessage = rmp_serde::from_slice(&decode_frame(&response, usize::MAX).unwrap()).unwrap();
+        assert_eq!(decoded.request_id, "req_zip_001");
+        assert_eq!(decoded.result, expected);
+
+        // Small payloads are sent as-is
+        assert_eq!(
+            encode_frame(b"{}".to_vec(), Compression::Gzip, DEFAULT_COMPRESSION_THRESHOLD),
+            [FRAME_PLAIN, b'{', b'}']
+        );
+    }
+
+    #[test]
+    fn test_compression_negotiation() {
+        let offer = |algorithms: serde_json::Value| negotiate_compression(&json!({ "compression": algorithms }));
+        assert_eq!(offer(json!(["gzip", "deflate"])), Some(Compression::Gzip));
+        assert_eq!(offer(json!(["br", "deflate", "gzip"])), Some(Compression::Deflate));
+        assert_eq!(offer(json!(["br"])), None);
+        assert_eq!(negotiate_compression(&json!({})), None);
+
+        // Either algorithm round-trips, and inflating past the limit fails
+        let payload = "lorem ipsum ".repeat(1000).into_bytes();
+        for algorithm in [Compression::Deflate, Compression::Gzip] {
+            let frame = encode_frame(payload.clone(), algorithm, DEFAULT_COMPRESSION_THRESHOLD);
+            assert_eq!(frame[0], algorithm.flag());
+            assert_eq!(decode_frame(&frame, payload.len()).unwrap().as_ref(), payload.as_slice());
+            assert!(decode_frame(&frame, payload.len() - 1).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_rejects_stalled_and_invalid_frames() {
+        let dispatch: RpcDispatchFn = Arc::new(|_func, _params, _context| Ok(json!(null)));
+        let limits = RpcLimits {
+            max_message_size: 1024,
+            read_timeout: Duration::from_millis(50),
+            ..RpcLimits::default()
+        };
+
+        // Length prefix with no body: closed with an error once the timeout passes
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        client.write_all(&64u32.to_be_bytes()).await.unwrap();
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle_rpc_connection(server, dispatch.clone(), limits, default_codecs().into(), CancellationToken::new()),
+        )
+        .await
+        .expect("stalled client should not hang the connection");
+        assert!(result.unwrap_err().to_string().contains("Timed out"));
+
+        // Zero-length frame
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        client.write_all(&0u32.to_be_bytes()).await.unwrap();
+        let result = handle_rpc_connection(server, dispatch.clone(), limits, default_codecs().into(), CancellationToken::new()).await;
+        assert!(result.unwrap_err().to_string().contains("Empty RPC frame"));
+
+        // Oversized frame
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        client.write_all(&2048u32.to_be_bytes()).await.unwrap();
+        let result = handle_rpc_connection(server, dispatch, limits, default_codecs().into(), CancellationToken::new()).await;
+        assert!(result.unwrap_err().to_string().contains("too large"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_is_closed() {
+        let dispatch: RpcDispatchFn = Arc::new(|_func, _params, _context| Ok(json!(null)));
+        let limits = RpcLimits {
+            idle_timeout: Some(Duration::from_millis(50)),
+            ..RpcLimits::default()
+        };
+
+        // Connect and send nothing
+        let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+        let connection = tokio::spawn(handle_rpc_connection(
+            server, dispatch, limits, default_codecs().into(), CancellationToken::new(),
+        ));
+
+        let mut buf = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_secs(5), client.read(&mut buf))
+            .await
+            .expect("idle connection should be closed");
+        assert_eq!(read.unwrap(), 0);
+
+        let result = connection.await.unwrap();
+        assert!(result.unwrap_err().to_string().contains("idle"));
+    }
+
+    #[test]
+    fn test_call_without_function_name_is_rejected() {
+        let data = br#"{"type": "rpc_call", "params": {}, "request_id": "req_1"}"#;
+        let err = deserialize_rpc_message(data, &default_codecs()).unwrap_err();
+        assert!(err.to_string().contains("function_name"), "{}", err);
+    }
+
     #[test]
     fn test_empty_message_error() {
-        let result = deserialize_rpc_message(&[]);
+        let codecs = default_codecs();
+        let result = deserialize_rpc_message(&[], &codecs);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Empty RPC message"));
     }
@@ -484,6 +1369,7 @@ mod tests {
             function_name: "ping".to_string(),
             params: json!({}),
             request_id: "req_ping_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
@@ -515,6 +1401,7 @@ mod tests {
             function_name: "add".to_string(),
             params: json!({"a": 10, "b": 32}),
             request_id: "req_add_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
@@ -541,6 +1428,7 @@ mod tests {
             function_name: "invalid_func".to_string(),
             params: json!({}),
             request_id: "req_error_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
@@ -556,6 +1444,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dispatch_typed_error_carries_code() {
+        let dispatch: RpcDispatchFn = Arc::new(|_func, params, _context| {
+            Err(RpcError::not_found(format!("User {} not found", params["id"])).into())
+        });
+
+        let call = RpcCallMessage {
+            msg_type: "rpc_call".to_string(),
+            function_name: "get_user".to_string(),
+            params: json!({"id": 7}),
+            request_id: "req_error_002".to_string(),
+            auth: None,
+        };
+
+        match dispatch_rpc_call(&call, &dispatch) {
+            RpcMessage::Error(err) => {
+                assert_eq!(err.code.as_deref(), Some("NOT_FOUND"));
+                assert_eq!(err.status, Some(404));
+                // The typed error itself is still there for the TypeScript client
+                let typed: RpcError = serde_json::from_str(err.error.strip_prefix(TYPED_ERROR_PREFIX).unwrap()).unwrap();
+                assert_eq!(typed, RpcError::not_found("User 7 not found"));
+            }
+            _ => panic!("Expected error response"),
+        }
+
+        assert_eq!(error_code("Unknown RPC method: get_user"), None);
+        assert_eq!(status_for_code("SOMETHING_ELSE"), 500);
+    }
+
+    #[test]
+    fn test_dispatch_panic_returns_error() {
+        let dispatch: RpcDispatchFn = Arc::new(|func, _params, _context| match func.as_str() {
+            "panic_function" => panic!("intentional panic"),
+            _ => Ok(json!({"ok": true})),
+        });
+
+        let call = RpcCallMessage {
+            msg_type: "rpc_call".to_string(),
+            function_name: "panic_function".to_string(),
+            params: json!({}),
+            request_id: "req_panic_001".to_string(),
+            auth: None,
+        };
+
+        match dispatch_rpc_call(&call, &dispatch) {
+            RpcMessage::Error(err) => {
+                assert_eq!(err.request_id, "req_panic_001");
+                assert_eq!(err.error_type, "Panic");
+                assert!(err.error.contains("intentional panic"));
+            }
+            _ => panic!("Expected error response"),
+        }
+
+        // The dispatcher keeps working after a panic
+        let call = RpcCallMessage {
+            function_name: "ok".to_string(),
+            ..call
+        };
+        assert!(matches!(dispatch_rpc_call(&call, &dispatch), RpcMessage::Response(_)));
+    }
+
     #[test]
     fn test_dispatch_error_invalid_params() {
         let dispatch: RpcDispatchFn = Arc::new(|func, params, _context| {
@@ -579,6 +1528,7 @@ mod tests {
             function_name: "divide".to_string(),
             params: json!({"a": 10}), // Missing 'b'
             request_id: "req_div_001".to_string(),
+            auth: None,
         };
 
         let response1 = dispatch_rpc_call(&call1, &dispatch);
@@ -595,6 +1545,7 @@ mod tests {
             function_name: "divide".to_string(),
             params: json!({"a": 10, "b": 0}),
             request_id: "req_div_002".to_string(),
+            auth: None,
         };
 
         let response2 = dispatch_rpc_call(&call2, &dispatch);
@@ -624,6 +1575,7 @@ mod tests {
             function_name: "get_user".to_string(),
             params: json!({"id": "user_123"}),
             request_id: "req_001".to_string(),
+            auth: None,
         };
 
         match dispatch_rpc_call(&call1, &dispatch) {
@@ -639,6 +1591,7 @@ mod tests {
             function_name: "get_benchmarks".to_string(),
             params: json!({}),
             request_id: "req_002".to_string(),
+            auth: None,
         };
 
         match dispatch_rpc_call(&call2, &dispatch) {
@@ -654,6 +1607,7 @@ mod tests {
             function_name: "list_users".to_string(),
             params: json!({"limit": 50}),
             request_id: "req_003".to_string(),
+            auth: None,
         };
 
         match dispatch_rpc_call(&call3, &dispatch) {
@@ -681,6 +1635,34 @@ mod tests {
     // Edge Case Tests
     // ============================================================================
 
+    #[tokio::test]
+    async fn test_shutdown_removes_socket_and_refuses_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("zap").to_string_lossy().into_owned();
+        let socket = format!("{}.rpc", base);
+
+        let dispatch: RpcDispatchFn = Arc::new(|_func, _params, _context| Ok(json!(null)));
+        let shutdown = RpcServerHandle::new(base, dispatch).start().await.unwrap();
+        assert!(std::path::Path::new(&socket).exists());
+
+        // A client idle after a call is closed rather than holding shutdown up
+        let mut idle = tokio::net::UnixStream::connect(&socket).await.unwrap();
+        let call = br#"{"type":"rpc_call","function_name":"ping","params":{},"request_id":"1"}"#;
+        idle.write_all(&(call.len() as u32).to_be_bytes()).await.unwrap();
+        idle.write_all(call).await.unwrap();
+        let mut len_buf = [0u8; 4];
+        idle.read_exact(&mut len_buf).await.unwrap();
+        idle.read_exact(&mut vec![0u8; u32::from_be_bytes(len_buf) as usize]).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), shutdown.shutdown()).await.unwrap();
+        assert!(shutdown.is_stopped());
+        assert!(!std::path::Path::new(&socket).exists());
+        assert!(tokio::net::UnixStream::connect(&socket).await.is_err());
+
+        let mut buf = [0u8; 1];
+        assert_eq!(idle.read(&mut buf).await.unwrap(), 0);
+    }
+
     #[test]
     fn test_large_payload_serialization() {
         // Test with large data payload (simulate realistic benchmark data)
@@ -704,7 +1686,7 @@ mod tests {
             result: large_data,
         });
 
-        let bytes = serialize_rpc_message(&response).unwrap();
+        let bytes = serialize_rpc_message(&response, &MessagePackCodec).unwrap();
 
         // Should handle large payloads (but still under 100MB limit)
         assert!(bytes.len() < 100 * 1024 * 1024);
@@ -729,6 +1711,7 @@ mod tests {
                 "symbols": "!@#$%^&*()_+-=[]{}|;':\",./<>?"
             }),
             request_id: "req_unicode_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
@@ -767,6 +1750,7 @@ mod tests {
                 }
             }),
             request_id: "req_nested_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
@@ -793,6 +1777,7 @@ mod tests {
                 "empty_object": {}
             }),
             request_id: "req_null_001".to_string(),
+            auth: None,
         };
 
         let response = dispatch_rpc_call(&call, &dispatch);
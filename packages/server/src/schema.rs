@@ -0,0 +1,206 @@
+//! JSON Schema validation for exported function parameters
+//!
+//! `#[zap::export]` derives a schema for each function's parameters from its
+//! signature and checks incoming params against it before deserializing, so
+//! callers get every structural problem at once, each tied to a field, instead
+//! of the first raw serde message.
+//!
+//! Only the subset of JSON Schema the macro emits is understood: `type`,
+//! `minimum`/`maximum`, `items`, `additionalProperties`, `properties`,
+//! `required` and `anyOf`. User-defined types have an empty schema and are
+//! left to serde.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::rpc::{RpcError, TYPED_ERROR_PREFIX};
+
+/// A single parameter that doesn't match the function's schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamViolation {
+    /// Offending field, e.g. `id`, `tags[2]` or `filters.status`
+    pub path: String,
+    /// What was wrong, e.g. `expected integer, found string`
+    pub message: String,
+}
+
+/// Check `params` against a parameters schema, collecting every violation
+pub fn validate_params(schema: &Value, params: &HashMap<String, Value>) -> Vec<ParamViolation> {
+    let mut violations = Vec::new();
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !params.contains_key(name) {
+                violations.push(ParamViolation {
+                    path: name.to_string(),
+                    message: "Missing parameter".to_string(),
+                });
+            }
+        }
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            if let Some(value) = params.get(name) {
+                validate_value(property, value, name.clone(), &mut violations);
+            }
+        }
+    }
+
+    violations
+}
+
+/// Parse the violations out of a wrapper error, if it is a validation error
+pub fn parse_validation_error(message: &str) -> Option<Vec<ParamViolation>> {
+    let error: RpcError = serde_json::from_str(message.strip_prefix(TYPED_ERROR_PREFIX)?).ok()?;
+    (error.code == "VALIDATION_ERROR" && !error.violations.is_empty()).then_some(error.violations)
+}
+
+/// Validate params in generated wrappers, reporting violations as a typed
+/// `VALIDATION_ERROR`
+#[doc(hidden)]
+pub fn check_params(schema: &Value, params: &HashMap<String, Value>) -> Result<(), String> {
+    let violations = validate_params(schema, params);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(RpcError::invalid_params(violations).into())
+}
+
+fn validate_value(schema: &Value, value: &Value, path: String, violations: &mut Vec<ParamViolation>) {
+    if let Some(alternatives) = schema.get("anyOf").and_then(Value::as_array) {
+        // Report the first alternative's problems when none of them match
+        let mut first = None;
+        for alternative in alternatives {
+            let mut found = Vec::new();
+            validate_value(alternative, value, path.clone(), &mut found);
+            if found.is_empty() {
+                return;
+            }
+            first.get_or_insert(found);
+        }
+        violations.extend(first.unwrap_or_default());
+        return;
+    }
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        if !matches_type(expected, value) {
+            violations.push(ParamViolation {
+                message: format!("expected {}, found {}", expected, type_name(value)),
+                path,
+            });
+            return;
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                violations.push(ParamViolation {
+                    path: path.clone(),
+                    message: format!("must be at least {}", minimum),
+                });
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                violations.push(ParamViolation {
+                    path: path.clone(),
+                    message: format!("must be at most {}", maximum),
+                });
+            }
+        }
+    }
+
+    match value {
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, format!("{}[{}]", path, i), violations);
+                }
+            }
+        }
+        Value::Object(map) => {
+            if let Some(value_schema) = schema.get("additionalProperties").filter(|s| s.is_object()) {
+                for (key, item) in map {
+                    validate_value(value_schema, item, format!("{}.{}", path, key), violations);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collects_all_violations() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 0 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "limit": { "anyOf": [{ "type": "integer" }, { "type": "null" }] },
+                "user": {}
+            },
+            "required": ["id", "tags", "name"]
+        });
+        let params: HashMap<String, Value> = serde_json::from_value(json!({
+            "id": -1,
+            "tags": ["a", 2],
+            "limit": "ten",
+            "user": { "anything": true }
+        }))
+        .unwrap();
+
+        let violations = validate_params(&schema, &params);
+        let found: Vec<(&str, &str)> = violations
+            .iter()
+            .map(|v| (v.path.as_str(), v.message.as_str()))
+            .collect();
+        assert_eq!(found.len(), 4);
+        assert!(found.contains(&("name", "Missing parameter")));
+        assert!(found.contains(&("id", "must be at least 0")));
+        assert!(found.contains(&("tags[1]", "expected string, found integer")));
+        assert!(found.contains(&("limit", "expected integer, found string")));
+
+        let error = check_params(&schema, &params).unwrap_err();
+        assert_eq!(parse_validation_error(&error), Some(violations.clone()));
+
+        // Violations travel in the typed-error envelope clients already parse
+        let typed: Value = serde_json::from_str(error.strip_prefix(TYPED_ERROR_PREFIX).unwrap()).unwrap();
+        assert_eq!(typed["code"], "VALIDATION_ERROR");
+        assert_eq!(typed["violations"], serde_json::to_value(&violations).unwrap());
+        assert!(typed["error"].as_str().unwrap().starts_with("Invalid parameters: "));
+    }
+}
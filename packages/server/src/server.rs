@@ -1,32 +1,134 @@
 //! Core ZapServer implementation
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use futures::FutureExt;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
-use hyper::{body::Incoming, Request as HyperRequest, Response as HyperResponse};
+use hyper::{Request as HyperRequest, Response as HyperResponse};
 use hyper_util::rt::TokioIo;
-use serde::Serialize;
-use tokio::net::TcpListener;
-use tracing::{debug, error, info, warn};
+use ipnet::IpNet;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+use tracing::{debug, error, info, warn, Instrument};
 
 use zap_core::{
-    HttpParser, Method, MiddlewareChain, Request, Router,
+    AllowedMethods, AppState, ChainOutcome, Context as MiddlewareContext, Extensions, HttpParser,
+    JwtClaims, MatchResult, Method, MiddlewareChain, MiddlewareResponse, ParseError, ParsedRequest,
+    Request, RequestId, Router, RouterError, Session, StatusCode,
 };
 
 use crate::config::{ServerConfig, ZapConfig};
-use crate::error::{ZapError, ZapResult};
-use crate::handler::{AsyncHandler, BoxedHandler, Handler, SimpleHandler};
+use crate::connection_pool::init_global_pool;
+use crate::deadline::{requested_timeout, to_deadline_ms};
+use crate::error::{ZapError, ZapResult, PROBLEM_JSON_CONTENT_TYPE};
+use crate::forwarded::{resolve_client_ip, FORWARDED_FOR_HEADER};
+use crate::handler::{AsyncHandler, BodyLimit, BodyStream, BoxedHandler, Handler, SimpleHandler, StreamingHandler, TryAsyncHandler};
+use crate::metrics::ConnectionCounters;
 use crate::proxy::ProxyHandler;
 use crate::reliability::{HealthChecker, HealthStatus};
 use crate::request::RequestData;
-use crate::response::{Json, ZapResponse};
-use crate::shutdown::{GracefulShutdown, ShutdownConfig};
-use crate::r#static::{handle_static_files, StaticHandler, StaticOptions};
+use crate::response::{full_body, Json, ResponseSize, ZapBody, ZapResponse};
+use crate::rpc::{panic_message, RpcShutdown};
+use crate::shutdown::{GracefulShutdown, ShutdownConfig, ShutdownHook};
+use crate::splice_client::DEFAULT_SPLICE_DEADLINE_MS;
+use crate::r#static::{handle_static_files_with_method, StaticHandler, StaticOptions};
 use crate::utils::convert_method;
 
+/// Outcome of a pre-routing rewrite hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteAction {
+    /// Route the request as if it had been made to this path
+    Rewrite(String),
+    /// Respond immediately with a redirect (e.g. 301 or 302)
+    Redirect { location: String, status: u16 },
+}
+
+impl RewriteAction {
+    /// Build the redirect response for a `Redirect` action
+    fn redirect_response(location: &str, status: u16) -> ZapResponse {
+        ZapResponse::Custom(
+            zap_core::Response::with_status(StatusCode(status)).header("Location", location),
+        )
+    }
+}
+
+/// Pre-routing hook: receives the request path (without query string)
+pub type RewriteFn = fn(&str) -> Option<RewriteAction>;
+
+/// Summary of a completed request, handed to [`ResponseHook`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseInfo {
+    /// Request method as received
+    pub method: String,
+    /// Request path (without query string) as received
+    pub path: String,
+    /// Response status code
+    pub status: u16,
+    /// Request body bytes read from the client
+    pub request_bytes: u64,
+    /// Response body bytes, if known before sending (see [`ResponseSize`])
+    pub response_bytes: Option<u64>,
+    /// Time from receiving the request to building the response
+    pub duration: Duration,
+}
+
+/// Post-response hook, e.g. for access logs, billing or quotas
+pub type ResponseHook = Arc<dyn Fn(&ResponseInfo) + Send + Sync>;
+
+/// A connection handed over to an [`UpgradeHandler`] after `101 Switching Protocols`
+pub struct UpgradedConnection {
+    /// Protocol token the client asked for, lowercased (e.g. `h2c`)
+    pub protocol: String,
+    /// Path of the upgrade request
+    pub path: String,
+    /// Headers of the upgrade request (e.g. `HTTP2-Settings`)
+    pub headers: hyper::HeaderMap,
+    /// The raw connection, now speaking `protocol`
+    pub io: TokioIo<hyper::upgrade::Upgraded>,
+}
+
+/// Takes over a connection upgraded to a non-HTTP/1.1 protocol
+pub type UpgradeHandler = fn(UpgradedConnection) -> futures::future::BoxFuture<'static, ()>;
+
+/// A route that failed to register, reported by `Zap::validate`
+#[derive(Debug, Clone)]
+struct RouteRegistrationError {
+    method: Method,
+    path: String,
+    error: RouterError,
+}
+
+impl std::fmt::Display for RouteRegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to register {} route '{}': {}", self.method, self.path, self.error)
+    }
+}
+
+/// Whether a file can be created in `dir`, checked by creating and removing
+/// a probe file (permission bits alone miss ACLs, read-only mounts and root)
+fn dir_is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".zap-write-probe-{}", std::process::id()));
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => std::fs::remove_file(&probe).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Join a mount prefix and a path: `/api` and `/users` give `/api/users`
+fn mount_path(prefix: &str, path: &str) -> String {
+    let segments: Vec<&str> = [prefix.trim_matches('/'), path.trim_start_matches('/')]
+        .into_iter()
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
 /// Main Zap server - the entry point for building high-performance web applications
 pub struct Zap {
     /// Server configuration
@@ -37,6 +139,29 @@ pub struct Zap {
     middleware: MiddlewareChain,
     /// Static file handlers
     static_handlers: Vec<StaticHandler>,
+    /// Pre-routing rewrite/redirect hook
+    rewrite: Option<RewriteFn>,
+    /// Shared application state handed to handlers
+    state: Arc<AppState>,
+    /// Cleanup callbacks run after connections drain
+    shutdown_hooks: Vec<ShutdownHook>,
+    /// Callbacks run after each response is built
+    response_hooks: Vec<ResponseHook>,
+    /// Protocol upgrade handlers keyed by lowercased `Upgrade` token
+    upgrade_handlers: HashMap<String, UpgradeHandler>,
+    /// Set once shutdown starts; fails readiness checks while draining
+    draining: Arc<AtomicBool>,
+    /// Connections accepted by this server
+    connections: Arc<ConnectionCounters>,
+    /// Route registration failures, reported by `validate` and `bind`
+    registration_errors: Vec<RouteRegistrationError>,
+    /// IPC socket of a server built by `from_config`; `validate` checks that
+    /// its directory is writable
+    ipc_socket_path: Option<PathBuf>,
+    /// Stops the RPC server started by `from_config` once HTTP has drained
+    rpc_shutdown: Option<RpcShutdown>,
+    /// Coordinator of the running server; upgraded connections register with it
+    shutdown: Option<GracefulShutdown>,
 }
 
 impl Zap {
@@ -47,6 +172,17 @@ impl Zap {
             router: Router::new(),
             middleware: MiddlewareChain::new(),
             static_handlers: Vec::new(),
+            rewrite: None,
+            state: Arc::new(AppState::new()),
+            shutdown_hooks: Vec::new(),
+            response_hooks: Vec::new(),
+            upgrade_handlers: HashMap::new(),
+            draining: Arc::new(AtomicBool::new(false)),
+            connections: Arc::default(),
+            registration_errors: Vec::new(),
+            ipc_socket_path: None,
+            rpc_shutdown: None,
+            shutdown: None,
         }
     }
 
@@ -62,6 +198,40 @@ impl Zap {
         self
     }
 
+    /// Fall back to the next ports when the configured port is busy (default: on)
+    ///
+    /// Disable it when orchestration expects the exact port; binding then fails
+    /// with `AddrInUse` instead of drifting.
+    pub fn port_cascade(mut self, enabled: bool) -> Self {
+        self.config.port_cascade = enabled;
+        self
+    }
+
+    /// Set `TCP_NODELAY` on accepted connections (default: off)
+    ///
+    /// Sends small responses immediately instead of coalescing them, which
+    /// helps latency under high connection churn.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Set `SO_REUSEADDR` on the listening socket (default: on)
+    ///
+    /// Lets a restarted server rebind while old connections sit in `TIME_WAIT`.
+    pub fn reuse_address(mut self, enabled: bool) -> Self {
+        self.config.reuse_address = enabled;
+        self
+    }
+
+    /// Set the listen backlog, the queue of not-yet-accepted connections (default: 1024)
+    ///
+    /// The kernel may cap it (e.g. `net.core.somaxconn` on Linux).
+    pub fn backlog(mut self, backlog: u32) -> Self {
+        self.config.backlog = backlog;
+        self
+    }
+
     /// Set keep-alive timeout
     pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
         self.config.keep_alive_timeout = timeout;
@@ -74,12 +244,161 @@ impl Zap {
         self
     }
 
+    /// Maximum number of request headers (default 100)
+    ///
+    /// Requests with more headers are rejected with 431.
+    pub fn max_headers(mut self, count: usize) -> Self {
+        self.config.max_headers = count;
+        self
+    }
+
+    /// Maximum combined size of request headers in bytes (default 8KB)
+    ///
+    /// Larger headers are rejected with 431.
+    pub fn max_header_size(mut self, bytes: usize) -> Self {
+        self.config.max_header_size = bytes;
+        self
+    }
+
+    /// Maximum length of any single request header value in bytes (default 4KB)
+    ///
+    /// Longer values, e.g. a giant `Cookie`, are rejected with 431.
+    pub fn max_header_value_size(mut self, bytes: usize) -> Self {
+        self.config.max_header_value_size = bytes;
+        self
+    }
+
+    /// Close keep-alive connections after serving this many requests
+    ///
+    /// The last allowed response carries `Connection: close`.
+    pub fn max_requests_per_connection(mut self, max: usize) -> Self {
+        self.config.max_requests_per_connection = Some(max);
+        self
+    }
+
     /// Set request timeout
     pub fn request_timeout(mut self, timeout: Duration) -> Self {
         self.config.request_timeout = timeout;
         self
     }
 
+    /// Trust X-Forwarded-For only from peers in these networks
+    pub fn trusted_proxies(mut self, proxies: Vec<IpNet>) -> Self {
+        self.config.trusted_proxies = proxies;
+        self
+    }
+
+    /// Let POST requests override their method for routing
+    ///
+    /// Reads `X-HTTP-Method-Override`, then a `_method` query or form field.
+    /// Only PUT, PATCH and DELETE are accepted, so HTML forms can reach REST routes.
+    pub fn method_override(mut self, enabled: bool) -> Self {
+        self.config.method_override = enabled;
+        self
+    }
+
+    /// Serialize integers beyond ±(2^53 - 1) in JSON responses as strings
+    ///
+    /// Keeps large IDs intact for JavaScript clients.
+    pub fn json_large_numbers_as_strings(mut self, enabled: bool) -> Self {
+        self.config.json.large_numbers_as_strings = enabled;
+        self
+    }
+
+    /// Rewrite or redirect requests before routing
+    ///
+    /// The hook runs before static files and routes are matched. Returning
+    /// `None` leaves the request untouched.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new().rewrite(|path| match path {
+    ///     "/old" => Some(RewriteAction::Redirect { location: "/new".into(), status: 301 }),
+    ///     p if p.starts_with("/v1/") => Some(RewriteAction::Rewrite(p.replacen("/v1/", "/api/", 1))),
+    ///     _ => None,
+    /// })
+    /// ```
+    pub fn rewrite(mut self, hook: RewriteFn) -> Self {
+        self.rewrite = Some(hook);
+        self
+    }
+
+    /// Register a hook called after every response, including errors
+    ///
+    /// Hooks run in registration order on the request task, so they should
+    /// be cheap (e.g. bump counters or queue a log line). They may capture
+    /// state, such as a metrics registry or a log channel.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new().on_response(|info| {
+    ///     println!("{} {} {} in={} out={:?}", info.method, info.path, info.status,
+    ///         info.request_bytes, info.response_bytes);
+    /// })
+    /// ```
+    pub fn on_response<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ResponseInfo) + Send + Sync + 'static,
+    {
+        self.response_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Register a handler for `Upgrade` requests asking for `protocol`
+    ///
+    /// The server answers `101 Switching Protocols` and hands the connection to
+    /// the handler once hyper releases it. Upgrades are only accepted on paths
+    /// with a route, after rewrites and the global middleware chain (auth, rate
+    /// limiting, CORS) have let the request through; the route's own handler is
+    /// not called. Upgrades to protocols without a handler get `501 Not
+    /// Implemented` instead of being routed; `websocket` upgrades without a
+    /// handler still go to the matching route.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new().on_upgrade("h2c", |conn| Box::pin(async move {
+    ///     serve_h2(conn.io).await;
+    /// }))
+    /// ```
+    pub fn on_upgrade(mut self, protocol: &str, handler: UpgradeHandler) -> Self {
+        self.upgrade_handlers.insert(protocol.to_ascii_lowercase(), handler);
+        self
+    }
+
+    /// Register shared state that handlers can look up by type
+    ///
+    /// Each type is stored once; registering the same type again replaces it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new()
+    ///     .with_state(DbPool::connect(url))
+    ///     .get_async("/users", |req| async move {
+    ///         let pool = req.state::<DbPool>().unwrap();
+    ///         // ...
+    ///     })
+    /// ```
+    pub fn with_state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        Arc::make_mut(&mut self.state).insert(state);
+        self
+    }
+
+    /// Register an async cleanup callback for graceful shutdown
+    ///
+    /// Callbacks run in registration order after connections have drained,
+    /// sharing `ShutdownConfig::cleanup_timeout`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new().on_shutdown(|| Box::pin(async {
+    ///     flush_metrics().await;
+    /// }))
+    /// ```
+    pub fn on_shutdown(mut self, hook: ShutdownHook) -> Self {
+        self.shutdown_hooks.push(hook);
+        self
+    }
+
     /// Add middleware to the chain
     pub fn use_middleware<M>(mut self, middleware: M) -> Self
     where
@@ -89,14 +408,124 @@ impl Zap {
         self
     }
 
+    /// Insert a route, recording a failure instead of panicking
+    fn add_route(&mut self, method: Method, path: &str, handler: BoxedHandler) {
+        if let Err(error) = self.router.insert(method, path, handler) {
+            self.registration_errors.push(RouteRegistrationError { method, path: path.to_string(), error });
+        }
+    }
+
+    /// Register a route, returning the router error instead of deferring it
+    ///
+    /// `get`, `post`, etc. record failures for [`Zap::validate`]; this lets
+    /// callers handle an invalid or conflicting path at registration time.
+    pub fn try_route<H>(mut self, method: Method, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.router.insert(method, path, Box::new(handler))?;
+        Ok(self)
+    }
+
+    /// Register a GET route, returning the error on an invalid path
+    pub fn try_get<H>(self, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.try_route(Method::GET, path, handler)
+    }
+
+    /// Register a POST route, returning the error on an invalid path
+    pub fn try_post<H>(self, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.try_route(Method::POST, path, handler)
+    }
+
+    /// Register a PUT route, returning the error on an invalid path
+    pub fn try_put<H>(self, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.try_route(Method::PUT, path, handler)
+    }
+
+    /// Register a PATCH route, returning the error on an invalid path
+    pub fn try_patch<H>(self, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.try_route(Method::PATCH, path, handler)
+    }
+
+    /// Register a DELETE route, returning the error on an invalid path
+    pub fn try_delete<H>(self, path: &str, handler: H) -> Result<Self, RouterError>
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.try_route(Method::DELETE, path, handler)
+    }
+
+    /// Check the whole configuration without starting the server
+    ///
+    /// Collects every problem instead of stopping at the first: routes that
+    /// failed to register (invalid or conflicting paths), static directories
+    /// that don't exist, an IPC socket directory that can't be written to, and
+    /// an unusable listen address. [`Zap::bind`] runs the same checks and
+    /// fails with the first error.
+    pub fn validate(&self) -> Result<(), Vec<ZapError>> {
+        let mut errors: Vec<ZapError> = self
+            .registration_errors
+            .iter()
+            .map(|e| ZapError::config(e.to_string()))
+            .collect();
+
+        for handler in &self.static_handlers {
+            if !handler.directory.is_dir() {
+                errors.push(ZapError::config(format!(
+                    "Static directory for '{}' does not exist: {}",
+                    handler.prefix,
+                    handler.directory.display()
+                )));
+            }
+        }
+
+        if let Some(socket_path) = &self.ipc_socket_path {
+            let socket_dir = socket_path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."));
+            if !dir_is_writable(socket_dir) {
+                errors.push(ZapError::config(format!(
+                    "IPC socket directory is not writable: {}",
+                    socket_dir.display()
+                )));
+            }
+        }
+
+        if self.config.hostname.is_empty() {
+            errors.push(ZapError::config("Hostname cannot be empty"));
+        } else if self.config.socket_addr().parse::<SocketAddr>().is_err() {
+            errors.push(ZapError::config(format!(
+                "Invalid listen address: {}",
+                self.config.socket_addr()
+            )));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Register a GET route
     pub fn get<H>(mut self, path: &str, handler: H) -> Self
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::GET, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register GET route '{}': {}", path, e));
+        self.add_route(Method::GET, path, Box::new(handler));
         self
     }
 
@@ -105,9 +534,7 @@ impl Zap {
     where
         F: Fn() -> String + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::GET, path, Box::new(SimpleHandler::new(handler)))
-            .unwrap_or_else(|e| panic!("Failed to register GET route '{}': {}", path, e));
+        self.add_route(Method::GET, path, Box::new(SimpleHandler::new(handler)));
         self
     }
 
@@ -117,9 +544,20 @@ impl Zap {
         F: Fn(RequestData) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
     {
-        self.router
-            .insert(Method::GET, path, Box::new(AsyncHandler::new(handler)))
-            .unwrap_or_else(|e| panic!("Failed to register GET route '{}': {}", path, e));
+        self.add_route(Method::GET, path, Box::new(AsyncHandler::new(handler)));
+        self
+    }
+
+    /// Register a GET route with a fallible async handler
+    ///
+    /// Returning `Err` sends the error's status and problem document, so
+    /// handlers can use `?` instead of building error responses by hand.
+    pub fn get_try_async<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<ZapResponse, ZapError>> + Send + 'static,
+    {
+        self.add_route(Method::GET, path, Box::new(TryAsyncHandler::new(handler)));
         self
     }
 
@@ -128,9 +566,7 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::POST, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register POST route '{}': {}", path, e));
+        self.add_route(Method::POST, path, Box::new(handler));
         self
     }
 
@@ -140,9 +576,58 @@ impl Zap {
         F: Fn(RequestData) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
     {
-        self.router
-            .insert(Method::POST, path, Box::new(AsyncHandler::new(handler)))
-            .unwrap_or_else(|e| panic!("Failed to register POST route '{}': {}", path, e));
+        self.add_route(Method::POST, path, Box::new(AsyncHandler::new(handler)));
+        self
+    }
+
+    /// Register a POST route with a fallible async handler
+    ///
+    /// See [`Zap::get_try_async`].
+    pub fn post_try_async<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<ZapResponse, ZapError>> + Send + 'static,
+    {
+        self.add_route(Method::POST, path, Box::new(TryAsyncHandler::new(handler)));
+        self
+    }
+
+    /// Register a POST route with its own request body size limit
+    ///
+    /// Overrides the global `max_request_body_size` for this route only,
+    /// e.g. to allow large uploads while keeping other routes small.
+    pub fn post_async_with_limit<F, Fut>(mut self, path: &str, handler: F, limit: usize) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::POST, path, Box::new(BodyLimit::new(AsyncHandler::new(handler), limit)));
+        self
+    }
+
+    /// Register a POST route whose handler streams the request body
+    ///
+    /// The body is not buffered: the handler reads it chunk by chunk from a
+    /// [`BodyStream`], e.g. to write a large upload to disk. The global
+    /// `max_request_body_size` still applies; wrap a [`StreamingHandler`] in
+    /// [`BodyLimit`] and register it with [`Zap::post`] to raise it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// Zap::new().post_streaming("/upload", |_req, mut body: BodyStream| async move {
+    ///     let mut file = tokio::fs::File::create("upload.bin").await.unwrap();
+    ///     while let Some(Ok(chunk)) = body.next().await {
+    ///         file.write_all(&chunk).await.unwrap();
+    ///     }
+    ///     ZapResponse::Status(StatusCode::CREATED)
+    /// })
+    /// ```
+    pub fn post_streaming<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData, BodyStream) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::POST, path, Box::new(StreamingHandler::new(handler)));
         self
     }
 
@@ -151,9 +636,7 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::PUT, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register PUT route '{}': {}", path, e));
+        self.add_route(Method::PUT, path, Box::new(handler));
         self
     }
 
@@ -163,9 +646,7 @@ impl Zap {
         F: Fn(RequestData) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
     {
-        self.router
-            .insert(Method::PUT, path, Box::new(AsyncHandler::new(handler)))
-            .unwrap_or_else(|e| panic!("Failed to register PUT route '{}': {}", path, e));
+        self.add_route(Method::PUT, path, Box::new(AsyncHandler::new(handler)));
         self
     }
 
@@ -174,9 +655,29 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::PATCH, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register PATCH route '{}': {}", path, e));
+        self.add_route(Method::PATCH, path, Box::new(handler));
+        self
+    }
+
+    /// Register a PATCH route with an async handler
+    pub fn patch_async<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::PATCH, path, Box::new(AsyncHandler::new(handler)));
+        self
+    }
+
+    /// Register a PUT route whose handler streams the request body
+    ///
+    /// See [`Zap::post_streaming`].
+    pub fn put_streaming<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData, BodyStream) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::PUT, path, Box::new(StreamingHandler::new(handler)));
         self
     }
 
@@ -185,9 +686,17 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::DELETE, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register DELETE route '{}': {}", path, e));
+        self.add_route(Method::DELETE, path, Box::new(handler));
+        self
+    }
+
+    /// Register a DELETE route with an async handler
+    pub fn delete_async<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::DELETE, path, Box::new(AsyncHandler::new(handler)));
         self
     }
 
@@ -196,9 +705,17 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::OPTIONS, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register OPTIONS route '{}': {}", path, e));
+        self.add_route(Method::OPTIONS, path, Box::new(handler));
+        self
+    }
+
+    /// Register an OPTIONS route with an async handler
+    pub fn options_async<F, Fut>(mut self, path: &str, handler: F) -> Self
+    where
+        F: Fn(RequestData) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ZapResponse> + Send + 'static,
+    {
+        self.add_route(Method::OPTIONS, path, Box::new(AsyncHandler::new(handler)));
         self
     }
 
@@ -207,9 +724,7 @@ impl Zap {
     where
         H: Handler + Send + Sync + 'static,
     {
-        self.router
-            .insert(Method::HEAD, path, Box::new(handler))
-            .unwrap_or_else(|e| panic!("Failed to register HEAD route '{}': {}", path, e));
+        self.add_route(Method::HEAD, path, Box::new(handler));
         self
     }
 
@@ -227,11 +742,7 @@ impl Zap {
             Method::OPTIONS,
             Method::HEAD,
         ] {
-            self.router
-                .insert(method, path, Box::new(handler.clone()))
-                .unwrap_or_else(|e| {
-                    panic!("Failed to register {} route '{}': {}", method, path, e)
-                });
+            self.add_route(method, path, Box::new(handler.clone()));
         }
         self
     }
@@ -253,6 +764,27 @@ impl Zap {
         self
     }
 
+    /// Mount another app's routes, static handlers, and middleware under `prefix`
+    ///
+    /// `other`'s routes and static prefixes are rewritten under `prefix`
+    /// (`/users` mounted at `/api` serves `/api/users`), and its middleware
+    /// runs after this app's, only for requests under `prefix`. Routes that
+    /// conflict with existing ones are reported by [`Zap::validate`] and
+    /// [`Zap::bind`], like any other registration failure. `other`'s config, state, and hooks are
+    /// not carried over.
+    pub fn mount(mut self, prefix: &str, other: Zap) -> Self {
+        for (method, path, handler) in other.router.into_routes() {
+            self.add_route(method, &mount_path(prefix, &path), handler);
+        }
+        for mut handler in other.static_handlers {
+            handler.prefix = mount_path(prefix, &handler.prefix);
+            self.static_handlers.push(handler);
+        }
+        self.middleware = self.middleware.merge(other.middleware.scoped(mount_path(prefix, "")));
+        self.registration_errors.extend(other.registration_errors);
+        self
+    }
+
     /// Register a JSON API endpoint with automatic serialization
     pub fn json_get<F, T>(self, path: &str, handler: F) -> Self
     where
@@ -277,6 +809,41 @@ impl Zap {
         })
     }
 
+    /// Register a JSON POST endpoint with a typed request body
+    ///
+    /// The body is deserialized into `In` before the handler runs; malformed
+    /// JSON is answered with a 400 validation error. The returned `Out` is
+    /// serialized as the JSON response.
+    ///
+    /// ```ignore
+    /// app.json_body_post::<NewUser, User>("/users", |user| create_user(user))
+    /// ```
+    pub fn json_body_post<In, Out>(
+        self,
+        path: &str,
+        handler: impl Fn(In) -> Out + Send + Sync + 'static,
+    ) -> Self
+    where
+        In: DeserializeOwned + Send + 'static,
+        Out: Serialize + Send + 'static,
+    {
+        self.post_async(path, move |req| {
+            let result = serde_json::from_slice::<In>(&req.body).map(&handler);
+            async move {
+                match result {
+                    Ok(output) => Json(output).into(),
+                    Err(e) => {
+                        let error = ZapError::validation_field(format!("Invalid JSON body: {}", e), "body");
+                        ZapResponse::JsonWithStatus(
+                            serde_json::to_value(error.to_error_response()).unwrap_or_default(),
+                            error.status_code(),
+                        )
+                    }
+                }
+            }
+        })
+    }
+
     /// Add CORS middleware with permissive settings
     pub fn cors(self) -> Self {
         self.use_middleware(zap_core::CorsMiddleware::permissive())
@@ -287,6 +854,12 @@ impl Zap {
         self.use_middleware(zap_core::LoggerMiddleware::new())
     }
 
+    /// Add ETags to buffered `200` responses and answer matching
+    /// `If-None-Match` requests with `304 Not Modified`
+    pub fn etag(self) -> Self {
+        self.use_middleware(zap_core::EtagMiddleware::new())
+    }
+
     /// Simple health check endpoint (backwards compatible)
     pub fn health_check(self, path: &str) -> Self {
         self.get(path, || "OK")
@@ -314,9 +887,13 @@ impl Zap {
     }
 
     /// Enhanced readiness probe (Kubernetes-style)
-    /// Returns 200 if the server can handle requests
+    /// Returns 200 if the server can handle requests, and 503 once shutdown
+    /// has started so load balancers stop sending traffic
     pub fn health_ready(self, path: &str) -> Self {
-        let checker = Arc::new(HealthChecker::new(env!("CARGO_PKG_VERSION").to_string()));
+        let checker = Arc::new(
+            HealthChecker::new(env!("CARGO_PKG_VERSION").to_string())
+                .with_draining_flag(self.draining.clone()),
+        );
         self.get_async(path, move |_req| {
             let checker = checker.clone();
             async move {
@@ -343,20 +920,91 @@ impl Zap {
     }
 
     /// Metrics endpoint (basic)
+    ///
+    /// Reports `uptime` in seconds, `memory_usage` as resident memory in
+    /// bytes (`"unavailable"` on platforms where it can't be read), and this
+    /// server's `connections`.
     pub fn metrics(self, path: &str) -> Self {
-        self.get_async(path, |_req| async move {
-            let metrics = serde_json::json!({
-                "status": "healthy",
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "memory_usage": "TODO",
-                "uptime": "TODO"
-            });
-            Json(metrics).into()
+        let connections = self.connections.clone();
+        self.get_async(path, move |_req| {
+            let connections = connections.clone();
+            async move {
+                let memory_usage = match crate::metrics::memory_rss_bytes() {
+                    Some(bytes) => serde_json::json!(bytes),
+                    None => serde_json::json!("unavailable"),
+                };
+                let metrics = serde_json::json!({
+                    "status": "healthy",
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                    "memory_usage": memory_usage,
+                    "uptime": crate::metrics::uptime_secs(),
+                    "connections": connections.stats(),
+                });
+                Json(metrics).into()
+            }
+        })
+    }
+
+    /// Route listing for diagnostics, served as JSON at `path`
+    ///
+    /// Lists each route's method, pattern, and whether a Rust handler or a
+    /// TypeScript proxy serves it, the route count per method, and the static
+    /// file prefixes. Routes registered after this call are not included, so
+    /// register it last.
+    ///
+    /// Only enabled in debug builds, or when `ZAP_DEBUG_ROUTES=1` is set, so
+    /// release builds don't expose their routing table by accident.
+    pub fn debug_routes(self, path: &str) -> Self {
+        if !cfg!(debug_assertions) && std::env::var("ZAP_DEBUG_ROUTES").as_deref() != Ok("1") {
+            debug!("Route diagnostics at {} disabled in release builds", path);
+            return self;
+        }
+
+        let mut routes: Vec<_> = self
+            .router
+            .routes()
+            .into_iter()
+            .map(|(method, pattern, handler)| (pattern, method, handler.is_typescript()))
+            .chain(std::iter::once((path.to_string(), Method::GET, false)))
+            .collect();
+        routes.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| (a.1 as u8).cmp(&(b.1 as u8))));
+
+        let mut counts = std::collections::BTreeMap::<String, usize>::new();
+        for (_, method, _) in &routes {
+            *counts.entry(method.to_string()).or_default() += 1;
+        }
+
+        let listing = serde_json::json!({
+            "routes": routes
+                .iter()
+                .map(|(pattern, method, typescript)| serde_json::json!({
+                    "method": method.to_string(),
+                    "path": pattern,
+                    "handler": if *typescript { "typescript" } else { "rust" },
+                }))
+                .collect::<Vec<_>>(),
+            "counts": counts,
+            "static": self
+                .static_handlers
+                .iter()
+                .map(|handler| serde_json::json!({
+                    "prefix": handler.prefix,
+                    "directory": handler.directory.display().to_string(),
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        self.get_async(path, move |_req| {
+            let listing = listing.clone();
+            async move { Json(listing).into() }
         })
     }
 
     /// Try to bind to a port, cascading through a range if the initial port is in use
-    async fn try_bind_with_cascade(hostname: &str, port: u16, max_attempts: u16) -> Result<(TcpListener, u16), ZapError> {
+    ///
+    /// The socket options come from `config`; its port is the first one tried.
+    fn try_bind_with_cascade(config: &ServerConfig, max_attempts: u16) -> Result<(TcpListener, u16), ZapError> {
+        let (hostname, port) = (&config.hostname, config.port);
         let mut current_port = port;
         let mut last_error = None;
 
@@ -366,7 +1014,7 @@ impl Zap {
                 ZapError::http(format!("Invalid address '{}': {}", addr, e))
             })?;
 
-            match TcpListener::bind(socket_addr).await {
+            match bind_listener(socket_addr, config) {
                 Ok(listener) => {
                     if attempt > 0 {
                         info!("⚠️  Port {} was in use, bound to port {} instead", port, current_port);
@@ -399,79 +1047,94 @@ impl Zap {
     /// - SIGTERM/SIGINT signal handling
     /// - Graceful connection draining
     /// - Proper resource cleanup
-    /// - Port cascading (tries next port if initial port is in use)
+    /// - Port cascading (tries next port if initial port is in use, see [`Zap::port_cascade`])
     ///
     /// For production use, prefer this over `listen()`.
     pub async fn listen_with_shutdown(self, shutdown_config: ShutdownConfig) -> Result<(), ZapError> {
-        let initial_port = self.config.port;
-        let hostname = self.config.hostname.clone();
+        self.listen_with_coordinator(GracefulShutdown::new(shutdown_config)).await
+    }
 
-        // Try to bind with port cascading (attempt up to 10 ports)
-        let (listener, actual_port) = Self::try_bind_with_cascade(&hostname, initial_port, 10).await?;
+    /// Start the server using an existing shutdown coordinator
+    ///
+    /// Lets callers trigger shutdown themselves via [`GracefulShutdown::trigger`].
+    pub async fn listen_with_coordinator(self, shutdown: GracefulShutdown) -> Result<(), ZapError> {
+        self.bind().await?.serve_with_coordinator(shutdown).await
+    }
 
-        let addr = format!("{}:{}", hostname, actual_port);
-        info!("🚀 Zap server listening on http://{}", addr);
-        info!("📊 Router contains {} routes", self.router.total_routes());
-        info!("🛡️  Graceful shutdown enabled (drain timeout: {:?})", shutdown_config.drain_timeout);
+    /// Bind the listening socket without serving yet
+    ///
+    /// The returned [`BoundServer`] reports the port actually bound, which
+    /// differs from the configured one after port cascading or with port 0.
+    pub async fn bind(self) -> Result<BoundServer, ZapError> {
+        if let Err(errors) = self.validate() {
+            for e in &errors {
+                error!("{}", e);
+            }
+            return Err(errors.into_iter().next().expect("validate reports at least one error"));
+        }
 
-        let server = Arc::new(self);
-        let shutdown = GracefulShutdown::new(shutdown_config);
+        // Try to bind with port cascading (attempt up to 10 ports) unless disabled
+        let max_attempts = if self.config.port_cascade { 10 } else { 1 };
+        let (listener, _) = Self::try_bind_with_cascade(&self.config, max_attempts)?;
+        let local_addr = listener.local_addr()?;
 
-        loop {
-            tokio::select! {
-                // Wait for shutdown signal
-                _ = shutdown.wait() => {
-                    info!("🛑 Shutdown signal received, stopping new connections");
-                    break;
-                }
-                // Accept new connections
-                result = listener.accept() => {
-                    match result {
-                        Ok((stream, remote_addr)) => {
-                            let server = server.clone();
-                            let shutdown = shutdown.clone();
-
-                            tokio::spawn(async move {
-                                // Track this connection
-                                let _guard = shutdown.connection_guard();
-
-                                let io = TokioIo::new(stream);
+        Ok(BoundServer {
+            server: self,
+            listener,
+            local_addr,
+        })
+    }
 
-                                let service = service_fn(move |req| {
-                                    let server = server.clone();
-                                    async move {
-                                        server.handle_request(req, remote_addr).await
-                                    }
-                                });
+    /// Run a request through the full pipeline without binding a socket
+    ///
+    /// Rewrites, static files, routing, middleware and the handler all run as
+    /// for a real connection from `127.0.0.1`. The final response, including
+    /// middleware changes, is returned as [`ZapResponse::Custom`] with the
+    /// body fully read. Available in tests and with the `testing` feature.
+    #[cfg(any(test, feature = "testing"))]
+    pub async fn test_request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: impl Into<bytes::Bytes>,
+    ) -> ZapResponse {
+        use http_body_util::BodyExt;
 
-                                if let Err(err) = http1::Builder::new()
-                                    .serve_connection(io, service)
-                                    .await
-                                {
-                                    debug!("Connection closed: {:?}", err);
-                                }
-                            });
-                        }
-                        Err(e) => {
-                            error!("Failed to accept connection: {}", e);
-                        }
-                    }
-                }
-            }
+        let mut builder = HyperRequest::builder().method(method.as_str()).uri(path);
+        if !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("host")) {
+            builder = builder.header("Host", "localhost");
         }
-
-        // Drain in-flight connections
-        info!("⏳ Draining active connections...");
-        let drained = shutdown.drain_connections().await;
-
-        if drained {
-            info!("✅ Server shutdown complete");
-        } else {
-            warn!("⚠️  Server shutdown with {} active connection(s) remaining",
-                  shutdown.active_connection_count());
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
         }
+        let request = match builder.body(http_body_util::Full::new(body.into())) {
+            Ok(request) => request,
+            Err(e) => return ZapResponse::Custom(zap_core::Response::bad_request(format!("Invalid test request: {}", e))),
+        };
 
-        Ok(())
+        let remote_addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let response = match self.handle_request(request, remote_addr).await {
+            Ok(response) => response,
+            Err(e) => return ZapResponse::Custom(zap_core::Response::internal_server_error(e.to_string())),
+        };
+
+        let (parts, body) = response.into_parts();
+        let body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(e) => return ZapResponse::Custom(zap_core::Response::internal_server_error(e.to_string())),
+        };
+        let headers = parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        ZapResponse::Custom(zap_core::Response {
+            status: StatusCode(parts.status.as_u16()),
+            headers,
+            body: zap_core::ResponseBody::Bytes(body.to_vec()),
+        })
     }
 
     /// Start the server and listen for connections (without graceful shutdown)
@@ -482,86 +1145,414 @@ impl Zap {
     }
 
     /// Handle an individual HTTP request
-    async fn handle_request(
+    ///
+    /// Errors become problem+json when the client accepts JSON, plain text
+    /// otherwise.
+    async fn handle_request<B>(
         &self,
-        hyper_req: HyperRequest<Incoming>,
+        hyper_req: HyperRequest<B>,
         remote_addr: SocketAddr,
-    ) -> Result<HyperResponse<String>, hyper::Error> {
-        let response = match self.process_request(hyper_req, remote_addr).await {
-            Ok(zap_response) => zap_response.to_hyper_response(),
-            Err(error) => {
-                error!("Request processing error: {}", error);
-                hyper::Response::builder()
-                    .status(500)
-                    .body("Internal Server Error".to_string())
-                    .unwrap()
-            }
+    ) -> Result<HyperResponse<ZapBody>, hyper::Error>
+    where
+        B: hyper::body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let wants_json = hyper_req
+            .headers()
+            .get(hyper::header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(accepts_json);
+
+        if self.response_hooks.is_empty() {
+            return Ok(self.respond(hyper_req, remote_addr, wants_json).await);
+        }
+
+        // Count request body bytes as they are read, without buffering twice
+        let started = Instant::now();
+        let method = hyper_req.method().to_string();
+        let path = hyper_req.uri().path().to_string();
+        let request_bytes = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&request_bytes);
+        let hyper_req = hyper_req.map(|body| {
+            use http_body_util::BodyExt;
+            use hyper::body::Buf;
+
+            body.map_frame(move |frame| {
+                if let Some(data) = frame.data_ref() {
+                    counter.fetch_add(data.remaining() as u64, Ordering::Relaxed);
+                }
+                frame
+            })
+        });
+
+        let response = self.respond(hyper_req, remote_addr, wants_json).await;
+
+        let info = ResponseInfo {
+            method,
+            path,
+            status: response.status().as_u16(),
+            request_bytes: request_bytes.load(Ordering::Relaxed),
+            response_bytes: ResponseSize::of(&response).map(|size| size.0),
+            duration: started.elapsed(),
         };
+        for hook in &self.response_hooks {
+            hook(&info);
+        }
 
         Ok(response)
     }
 
+    /// Run the pipeline, rendering errors for the client
+    async fn respond<B>(
+        &self,
+        hyper_req: HyperRequest<B>,
+        remote_addr: SocketAddr,
+        wants_json: bool,
+    ) -> HyperResponse<ZapBody>
+    where
+        B: hyper::body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        match self.process_request(hyper_req, remote_addr).await {
+            Ok(response) => response,
+            Err(error) => {
+                error!("Request processing error: {}", error);
+                error_response(&error, wants_json)
+            }
+        }
+    }
+
     /// Process the request through our complete pipeline
-    async fn process_request(
+    async fn process_request<B>(
         &self,
-        hyper_req: HyperRequest<Incoming>,
-        _remote_addr: SocketAddr,
-    ) -> Result<ZapResponse, ZapError> {
-        use http_body_util::BodyExt;
+        mut hyper_req: HyperRequest<B>,
+        remote_addr: SocketAddr,
+    ) -> Result<HyperResponse<ZapBody>, ZapError>
+    where
+        B: hyper::body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let received_at = Instant::now();
+
+        // `OPTIONS *` asks about the server as a whole rather than a resource
+        if hyper_req.method() == hyper::Method::OPTIONS && hyper_req.uri() == "*" {
+            return Ok(self.server_options_response().to_hyper_response());
+        }
+
+        // Reject targets that cannot name a resource before anything looks at the path
+        check_request_path(hyper_req.uri().path())?;
+
+        // Step 0: Pre-routing rewrite hook (redirects skip reading the body)
+        let rewritten_path = match self.rewrite.and_then(|hook| hook(hyper_req.uri().path())) {
+            Some(RewriteAction::Redirect { location, status }) => {
+                return Ok(RewriteAction::redirect_response(&location, status).to_hyper_response());
+            }
+            Some(RewriteAction::Rewrite(path)) => Some(path),
+            None => None,
+        };
+
+        // Protocol upgrades are dispatched by the requested token once the request
+        // has been routed and passed the middleware chain
+        let mut upgrade = None;
+        if let Some(protocols) = requested_upgrades(hyper_req.headers()) {
+            let handler = protocols.iter().find_map(|protocol| {
+                let name = protocol.split('/').next().unwrap_or(protocol);
+                self.upgrade_handlers
+                    .get(protocol)
+                    .or_else(|| self.upgrade_handlers.get(name))
+                    .map(|handler| (protocol.clone(), *handler))
+            });
+            match handler {
+                Some((protocol, handler)) => {
+                    upgrade = Some((protocol, handler, hyper::upgrade::on(&mut hyper_req)));
+                }
+                None if protocols.iter().any(|protocol| protocol == "websocket") => {}
+                None => {
+                    return Err(ZapError::not_implemented(format!(
+                        "Unsupported upgrade protocol: {}",
+                        protocols.join(", ")
+                    )));
+                }
+            }
+        }
 
-        // Step 1: Convert Hyper request to raw bytes
+        // Step 1: Split the Hyper request into parts
         let (parts, body) = hyper_req.into_parts();
 
-        // Collect the body bytes
-        let body_bytes = body.collect().await
-            .map_err(|e| ZapError::http(format!("Failed to read request body: {}", e)))?
-            .to_bytes()
-            .to_vec();
+        // Convert method, applying a header/query method override if enabled
+        let overridable = self.config.method_override && parts.method == hyper::Method::POST;
+        let mut http_method = parts.method.clone();
+        if overridable {
+            if let Some(overridden) = method_override(&parts.headers, parts.uri.query(), None) {
+                http_method = overridden;
+            }
+        }
+        let mut method = convert_method(&http_method)?;
+
+        // Collect the body bytes, enforcing the route's limit (or the global one);
+        // streaming routes get the body as it arrives instead
+        let (limit, streaming) = self.body_handling_for(method, rewritten_path.as_deref().unwrap_or(parts.uri.path()));
+        let (body_bytes, body_stream) = if streaming {
+            (bytes::Bytes::new(), Some(stream_limited(body, limit)))
+        } else {
+            (collect_limited(body, limit).await?, None)
+        };
 
-        // Convert method
-        let method = convert_method(&parts.method)?;
+        // A `_method` form field can only be read once the body is in
+        if overridable && http_method == hyper::Method::POST {
+            if let Some(overridden) = method_override(&parts.headers, None, Some(&body_bytes)) {
+                method = convert_method(&overridden)?;
+            }
+        }
 
-        // Step 2: Reconstruct HTTP request bytes for our parser  
-        let mut request_bytes = Vec::new();
-        request_bytes.extend_from_slice(format!("{} {} {:?}\r\n", parts.method, parts.uri, parts.version).as_bytes());
-        
-        for (name, value) in &parts.headers {
-            request_bytes.extend_from_slice(name.as_str().as_bytes());
-            request_bytes.extend_from_slice(b": ");
-            request_bytes.extend_from_slice(value.as_bytes());
-            request_bytes.extend_from_slice(b"\r\n");
-        }
-        request_bytes.extend_from_slice(b"\r\n");
-        request_bytes.extend_from_slice(&body_bytes);
-
-        // Step 3: Parse using our fast HTTP parser
-        let parser = HttpParser::new();
-        let parsed = parser.parse_request(&request_bytes)
-            .map_err(|e| ZapError::http(format!("HTTP parsing failed: {:?}", e)))?;
-
-        // Step 4: Check for static file handlers first
-        let path_for_routing = parsed.path.split('?').next().unwrap_or(parsed.path);
+        // Step 2: Build the parsed request straight from hyper's parts
+        // (raw sockets and benchmarks use `HttpParser::parse_request` instead)
+        let parser = HttpParser::with_limits(self.config.max_header_size, self.config.max_headers)
+            .max_header_value_size(self.config.max_header_value_size);
+        let path = parts.uri.path_and_query().map_or("/", |path| path.as_str());
+        let headers = parts.headers.iter().map(|(name, value)| (name.as_str(), value.as_bytes()));
+        let parsed = parser
+            .parse_parts(method, path, http_version(parts.version), headers, body_bytes.len())
+            .map_err(|e| match e {
+                ParseError::TooManyHeaders | ParseError::HeadersTooLarge | ParseError::HeaderValueTooLong => {
+                    ZapError::headers_too_large(e.to_string())
+                }
+                _ => ZapError::http(format!("HTTP parsing failed: {:?}", e)),
+            })?;
+
+        // HTTP/1.0 keep-alive is opt-in, HTTP/1.1 is opt-out
+        let keep_alive = parsed.keep_alive();
+        let http_1_0 = parsed.is_http_1_0();
+
+        // Step 3: Check for static file handlers first
+        let path_for_routing = match rewritten_path.as_deref() {
+            Some(path) => path,
+            None => parsed.path.split('?').next().unwrap_or(parsed.path),
+        };
         
-        // Check static handlers
-        if let Some(static_response) = handle_static_files(&self.static_handlers, path_for_routing).await? {
-            return Ok(static_response);
+        // Check static handlers (request headers drive conditional and encoding negotiation)
+        if !self.static_handlers.is_empty() {
+            let static_headers: HashMap<String, String> = parsed.headers.iter()
+                .map(|(k, v)| (k.to_ascii_lowercase(), v.to_string()))
+                .collect();
+            if let Some(static_response) = handle_static_files_with_method(&self.static_handlers, method, path_for_routing, &static_headers).await? {
+                let mut response = static_response.to_hyper_response();
+                set_connection_header(&mut response, keep_alive, http_1_0);
+                return Ok(response);
+            }
+        }
+
+        // Step 4: Route the request using our fast router
+        let body_start = &body_bytes[parsed.body_offset..];
+        let (handler, route_params) = match self.router.match_request(method, path_for_routing) {
+            MatchResult::Matched { handler, params } => (handler, params),
+            MatchResult::MethodNotAllowed { allowed } => {
+                let error = ZapError::method_not_allowed(
+                    method.as_str(),
+                    path_for_routing,
+                    allowed.iter().map(|method| method.as_str()),
+                );
+                // Unrouted preflights can still be answered by middleware (e.g. CORS)
+                if method == Method::OPTIONS {
+                    if let Some(mut response) = self.preflight(&parsed, body_start, allowed).await? {
+                        set_connection_header(&mut response, keep_alive, http_1_0);
+                        return Ok(response);
+                    }
+                }
+                return Err(error);
+            }
+            MatchResult::NotFound => return Err(ZapError::route_not_found(path_for_routing)),
+        };
+
+        // Step 5: Create Request object
+        let client_ip = resolve_client_ip(
+            remote_addr.ip(),
+            parsed.headers.get(FORWARDED_FOR_HEADER),
+            &self.config.trusted_proxies,
+        );
+        let request = Request::new(&parsed, body_start, route_params)
+            .with_client_ip(client_ip)
+            .with_state(self.state.clone());
+
+        // Step 6: Run global middleware; an early response skips the handler
+        let mut middleware_ctx = None;
+        if !self.middleware.is_empty() {
+            let mut ctx = MiddlewareContext::new(&parsed, body_start);
+            if method == Method::OPTIONS {
+                ctx.extensions.insert(AllowedMethods(self.router.allowed_methods(path_for_routing)));
+            }
+            match self.middleware.run(ctx).await? {
+                ChainOutcome::Response(response) => {
+                    let mut response = middleware_response(response, None);
+                    set_connection_header(&mut response, keep_alive, http_1_0);
+                    return Ok(response);
+                }
+                ChainOutcome::Continue(ctx) => middleware_ctx = Some(ctx),
+            }
         }
 
-        // Step 5: Route the request using our fast router
-        let (handler, route_params) = self.router.at(method, path_for_routing)
-            .ok_or_else(|| ZapError::route_not_found(path_for_routing))?;
+        // The upgrade passed routing and middleware (auth, rate limits); hand it over
+        if let Some((protocol, handler, on_upgrade)) = upgrade {
+            let connection = PendingUpgrade {
+                protocol,
+                path: path_for_routing.to_string(),
+                headers: parts.headers.clone(),
+                on_upgrade,
+            };
+            return Ok(start_upgrade(connection, handler, self.shutdown.as_ref()));
+        }
 
-        // Step 6: Create Request object
-        let body_start = &request_bytes[parsed.body_offset..];
-        let request = Request::new(&parsed, body_start, route_params);
+        // Hand middleware results to the handler; the session is saved afterwards
+        let request = match &middleware_ctx {
+            Some(ctx) => with_middleware_extensions(request, &ctx.extensions),
+            None => request,
+        };
 
-        // Step 7: Execute the handler (middleware is handled separately in a real implementation)
-        let response = handler.handle(request).await
-            .map_err(|e| ZapError::handler(format!("Handler execution failed: {}", e)))?;
+        // Step 7: Execute the handler within the caller's deadline, which may shorten
+        // but never extend the server's request timeout
+        let timeout = requested_timeout(parsed.headers.iter())
+            .map(|t| t.min(self.config.request_timeout))
+            .unwrap_or(self.config.request_timeout);
+        // Everything the handler logs carries the request ID
+        let span = tracing::info_span!(
+            "request",
+            method = %method,
+            path = %path_for_routing,
+            request_id = tracing::field::Empty,
+        );
+        if let Some(request_id) = request.request_id() {
+            span.record("request_id", request_id);
+        }
+        // A panicking handler is answered with a 500 instead of tearing down the connection
+        let execution = match body_stream {
+            Some(body) => handler.handle_stream(RequestData::from_request(&request), body),
+            None => handler.handle(request),
+        };
+        let handled = tokio::time::timeout(
+            timeout.saturating_sub(received_at.elapsed()),
+            std::panic::AssertUnwindSafe(execution).catch_unwind(),
+        )
+        .instrument(span.clone())
+        .await
+        .map_err(|_| {
+            let timeout_ms = timeout.as_millis() as u64;
+            ZapError::timeout(format!("Request exceeded its {}ms deadline", timeout_ms), timeout_ms)
+        })?;
+        let response = match handled {
+            Ok(result) => result?,
+            Err(payload) => {
+                span.in_scope(|| {
+                    error!("Handler for {} {} panicked: {}", method, path_for_routing, panic_message(payload.as_ref()))
+                });
+                return Err(ZapError::handler("Handler panicked"));
+            }
+        }
+        .to_hyper_response_with(&self.config.json);
 
+        // Step 8: Post-response middleware phase
+        let mut response = match middleware_ctx {
+            Some(ctx) => self.finish_with_middleware(&ctx, response).await,
+            None => response,
+        };
+        set_connection_header(&mut response, keep_alive, http_1_0);
         Ok(response)
     }
 
+    /// `204 No Content` listing every method any route handles, plus `OPTIONS`
+    fn server_options_response(&self) -> ZapResponse {
+        let mut methods: Vec<Method> = self.router.methods().collect();
+        if !methods.contains(&Method::OPTIONS) {
+            methods.push(Method::OPTIONS);
+        }
+        methods.sort_by_key(|method| *method as u8);
+        let allow = methods.iter().map(|method| method.as_str()).collect::<Vec<_>>().join(", ");
+
+        ZapResponse::Custom(zap_core::Response::with_status(StatusCode(204)).header("Allow", allow))
+    }
+
+    /// Let middleware answer an `OPTIONS` request for a path that has routes
+    /// for other methods (`allowed`), without requiring an explicit OPTIONS route
+    async fn preflight(
+        &self,
+        parsed: &ParsedRequest<'_>,
+        body: &[u8],
+        allowed: Vec<Method>,
+    ) -> ZapResult<Option<HyperResponse<ZapBody>>> {
+        if self.middleware.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ctx = MiddlewareContext::new(parsed, body);
+        ctx.extensions.insert(AllowedMethods(allowed));
+        match self.middleware.run(ctx).await? {
+            ChainOutcome::Response(response) => Ok(Some(middleware_response(response, None))),
+            ChainOutcome::Continue(_) => Ok(None),
+        }
+    }
+
+    /// Merge headers added by middleware into the handler's response and run
+    /// the `after` hooks
+    ///
+    /// Streamed bodies (e.g. files) are not buffered: hooks see an empty body
+    /// and the stream is kept unless a hook sets a new body.
+    async fn finish_with_middleware(
+        &self,
+        ctx: &MiddlewareContext<'_>,
+        response: HyperResponse<ZapBody>,
+    ) -> HyperResponse<ZapBody> {
+        use http_body_util::BodyExt;
+        use hyper::body::Body;
+
+        let (parts, body) = response.into_parts();
+        let (body_bytes, stream) = if body.size_hint().exact().is_some() {
+            match body.collect().await {
+                Ok(collected) => (collected.to_bytes().to_vec(), None),
+                Err(e) => {
+                    error!("Failed to buffer response body for middleware: {}", e);
+                    (Vec::new(), None)
+                }
+            }
+        } else {
+            (Vec::new(), Some(body))
+        };
+
+        let mut headers: Vec<(String, String)> = parts
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned())
+            })
+            .collect();
+        headers.extend(ctx.response.clone().finish().headers);
+
+        let response = self.middleware.after(
+            ctx,
+            MiddlewareResponse {
+                status: parts.status.as_u16(),
+                headers,
+                body: body_bytes,
+            },
+        );
+
+        let stream = stream.filter(|_| response.body.is_empty());
+        middleware_response(response, stream)
+    }
+
+    /// Body size limit for the route matching `method` and `path`, and
+    /// whether its handler streams the body
+    fn body_handling_for(&self, method: Method, path: &str) -> (usize, bool) {
+        match self.router.at(method, path) {
+            Some((handler, _)) => (
+                handler.body_limit().unwrap_or(self.config.max_request_body_size),
+                handler.streams_body(),
+            ),
+            None => (self.config.max_request_body_size, false),
+        }
+    }
+
     /// Get router reference for testing
     pub fn router(&self) -> &Router<BoxedHandler> {
         &self.router
@@ -590,10 +1581,22 @@ impl Zap {
                 .hostname(config.hostname.clone())
                 .max_request_body_size(config.max_request_body_size)
                 .request_timeout(Duration::from_secs(config.request_timeout_secs))
-                .keep_alive_timeout(Duration::from_secs(config.keepalive_timeout_secs)),
+                .keep_alive_timeout(Duration::from_secs(config.keepalive_timeout_secs))
+                .trusted_proxies(config.trusted_proxies.clone()),
             router: Router::new(),
             middleware: MiddlewareChain::new(),
             static_handlers: Vec::new(),
+            rewrite: None,
+            state: Arc::new(AppState::new()),
+            shutdown_hooks: Vec::new(),
+            response_hooks: Vec::new(),
+            upgrade_handlers: HashMap::new(),
+            draining: Arc::new(AtomicBool::new(false)),
+            connections: Arc::default(),
+            registration_errors: Vec::new(),
+            ipc_socket_path: Some(PathBuf::from(&config.ipc_socket_path)),
+            rpc_shutdown: None,
+            shutdown: None,
         };
 
         // Add middleware
@@ -607,6 +1610,17 @@ impl Zap {
             server = server.logging();
         }
 
+        // Warm the IPC pool so the first TypeScript requests skip connection setup
+        let ipc_pool = if config.routes.iter().any(|route| route.is_typescript) {
+            let pool = init_global_pool(config.ipc_socket_path.clone())?;
+            let size = pool.config().size;
+            let warm = pool.warm_up(size).await;
+            info!("✓ IPC connection pool warmed: {}/{} connections", warm, size);
+            Some(pool)
+        } else {
+            None
+        };
+
         // Register all routes from configuration
         for route_cfg in &config.routes {
             let method = route_cfg.method.to_uppercase();
@@ -628,11 +1642,19 @@ impl Zap {
 
             if route_cfg.is_typescript {
                 // TypeScript handler - use proxy
-                let proxy = ProxyHandler::with_timeout(
-                    route_cfg.handler_id.clone(),
-                    config.ipc_socket_path.clone(),
-                    config.request_timeout_secs,
-                );
+                let proxy = match &ipc_pool {
+                    Some(pool) => ProxyHandler::with_timeout_and_pool(
+                        route_cfg.handler_id.clone(),
+                        config.ipc_socket_path.clone(),
+                        config.request_timeout_secs,
+                        pool.clone(),
+                    ),
+                    None => ProxyHandler::with_timeout(
+                        route_cfg.handler_id.clone(),
+                        config.ipc_socket_path.clone(),
+                        config.request_timeout_secs,
+                    ),
+                };
                 server.router.insert(method_enum, &route_cfg.path, Box::new(proxy))
                     .map_err(|e| ZapError::config(format!(
                         "Failed to register route {}: {}",
@@ -668,17 +1690,29 @@ impl Zap {
 
             // Build dispatch function that forwards to Splice
             let splice_client = std::sync::Arc::new(tokio::sync::RwLock::new(splice_client));
-            std::sync::Arc::new(move |function_name: String, params: serde_json::Value, _context: Option<splice::protocol::RequestContext>| {
+            std::sync::Arc::new(move |function_name: String, params: serde_json::Value, context: Option<splice::protocol::RequestContext>| {
                 let splice_client = splice_client.clone();
                 let function_name = function_name.clone();
                 let params = params.clone();
 
+                // Forward the caller's deadline, capped at the default so a client can't
+                // hold a worker longer; the rest of the context is handled by Splice
+                let deadline_ms = context
+                    .as_ref()
+                    .and_then(|ctx| requested_timeout(ctx.headers.iter().map(|(k, v)| (k.as_str(), v.as_str()))))
+                    .map_or(DEFAULT_SPLICE_DEADLINE_MS, |t| to_deadline_ms(t).min(DEFAULT_SPLICE_DEADLINE_MS));
+                let context = context.unwrap_or(splice::protocol::RequestContext {
+                    trace_id: 0,
+                    span_id: 0,
+                    headers: vec![],
+                    auth: None,
+                });
+
                 // Spawn async task and block on result (required by RpcDispatchFn signature)
-                // Note: Context is ignored here as Splice handles context internally
                 tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async move {
                         splice_client.read().await
-                            .invoke(function_name, params)
+                            .invoke_with_context(function_name, params, deadline_ms, context)
                             .await
                     })
                 })
@@ -697,8 +1731,9 @@ impl Zap {
             dispatch_fn,
         );
 
-        rpc_server.start().await
+        let rpc_shutdown = rpc_server.start().await
             .map_err(|e| ZapError::config(format!("Failed to start RPC server: {}", e)))?;
+        server.rpc_shutdown = Some(rpc_shutdown);
 
         info!("✅ RPC server started on {}.rpc", config.ipc_socket_path);
 
@@ -731,4 +1766,1867 @@ impl Default for Zap {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+}
+
+/// A [`Zap`] server whose socket is bound but not yet accepting connections
+///
+/// Obtained from [`Zap::bind`]; call one of the `serve` methods to start.
+pub struct BoundServer {
+    server: Zap,
+    listener: TcpListener,
+    local_addr: SocketAddr,
+}
+
+impl BoundServer {
+    /// Address the server is bound to, including the actual port
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Serve with production shutdown settings (SIGTERM/SIGINT handling)
+    pub async fn serve(self) -> Result<(), ZapError> {
+        self.serve_with_shutdown(ShutdownConfig::production()).await
+    }
+
+    /// Serve with the given shutdown settings
+    pub async fn serve_with_shutdown(self, shutdown_config: ShutdownConfig) -> Result<(), ZapError> {
+        self.serve_with_coordinator(GracefulShutdown::new(shutdown_config)).await
+    }
+
+    /// Serve until the given shutdown coordinator is triggered
+    pub async fn serve_with_coordinator(self, shutdown: GracefulShutdown) -> Result<(), ZapError> {
+        let BoundServer { mut server, listener, local_addr } = self;
+        server.shutdown = Some(shutdown.clone());
+        crate::metrics::record_start();
+
+        info!("🚀 Zap server listening on http://{}", local_addr);
+        info!("📊 Router contains {} routes", server.router.total_routes());
+        info!("🛡️  Graceful shutdown enabled (drain timeout: {:?})", shutdown.config().drain_timeout);
+
+        let server = Arc::new(server);
+
+        loop {
+            tokio::select! {
+                // Wait for shutdown signal
+                _ = shutdown.wait() => {
+                    // Fail readiness first so load balancers stop routing here
+                    // before the listener goes away
+                    server.draining.store(true, Ordering::SeqCst);
+                    info!("🛑 Shutdown signal received, stopping new connections");
+                    break;
+                }
+                // Accept new connections
+                result = listener.accept() => {
+                    match result {
+                        Ok((stream, remote_addr)) => {
+                            configure_stream(&stream, &server.config);
+                            let server = server.clone();
+                            let shutdown = shutdown.clone();
+
+                            let connection_metrics = server.connections.track();
+
+                            tokio::spawn(async move {
+                                // Track this connection
+                                let _guard = shutdown.connection_guard();
+                                let _connection_metrics = connection_metrics;
+
+                                let io = TokioIo::new(stream);
+
+                                // Requests served on this connection so far
+                                let served = AtomicUsize::new(0);
+                                let max_requests = server.config.max_requests_per_connection;
+                                let max_headers = server.config.max_headers;
+
+                                let service = service_fn(move |req| {
+                                    let server = server.clone();
+                                    let count = served.fetch_add(1, Ordering::Relaxed) + 1;
+                                    async move {
+                                        let mut response = server.handle_request(req, remote_addr).await?;
+                                        // hyper closes the connection after a `Connection: close` response
+                                        if max_requests.is_some_and(|max| count >= max) {
+                                            response.headers_mut().insert(
+                                                hyper::header::CONNECTION,
+                                                hyper::header::HeaderValue::from_static("close"),
+                                            );
+                                        }
+                                        Ok::<_, hyper::Error>(response)
+                                    }
+                                });
+
+                                let mut builder = http1::Builder::new();
+                                // hyper rejects more than 100 headers on its own; let ours decide
+                                if max_headers > 100 {
+                                    builder.max_headers(max_headers);
+                                }
+
+                                // Dropping the connection at the hard shutdown deadline
+                                // also drops any in-flight handler
+                                tokio::select! {
+                                    result = builder.serve_connection(io, service).with_upgrades() => {
+                                        if let Err(err) = result {
+                                            debug!("Connection closed: {:?}", err);
+                                        }
+                                    }
+                                    _ = shutdown.force_closed() => {
+                                        debug!("Connection from {} force-closed", remote_addr);
+                                    }
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            error!("Failed to accept connection: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Drain in-flight connections
+        info!("⏳ Draining active connections...");
+        let drained = shutdown.drain_connections().await;
+
+        // Stop the RPC server too; it drains its own in-flight calls
+        if let Some(rpc) = &server.rpc_shutdown {
+            info!("⏳ Stopping RPC server...");
+            rpc.shutdown().await;
+        }
+
+        // Run user cleanup hooks once connections are gone
+        shutdown.run_cleanup(&server.shutdown_hooks).await;
+
+        if drained {
+            info!("✅ Server shutdown complete");
+        } else {
+            warn!("⚠️  Server shutdown with {} active connection(s) remaining",
+                  shutdown.active_connection_count());
+        }
+
+        Ok(())
+    }
+}
+
+/// Bind a listening socket with the configured socket options
+fn bind_listener(addr: SocketAddr, config: &ServerConfig) -> std::io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.set_reuseaddr(config.reuse_address)?;
+    socket.bind(addr)?;
+    socket.listen(config.backlog)
+}
+
+/// Apply per-connection socket options to an accepted stream
+fn configure_stream(stream: &TcpStream, config: &ServerConfig) {
+    if config.tcp_nodelay {
+        if let Err(e) = stream.set_nodelay(true) {
+            debug!("Failed to set TCP_NODELAY: {}", e);
+        }
+    }
+}
+
+/// Convert a response produced by middleware into a hyper response
+///
+/// `stream` replaces the buffered body when the original response was streamed.
+fn middleware_response(response: MiddlewareResponse, stream: Option<ZapBody>) -> HyperResponse<ZapBody> {
+    let mut builder = hyper::Response::builder().status(response.status);
+    // The handler and middleware may each add `Vary`; send them as one header
+    let mut vary = zap_core::Vary::new();
+    for (name, value) in &response.headers {
+        // Buffered bodies may have been rewritten; let hyper compute the length
+        if stream.is_none() && name.eq_ignore_ascii_case("content-length") {
+            continue;
+        }
+        if name.eq_ignore_ascii_case("vary") {
+            vary.add(value);
+            continue;
+        }
+        match (
+            hyper::header::HeaderName::from_bytes(name.as_bytes()),
+            hyper::header::HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => builder = builder.header(name, value),
+            _ => warn!("Dropping invalid middleware header: {}", name),
+        }
+    }
+    if let Ok(value) = hyper::header::HeaderValue::from_str(&vary.to_string()) {
+        if !vary.is_empty() {
+            builder = builder.header(hyper::header::VARY, value);
+        }
+    }
+
+    let body = stream.unwrap_or_else(|| full_body(response.body));
+    builder.body(body).unwrap_or_else(|_| {
+        hyper::Response::builder()
+            .status(500)
+            .body(full_body("Internal Server Error"))
+            .unwrap()
+    })
+}
+
+/// Check that a request path can be routed
+///
+/// Origin-form targets always start with `/`; an authority-form target
+/// (`example.com:443`) or `*` outside `OPTIONS` leaves an empty or relative
+/// path, and NUL or whitespace only comes from a broken client.
+fn check_request_path(path: &str) -> ZapResult<()> {
+    if path.trim().is_empty() {
+        return Err(ZapError::bad_request("Request target has an empty path"));
+    }
+    if !path.starts_with('/') {
+        return Err(ZapError::bad_request(format!("Request path must start with '/': {}", path)));
+    }
+    if path.bytes().any(|b| b == 0 || b.is_ascii_whitespace()) {
+        return Err(ZapError::bad_request("Request path contains NUL or whitespace"));
+    }
+    Ok(())
+}
+
+/// Signal the connection's keep-alive state in the response
+///
+/// Clients that asked to close get `Connection: close`; HTTP/1.0 clients
+/// that opted into keep-alive get `Connection: keep-alive` unless the
+/// handler already chose a Connection header.
+fn set_connection_header(response: &mut HyperResponse<ZapBody>, keep_alive: bool, http_1_0: bool) {
+    use hyper::header::{HeaderValue, CONNECTION};
+
+    if !keep_alive {
+        response.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+    } else if http_1_0 {
+        response
+            .headers_mut()
+            .entry(CONNECTION)
+            .or_insert(HeaderValue::from_static("keep-alive"));
+    }
+}
+
+/// Attach what middleware left for handlers (session, verified JWT claims, request ID)
+fn with_middleware_extensions<'a>(mut request: Request<'a>, extensions: &Extensions) -> Request<'a> {
+    if let Some(session) = extensions.get::<Session>() {
+        request = request.with_session(session.clone());
+    }
+    if let Some(claims) = extensions.get::<JwtClaims>() {
+        request = request.with_claims(claims.clone());
+    }
+    if let Some(RequestId(id)) = extensions.get::<RequestId>() {
+        request = request.with_request_id(id.as_str());
+    }
+    request
+}
+
+/// Protocols named by the `Upgrade` header, lowercased, when `Connection`
+/// includes the `upgrade` token
+fn requested_upgrades(headers: &hyper::HeaderMap) -> Option<Vec<String>> {
+    let wants_upgrade = headers
+        .get_all(hyper::header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+    if !wants_upgrade {
+        return None;
+    }
+
+    let protocols: Vec<String> = headers
+        .get(hyper::header::UPGRADE)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .map(|protocol| protocol.trim().to_ascii_lowercase())
+        .filter(|protocol| !protocol.is_empty())
+        .collect();
+    (!protocols.is_empty()).then_some(protocols)
+}
+
+/// An accepted upgrade waiting for hyper to release the connection
+struct PendingUpgrade {
+    protocol: String,
+    path: String,
+    headers: hyper::HeaderMap,
+    on_upgrade: hyper::upgrade::OnUpgrade,
+}
+
+/// Answer `101 Switching Protocols` and hand the connection to `handler`
+/// once hyper has flushed the response
+///
+/// The upgraded connection counts as an active connection of `shutdown`, so
+/// graceful shutdown waits for the handler and aborts it at the hard deadline.
+fn start_upgrade(upgrade: PendingUpgrade, handler: UpgradeHandler, shutdown: Option<&GracefulShutdown>) -> HyperResponse<ZapBody> {
+    let PendingUpgrade { protocol, path, headers, on_upgrade } = upgrade;
+    let response_protocol = protocol.clone();
+    let shutdown = shutdown.cloned();
+
+    tokio::spawn(async move {
+        let _guard = shutdown.as_ref().map(GracefulShutdown::connection_guard);
+        let upgraded = match on_upgrade.await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                debug!("Upgrade to {} failed: {}", protocol, e);
+                return;
+            }
+        };
+
+        let serve = handler(UpgradedConnection { protocol, path, headers, io: TokioIo::new(upgraded) });
+        match shutdown {
+            Some(shutdown) => {
+                tokio::select! {
+                    _ = serve => {}
+                    _ = shutdown.force_closed() => debug!("Upgraded connection force-closed"),
+                }
+            }
+            None => serve.await,
+        }
+    });
+
+    ZapResponse::Custom(
+        zap_core::Response::with_status(StatusCode(101))
+            .header("Upgrade", response_protocol)
+            .header("Connection", "upgrade"),
+    )
+    .to_hyper_response()
+}
+
+/// Render an error as an `application/problem+json` response, or as its
+/// plain-text status reason
+///
+/// A 405 lists the methods the path does handle in `Allow`.
+fn error_response(error: &ZapError, json: bool) -> HyperResponse<ZapBody> {
+    let status = hyper::StatusCode::from_u16(error.status_code())
+        .unwrap_or(hyper::StatusCode::INTERNAL_SERVER_ERROR);
+    let mut builder = hyper::Response::builder().status(status);
+    if let ZapError::MethodNotAllowed { allowed, .. } = error {
+        builder = builder.header(hyper::header::ALLOW, allowed.join(", "));
+    }
+
+    if json {
+        builder
+            .header(hyper::header::CONTENT_TYPE, PROBLEM_JSON_CONTENT_TYPE)
+            .body(full_body(error.to_problem_json().to_string()))
+            .unwrap()
+    } else {
+        builder
+            .header(hyper::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(full_body(status.canonical_reason().unwrap_or("Internal Server Error")))
+            .unwrap()
+    }
+}
+
+/// Check whether an `Accept` header admits a JSON response
+///
+/// Matches `application/json` and `+json` types such as
+/// `application/problem+json`, unless refused with `q=0`. Wildcards don't
+/// count, so browsers sending `*/*` keep getting plain text.
+fn accepts_json(accept: &str) -> bool {
+    accept.split(',').any(|entry| {
+        let mut parts = entry.split(';');
+        let media_type = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let refused = parts.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+
+        !refused && (media_type == "application/json" || media_type.ends_with("+json"))
+    })
+}
+
+/// HTTP version as it appears in a request line
+fn http_version(version: hyper::Version) -> &'static str {
+    match version {
+        hyper::Version::HTTP_09 => "HTTP/0.9",
+        hyper::Version::HTTP_10 => "HTTP/1.0",
+        hyper::Version::HTTP_2 => "HTTP/2.0",
+        hyper::Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/1.1",
+    }
+}
+
+/// Resolve a method override for a POST request
+///
+/// Checks the `X-HTTP-Method-Override` header, then the `_method` field of the
+/// query string or an urlencoded form body. Anything other than PUT, PATCH or
+/// DELETE is ignored.
+fn method_override(
+    headers: &hyper::HeaderMap,
+    query: Option<&str>,
+    form_body: Option<&[u8]>,
+) -> Option<hyper::Method> {
+    fn field(pairs: &str) -> Option<&str> {
+        pairs
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "_method")
+            .map(|(_, value)| value)
+    }
+
+    let is_form = headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+    let requested = headers
+        .get("x-http-method-override")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| query.and_then(field))
+        .or_else(|| {
+            form_body
+                .filter(|_| is_form)
+                .and_then(|body| std::str::from_utf8(body).ok())
+                .and_then(field)
+        })?;
+
+    match requested.trim().to_ascii_uppercase().as_str() {
+        "PUT" => Some(hyper::Method::PUT),
+        "PATCH" => Some(hyper::Method::PATCH),
+        "DELETE" => Some(hyper::Method::DELETE),
+        _ => None,
+    }
+}
+
+/// Collect a request body, failing with 413 once it exceeds `limit` bytes
+async fn collect_limited<B>(body: B, limit: usize) -> Result<bytes::Bytes, ZapError>
+where
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    use http_body_util::{BodyExt, LengthLimitError, Limited};
+
+    match Limited::new(body, limit).collect().await {
+        Ok(collected) => Ok(collected.to_bytes()),
+        Err(e) if e.is::<LengthLimitError>() => Err(ZapError::payload_too_large(limit)),
+        Err(e) => Err(ZapError::http(format!("Failed to read request body: {}", e))),
+    }
+}
+
+/// Stream a request body, yielding a 413 error once it exceeds `limit` bytes
+fn stream_limited<B>(body: B, limit: usize) -> BodyStream
+where
+    B: hyper::body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    use futures::StreamExt;
+    use http_body_util::{BodyExt, LengthLimitError, Limited};
+    use hyper::body::Buf;
+
+    Box::pin(Limited::new(body, limit).into_data_stream().map(move |chunk| match chunk {
+        Ok(mut data) => Ok(data.copy_to_bytes(data.remaining())),
+        Err(e) if e.is::<LengthLimitError>() => Err(ZapError::payload_too_large(limit)),
+        Err(e) => Err(ZapError::http(format!("Failed to read request body: {}", e))),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rewrite_redirect() {
+        let server = Zap::new()
+            .rewrite(|path| match path {
+                "/old" => Some(RewriteAction::Redirect {
+                    location: "/new".to_string(),
+                    status: 301,
+                }),
+                "/legacy" => Some(RewriteAction::Rewrite("/current".to_string())),
+                _ => None,
+            })
+            .get_simple("/current", || "current".to_string());
+
+        match server.test_request(Method::GET, "/old", &[], "").await {
+            ZapResponse::Custom(response) => {
+                assert_eq!(response.status.as_u16(), 301);
+                assert_eq!(response.headers["location"], "/new");
+            }
+            other => panic!("expected redirect, got {:?}", other),
+        }
+
+        match server.test_request(Method::GET, "/legacy", &[], "").await {
+            ZapResponse::Custom(response) => {
+                assert_eq!(response.status.as_u16(), 200);
+                assert!(matches!(response.body, zap_core::ResponseBody::Bytes(ref b) if b == b"current"));
+            }
+            other => panic!("expected rewritten route, got {:?}", other),
+        }
+
+        assert!(matches!(
+            server.test_request(Method::GET, "/other", &[], "").await,
+            ZapResponse::Custom(ref r) if r.status.as_u16() == 404
+        ));
+    }
+
+    fn test_request(uri: &str, headers: &[(&str, &str)]) -> HyperRequest<http_body_util::Full<bytes::Bytes>> {
+        let mut builder = HyperRequest::builder().uri(uri).header("Host", "localhost");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(http_body_util::Full::new(bytes::Bytes::new())).unwrap()
+    }
+
+    fn test_addr() -> SocketAddr {
+        "127.0.0.1:40000".parse().unwrap()
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    async fn connect(port: u16) -> tokio::net::TcpStream {
+        loop {
+            match tokio::net::TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => return stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    }
+
+    /// Read one HTTP/1.1 response (headers plus Content-Length body)
+    async fn read_response(stream: &mut tokio::net::TcpStream) -> Option<String> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = Vec::new();
+        let mut byte = [0u8; 1];
+        while !buf.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte).await.ok()? == 0 {
+                return None;
+            }
+            buf.push(byte[0]);
+        }
+
+        let head = String::from_utf8(buf).ok()?;
+        let length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).await.ok()?;
+        Some(head + &String::from_utf8_lossy(&body))
+    }
+
+    #[tokio::test]
+    async fn test_middleware_runs_for_routed_requests() {
+        use http_body_util::BodyExt;
+        use zap_core::{Middleware, MiddlewareFuture, MiddlewareResult};
+
+        struct InjectHeader;
+
+        impl Middleware for InjectHeader {
+            fn call<'a>(&'a self, ctx: MiddlewareContext<'a>) -> MiddlewareFuture<'a> {
+                Box::pin(async move {
+                    let mut ctx = ctx;
+                    ctx.response = ctx.response.header("X-Injected", "yes");
+                    Ok((ctx, MiddlewareResult::Continue))
+                })
+            }
+        }
+
+        let server = Zap::new()
+            .use_middleware(InjectHeader)
+            .get("/hello", || "hi");
+
+        let response = server.process_request(test_request("/hello", &[]), test_addr()).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["x-injected"], "yes");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), b"hi");
+    }
+
+    #[tokio::test]
+    async fn test_method_override() {
+        let server = Zap::new()
+            .method_override(true)
+            .post("/items/:id", || "posted")
+            .delete("/items/:id", || "deleted");
+
+        let post = |headers: &[(&str, &str)], uri: &str| {
+            let mut req = test_request(uri, headers);
+            *req.method_mut() = hyper::Method::POST;
+            req
+        };
+        let body = |response: HyperResponse<ZapBody>| async move {
+            use http_body_util::BodyExt;
+            response.into_body().collect().await.unwrap().to_bytes()
+        };
+
+        let header = post(&[("X-HTTP-Method-Override", "DELETE")], "/items/1");
+        let response = server.process_request(header, test_addr()).await.unwrap();
+        assert_eq!(body(response).await.as_ref(), b"deleted");
+
+        let query = post(&[], "/items/1?_method=delete");
+        let response = server.process_request(query, test_addr()).await.unwrap();
+        assert_eq!(body(response).await.as_ref(), b"deleted");
+
+        // Only PUT/PATCH/DELETE may be requested
+        let unsafe_override = post(&[("X-HTTP-Method-Override", "GET")], "/items/1");
+        let response = server.process_request(unsafe_override, test_addr()).await.unwrap();
+        assert_eq!(body(response).await.as_ref(), b"posted");
+
+        // Disabled by default
+        let server = Zap::new()
+            .post("/items/:id", || "posted")
+            .delete("/items/:id", || "deleted");
+        let header = post(&[("X-HTTP-Method-Override", "DELETE")], "/items/1");
+        let response = server.process_request(header, test_addr()).await.unwrap();
+        assert_eq!(body(response).await.as_ref(), b"posted");
+    }
+
+    #[test]
+    fn test_method_override_form_field() {
+        let mut headers = hyper::HeaderMap::new();
+        assert_eq!(method_override(&headers, None, Some(b"_method=PUT")), None);
+
+        headers.insert(
+            hyper::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded".parse().unwrap(),
+        );
+        assert_eq!(
+            method_override(&headers, None, Some(b"name=zap&_method=PATCH")),
+            Some(hyper::Method::PATCH)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_after_middleware_modifies_response() {
+        use zap_core::{Middleware, MiddlewareFuture, MiddlewareResponse, MiddlewareResult};
+
+        struct ServerTiming;
+
+        impl Middleware for ServerTiming {
+            fn call<'a>(&'a self, ctx: MiddlewareContext<'a>) -> MiddlewareFuture<'a> {
+                Box::pin(async move { Ok((ctx, MiddlewareResult::Continue)) })
+            }
+
+            fn after(&self, _ctx: &MiddlewareContext<'_>, mut response: MiddlewareResponse) -> MiddlewareResponse {
+                response.headers.push(("Server-Timing".to_string(), format!("status;desc={}", response.status)));
+                response
+            }
+        }
+
+        let server = Zap::new()
+            .use_middleware(ServerTiming)
+            .get("/hello", || "hi");
+
+        let response = server.process_request(test_request("/hello", &[]), test_addr()).await.unwrap();
+        assert_eq!(response.headers()["server-timing"], "status;desc=200");
+        assert_eq!(response.headers()["content-type"], "text/plain; charset=utf-8");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_short_circuits_handler() {
+        let server = Zap::new()
+            .use_middleware(zap_core::BasicAuthMiddleware::with_credentials("admin", "alice", "s3cret"))
+            .get("/admin", || "secret area");
+
+        let response = server.process_request(test_request("/admin", &[]), test_addr()).await.unwrap();
+        assert_eq!(response.status(), 401);
+        assert!(response.headers().contains_key("www-authenticate"));
+
+        let authorized = test_request("/admin", &[("Authorization", "Basic YWxpY2U6czNjcmV0")]);
+        let response = server.process_request(authorized, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_per_route_body_limit() {
+        use http_body_util::Full;
+
+        let server = Zap::new()
+            .max_request_body_size(1024)
+            .post_async("/api/small", |_req| async { ZapResponse::Text("ok".into()) })
+            .post_async_with_limit("/api/upload", |_req| async { ZapResponse::Text("ok".into()) }, 64 * 1024);
+
+        assert_eq!(server.body_handling_for(Method::POST, "/api/small").0, 1024);
+        assert_eq!(server.body_handling_for(Method::POST, "/api/upload").0, 64 * 1024);
+        assert_eq!(server.body_handling_for(Method::POST, "/missing").0, 1024);
+
+        let body = vec![0u8; 4 * 1024];
+
+        let small = server.body_handling_for(Method::POST, "/api/small").0;
+        let err = collect_limited(Full::new(bytes::Bytes::from(body.clone())), small)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 413);
+
+        let upload = server.body_handling_for(Method::POST, "/api/upload").0;
+        let collected = collect_limited(Full::new(bytes::Bytes::from(body)), upload)
+            .await
+            .unwrap();
+        assert_eq!(collected.len(), 4 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_report_uptime_and_memory() {
+        use http_body_util::BodyExt;
+
+        let server = Zap::new().metrics("/metrics");
+        let response = server.process_request(test_request("/metrics", &[]), test_addr()).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let metrics: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert!(metrics["uptime"].as_u64().is_some());
+        assert_ne!(metrics["memory_usage"], "TODO");
+        #[cfg(target_os = "linux")]
+        assert!(metrics["memory_usage"].as_u64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_connection_metrics_track_open_connections() {
+        use crate::metrics::ConnectionStats;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::sync::Notify;
+
+        let entered = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+
+        let port = free_port();
+        let server = {
+            let entered = entered.clone();
+            let release = release.clone();
+            Zap::new()
+                .hostname("127.0.0.1")
+                .port(port)
+                .get_async("/slow", move |_req| {
+                    let entered = entered.clone();
+                    let release = release.clone();
+                    async move {
+                        entered.notify_one();
+                        release.notified().await;
+                        ZapResponse::Text("done".into())
+                    }
+                })
+        };
+
+        let connections = server.connections.clone();
+        let connection_stats = move || connections.stats();
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(server.listen_with_coordinator(shutdown.clone()));
+
+        assert_eq!(connection_stats(), ConnectionStats { active: 0, accepted: 0, completed: 0 });
+        let mut stream = connect(port).await;
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        // The handler is now parked, so the connection must be counted as active
+        entered.notified().await;
+        assert_eq!(connection_stats(), ConnectionStats { active: 1, accepted: 1, completed: 0 });
+
+        release.notify_one();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("done"));
+
+        // Completion is recorded once the server side of the connection closes
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while connection_stats().completed == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("connection should be marked completed");
+        assert_eq!(connection_stats(), ConnectionStats { active: 0, accepted: 1, completed: 1 });
+
+        shutdown.trigger();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_requests_per_connection() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let port = free_port();
+        let server = Zap::new()
+            .hostname("127.0.0.1")
+            .port(port)
+            .max_requests_per_connection(2)
+            .get("/ping", || "pong");
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(server.listen_with_coordinator(shutdown.clone()));
+
+        let mut stream = connect(port).await;
+        let request = b"GET /ping HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+        stream.write_all(request).await.unwrap();
+        let first = read_response(&mut stream).await.unwrap();
+        assert!(first.ends_with("pong"));
+        assert!(!first.to_ascii_lowercase().contains("connection: close"));
+
+        stream.write_all(request).await.unwrap();
+        let second = read_response(&mut stream).await.unwrap();
+        assert!(second.ends_with("pong"));
+        assert!(second.to_ascii_lowercase().contains("connection: close"));
+
+        // The server hangs up instead of serving a third request
+        let _ = stream.write_all(request).await;
+        let mut rest = Vec::new();
+        let read = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut rest))
+            .await
+            .expect("connection should be closed");
+        assert!(read.is_err() || rest.is_empty());
+
+        shutdown.trigger();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_http_1_0_connection_semantics() {
+        let server = Zap::new().get("/ping", || "pong");
+        let http_1_0 = |headers: &[(&str, &str)]| {
+            let mut request = test_request("/ping", headers);
+            *request.version_mut() = hyper::Version::HTTP_10;
+            request
+        };
+
+        // HTTP/1.0 closes by default
+        let response = server.process_request(http_1_0(&[]), test_addr()).await.unwrap();
+        assert_eq!(response.headers()["connection"], "close");
+
+        // ...unless the client opts into keep-alive
+        let response = server
+            .process_request(http_1_0(&[("Connection", "keep-alive")]), test_addr())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["connection"], "keep-alive");
+
+        // HTTP/1.1 stays open without announcing it, and closes on request
+        let response = server.process_request(test_request("/ping", &[]), test_addr()).await.unwrap();
+        assert!(response.headers().get("connection").is_none());
+        let response = server
+            .process_request(test_request("/ping", &[("Connection", "close")]), test_addr())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["connection"], "close");
+    }
+
+    #[tokio::test]
+    async fn test_http_1_0_connection_closes_after_response() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let port = free_port();
+        let server = Zap::new()
+            .hostname("127.0.0.1")
+            .port(port)
+            .get("/ping", || "pong");
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(server.listen_with_coordinator(shutdown.clone()));
+
+        let mut stream = connect(port).await;
+        stream.write_all(b"GET /ping HTTP/1.0\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+            .await
+            .expect("connection should be closed")
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.0 200"));
+        assert!(response.to_ascii_lowercase().contains("connection: close"));
+        assert!(response.ends_with("pong"));
+
+        drop(stream);
+        shutdown.trigger();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_force_close_aborts_hung_handler() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let port = free_port();
+        let server = Zap::new()
+            .hostname("127.0.0.1")
+            .port(port)
+            .get_async("/hang", |_req| async {
+                std::future::pending::<()>().await;
+                ZapResponse::Text("unreachable".into())
+            });
+
+        let shutdown = GracefulShutdown::new(
+            ShutdownConfig::default()
+                .without_signal_handlers()
+                .with_force_close_timeout(Duration::from_millis(200)),
+        );
+        let handle = tokio::spawn(server.listen_with_coordinator(shutdown.clone()));
+
+        let mut stream = connect(port).await;
+        stream.write_all(b"GET /hang HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        while shutdown.active_connection_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let started = std::time::Instant::now();
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("shutdown should finish at the hard deadline")
+            .unwrap()
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(shutdown.force_closed_count(), 1);
+
+        // The client sees the connection closed without a response
+        let mut rest = Vec::new();
+        let read = tokio::time::timeout(Duration::from_secs(1), stream.read_to_end(&mut rest))
+            .await
+            .expect("connection should be closed");
+        assert!(read.is_err() || rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bind_reports_ephemeral_port() {
+        use tokio::io::AsyncWriteExt;
+
+        let bound = Zap::new()
+            .hostname("127.0.0.1")
+            .port(0)
+            .get("/ping", || "pong")
+            .bind()
+            .await
+            .unwrap();
+        let port = bound.local_addr().port();
+        assert_ne!(port, 0);
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(bound.serve_with_coordinator(shutdown.clone()));
+
+        let mut stream = connect(port).await;
+        stream.write_all(b"GET /ping HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        assert!(read_response(&mut stream).await.unwrap().ends_with("pong"));
+        drop(stream);
+
+        shutdown.trigger();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_busy_port_without_cascade_fails() {
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = occupied.local_addr().unwrap().port();
+
+        let server = Zap::new()
+            .hostname("127.0.0.1")
+            .port(port)
+            .port_cascade(false);
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let result = tokio::time::timeout(Duration::from_secs(5), server.listen_with_coordinator(shutdown))
+            .await
+            .expect("bind should fail instead of serving on another port");
+
+        match result {
+            Err(ZapError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::AddrInUse),
+            other => panic!("expected AddrInUse, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_hook_runs_after_signal() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static CLEANED_UP: AtomicBool = AtomicBool::new(false);
+
+        let server = Zap::new()
+            .hostname("127.0.0.1")
+            .port(0)
+            .on_shutdown(|| Box::pin(async { CLEANED_UP.store(true, Ordering::SeqCst) }));
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(server.listen_with_coordinator(shutdown.clone()));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!CLEANED_UP.load(Ordering::SeqCst));
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("server should stop after shutdown signal")
+            .unwrap()
+            .unwrap();
+        assert!(CLEANED_UP.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_handler_reads_state_by_type() {
+        struct AppConfig {
+            greeting: String,
+        }
+
+        let server = Zap::new()
+            .with_state(AppConfig { greeting: "hello".to_string() })
+            .get_async("/greet", |req: RequestData| async move {
+                match (req.state::<AppConfig>(), req.state::<u64>()) {
+                    (Some(config), None) => ZapResponse::Text(config.greeting.clone()),
+                    _ => ZapResponse::Status(StatusCode::INTERNAL_SERVER_ERROR),
+                }
+            });
+
+        let raw = b"GET /greet HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let parsed = HttpParser::new().parse_request(raw).unwrap();
+        let (handler, params) = server.router.at(Method::GET, "/greet").unwrap();
+        let request = Request::new(&parsed, &[], params).with_state(server.state.clone());
+
+        match handler.handle(request).await.unwrap() {
+            ZapResponse::Text(text) => assert_eq!(text, "hello"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parts_path_matches_raw_parse() {
+        use http_body_util::BodyExt;
+
+        fn describe(req: &RequestData) -> String {
+            let mut params: Vec<_> = req.params.iter().collect();
+            let mut query: Vec<_> = req.query.iter().collect();
+            params.sort();
+            query.sort();
+            format!(
+                "{:?} {} {} {} {:?} {:?} {:?} {:?} {}",
+                req.method,
+                req.path,
+                req.path_only,
+                req.version,
+                params,
+                query,
+                req.header("content-type"),
+                req.cookie("session"),
+                String::from_utf8_lossy(&req.body),
+            )
+        }
+
+        let server = Zap::new().post_async("/users/:id/posts/:post_id", |req: RequestData| async move {
+            ZapResponse::Text(describe(&req))
+        });
+
+        let body = r#"{"title":"hi"}"#;
+        let request = HyperRequest::builder()
+            .method("POST")
+            .uri("/users/42/posts/7?draft=true&tag=rust")
+            .header("Host", "localhost")
+            .header("Content-Type", "application/json")
+            .header("Cookie", "session=abc")
+            .body(http_body_util::Full::new(bytes::Bytes::from(body)))
+            .unwrap();
+        let response = server.process_request(request, test_addr()).await.unwrap();
+        let via_parts = response.into_body().collect().await.unwrap().to_bytes();
+
+        let raw = format!(
+            "POST /users/42/posts/7?draft=true&tag=rust HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\ncookie: session=abc\r\n\r\n{}",
+            body
+        );
+        let parsed = HttpParser::new().parse_request(raw.as_bytes()).unwrap();
+        let (_, params) = server.router.at(Method::POST, "/users/42/posts/7").unwrap();
+        let request = Request::new(&parsed, &raw.as_bytes()[parsed.body_offset..], params);
+        let via_bytes = describe(&RequestData::from_request(&request));
+
+        assert_eq!(String::from_utf8_lossy(&via_parts), via_bytes);
+        assert!(via_bytes.contains(r#"("id", "42")"#));
+        assert!(via_bytes.contains(r#"("post_id", "7")"#));
+    }
+
+    #[tokio::test]
+    async fn test_async_patch_delete_options_routes() {
+        let server = Zap::new()
+            .patch_async("/items/:id", |_req| async { ZapResponse::Text("patched".into()) })
+            .delete_async("/items/:id", |req: RequestData| async move {
+                ZapResponse::Text(format!("deleted {}", req.param("id").unwrap_or_default()))
+            })
+            .options_async("/items", |_req| async { ZapResponse::Status(StatusCode::NO_CONTENT) });
+
+        assert!(server.router.at(Method::PATCH, "/items/1").is_some());
+        assert!(server.router.at(Method::OPTIONS, "/items").is_some());
+        assert!(server.router.at(Method::GET, "/items/1").is_none());
+
+        let raw = b"DELETE /items/7 HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let parsed = HttpParser::new().parse_request(raw).unwrap();
+        let (handler, params) = server.router.at(Method::DELETE, "/items/7").unwrap();
+
+        match handler.handle(Request::new(&parsed, &[], params)).await.unwrap() {
+            ZapResponse::Text(text) => assert_eq!(text, "deleted 7"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_body_post_deserializes_typed_body() {
+        use http_body_util::BodyExt;
+
+        #[derive(serde::Deserialize)]
+        struct NewUser {
+            name: String,
+            age: u32,
+        }
+
+        #[derive(Serialize)]
+        struct Created {
+            greeting: String,
+            adult: bool,
+        }
+
+        let server = Zap::new().json_body_post::<NewUser, Created>("/users", |user| Created {
+            greeting: format!("hello {}", user.name),
+            adult: user.age >= 18,
+        });
+
+        let post = |body: &'static str| {
+            HyperRequest::builder()
+                .method("POST")
+                .uri("/users")
+                .header("Host", "localhost")
+                .header("Content-Type", "application/json")
+                .body(http_body_util::Full::new(bytes::Bytes::from(body)))
+                .unwrap()
+        };
+
+        let response = server.process_request(post(r#"{"name":"Ada","age":36}"#), test_addr()).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, serde_json::json!({"greeting": "hello Ada", "adult": true}));
+
+        let response = server.process_request(post(r#"{"name":"Ada"}"#), test_addr()).await.unwrap();
+        assert_eq!(response.status(), 400);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "VALIDATION_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_readiness_fails_while_draining() {
+        let server = Zap::new().health_endpoints();
+
+        let ready = server.process_request(test_request("/health/ready", &[]), test_addr()).await.unwrap();
+        assert_eq!(ready.status(), 200);
+
+        server.draining.store(true, Ordering::SeqCst);
+        let ready = server.process_request(test_request("/health/ready", &[]), test_addr()).await.unwrap();
+        assert_eq!(ready.status(), 503);
+        let live = server.process_request(test_request("/health/live", &[]), test_addr()).await.unwrap();
+        assert_eq!(live.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_header_limits_reject_with_431() {
+        let server = Zap::new().max_headers(3).get("/hello", || "hi");
+
+        let ok = test_request("/hello", &[("Accept", "*/*")]);
+        assert_eq!(server.process_request(ok, test_addr()).await.unwrap().status(), 200);
+
+        let crowded = test_request("/hello", &[("A", "1"), ("B", "2"), ("C", "3"), ("D", "4")]);
+        let error = server.process_request(crowded, test_addr()).await.unwrap_err();
+        assert_eq!(error.status_code(), 431);
+
+        let server = Zap::new().max_header_size(64).get("/hello", || "hi");
+        let long_value = "x".repeat(128);
+        let oversized = test_request("/hello", &[("X-Long", long_value.as_str())]);
+        let error = server.process_request(oversized, test_addr()).await.unwrap_err();
+        assert_eq!(error.status_code(), 431);
+
+        let server = Zap::new().max_header_value_size(64).get("/hello", || "hi");
+        let cookie = test_request("/hello", &[("Cookie", long_value.as_str())]);
+        let error = server.process_request(cookie, test_addr()).await.unwrap_err();
+        assert_eq!(error.status_code(), 431);
+    }
+
+    #[tokio::test]
+    async fn test_errors_render_as_problem_json() {
+        use http_body_util::BodyExt;
+
+        let response = error_response(&ZapError::headers_too_large("Too many headers"), true);
+        assert_eq!(response.status(), 431);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_CONTENT_TYPE);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["status"], 431);
+        assert_eq!(problem["code"], "HEADERS_TOO_LARGE");
+    }
+
+    #[tokio::test]
+    async fn test_not_found_negotiates_json() {
+        use http_body_util::BodyExt;
+
+        let server = Zap::new().get("/hello", || "hi");
+
+        let request = test_request("/missing", &[("Accept", "application/json")]);
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_CONTENT_TYPE);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["status"], 404);
+        assert_eq!(problem["code"], "ROUTE_NOT_FOUND");
+
+        // Browsers and clients without an Accept header get plain text
+        for accept in [None, Some("text/html,*/*;q=0.8"), Some("application/json;q=0")] {
+            let headers: Vec<_> = accept.map(|value| ("Accept", value)).into_iter().collect();
+            let response = server.handle_request(test_request("/missing", &headers), test_addr()).await.unwrap();
+            assert_eq!(response.status(), 404);
+            assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, "Not Found");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wrong_method_is_405_with_allow() {
+        use http_body_util::BodyExt;
+
+        let server = Zap::new().get("/items", || "list").post("/items", || "created");
+
+        let request = HyperRequest::builder()
+            .method("DELETE")
+            .uri("/items")
+            .header("Host", "localhost")
+            .header("Accept", "application/json")
+            .body(http_body_util::Full::new(bytes::Bytes::new()))
+            .unwrap();
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 405);
+        assert_eq!(response.headers()["allow"], "GET, POST");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "METHOD_NOT_ALLOWED");
+        assert_eq!(problem["allowed"], serde_json::json!(["GET", "POST"]));
+
+        // Unrouted paths are still 404
+        let response = server.handle_request(test_request("/other", &[]), test_addr()).await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_response_hook_records_sizes() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::<ResponseInfo>::new()));
+        let recorded = Arc::clone(&seen);
+
+        let server = Zap::new()
+            .post("/echo", || "hello world")
+            .on_response(move |info| recorded.lock().unwrap().push(info.clone()));
+
+        let request = HyperRequest::builder()
+            .method("POST")
+            .uri("/echo")
+            .header("Host", "localhost")
+            .body(http_body_util::Full::new(bytes::Bytes::from_static(b"12345")))
+            .unwrap();
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(ResponseSize::of(&response), Some(ResponseSize(11)));
+
+        server.handle_request(test_request("/missing", &[]), test_addr()).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!((seen[0].method.as_str(), seen[0].path.as_str()), ("POST", "/echo"));
+        assert_eq!(seen[0].status, 200);
+        assert_eq!(seen[0].request_bytes, 5);
+        assert_eq!(seen[0].response_bytes, Some(11));
+        assert_eq!(seen[1].status, 404);
+        assert_eq!(seen[1].request_bytes, 0);
+        assert_eq!(seen[1].response_bytes, Some("Not Found".len() as u64));
+    }
+
+    #[tokio::test]
+    async fn test_handler_and_cors_vary_are_merged() {
+        let server = Zap::new()
+            .get_async("/negotiated", |_req| async {
+                ZapResponse::Text("hi".to_string()).vary("Accept-Encoding")
+            })
+            .cors();
+
+        let request = test_request("/negotiated", &[("Origin", "https://app.example")]);
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        let vary: Vec<_> = response.headers().get_all("vary").iter().collect();
+        assert_eq!(vary, ["Accept-Encoding, Origin"]);
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_without_options_route() {
+        let server = Zap::new().get("/api/users", || "users").cors();
+
+        let request = HyperRequest::builder()
+            .method("OPTIONS")
+            .uri("/api/users")
+            .header("Host", "localhost")
+            .header("Origin", "https://app.example")
+            .header("Access-Control-Request-Method", "GET")
+            .body(http_body_util::Full::new(bytes::Bytes::new()))
+            .unwrap();
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 204);
+        assert_eq!(response.headers()["access-control-allow-methods"], "GET");
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+        // Paths with no routes at all still 404
+        let request = HyperRequest::builder()
+            .method("OPTIONS")
+            .uri("/api/missing")
+            .header("Host", "localhost")
+            .body(http_body_util::Full::new(bytes::Bytes::new()))
+            .unwrap();
+        let response = server.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 404);
+
+        // Without CORS middleware an unrouted OPTIONS is refused like any other method
+        let plain = Zap::new().get("/api/users", || "users");
+        let request = HyperRequest::builder()
+            .method("OPTIONS")
+            .uri("/api/users")
+            .header("Host", "localhost")
+            .body(http_body_util::Full::new(bytes::Bytes::new()))
+            .unwrap();
+        let response = plain.handle_request(request, test_addr()).await.unwrap();
+        assert_eq!(response.status(), 405);
+        assert_eq!(response.headers()["allow"], "GET");
+    }
+
+    #[tokio::test]
+    async fn test_socket_options_applied() {
+        let config = ServerConfig::new()
+            .hostname("127.0.0.1")
+            .port(0)
+            .tcp_nodelay(true)
+            .reuse_address(true)
+            .backlog(16);
+
+        let (listener, _) = Zap::try_bind_with_cascade(&config, 1).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        assert!(!accepted.nodelay().unwrap());
+
+        configure_stream(&accepted, &config);
+        assert!(accepted.nodelay().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_body_route_reads_incrementally() {
+        use futures::{SinkExt, StreamExt};
+        use http_body_util::BodyExt;
+        use hyper::body::Frame;
+
+        let (seen_tx, mut seen_rx) = tokio::sync::mpsc::unbounded_channel::<bytes::Bytes>();
+        let server = Zap::new()
+            .max_request_body_size(8)
+            .post_streaming("/upload", move |_req, mut body: BodyStream| {
+                let seen = seen_tx.clone();
+                async move {
+                    let mut total = 0;
+                    while let Some(chunk) = body.next().await {
+                        match chunk {
+                            Ok(chunk) => {
+                                total += chunk.len();
+                                seen.send(chunk).unwrap();
+                            }
+                            Err(e) => return ZapResponse::Text(format!("error {}", e.status_code())),
+                        }
+                    }
+                    ZapResponse::Text(format!("received {} bytes", total))
+                }
+            });
+        let server = Arc::new(server);
+
+        let upload = |server: Arc<Zap>| {
+            let (tx, rx) = futures::channel::mpsc::channel::<Result<Frame<bytes::Bytes>, std::io::Error>>(1);
+            let request = HyperRequest::builder()
+                .method("POST")
+                .uri("/upload")
+                .header("Host", "localhost")
+                .body(http_body_util::StreamBody::new(rx))
+                .unwrap();
+            let task = tokio::spawn(async move {
+                let response = server.handle_request(request, test_addr()).await.unwrap();
+                response.into_body().collect().await.unwrap().to_bytes()
+            });
+            (tx, task)
+        };
+
+        // Each chunk reaches the handler before the next one is sent
+        let (mut tx, task) = upload(server.clone());
+        tx.send(Ok(Frame::data(bytes::Bytes::from_static(b"abc")))).await.unwrap();
+        assert_eq!(seen_rx.recv().await.unwrap(), "abc");
+        tx.send(Ok(Frame::data(bytes::Bytes::from_static(b"defg")))).await.unwrap();
+        assert_eq!(seen_rx.recv().await.unwrap(), "defg");
+        drop(tx);
+        assert_eq!(task.await.unwrap(), "received 7 bytes");
+
+        // The body limit still applies, surfacing as an error in the stream
+        let (mut tx, task) = upload(server);
+        tx.send(Ok(Frame::data(bytes::Bytes::from_static(b"0123456789")))).await.unwrap();
+        drop(tx);
+        assert_eq!(task.await.unwrap(), "error 413");
+    }
+
+    #[tokio::test]
+    async fn test_validate_collects_configuration_errors() {
+        let server = Zap::new()
+            .port(0)
+            .get_simple("/users/:id", || "by id".to_string())
+            .get_simple("/users/:name", || "by name".to_string())
+            .static_files("/assets", "/nonexistent/zap-static-dir");
+
+        let errors = server.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains("Failed to register GET route '/users/:name'"));
+        assert!(errors[1].to_string().contains("/nonexistent/zap-static-dir"));
+
+        // Binding fails with the first problem instead of starting the server
+        assert!(matches!(server.bind().await, Err(ZapError::Config { .. })));
+
+        assert!(Zap::new().get_simple("/ok", || "ok".to_string()).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_checks_ipc_socket_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = Zap::new();
+        server.ipc_socket_path = Some(dir.path().join("zap.sock"));
+        assert!(server.validate().is_ok());
+        // The probe file is cleaned up
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        server.ipc_socket_path = Some(dir.path().join("missing").join("zap.sock"));
+        let errors = server.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("IPC socket directory is not writable"));
+    }
+
+    #[tokio::test]
+    async fn test_debug_routes_lists_registered_routes() {
+        let server = Zap::new()
+            .get_simple("/users", || "users".to_string())
+            .get_simple("/users/:id", || "user".to_string())
+            .post("/users", ProxyHandler::new("handler_0".to_string(), "/tmp/zap-test.sock".to_string()))
+            .static_files("/assets", "/tmp")
+            .debug_routes("/debug/routes");
+
+        let listing = match server.test_request(Method::GET, "/debug/routes", &[], "").await {
+            ZapResponse::Custom(response) => match response.body {
+                zap_core::ResponseBody::Bytes(body) => serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+                other => panic!("unexpected body: {:?}", other),
+            },
+            other => panic!("unexpected response: {:?}", other),
+        };
+
+        assert_eq!(
+            listing["routes"],
+            serde_json::json!([
+                { "method": "GET", "path": "/debug/routes", "handler": "rust" },
+                { "method": "GET", "path": "/users", "handler": "rust" },
+                { "method": "POST", "path": "/users", "handler": "typescript" },
+                { "method": "GET", "path": "/users/:id", "handler": "rust" },
+            ])
+        );
+        assert_eq!(listing["counts"], serde_json::json!({ "GET": 3, "POST": 1 }));
+        assert_eq!(listing["static"][0]["prefix"], "/assets");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_paths_are_rejected() {
+        let server = Zap::new().get_simple("/", || "root".to_string());
+
+        // Authority-form and asterisk targets leave no usable path
+        for target in ["example.com:443", "*"] {
+            let request = test_request(target, &[]);
+            let error = server.process_request(request, test_addr()).await.unwrap_err();
+            assert_eq!(error.status_code(), 400, "{}", target);
+        }
+
+        let response = server.test_request(Method::GET, "example.com:443", &[], "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 400));
+
+        assert!(check_request_path("").is_err());
+        assert!(check_request_path("/a\0b").is_err());
+        assert!(check_request_path("/a b").is_err());
+        assert!(check_request_path("/users/42").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_returns_500() {
+        fn explode() -> ZapResponse {
+            panic!("handler blew up")
+        }
+
+        let server = Zap::new()
+            .get_async("/boom", |_req: RequestData| async { explode() })
+            .get_simple("/ok", || "ok".to_string());
+
+        let response = server.test_request(Method::GET, "/boom", &[("accept", "application/json")], "").await;
+        match response {
+            ZapResponse::Custom(response) => {
+                assert_eq!(response.status.as_u16(), 500);
+                // The panic message is logged, not sent to the client
+                assert!(matches!(response.body, zap_core::ResponseBody::Bytes(ref b) if !String::from_utf8_lossy(b).contains("blew up")));
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let response = server.test_request(Method::GET, "/ok", &[], "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 200));
+    }
+
+    #[tokio::test]
+    async fn test_etag_revalidation() {
+        let server = Zap::new()
+            .etag()
+            .json_get("/posts", |_req| serde_json::json!({ "posts": [{ "id": 1, "title": "Hello" }] }));
+
+        let first = match server.test_request(Method::GET, "/posts", &[], "").await {
+            ZapResponse::Custom(response) => response,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(first.status.as_u16(), 200);
+        let etag = first.headers.get("etag").expect("ETag header").clone();
+
+        let revalidated = match server.test_request(Method::GET, "/posts", &[("If-None-Match", &etag)], "").await {
+            ZapResponse::Custom(response) => response,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(revalidated.status.as_u16(), 304);
+        assert_eq!(revalidated.headers.get("etag"), Some(&etag));
+        assert!(matches!(revalidated.body, zap_core::ResponseBody::Bytes(ref b) if b.is_empty()));
+
+        let stale = server.test_request(Method::GET, "/posts", &[("If-None-Match", "\"0000\"")], "").await;
+        assert!(matches!(stale, ZapResponse::Custom(ref r) if r.status.as_u16() == 200));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_read_and_write_the_session() {
+        let server = Zap::new()
+            .use_middleware(zap_core::SessionMiddleware::new("secret").secure(false))
+            .get_async("/login", |req: RequestData| async move {
+                req.session().expect("session middleware installed").insert("user", "ada");
+                ZapResponse::Text("logged in".to_string())
+            })
+            .get_async("/me", |req: RequestData| async move {
+                let user = req.session().and_then(|session| session.get("user"));
+                ZapResponse::Text(user.unwrap_or_else(|| "anonymous".to_string()))
+            });
+
+        let login = match server.test_request(Method::GET, "/login", &[], "").await {
+            ZapResponse::Custom(response) => response,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        let set_cookie = login.headers.get("set-cookie").expect("session cookie");
+        let cookie = set_cookie.split(';').next().unwrap();
+
+        let me = server.test_request(Method::GET, "/me", &[("Cookie", cookie)], "").await;
+        assert!(matches!(me, ZapResponse::Custom(ref r) if matches!(r.body, zap_core::ResponseBody::Bytes(ref b) if b == b"ada")));
+
+        // Reading the session leaves the cookie alone
+        let anonymous = server.test_request(Method::GET, "/me", &[], "").await;
+        assert!(matches!(anonymous, ZapResponse::Custom(ref r) if !r.headers.contains_key("set-cookie")));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_read_verified_jwt_claims() {
+        let jwt = zap_core::JwtMiddleware::new("secret", zap_core::JwtAlgorithm::HS256);
+        let token = jwt.sign(&JwtClaims {
+            sub: Some("ada".to_string()),
+            roles: vec!["admin".to_string()],
+            ..Default::default()
+        });
+        let server = Zap::new()
+            .use_middleware(jwt)
+            .get_async("/me", |req: RequestData| async move {
+                let claims = req.claims().expect("verified claims");
+                let auth = req.auth_context().expect("subject present");
+                ZapResponse::Text(format!("{} {} {:?}", claims.user_id().unwrap(), auth.user_id, auth.roles))
+            });
+
+        let authorization = format!("Bearer {}", token);
+        let response = server.test_request(Method::GET, "/me", &[("Authorization", &authorization)], "").await;
+        assert!(matches!(
+            response,
+            ZapResponse::Custom(ref r) if matches!(r.body, zap_core::ResponseBody::Bytes(ref b) if b == br#"ada ada ["admin"]"#)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_see_the_request_id() {
+        let server = Zap::new()
+            .use_middleware(zap_core::RequestIdMiddleware::new())
+            .get_async("/id", |req: RequestData| async move {
+                ZapResponse::Text(req.request_id().unwrap_or("none").to_string())
+            });
+
+        let response = server.test_request(Method::GET, "/id", &[("X-Request-Id", "req-42")], "").await;
+        assert!(matches!(
+            response,
+            ZapResponse::Custom(ref r) if matches!(r.body, zap_core::ResponseBody::Bytes(ref b) if b == b"req-42")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_try_async_handler_errors_map_to_status() {
+        let server = Zap::new()
+            .post_try_async("/users", |req: RequestData| async move {
+                let user: serde_json::Value = serde_json::from_slice(&req.body)
+                    .map_err(|e| ZapError::validation(format!("Invalid JSON: {}", e)))?;
+                if user["email"].as_str().is_none_or(|email| !email.contains('@')) {
+                    return Err(ZapError::validation_field("Invalid email", "email"));
+                }
+                Ok(ZapResponse::Text("created".to_string()))
+            })
+            .get_try_async("/users/:id", |_req: RequestData| async { Err(ZapError::route_not_found("/users/7")) });
+
+        let response = server.test_request(Method::POST, "/users", &[("accept", "application/json")], r#"{"email":"nope"}"#).await;
+        match response {
+            ZapResponse::Custom(response) => {
+                assert_eq!(response.status.as_u16(), 400);
+                let problem: serde_json::Value = match response.body {
+                    zap_core::ResponseBody::Bytes(ref body) => serde_json::from_slice(body).unwrap(),
+                    ref other => panic!("unexpected body: {:?}", other),
+                };
+                assert_eq!(problem["status"], 400);
+                assert!(problem["detail"].as_str().unwrap().contains("Invalid email"));
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let created = server.test_request(Method::POST, "/users", &[], r#"{"email":"a@b.c"}"#).await;
+        assert!(matches!(created, ZapResponse::Custom(ref r) if r.status.as_u16() == 200));
+        let missing = server.test_request(Method::GET, "/users/7", &[], "").await;
+        assert!(matches!(missing, ZapResponse::Custom(ref r) if r.status.as_u16() == 404));
+    }
+
+    #[tokio::test]
+    async fn test_mount_sub_app_under_prefix() {
+        let users = Zap::new()
+            .get_simple("/users", || "all users".to_string())
+            .get_simple("/users/:id", || "one user".to_string())
+            .get_simple("/", || "api root".to_string())
+            .static_files("/assets", "/tmp");
+        let server = Zap::new().get_simple("/", || "home".to_string()).mount("/api/", users);
+
+        assert!(server.validate().is_ok());
+        assert_eq!(server.static_handlers()[0].prefix, "/api/assets");
+        for (path, body) in [("/api/users", "all users"), ("/api/users/7", "one user"), ("/api", "api root"), ("/", "home")] {
+            match server.test_request(Method::GET, path, &[], "").await {
+                ZapResponse::Custom(response) => {
+                    assert_eq!(response.status.as_u16(), 200, "{}", path);
+                    assert!(matches!(response.body, zap_core::ResponseBody::Bytes(ref b) if b == body.as_bytes()), "{}", path);
+                }
+                other => panic!("unexpected response for {}: {:?}", path, other),
+            }
+        }
+        assert!(matches!(
+            server.test_request(Method::GET, "/users", &[], "").await,
+            ZapResponse::Custom(ref r) if r.status.as_u16() == 404
+        ));
+
+        // Conflicts with the parent's routes fail validation
+        let admin = Zap::new().get_simple("/users/:name", || "by name".to_string());
+        let errors = server.mount("/api", admin).validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("'/api/users/:name'"));
+    }
+
+    #[tokio::test]
+    async fn test_mounted_middleware_stays_under_prefix() {
+        let admin = Zap::new()
+            .get_simple("/stats", || "stats".to_string())
+            .use_middleware(zap_core::BasicAuthMiddleware::with_credentials("admin", "user", "pass"));
+        let server = Zap::new()
+            .get_simple("/", || "home".to_string())
+            .get_simple("/administrator", || "lookalike".to_string())
+            .mount("/admin", admin);
+
+        let status = |response: ZapResponse| match response {
+            ZapResponse::Custom(response) => response.status.as_u16(),
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(status(server.test_request(Method::GET, "/", &[], "").await), 200);
+        assert_eq!(status(server.test_request(Method::GET, "/administrator", &[], "").await), 200);
+        assert_eq!(status(server.test_request(Method::GET, "/admin/stats", &[], "").await), 401);
+        let authorized = [("Authorization", "Basic dXNlcjpwYXNz")];
+        assert_eq!(status(server.test_request(Method::GET, "/admin/stats?full=1", &authorized, "").await), 200);
+    }
+
+    #[test]
+    fn test_try_get_returns_router_error() {
+        let result = Zap::new().try_get("no-leading-slash", SimpleHandler::new(|| "x".to_string()));
+        assert!(matches!(result, Err(RouterError::InvalidPath(ref path)) if path == "no-leading-slash"));
+
+        let server = Zap::new()
+            .try_get("/users/:id", SimpleHandler::new(|| "x".to_string()))
+            .unwrap();
+        assert!(server.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_header_times_out_slow_handler() {
+        let server = Zap::new().get_async("/slow", |_req: RequestData| async {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            ZapResponse::Text("late".to_string())
+        });
+
+        let started = Instant::now();
+        let response = server.test_request(Method::GET, "/slow", &[("X-Deadline-Ms", "50")], "").await;
+        let elapsed = started.elapsed();
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 504));
+        assert!(elapsed >= Duration::from_millis(50) && elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+
+        let response = server.test_request(Method::GET, "/slow", &[("Grpc-Timeout", "20m")], "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 504));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_header_cannot_extend_request_timeout() {
+        let server = Zap::new()
+            .request_timeout(Duration::from_millis(50))
+            .get_async("/slow", |_req: RequestData| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                ZapResponse::Text("late".to_string())
+            });
+
+        for header in [("Grpc-Timeout", "99999999H"), ("X-Deadline-Ms", "86400000")] {
+            let started = Instant::now();
+            let response = server.test_request(Method::GET, "/slow", &[header], "").await;
+            assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 504));
+            assert!(started.elapsed() < Duration::from_secs(1), "{:?} extended the timeout", header);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_options_asterisk_lists_server_methods() {
+        let server = Zap::new()
+            .get_simple("/", || "home".to_string())
+            .post_async("/users", |_req: RequestData| async { ZapResponse::Text("created".to_string()) })
+            .delete_async("/users/:id", |_req: RequestData| async { ZapResponse::Text("deleted".to_string()) });
+
+        let response = server.test_request(Method::OPTIONS, "*", &[], "").await;
+        let ZapResponse::Custom(response) = response else {
+            panic!("expected a custom response");
+        };
+        assert_eq!(response.status.as_u16(), 204);
+        assert_eq!(response.headers["allow"], "GET, POST, DELETE, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_upgrade_is_not_implemented() {
+        let server = Zap::new()
+            .get_simple("/", || "home".to_string())
+            .on_upgrade("x-echo", |conn| Box::pin(async move { drop(conn.io) }));
+
+        let response = server
+            .test_request(Method::GET, "/", &[("Connection", "Upgrade"), ("Upgrade", "h2c")], "")
+            .await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 501));
+
+        let response = server
+            .test_request(Method::GET, "/", &[("Connection", "keep-alive, Upgrade"), ("Upgrade", "X-Echo")], "")
+            .await;
+        let ZapResponse::Custom(response) = response else {
+            panic!("expected a custom response");
+        };
+        assert_eq!(response.status.as_u16(), 101);
+        assert_eq!(response.headers["upgrade"], "x-echo");
+
+        // An Upgrade header without `Connection: upgrade` is routed as usual
+        let response = server.test_request(Method::GET, "/", &[("Upgrade", "h2c")], "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 200));
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_passes_routing_and_middleware() {
+        let server = Zap::new()
+            .get_simple("/", || "home".to_string())
+            .on_upgrade("x-echo", |conn| Box::pin(async move { drop(conn.io) }))
+            .use_middleware(zap_core::BasicAuthMiddleware::with_credentials("zap", "user", "pass"));
+        let upgrade = [("Connection", "Upgrade"), ("Upgrade", "x-echo")];
+        let authorized = [upgrade[0], upgrade[1], ("Authorization", "Basic dXNlcjpwYXNz")];
+
+        let response = server.test_request(Method::GET, "/", &upgrade, "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 401));
+
+        let response = server.test_request(Method::GET, "/missing", &authorized, "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 404));
+
+        let response = server.test_request(Method::GET, "/", &authorized, "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 101));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_upgraded_connection() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let bound = Zap::new()
+            .hostname("127.0.0.1")
+            .port(0)
+            .get_simple("/", || "home".to_string())
+            .on_upgrade("x-echo", |conn| Box::pin(async move {
+                let mut io = conn.io;
+                let mut buf = [0u8; 64];
+                while let Ok(n @ 1..) = io.read(&mut buf).await {
+                    if io.write_all(&buf[..n]).await.is_err() {
+                        break;
+                    }
+                }
+            }))
+            .bind()
+            .await
+            .unwrap();
+        let port = bound.local_addr().port();
+
+        let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+        let handle = tokio::spawn(bound.serve_with_coordinator(shutdown.clone()));
+
+        let mut stream = connect(port).await;
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: upgrade\r\nUpgrade: x-echo\r\n\r\n")
+            .await
+            .unwrap();
+        assert!(read_response(&mut stream).await.unwrap().starts_with("HTTP/1.1 101"));
+
+        // Shutdown waits while the upgraded connection is in use
+        shutdown.trigger();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_finished());
+        stream.write_all(b"ping").await.unwrap();
+        let mut echo = [0u8; 4];
+        stream.read_exact(&mut echo).await.unwrap();
+        assert_eq!(&echo, b"ping");
+
+        drop(stream);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("shutdown should finish once the upgraded connection closes")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_process_test_request() {
+        let server = Zap::new()
+            .get_simple("/", || "home".to_string())
+            .post_async("/echo", |req: RequestData| async move { ZapResponse::Text(req.body_string().unwrap_or_default()) })
+            .use_middleware(zap_core::RequestIdMiddleware::new());
+
+        let response = server.test_request(Method::GET, "/", &[("X-Request-Id", "abc")], "").await;
+        let ZapResponse::Custom(response) = response else {
+            panic!("expected a custom response");
+        };
+        assert_eq!(response.status.as_u16(), 200);
+        assert_eq!(response.headers["x-request-id"], "abc");
+        assert!(matches!(response.body, zap_core::ResponseBody::Bytes(ref b) if b == b"home"));
+
+        let response = server.test_request(Method::POST, "/echo", &[], "ping").await;
+        assert!(matches!(
+            response,
+            ZapResponse::Custom(zap_core::Response { body: zap_core::ResponseBody::Bytes(ref b), .. }) if b == b"ping"
+        ));
+
+        let response = server.test_request(Method::GET, "/missing", &[], "").await;
+        assert!(matches!(response, ZapResponse::Custom(ref r) if r.status.as_u16() == 404));
+    }
+}
@@ -5,6 +5,7 @@
 //! ## Features
 //! - SIGTERM and SIGINT signal handling
 //! - Configurable drain period for in-flight requests
+//! - Optional hard deadline that force-closes connections still open
 //! - Connection tracking
 //! - Proper resource cleanup
 //!
@@ -35,11 +36,13 @@
 //! }
 //! ```
 
+use futures::future::BoxFuture;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Notify;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
 /// Configuration for graceful shutdown
@@ -51,14 +54,26 @@ pub struct ShutdownConfig {
     pub enable_signal_handlers: bool,
     /// Poll interval for checking connection count during drain (default: 100ms)
     pub drain_poll_interval: Duration,
+    /// Combined time budget for all cleanup hooks (default: 10s)
+    pub cleanup_timeout: Duration,
+    /// Hard deadline for the drain, after which remaining connections are
+    /// aborted (default: none - connections outlive `drain_timeout`)
+    ///
+    /// When set, the drain waits up to this long instead of `drain_timeout`.
+    pub force_close_timeout: Option<Duration>,
 }
 
+/// Async cleanup callback run after connections have drained
+pub type ShutdownHook = fn() -> BoxFuture<'static, ()>;
+
 impl Default for ShutdownConfig {
     fn default() -> Self {
         Self {
             drain_timeout: Duration::from_secs(30),
             enable_signal_handlers: true,
             drain_poll_interval: Duration::from_millis(100),
+            cleanup_timeout: Duration::from_secs(10),
+            force_close_timeout: None,
         }
     }
 }
@@ -83,6 +98,18 @@ impl ShutdownConfig {
         self
     }
 
+    /// Set the combined timeout for cleanup hooks
+    pub fn with_cleanup_timeout(mut self, timeout: Duration) -> Self {
+        self.cleanup_timeout = timeout;
+        self
+    }
+
+    /// Abort connections still open after `timeout`
+    pub fn with_force_close_timeout(mut self, timeout: Duration) -> Self {
+        self.force_close_timeout = Some(timeout);
+        self
+    }
+
     /// Disable signal handlers (for testing or custom signal handling)
     pub fn without_signal_handlers(mut self) -> Self {
         self.enable_signal_handlers = false;
@@ -104,6 +131,10 @@ pub struct GracefulShutdown {
     active_connections: Arc<AtomicU64>,
     /// Whether we're currently draining
     draining: Arc<AtomicBool>,
+    /// Cancelled when the hard deadline passes to abort connections
+    force_close: CancellationToken,
+    /// Connections aborted at the hard deadline
+    force_closed: Arc<AtomicU64>,
 }
 
 impl GracefulShutdown {
@@ -115,6 +146,8 @@ impl GracefulShutdown {
             shutdown_triggered: Arc::new(AtomicBool::new(false)),
             active_connections: Arc::new(AtomicU64::new(0)),
             draining: Arc::new(AtomicBool::new(false)),
+            force_close: CancellationToken::new(),
+            force_closed: Arc::new(AtomicU64::new(0)),
         };
 
         if config.enable_signal_handlers {
@@ -166,7 +199,12 @@ impl GracefulShutdown {
     ///
     /// This should be used in a tokio::select! block in the main server loop.
     pub async fn wait(&self) {
-        self.shutdown_notifier.notified().await;
+        // Register before checking the flag so a trigger in between isn't lost
+        let notified = self.shutdown_notifier.notified();
+        if self.is_shutdown() {
+            return;
+        }
+        notified.await;
     }
 
     /// Check if shutdown has been triggered
@@ -211,10 +249,25 @@ impl GracefulShutdown {
         }
     }
 
+    /// Wait until remaining connections must be aborted
+    ///
+    /// Connection tasks select on this and drop the connection (and any
+    /// in-flight handler) when it completes.
+    pub async fn force_closed(&self) {
+        self.force_close.cancelled().await
+    }
+
+    /// Number of connections aborted at the hard deadline
+    pub fn force_closed_count(&self) -> u64 {
+        self.force_closed.load(Ordering::SeqCst)
+    }
+
     /// Drain active connections with timeout
     ///
     /// Waits for all in-flight connections to complete, up to the configured timeout.
     /// Returns true if all connections drained successfully, false if timeout occurred.
+    /// With a `force_close_timeout`, connections still open at that deadline
+    /// are aborted before returning.
     pub async fn drain_connections(&self) -> bool {
         self.draining.store(true, Ordering::SeqCst);
 
@@ -224,8 +277,8 @@ impl GracefulShutdown {
             return true;
         }
 
-        info!("⏳ Draining {} active connection(s), timeout: {:?}",
-              active, self.config.drain_timeout);
+        let timeout = self.config.force_close_timeout.unwrap_or(self.config.drain_timeout);
+        info!("⏳ Draining {} active connection(s), timeout: {:?}", active, timeout);
 
         let start = std::time::Instant::now();
         let mut last_count = active;
@@ -245,8 +298,13 @@ impl GracefulShutdown {
             }
 
             // Check timeout
-            if start.elapsed() >= self.config.drain_timeout {
+            if start.elapsed() >= timeout {
                 warn!("⚠️  Drain timeout reached with {} connection(s) still active", current_count);
+                if self.config.force_close_timeout.is_some() {
+                    self.force_close.cancel();
+                    self.force_closed.store(current_count, Ordering::SeqCst);
+                    warn!("🔌 Force-closed {} connection(s)", current_count);
+                }
                 return false;
             }
 
@@ -254,6 +312,31 @@ impl GracefulShutdown {
         }
     }
 
+    /// Run cleanup hooks in registration order
+    ///
+    /// All hooks share the configured `cleanup_timeout`. Returns false if the
+    /// timeout expired before every hook finished; remaining hooks are skipped.
+    pub async fn run_cleanup(&self, hooks: &[ShutdownHook]) -> bool {
+        if hooks.is_empty() {
+            return true;
+        }
+
+        info!("🧹 Running {} cleanup hook(s)", hooks.len());
+        let run_all = async {
+            for hook in hooks {
+                hook().await;
+            }
+        };
+
+        match tokio::time::timeout(self.config.cleanup_timeout, run_all).await {
+            Ok(()) => true,
+            Err(_) => {
+                warn!("⚠️  Cleanup hooks exceeded timeout of {:?}", self.config.cleanup_timeout);
+                false
+            }
+        }
+    }
+
     /// Check if currently draining
     pub fn is_draining(&self) -> bool {
         self.draining.load(Ordering::SeqCst)
@@ -273,6 +356,8 @@ impl Clone for GracefulShutdown {
             shutdown_triggered: self.shutdown_triggered.clone(),
             active_connections: self.active_connections.clone(),
             draining: self.draining.clone(),
+            force_close: self.force_close.clone(),
+            force_closed: self.force_closed.clone(),
         }
     }
 }
@@ -390,6 +475,79 @@ mod tests {
         assert_eq!(shutdown.active_connection_count(), 2);
     }
 
+    #[tokio::test]
+    async fn test_force_close_aborts_hung_connection() {
+        let config = ShutdownConfig::default()
+            .without_signal_handlers()
+            .with_force_close_timeout(Duration::from_millis(100));
+        let shutdown = GracefulShutdown::new(config);
+
+        // A connection whose handler never completes
+        let conn = shutdown.clone();
+        let task = tokio::spawn(async move {
+            let _guard = conn.connection_guard();
+            tokio::select! {
+                _ = std::future::pending::<()>() => {}
+                _ = conn.force_closed() => {}
+            }
+        });
+        while shutdown.active_connection_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let drained = tokio::time::timeout(Duration::from_secs(1), shutdown.drain_connections())
+            .await
+            .expect("drain should return at the hard deadline");
+        assert!(!drained);
+        assert_eq!(shutdown.force_closed_count(), 1);
+
+        task.await.unwrap();
+        assert_eq!(shutdown.active_connection_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_after_trigger_returns() {
+        let config = ShutdownConfig::default().without_signal_handlers();
+        let shutdown = GracefulShutdown::new(config);
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait())
+            .await
+            .expect("wait() should return once shutdown was triggered");
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_hooks_run_in_order_with_timeout() {
+        use std::sync::Mutex;
+
+        static ORDER: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+        fn first() -> BoxFuture<'static, ()> {
+            Box::pin(async {
+                sleep(Duration::from_millis(20)).await;
+                ORDER.lock().unwrap().push(1);
+            })
+        }
+        fn second() -> BoxFuture<'static, ()> {
+            Box::pin(async { ORDER.lock().unwrap().push(2) })
+        }
+        fn stuck() -> BoxFuture<'static, ()> {
+            Box::pin(sleep(Duration::from_secs(60)))
+        }
+
+        let config = ShutdownConfig::default()
+            .without_signal_handlers()
+            .with_cleanup_timeout(Duration::from_millis(200));
+        let shutdown = GracefulShutdown::new(config);
+
+        assert!(shutdown.run_cleanup(&[first, second]).await);
+        assert_eq!(*ORDER.lock().unwrap(), vec![1, 2]);
+
+        // A hung hook is cut off by the shared timeout and later hooks are skipped
+        assert!(!shutdown.run_cleanup(&[stuck, second]).await);
+        assert_eq!(*ORDER.lock().unwrap(), vec![1, 2]);
+    }
+
     #[tokio::test]
     async fn test_wait_for_shutdown() {
         let config = ShutdownConfig::default().without_signal_handlers();
@@ -13,6 +13,9 @@ use std::collections::HashMap;
 // Import Splice protocol types from canonical source
 use splice::protocol::{Message, ExportMetadata, RequestContext, Role, SpliceCodec};
 
+/// Deadline sent with invocations whose caller did not ask for one
+pub const DEFAULT_SPLICE_DEADLINE_MS: u32 = 30_000;
+
 pub struct SpliceClient {
     tx: mpsc::Sender<ClientRequest>,
     exports: Arc<tokio::sync::RwLock<Vec<ExportMetadata>>>,
@@ -22,6 +25,8 @@ enum ClientRequest {
     Invoke {
         function_name: String,
         params: serde_json::Value,
+        deadline_ms: u32,
+        context: RequestContext,
         response_tx: oneshot::Sender<Result<serde_json::Value, String>>,
     },
     Shutdown,
@@ -84,6 +89,34 @@ impl SpliceClient {
         &self,
         function_name: String,
         params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        self.invoke_with_deadline(function_name, params, DEFAULT_SPLICE_DEADLINE_MS).await
+    }
+
+    /// Invoke a Rust function, giving it `deadline_ms` milliseconds to finish
+    pub async fn invoke_with_deadline(
+        &self,
+        function_name: String,
+        params: serde_json::Value,
+        deadline_ms: u32,
+    ) -> Result<serde_json::Value, String> {
+        let context = RequestContext {
+            trace_id: 0,
+            span_id: 0,
+            headers: vec![],
+            auth: None,
+        };
+        self.invoke_with_context(function_name, params, deadline_ms, context).await
+    }
+
+    /// Invoke a Rust function on behalf of a request, forwarding its context
+    /// (headers and caller identity) to the worker
+    pub async fn invoke_with_context(
+        &self,
+        function_name: String,
+        params: serde_json::Value,
+        deadline_ms: u32,
+        context: RequestContext,
     ) -> Result<serde_json::Value, String> {
         let (response_tx, response_rx) = oneshot::channel();
 
@@ -91,6 +124,8 @@ impl SpliceClient {
             .send(ClientRequest::Invoke {
                 function_name,
                 params,
+                deadline_ms,
+                context,
                 response_tx,
             })
             .await
@@ -135,6 +170,8 @@ impl SpliceClient {
                         ClientRequest::Invoke {
                             function_name,
                             params,
+                            deadline_ms,
+                            context,
                             response_tx,
                         } => {
                             let request_id = next_request_id;
@@ -149,13 +186,8 @@ impl SpliceClient {
                                 request_id,
                                 function_name,
                                 params: Bytes::from(params_bytes),
-                                deadline_ms: 30000, // 30 second timeout
-                                context: RequestContext {
-                                    trace_id: 0,
-                                    span_id: 0,
-                                    headers: vec![],
-                                    auth: None,
-                                },
+                                deadline_ms,
+                                context,
                             };
 
                             framed.send(msg).await.map_err(|e| e.to_string())?;
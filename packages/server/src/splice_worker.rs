@@ -17,10 +17,10 @@ use futures::stream::StreamExt;
 use futures::sink::SinkExt;
 
 // Import Splice protocol types from the canonical source
-use splice::protocol::{Message, Role, SpliceCodec, ExportMetadata, ErrorKind};
+use splice::protocol::{Message, Role, SpliceCodec, ExportMetadata, ErrorKind, CAP_CANCELLATION, CAP_STREAMING};
 
 // Import registry for function dispatch and Context wrapper
-use crate::registry::build_rpc_dispatcher;
+use crate::registry::{build_context_dispatcher, find_export, ExportedFunction};
 use crate::context::Context;
 
 /// Tracks an in-flight request that can be cancelled
@@ -54,26 +54,28 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let mut framed = create_framed_stream(stream);
 
     // Build RPC dispatcher from linkme exports
-    let dispatcher = Arc::new(build_rpc_dispatcher());
+    let dispatcher = build_context_dispatcher();
     let exports = collect_exports();
 
     // Send handshake
+    let offered = CAP_STREAMING | CAP_CANCELLATION;
     send_message(&mut framed, Message::Handshake {
         protocol_version: 0x00010000,
         role: Role::Worker,
-        capabilities: 0b11, // Streaming + Cancellation
+        capabilities: offered,
         max_frame_size: 100 * 1024 * 1024,
     }).await?;
 
-    // Wait for handshake ack
-    match receive_message(&mut framed).await? {
-        Message::HandshakeAck { .. } => {
-            info!("Handshake complete");
+    // Wait for handshake ack; only capabilities both sides agreed on are used
+    let capabilities = match receive_message(&mut framed).await? {
+        Message::HandshakeAck { capabilities, .. } => {
+            info!("Handshake complete (capabilities: {:#b})", capabilities & offered);
+            capabilities & offered
         }
         _ => {
             return Err("Expected HandshakeAck".into());
         }
-    }
+    };
 
     // Split framed stream for concurrent access
     let (write_half, mut read_half) = framed.split();
@@ -125,10 +127,11 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
                 request_id,
                 function_name,
                 params,
-                deadline_ms: _,
+                deadline_ms,
                 context,
             } => {
                 debug!("Invoking function: {} (request_id: {})", function_name, request_id);
+                let received_at = std::time::Instant::now();
 
                 // Create cancellation token for this request
                 let cancellation_token = CancellationToken::new();
@@ -139,6 +142,9 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
                 let token = cancellation_token.clone();
                 let in_flight_clone = in_flight.clone();
                 let function_name_for_task = function_name.clone();
+                // Without negotiated streaming, stream items are collected into one result
+                let streaming = find_export(&function_name)
+                    .filter(|f| f.wrapper.is_streaming() && capabilities & CAP_STREAMING != 0);
 
                 // Spawn task to handle invocation
                 let task_handle = tokio::spawn(async move {
@@ -148,11 +154,25 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
                     let params_json: serde_json::Value = rmp_serde::from_slice(&params)
                         .unwrap_or_else(|_| serde_json::json!({}));
 
+                    // Expose the cancellation token and caller deadline to the function
+                    let ctx = Context::with_cancellation(context, token.clone())
+                        .with_deadline_ms(deadline_ms)
+                        .with_capabilities(capabilities)
+                        .with_received_at(received_at);
+
+                    // Streaming exports reply with StreamStart/StreamChunk/StreamEnd
+                    if let Some(func) = streaming {
+                        stream_invocation(request_id, func, params_json, ctx, &response_tx).await;
+                        in_flight_clone.write().await.remove(&request_id);
+                        debug!("Stream {} completed", request_id);
+                        return;
+                    }
+
                     // Execute function with automatic cancellation via tokio::select!
                     let result = tokio::select! {
                         // Function execution path
                         res = async {
-                            dispatcher(function_name_for_task.clone(), params_json, Some(context))
+                            dispatcher(function_name_for_task.clone(), params_json, Some(ctx))
                         } => res,
 
                         // Cancellation path - triggers when token is cancelled
@@ -276,13 +296,95 @@ fn collect_exports() -> Vec<ExportMetadata> {
         .map(|f| ExportMetadata {
             name: f.name.to_string(),
             is_async: f.is_async,
-            is_streaming: false, // TODO: Support streaming
-            params_schema: "{}".to_string(), // TODO: Extract from function
+            is_streaming: f.wrapper.is_streaming(),
+            params_schema: f.params_schema.to_string(),
             return_schema: "{}".to_string(), // TODO: Extract from function
         })
         .collect()
 }
 
+/// Run a streaming export, forwarding each item as a `StreamChunk`
+///
+/// Errors raised before the stream starts are reported as `InvokeError`; errors
+/// from individual items (or cancellation) terminate the stream with `StreamError`.
+async fn stream_invocation(
+    request_id: u64,
+    func: &'static ExportedFunction,
+    params: serde_json::Value,
+    ctx: Context,
+    response_tx: &mpsc::Sender<Message>,
+) {
+    let params_map: HashMap<String, serde_json::Value> = match params {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+
+    let mut stream = match func.wrapper.call_stream(Some(&ctx), &params_map).await {
+        Ok(stream) => stream,
+        Err(message) => {
+            let _ = response_tx.send(Message::InvokeError {
+                request_id,
+                code: 2000, // ERR_EXECUTION_FAILED
+                kind: ErrorKind::User,
+                message,
+                details: None,
+            }).await;
+            return;
+        }
+    };
+
+    // The worker does not wait for StreamAck, so no window is advertised
+    if response_tx.send(Message::StreamStart { request_id, window: 0 }).await.is_err() {
+        return;
+    }
+
+    let mut sequence = 0u64;
+    loop {
+        let item = tokio::select! {
+            item = stream.next() => item,
+            _ = ctx.cancelled() => {
+                debug!("Stream {} cancelled after {} chunks", request_id, sequence);
+                let _ = response_tx.send(Message::StreamError {
+                    request_id,
+                    code: 2002, // ERR_CANCELLED
+                    message: "Request cancelled".to_string(),
+                }).await;
+                return;
+            }
+        };
+
+        let msg = match item {
+            Some(Ok(value)) => match rmp_serde::to_vec(&value) {
+                Ok(data) => Message::StreamChunk {
+                    request_id,
+                    sequence,
+                    data: Bytes::from(data),
+                },
+                Err(e) => Message::StreamError {
+                    request_id,
+                    code: 2000, // ERR_EXECUTION_FAILED
+                    message: format!("Failed to serialize chunk: {}", e),
+                },
+            },
+            Some(Err(message)) => Message::StreamError {
+                request_id,
+                code: 2000, // ERR_EXECUTION_FAILED
+                message,
+            },
+            None => Message::StreamEnd {
+                request_id,
+                total_chunks: sequence,
+            },
+        };
+
+        let done = !matches!(msg, Message::StreamChunk { .. });
+        if response_tx.send(msg).await.is_err() || done {
+            return;
+        }
+        sequence += 1;
+    }
+}
+
 // Create framed stream using Splice protocol codec
 fn create_framed_stream(stream: UnixStream) -> Framed<UnixStream, SpliceCodec> {
     Framed::new(stream, SpliceCodec::default())
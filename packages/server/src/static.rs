@@ -5,15 +5,22 @@
 //! - Last-Modified headers
 //! - Conditional request handling (304 Not Modified)
 //! - Cache-Control configuration
+//! - Precompressed `.br` / `.gz` sibling negotiation
 //! - Content-Type detection
+//! - Directory listing pages
 //! - Directory traversal protection
+//! - Optional file metadata caching
 
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::SystemTime;
-use zap_core::{Response, StatusCode};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::AsyncReadExt;
+use tokio_util::io::ReaderStream;
+use zap_core::{Method, Response, StatusCode};
 use crate::error::ZapError;
-use crate::response::ZapResponse;
+use crate::response::{StreamedBody, ZapResponse, FILE_CHUNK_SIZE};
 
 /// ETag generation strategy
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -38,6 +45,10 @@ pub struct StaticHandler {
     pub directory: PathBuf,
     /// Options for static serving
     pub options: StaticOptions,
+    /// File metadata reused while `options.metadata_cache_ttl` allows
+    metadata_cache: Arc<MetadataCache>,
+    /// `directory` with symlinks resolved, once it exists
+    canonical_root: Arc<OnceLock<PathBuf>>,
 }
 
 /// Static file serving options
@@ -55,6 +66,22 @@ pub struct StaticOptions {
     pub etag_strategy: ETagStrategy,
     /// Enable Last-Modified header (default: true)
     pub enable_last_modified: bool,
+    /// Serve precompressed `.br` / `.gz` siblings when accepted (default: false)
+    pub precompressed: bool,
+    /// Include dotfiles in directory listings (default: false)
+    pub show_hidden: bool,
+    /// Content-Type for unrecognized extensions (default: application/octet-stream)
+    pub default_mime: String,
+    /// Reuse each file's metadata (size, mtime, ETag, Content-Type) for this
+    /// long instead of checking the disk on every request (default: off)
+    ///
+    /// A file changed in place keeps its old validators (`ETag`,
+    /// `Last-Modified`) for up to the TTL, and `HEAD` keeps its old
+    /// `Content-Length`, so those may be answered from stale metadata until
+    /// it runs out. A `GET` hit checks the file it opens against the cached
+    /// one and looks the path up again if it is a different file or size, so
+    /// a file swapped for a symlink still cannot escape the root.
+    pub metadata_cache_ttl: Option<Duration>,
 }
 
 impl Default for StaticOptions {
@@ -66,17 +93,167 @@ impl Default for StaticOptions {
             compress: true,
             etag_strategy: ETagStrategy::default(),
             enable_last_modified: true,
+            precompressed: false,
+            show_hidden: false,
+            default_mime: "application/octet-stream".to_string(),
+            metadata_cache_ttl: None,
         }
     }
 }
 
-/// File metadata for caching headers
+/// Files larger than this are streamed from disk instead of buffered
+const STREAM_THRESHOLD: u64 = 256 * 1024;
+
+/// Precompressed encodings in order of preference: (Content-Encoding, file suffix)
+const PRECOMPRESSED_ENCODINGS: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+/// Extension overrides checked before `mime_guess`
+///
+/// Covers modern web formats and text types that should always carry a charset.
+const MIME_OVERRIDES: &[(&str, &str)] = &[
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("cjs", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("webmanifest", "application/manifest+json"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("xml", "application/xml"),
+    ("svg", "image/svg+xml"),
+    ("wasm", "application/wasm"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("apng", "image/apng"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("opus", "audio/opus"),
+];
+
+/// Directory entry shown in a listing page
 #[derive(Debug, Clone)]
+struct ListingEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// File metadata for caching headers
+#[derive(Debug, Clone, PartialEq)]
 struct FileMetadata {
     size: u64,
     modified: SystemTime,
 }
 
+/// A request path resolved to the file that serves it
+#[derive(Debug, Clone)]
+struct ResolvedFile {
+    /// File on disk (a directory's `index.html` for directory requests)
+    path: PathBuf,
+    /// Which file `path` named when it was looked up
+    id: FileId,
+    meta: FileMetadata,
+    /// ETag of the identity encoding
+    etag: Option<String>,
+    content_type: String,
+}
+
+/// Outcome of looking a request path up on disk
+enum Lookup {
+    File(ResolvedFile),
+    /// Answered without serving a file (forbidden, directory listing)
+    Respond(ZapResponse),
+    NotFound,
+}
+
+/// Outcome of answering a request for a resolved file
+enum Served {
+    Response(ZapResponse),
+    NotFound,
+    /// The file on disk is no longer the one that was resolved
+    Stale,
+}
+
+/// A precompressed `.br` / `.gz` sibling chosen for a request
+struct Precompressed {
+    encoding: &'static str,
+    path: PathBuf,
+    size: u64,
+}
+
+/// Device and inode of a file, to tell when a path names a different file
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(meta: &std::fs::Metadata) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+    (meta.dev(), meta.ino())
+}
+
+/// Not available here; cache hits re-check the root instead
+#[cfg(not(unix))]
+fn file_id(_meta: &std::fs::Metadata) -> FileId {
+    (0, 0)
+}
+
+/// Resolved files keyed by requested path, shared by clones of a handler
+#[derive(Debug, Default)]
+struct MetadataCache {
+    entries: Mutex<HashMap<PathBuf, (ResolvedFile, Instant)>>,
+    /// Path metadata calls (`stat`, `canonicalize`) made by the handler
+    disk_stats: AtomicUsize,
+    /// Content hashes for strong ETags, valid while the file is unchanged
+    strong_etags: Mutex<HashMap<PathBuf, (FileId, FileMetadata, String)>>,
+    /// Files hashed for a strong ETag
+    hashes: AtomicUsize,
+}
+
+impl MetadataCache {
+    fn get(&self, path: &Path, ttl: Duration) -> Option<ResolvedFile> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(path) {
+            Some((file, cached_at)) if cached_at.elapsed() < ttl => Some(file.clone()),
+            Some(_) => {
+                entries.remove(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, path: PathBuf, file: ResolvedFile) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(path, (file, Instant::now()));
+    }
+
+    fn remove(&self, path: &Path) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(path);
+    }
+
+    fn strong_etag(&self, path: &Path, id: FileId, meta: &FileMetadata) -> Option<String> {
+        let etags = self.strong_etags.lock().unwrap_or_else(|e| e.into_inner());
+        etags
+            .get(path)
+            .filter(|(cached_id, cached_meta, _)| *cached_id == id && cached_meta == meta)
+            .map(|(_, _, etag)| etag.clone())
+    }
+
+    fn insert_strong_etag(&self, path: PathBuf, id: FileId, meta: FileMetadata, etag: String) {
+        let mut etags = self.strong_etags.lock().unwrap_or_else(|e| e.into_inner());
+        etags.insert(path, (id, meta, etag));
+    }
+}
+
 impl StaticHandler {
     /// Create a new static handler
     pub fn new<P: Into<PathBuf>>(prefix: &str, directory: P) -> Self {
@@ -84,6 +261,8 @@ impl StaticHandler {
             prefix: prefix.to_string(),
             directory: directory.into(),
             options: StaticOptions::default(),
+            metadata_cache: Arc::default(),
+            canonical_root: Arc::default(),
         }
     }
 
@@ -97,6 +276,8 @@ impl StaticHandler {
             prefix: prefix.to_string(),
             directory: directory.into(),
             options,
+            metadata_cache: Arc::default(),
+            canonical_root: Arc::default(),
         }
     }
 
@@ -110,43 +291,105 @@ impl StaticHandler {
         &self,
         path: &str,
         request_headers: &HashMap<String, String>,
+    ) -> Result<Option<ZapResponse>, ZapError> {
+        self.serve(path, request_headers, false).await
+    }
+
+    /// Handle a `HEAD` request: the same headers as `GET`, taken from file
+    /// metadata, with an empty body
+    ///
+    /// Only a strong ETag not yet worked out for the file's current version
+    /// reads its contents; the hash is then shared with `GET`.
+    pub async fn handle_head(
+        &self,
+        path: &str,
+        request_headers: &HashMap<String, String>,
+    ) -> Result<Option<ZapResponse>, ZapError> {
+        self.serve(path, request_headers, true).await
+    }
+
+    async fn serve(
+        &self,
+        path: &str,
+        request_headers: &HashMap<String, String>,
+        head: bool,
     ) -> Result<Option<ZapResponse>, ZapError> {
         if !path.starts_with(&self.prefix) {
             return Ok(None);
         }
 
         let file_path = path.strip_prefix(&self.prefix).unwrap_or("");
-        // Handle empty path or root
-        let file_path = if file_path.is_empty() || file_path == "/" {
-            "index.html"
-        } else {
-            file_path.trim_start_matches('/')
-        };
-        let full_path = self.directory.join(file_path);
 
-        // Security check: ensure path doesn't escape the directory
-        let canonical_dir = self.directory.canonicalize().unwrap_or_else(|_| self.directory.clone());
-        let canonical_path = full_path.canonicalize();
+        // Security check: `..` (including percent-encoded forms) must stay inside the root
+        let requested = match resolve_request_path(&self.directory, file_path) {
+            Some(resolved) => resolved,
+            None => return Ok(Some(ZapResponse::Custom(Response::forbidden("Access denied")))),
+        };
 
-        if let Ok(canonical) = &canonical_path {
-            if !canonical.starts_with(&canonical_dir) {
-                return Ok(Some(ZapResponse::Custom(Response::forbidden("Access denied"))));
+        let ttl = self.options.metadata_cache_ttl;
+        if let Some(file) = ttl.and_then(|ttl| self.metadata_cache.get(&requested, ttl)) {
+            match self.respond(file, request_headers, head, true).await? {
+                Served::Response(response) => return Ok(Some(response)),
+                // Deleted since its metadata was cached
+                Served::NotFound => {
+                    self.metadata_cache.remove(&requested);
+                    return Ok(None);
+                }
+                // A different file now: look it up again, re-checking the root
+                Served::Stale => self.metadata_cache.remove(&requested),
             }
         }
 
-        // Get file metadata
-        let metadata = match tokio::fs::metadata(&full_path).await {
-            Ok(m) if m.is_file() => m,
-            _ => return Ok(None),
+        let file = match self.lookup(path, &requested).await? {
+            Lookup::File(file) => {
+                if ttl.is_some() {
+                    self.metadata_cache.insert(requested.clone(), file.clone());
+                }
+                file
+            }
+            Lookup::Respond(response) => return Ok(Some(response)),
+            Lookup::NotFound => return Ok(None),
         };
+        match self.respond(file, request_headers, head, false).await? {
+            Served::Response(response) => Ok(Some(response)),
+            Served::NotFound | Served::Stale => Ok(None),
+        }
+    }
 
-        let file_meta = FileMetadata {
-            size: metadata.len(),
-            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+    /// Answer a request for a resolved file
+    ///
+    /// With `verify` (a cache hit) the opened file must still be the one that
+    /// was cached, with the same size; otherwise [`Served::Stale`] is returned
+    /// without sending anything.
+    async fn respond(
+        &self,
+        file: ResolvedFile,
+        request_headers: &HashMap<String, String>,
+        head: bool,
+        verify: bool,
+    ) -> Result<Served, ZapError> {
+        let ResolvedFile { path: full_path, meta: file_meta, etag: identity_etag, content_type, .. } = &file;
+
+        // Pick a precompressed sibling if the client accepts one
+        let encoded = if self.options.precompressed {
+            let accept_encoding = request_headers.get("accept-encoding")
+                .or_else(|| request_headers.get("Accept-Encoding"));
+            match accept_encoding {
+                Some(accept) => self.find_precompressed(full_path, accept).await,
+                None => None,
+            }
+        } else {
+            None
         };
 
-        // Generate ETag if enabled
-        let etag = self.generate_etag(&file_meta, &full_path).await;
+        // ETag is tagged per encoding so caches keep variants apart
+        let etag = match identity_etag {
+            Some(tag) => match &encoded {
+                Some(sibling) => Some(encoded_etag(tag, sibling.encoding)),
+                None => Some(tag.clone()),
+            },
+            None => None,
+        };
 
         // Generate Last-Modified header value
         let last_modified = if self.options.enable_last_modified {
@@ -156,99 +399,282 @@ impl StaticHandler {
         };
 
         // Check conditional request headers
-        if let Some(ref etag_value) = etag {
+        let if_none_match = request_headers.get("if-none-match")
+            .or_else(|| request_headers.get("If-None-Match"));
+
+        if let (Some(ref etag_value), Some(if_none_match)) = (&etag, if_none_match) {
             // Check If-None-Match
-            if let Some(if_none_match) = request_headers.get("if-none-match")
-                .or_else(|| request_headers.get("If-None-Match"))
-            {
-                if etags_match(if_none_match, etag_value) {
-                    return Ok(Some(self.not_modified_response(&etag, &last_modified)));
-                }
+            if etags_match(if_none_match, etag_value) {
+                return Ok(Served::Response(self.not_modified_response(&etag, &last_modified)));
             }
         }
 
-        // Check If-Modified-Since
+        // Check If-Modified-Since (ignored when If-None-Match is present, RFC 7232 §3.3)
         if let Some(ref last_mod) = last_modified {
             if let Some(if_modified_since) = request_headers.get("if-modified-since")
                 .or_else(|| request_headers.get("If-Modified-Since"))
+                .filter(|_| if_none_match.is_none())
             {
                 if let Some(since_time) = parse_http_date(if_modified_since) {
-                    // File not modified since the specified time
-                    if file_meta.modified <= since_time {
-                        return Ok(Some(self.not_modified_response(&etag, &Some(last_mod.clone()))));
+                    // HTTP dates have second granularity, so drop sub-second mtime precision
+                    if truncate_to_secs(file_meta.modified) <= since_time {
+                        return Ok(Served::Response(self.not_modified_response(&etag, &Some(last_mod.clone()))));
                     }
                 }
             }
         }
 
-        // Read file and serve
-        match tokio::fs::read(&full_path).await {
-            Ok(contents) => {
-                let content_type = mime_guess::from_path(&full_path)
-                    .first_or_octet_stream()
-                    .to_string();
+        // Content type always comes from the original file, not the .br/.gz sibling
+        let mut headers = vec![("Content-Type".to_string(), content_type.clone())];
 
-                let mut response = Response::new()
-                    .status(StatusCode::OK)
-                    .content_type(content_type)
-                    .body(contents);
+        if let Some(sibling) = &encoded {
+            headers.push(("Content-Encoding".to_string(), sibling.encoding.to_string()));
+        }
 
-                // Add cache control if specified
-                if let Some(cache_control) = &self.options.cache_control {
-                    response = response.cache_control(cache_control);
-                }
+        // Add cache control if specified
+        if let Some(cache_control) = &self.options.cache_control {
+            headers.push(("Cache-Control".to_string(), cache_control.clone()));
+        }
 
-                // Add ETag header
-                if let Some(etag_value) = etag {
-                    response = response.header("ETag", etag_value);
-                }
+        // Add ETag header
+        if let Some(etag_value) = etag {
+            headers.push(("ETag".to_string(), etag_value));
+        }
 
-                // Add Last-Modified header
-                if let Some(last_mod) = last_modified {
-                    response = response.header("Last-Modified", last_mod);
-                }
+        // Add Last-Modified header
+        if let Some(last_mod) = last_modified {
+            headers.push(("Last-Modified".to_string(), last_mod));
+        }
 
-                // Add custom headers
-                for (key, value) in &self.options.headers {
-                    response = response.header(key, value);
-                }
+        // Add custom headers
+        for (key, value) in &self.options.headers {
+            headers.push((key.clone(), value.clone()));
+        }
+
+        let (read_path, size) = match &encoded {
+            Some(sibling) => (&sibling.path, sibling.size),
+            None => (full_path, file_meta.size),
+        };
+
+        // HEAD stops at metadata; Content-Length is set explicitly since there's no body
+        if head {
+            let mut response = Response::new()
+                .status(StatusCode::OK)
+                .header("Content-Length", size.to_string());
+            for (key, value) in headers {
+                response = response.header(key, value);
+            }
+            return Ok(Served::Response(ZapResponse::Custom(self.vary(response))));
+        }
+
+        let internal_error = || Served::Response(ZapResponse::Custom(
+            Response::internal_server_error("Failed to read file"),
+        ));
+        let mut opened = match tokio::fs::File::open(read_path).await {
+            Ok(opened) => opened,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Served::NotFound),
+            Err(_) => return Ok(internal_error()),
+        };
 
-                Ok(Some(ZapResponse::Custom(response)))
+        // The path opened may have been swapped (for a symlink out of the
+        // root, say) since it was cached, so check it is still that file
+        if verify && encoded.is_none() {
+            let same = match opened.metadata().await {
+                Ok(opened_meta) => file_id(&opened_meta) == file.id && opened_meta.len() == size,
+                Err(_) => return Ok(internal_error()),
+            };
+            if !same || (cfg!(not(unix)) && self.escapes_root(full_path)) {
+                return Ok(Served::Stale);
             }
-            Err(_) => Ok(Some(ZapResponse::Custom(
-                Response::internal_server_error("Failed to read file"),
-            ))),
-        }
-    }
-
-    /// Generate ETag based on configured strategy
-    async fn generate_etag(&self, meta: &FileMetadata, path: &PathBuf) -> Option<String> {
-        match self.options.etag_strategy {
-            ETagStrategy::Weak => {
-                // Weak ETag from size + mtime
-                let mtime_secs = meta
-                    .modified
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-                Some(format!("W/\"{:x}-{:x}\"", meta.size, mtime_secs))
+        }
+
+        // Large files are streamed in chunks rather than read into memory
+        if size > STREAM_THRESHOLD {
+            let reader = ReaderStream::with_capacity(opened.take(size), FILE_CHUNK_SIZE);
+            let mut response = StreamedBody::from_stream(reader).header("Content-Length", size.to_string());
+            for (key, value) in headers {
+                response = response.header(key, value);
             }
-            ETagStrategy::Strong => {
-                // Strong ETag using SHA256 hash of content
-                match tokio::fs::read(path).await {
-                    Ok(contents) => {
-                        use sha2::{Digest, Sha256};
-                        let mut hasher = Sha256::new();
-                        hasher.update(&contents);
-                        let hash = hasher.finalize();
-                        // Use first 16 bytes (32 hex chars) for reasonable length
-                        Some(format!("\"{}\"", hex::encode(&hash[..16])))
-                    }
-                    Err(_) => None,
+            let response = ZapResponse::Streamed(response);
+            return Ok(Served::Response(if self.options.precompressed {
+                response.vary("Accept-Encoding")
+            } else {
+                response
+            }));
+        }
+
+        let mut contents = Vec::with_capacity(size as usize);
+        if opened.read_to_end(&mut contents).await.is_err() {
+            return Ok(internal_error());
+        }
+        // Truncated or grown while being read
+        if verify && contents.len() as u64 != size {
+            return Ok(Served::Stale);
+        }
+
+        let mut response = Response::new()
+            .status(StatusCode::OK)
+            .body(contents);
+
+        for (key, value) in headers {
+            response = response.header(key, value);
+        }
+
+        Ok(Served::Response(ZapResponse::Custom(self.vary(response))))
+    }
+
+    /// Note that the response depends on `Accept-Encoding` when precompressed
+    /// siblings may be picked, keeping any `Vary` from custom headers
+    fn vary(&self, response: Response) -> Response {
+        if self.options.precompressed {
+            response.vary("Accept-Encoding")
+        } else {
+            response
+        }
+    }
+
+    /// Resolve a request path to the file serving it, checking the disk
+    async fn lookup(&self, request_path: &str, requested: &Path) -> Result<Lookup, ZapError> {
+        // Security check: symlinks must not lead outside the root either
+        if self.escapes_root(requested) {
+            return Ok(Lookup::Respond(ZapResponse::Custom(Response::forbidden("Access denied"))));
+        }
+
+        // Get file metadata
+        let mut full_path = requested.to_path_buf();
+        let mut metadata = match self.stat(&full_path).await {
+            Ok(m) => m,
+            Err(_) => return Ok(Lookup::NotFound),
+        };
+
+        // Directories serve their index.html, or a listing when enabled
+        if metadata.is_dir() {
+            let index_path = full_path.join("index.html");
+            match self.stat(&index_path).await {
+                Ok(m) if m.is_file() => {
+                    full_path = index_path;
+                    metadata = m;
+                }
+                _ if self.options.directory_listing => {
+                    return self.directory_listing(request_path, &full_path).await.map(Lookup::Respond);
+                }
+                _ => return Ok(Lookup::NotFound),
+            }
+        } else if !metadata.is_file() {
+            return Ok(Lookup::NotFound);
+        }
+
+        let meta = FileMetadata {
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        };
+        let id = file_id(&metadata);
+        let etag = match self.options.etag_strategy {
+            ETagStrategy::Strong => self.strong_etag(&full_path, id, &meta).await,
+            strategy => generate_etag(strategy, &meta, &full_path).await,
+        };
+        let content_type = content_type_for(&full_path, &self.options.default_mime);
+
+        Ok(Lookup::File(ResolvedFile { path: full_path, id, meta, etag, content_type }))
+    }
+
+    /// Strong ETag for a file, hashing it only when it changed since the last hash
+    ///
+    /// `GET` and `HEAD` share these, so both send the same validator.
+    async fn strong_etag(&self, path: &Path, id: FileId, meta: &FileMetadata) -> Option<String> {
+        if let Some(etag) = self.metadata_cache.strong_etag(path, id, meta) {
+            return Some(etag);
+        }
+        self.metadata_cache.hashes.fetch_add(1, Ordering::Relaxed);
+        let etag = generate_etag(ETagStrategy::Strong, meta, path).await?;
+        self.metadata_cache.insert_strong_etag(path.to_path_buf(), id, meta.clone(), etag.clone());
+        Some(etag)
+    }
+
+    /// Path metadata, counted so tests can see which requests touch the disk
+    async fn stat(&self, path: &Path) -> std::io::Result<std::fs::Metadata> {
+        self.metadata_cache.disk_stats.fetch_add(1, Ordering::Relaxed);
+        tokio::fs::metadata(path).await
+    }
+
+    /// Whether `path` resolves, through symlinks, to somewhere outside the root
+    fn escapes_root(&self, path: &Path) -> bool {
+        self.metadata_cache.disk_stats.fetch_add(1, Ordering::Relaxed);
+        match path.canonicalize() {
+            Ok(canonical) => !canonical.starts_with(self.canonical_root()),
+            Err(_) => false,
+        }
+    }
+
+    /// The root directory with symlinks resolved, worked out once it exists
+    fn canonical_root(&self) -> &Path {
+        if let Some(root) = self.canonical_root.get() {
+            return root;
+        }
+        match self.directory.canonicalize() {
+            Ok(root) => self.canonical_root.get_or_init(|| root),
+            Err(_) => &self.directory,
+        }
+    }
+
+    /// Find the preferred precompressed sibling of `path` accepted by the client
+    async fn find_precompressed(&self, path: &Path, accept_encoding: &str) -> Option<Precompressed> {
+        for (encoding, suffix) in PRECOMPRESSED_ENCODINGS {
+            if !accepts_encoding(accept_encoding, encoding) {
+                continue;
+            }
+
+            let mut candidate = path.as_os_str().to_owned();
+            candidate.push(".");
+            candidate.push(suffix);
+            let candidate = PathBuf::from(candidate);
+
+            if let Ok(meta) = self.stat(&candidate).await {
+                if meta.is_file() {
+                    return Some(Precompressed { encoding, path: candidate, size: meta.len() });
                 }
             }
-            ETagStrategy::None => None,
         }
+        None
+    }
+
+    /// Render an HTML listing of a directory's entries
+    async fn directory_listing(&self, request_path: &str, dir: &Path) -> Result<ZapResponse, ZapError> {
+        let mut read_dir = tokio::fs::read_dir(dir).await?;
+        let mut entries = Vec::new();
+
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !self.options.show_hidden {
+                continue;
+            }
+            let meta = match entry.metadata().await {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            entries.push(ListingEntry {
+                name,
+                is_dir: meta.is_dir(),
+                size: meta.len(),
+                modified: meta.modified().ok(),
+            });
+        }
+
+        // Directories first, then alphabetical
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+        let html = render_listing_html(request_path, &self.prefix, &entries);
+
+        let mut response = Response::new()
+            .status(StatusCode::OK)
+            .content_type("text/html; charset=utf-8")
+            .body(html);
+
+        for (key, value) in &self.options.headers {
+            response = response.header(key, value);
+        }
+
+        Ok(ZapResponse::Custom(response))
     }
 
     /// Generate a 304 Not Modified response
@@ -274,7 +700,7 @@ impl StaticHandler {
             response = response.header("Last-Modified", last_mod);
         }
 
-        ZapResponse::Custom(response)
+        ZapResponse::Custom(self.vary(response))
     }
 }
 
@@ -291,15 +717,224 @@ pub async fn handle_static_files_with_headers(
     handlers: &[StaticHandler],
     path: &str,
     request_headers: &HashMap<String, String>,
+) -> Result<Option<ZapResponse>, ZapError> {
+    handle_static_files_with_method(handlers, Method::GET, path, request_headers).await
+}
+
+/// Handle static file requests, answering `HEAD` from metadata alone
+pub async fn handle_static_files_with_method(
+    handlers: &[StaticHandler],
+    method: Method,
+    path: &str,
+    request_headers: &HashMap<String, String>,
 ) -> Result<Option<ZapResponse>, ZapError> {
     for handler in handlers {
-        if let Some(response) = handler.handle_with_headers(path, request_headers).await? {
+        let response = match method {
+            Method::HEAD => handler.handle_head(path, request_headers).await?,
+            _ => handler.handle_with_headers(path, request_headers).await?,
+        };
+        if let Some(response) = response {
             return Ok(Some(response));
         }
     }
     Ok(None)
 }
 
+// ============================================================================
+// Precompressed Variants
+// ============================================================================
+
+/// Check whether an Accept-Encoding header allows an encoding (q=0 means refused)
+fn accepts_encoding(accept_encoding: &str, encoding: &str) -> bool {
+    let mut wildcard = false;
+
+    for entry in accept_encoding.split(',') {
+        let mut parts = entry.split(';');
+        let name = parts.next().unwrap_or("").trim();
+        let refused = parts.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .map(|q| q <= 0.0)
+                .unwrap_or(false)
+        });
+
+        if name.eq_ignore_ascii_case(encoding) {
+            return !refused;
+        }
+        if name == "*" {
+            wildcard = !refused;
+        }
+    }
+
+    wildcard
+}
+
+/// Generate an ETag with the given strategy
+async fn generate_etag(strategy: ETagStrategy, meta: &FileMetadata, path: &Path) -> Option<String> {
+    match strategy {
+        ETagStrategy::Weak => {
+            // Weak ETag from size + mtime
+            let mtime_secs = meta
+                .modified
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(format!("W/\"{:x}-{:x}\"", meta.size, mtime_secs))
+        }
+        ETagStrategy::Strong => {
+            // Strong ETag using SHA256 hash of content, read in chunks
+            use sha2::{Digest, Sha256};
+            use tokio::io::AsyncReadExt;
+
+            let mut file = tokio::fs::File::open(path).await.ok()?;
+            let mut hasher = Sha256::new();
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                match file.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(n) => hasher.update(&buf[..n]),
+                    Err(_) => return None,
+                }
+            }
+            let hash = hasher.finalize();
+            // Use first 16 bytes (32 hex chars) for reasonable length
+            Some(format!("\"{}\"", hex::encode(&hash[..16])))
+        }
+        ETagStrategy::None => None,
+    }
+}
+
+/// Derive a per-encoding ETag so compressed and identity variants never collide
+fn encoded_etag(etag: &str, encoding: &str) -> String {
+    match etag.strip_suffix('"') {
+        Some(stripped) => format!("{}-{}\"", stripped, encoding),
+        None => format!("{}-{}", etag, encoding),
+    }
+}
+
+// ============================================================================
+// Content-Type Detection
+// ============================================================================
+
+/// Resolve the Content-Type for a file from its extension
+///
+/// Checks [`MIME_OVERRIDES`] first, then `mime_guess`, then falls back to `default_mime`.
+pub(crate) fn content_type_for(path: &Path, default_mime: &str) -> String {
+    let ext = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.to_ascii_lowercase(),
+        None => return default_mime.to_string(),
+    };
+
+    if let Some((_, mime)) = MIME_OVERRIDES.iter().find(|(e, _)| *e == ext) {
+        return (*mime).to_string();
+    }
+
+    mime_guess::from_ext(&ext)
+        .first()
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| default_mime.to_string())
+}
+
+// ============================================================================
+// Directory Listing
+// ============================================================================
+
+/// Build the HTML page for a directory listing
+fn render_listing_html(request_path: &str, prefix: &str, entries: &[ListingEntry]) -> String {
+    let base = if request_path.ends_with('/') {
+        request_path.to_string()
+    } else {
+        format!("{}/", request_path)
+    };
+    let title = html_escape(&base);
+
+    let mut html = String::with_capacity(512 + entries.len() * 128);
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Index of {}</title>\n</head>\n<body>\n", title));
+    html.push_str(&format!("<h1>Index of {}</h1>\n<table>\n", title));
+    html.push_str("<tr><th>Name</th><th>Size</th><th>Last Modified</th></tr>\n");
+
+    // Parent link, unless we're at the mount root
+    if base.trim_end_matches('/') != prefix.trim_end_matches('/') {
+        html.push_str("<tr><td><a href=\"../\">../</a></td><td>-</td><td>-</td></tr>\n");
+    }
+
+    for entry in entries {
+        let suffix = if entry.is_dir { "/" } else { "" };
+        let href = format!("{}{}{}", base, encode_path_segment(&entry.name), suffix);
+        let size = if entry.is_dir { "-".to_string() } else { entry.size.to_string() };
+        let modified = entry.modified.map(format_http_date).unwrap_or_else(|| "-".to_string());
+
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&href),
+            html_escape(&entry.name),
+            suffix,
+            size,
+            modified
+        ));
+    }
+
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Escape text for inclusion in HTML content and attributes
+fn html_escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Resolve a request path below `root` without touching the filesystem
+///
+/// Percent-escapes are decoded first so `..%2f` can't slip past, then `.` and
+/// `..` segments are normalized lexically. Returns `None` if the path would
+/// climb above `root` or isn't valid UTF-8 / contains NUL.
+fn resolve_request_path(root: &Path, request_path: &str) -> Option<PathBuf> {
+    let decoded = zap_core::percent_decode(request_path, false)?;
+    if decoded.contains('\0') {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = Vec::new();
+    // Backslashes count as separators so Windows-style `..\` is caught too
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    Some(segments.iter().fold(root.to_path_buf(), |path, segment| path.join(segment)))
+}
+
+/// Percent-encode a single path segment (RFC 3986 unreserved characters pass through)
+fn encode_path_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
 // ============================================================================
 // HTTP Date Formatting (RFC 7231)
 // ============================================================================
@@ -358,6 +993,16 @@ fn format_http_date(time: SystemTime) -> String {
     )
 }
 
+/// Truncate a SystemTime to whole seconds (HTTP-date precision)
+fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => UNIX_EPOCH + Duration::from_secs(d.as_secs()),
+        Err(_) => UNIX_EPOCH,
+    }
+}
+
 /// Convert days since epoch to year, month, day, weekday
 fn days_to_ymd(days: i64) -> (i32, u32, u32, u32) {
     // Weekday: Thursday = 0 for Jan 1, 1970
@@ -547,6 +1192,306 @@ mod tests {
         assert!(invalid.is_none());
     }
 
+    #[test]
+    fn test_accepts_encoding() {
+        assert!(accepts_encoding("gzip, deflate, br", "br"));
+        assert!(accepts_encoding("GZIP", "gzip"));
+        assert!(!accepts_encoding("gzip;q=0, br", "gzip"));
+        assert!(accepts_encoding("*", "br"));
+        assert!(!accepts_encoding("*, br;q=0", "br"));
+        assert!(!accepts_encoding("deflate", "gzip"));
+    }
+
+    #[test]
+    fn test_encoded_etag() {
+        assert_eq!(encoded_etag("W/\"10-5\"", "br"), "W/\"10-5-br\"");
+        assert_eq!(encoded_etag("\"abc\"", "gzip"), "\"abc-gzip\"");
+    }
+
+    #[tokio::test]
+    async fn test_precompressed_gzip_served() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app.js"), b"console.log('plain');").unwrap();
+        let gz_bytes: &[u8] = &[0x1f, 0x8b, 0x08, 0x00, 0xde, 0xad, 0xbe, 0xef];
+        std::fs::write(dir.path().join("app.js.gz"), gz_bytes).unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/assets",
+            dir.path(),
+            StaticOptions {
+                precompressed: true,
+                ..Default::default()
+            },
+        );
+
+        let mut headers = HashMap::new();
+        headers.insert("accept-encoding".to_string(), "gzip, deflate".to_string());
+
+        let response = handler.handle_with_headers("/assets/app.js", &headers).await.unwrap();
+        match response {
+            Some(ZapResponse::Custom(resp)) => {
+                assert_eq!(resp.status, StatusCode::OK);
+                assert_eq!(resp.headers.get("Content-Encoding").map(String::as_str), Some("gzip"));
+                assert_eq!(resp.headers.get("Vary").map(String::as_str), Some("Accept-Encoding"));
+                assert!(resp.headers.get("Content-Type").unwrap().contains("javascript"));
+                match resp.body {
+                    zap_core::ResponseBody::Bytes(bytes) => assert_eq!(&bytes[..], gz_bytes),
+                    other => panic!("Expected bytes body, got {:?}", other),
+                }
+            }
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+
+        // Without Accept-Encoding the identity file is served
+        let response = handler.handle_with_headers("/assets/app.js", &HashMap::new()).await.unwrap();
+        match response {
+            Some(ZapResponse::Custom(resp)) => {
+                assert!(!resp.headers.contains_key("Content-Encoding"));
+                assert_eq!(resp.headers.get("Vary").map(String::as_str), Some("Accept-Encoding"));
+            }
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metadata_cache_skips_disk_until_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app.css"), b"a{}").unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/assets",
+            dir.path(),
+            StaticOptions {
+                metadata_cache_ttl: Some(std::time::Duration::from_millis(200)),
+                ..Default::default()
+            },
+        );
+        let stats = || handler.metadata_cache.disk_stats.load(Ordering::Relaxed);
+        let fetch = || async {
+            match handler.handle("/assets/app.css").await.unwrap() {
+                Some(ZapResponse::Custom(resp)) => (resp.headers.get("ETag").cloned().unwrap(), resp.body),
+                other => panic!("Expected custom response, got {:?}", other),
+            }
+        };
+
+        let (first_etag, _) = fetch().await;
+        let looked_up = stats();
+        assert!(looked_up > 0);
+        let (second_etag, _) = fetch().await;
+        assert_eq!(stats(), looked_up, "second request should be served from the cache");
+        assert_eq!(first_etag, second_etag);
+
+        // Once the TTL passes a changed file is described afresh
+        std::fs::write(dir.path().join("app.css"), b"body { color: red; }").unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        let (changed_etag, body) = fetch().await;
+        assert!(stats() > looked_up);
+        assert_ne!(changed_etag, first_etag);
+        assert!(matches!(body, zap_core::ResponseBody::Bytes(ref b) if b == b"body { color: red; }"));
+
+        // A file deleted within the TTL is not found rather than a read error
+        std::fs::remove_file(dir.path().join("app.css")).unwrap();
+        let looked_up = stats();
+        assert!(handler.handle("/assets/app.css").await.unwrap().is_none());
+        assert_eq!(stats(), looked_up, "the cached entry is found missing when opened");
+        assert!(handler.handle("/assets/app.css").await.unwrap().is_none());
+        assert!(stats() > looked_up);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_metadata_cache_hits_verify_opened_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("public");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("notes.txt"), b"short").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), b"secret").unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/assets",
+            &root,
+            StaticOptions {
+                metadata_cache_ttl: Some(std::time::Duration::from_secs(60)),
+                ..Default::default()
+            },
+        );
+        let stats = || handler.metadata_cache.disk_stats.load(Ordering::Relaxed);
+        let get = || async {
+            match handler.handle("/assets/notes.txt").await.unwrap() {
+                Some(ZapResponse::Custom(resp)) => resp,
+                other => panic!("Expected custom response, got {:?}", other),
+            }
+        };
+        let has_body = |resp: &Response, expected: &[u8]| {
+            matches!(resp.body, zap_core::ResponseBody::Bytes(ref b) if b == expected)
+        };
+
+        assert!(has_body(&get().await, b"short"));
+        let looked_up = stats();
+
+        // Hits open the file without any metadata calls; HEAD doesn't even open it
+        assert!(has_body(&get().await, b"short"));
+        match handler.handle_head("/assets/notes.txt", &HashMap::new()).await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => assert_eq!(resp.headers["Content-Length"], "5"),
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+        assert_eq!(stats(), looked_up);
+
+        // A GET hit whose file changed size is looked up again
+        std::fs::write(root.join("notes.txt"), b"a good deal longer").unwrap();
+        assert!(has_body(&get().await, b"a good deal longer"));
+        assert!(stats() > looked_up);
+
+        // Swapping the cached file for a symlink out of the root is refused
+        std::fs::remove_file(root.join("notes.txt")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("notes.txt")).unwrap();
+        assert_eq!(get().await.status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_truncate_to_secs() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_445_412_480_750);
+        assert_eq!(truncate_to_secs(time), UNIX_EPOCH + Duration::from_secs(1_445_412_480));
+    }
+
+    #[tokio::test]
+    async fn test_if_modified_since_returns_304() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("style.css"), b"body { color: red; }").unwrap();
+
+        let handler = StaticHandler::new("/static", dir.path());
+
+        // First fetch: note the Last-Modified header
+        let last_modified = match handler.handle("/static/style.css").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => {
+                assert_eq!(resp.status, StatusCode::OK);
+                resp.headers.get("Last-Modified").cloned().expect("Last-Modified header")
+            }
+            other => panic!("Expected custom response, got {:?}", other),
+        };
+
+        // Re-request with the same date: not modified
+        let mut headers = HashMap::new();
+        headers.insert("if-modified-since".to_string(), last_modified.clone());
+        match handler.handle_with_headers("/static/style.css", &headers).await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => {
+                assert_eq!(resp.status, StatusCode::NOT_MODIFIED);
+                assert_eq!(resp.headers.get("Last-Modified"), Some(&last_modified));
+            }
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+
+        // A date well before the mtime serves the full file
+        headers.insert("if-modified-since".to_string(), "Thu, 01 Jan 1970 00:00:00 GMT".to_string());
+        match handler.handle_with_headers("/static/style.css", &headers).await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => assert_eq!(resp.status, StatusCode::OK),
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_content_type_for() {
+        let default = "application/octet-stream";
+        assert_eq!(content_type_for(Path::new("app.wasm"), default), "application/wasm");
+        assert_eq!(content_type_for(Path::new("photo.WEBP"), default), "image/webp");
+        assert_eq!(content_type_for(Path::new("photo.avif"), default), "image/avif");
+        assert_eq!(content_type_for(Path::new("font.woff2"), default), "font/woff2");
+        assert_eq!(content_type_for(Path::new("data.json"), default), "application/json");
+        assert_eq!(content_type_for(Path::new("logo.svg"), default), "image/svg+xml");
+        assert_eq!(content_type_for(Path::new("doc.pdf"), default), "application/pdf");
+        assert_eq!(content_type_for(Path::new("blob.zzunknown"), default), default);
+        assert_eq!(content_type_for(Path::new("Makefile"), "text/plain"), "text/plain");
+    }
+
+    #[tokio::test]
+    async fn test_served_content_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases = [
+            ("module.wasm", "application/wasm"),
+            ("image.webp", "image/webp"),
+            ("image.avif", "image/avif"),
+            ("font.woff2", "font/woff2"),
+            ("data.json", "application/json"),
+            ("icon.svg", "image/svg+xml"),
+            ("mystery.qqq", "application/x-custom"),
+        ];
+        for (name, _) in &cases {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        let handler = StaticHandler::new_with_options(
+            "/s",
+            dir.path(),
+            StaticOptions {
+                default_mime: "application/x-custom".to_string(),
+                ..Default::default()
+            },
+        );
+
+        for (name, expected) in &cases {
+            match handler.handle(&format!("/s/{}", name)).await.unwrap() {
+                Some(ZapResponse::Custom(resp)) => {
+                    assert_eq!(resp.headers.get("Content-Type").map(String::as_str), Some(*expected), "{}", name);
+                }
+                other => panic!("Expected custom response for {}, got {:?}", name, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_directory_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alpha.txt"), b"alpha").unwrap();
+        std::fs::write(dir.path().join("beta file.txt"), b"beta!").unwrap();
+        std::fs::write(dir.path().join(".secret"), b"hidden").unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/files",
+            dir.path(),
+            StaticOptions {
+                directory_listing: true,
+                ..Default::default()
+            },
+        );
+
+        let html = match handler.handle("/files/").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => {
+                assert_eq!(resp.status, StatusCode::OK);
+                assert!(resp.headers.get("Content-Type").unwrap().starts_with("text/html"));
+                match resp.body {
+                    zap_core::ResponseBody::Bytes(bytes) => String::from_utf8(bytes).unwrap(),
+                    other => panic!("Expected bytes body, got {:?}", other),
+                }
+            }
+            other => panic!("Expected custom response, got {:?}", other),
+        };
+
+        assert!(html.contains("alpha.txt"));
+        assert!(html.contains("beta file.txt"));
+        assert!(html.contains("href=\"/files/beta%20file.txt\""));
+        assert!(html.contains("href=\"/files/nested/\""));
+        assert!(!html.contains(".secret"));
+    }
+
+    #[tokio::test]
+    async fn test_directory_without_listing_or_index() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alpha.txt"), b"alpha").unwrap();
+
+        let handler = StaticHandler::new("/files", dir.path());
+        assert!(handler.handle("/files/").await.unwrap().is_none());
+
+        // An index file takes precedence over listing
+        std::fs::write(dir.path().join("index.html"), b"<h1>home</h1>").unwrap();
+        match handler.handle("/files/").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => assert_eq!(resp.status, StatusCode::OK),
+            other => panic!("Expected custom response, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_static_handler_creation() {
         let handler = StaticHandler::new("/assets", "./public");
@@ -569,4 +1514,174 @@ mod tests {
         assert_eq!(handler.options.etag_strategy, ETagStrategy::Strong);
         assert!(!handler.options.enable_last_modified);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_head_uses_metadata_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app.js"), b"console.log(1);").unwrap();
+        // Sparse, so only a read (not a stat) would touch a terabyte of data
+        let huge = std::fs::File::create(dir.path().join("huge.bin")).unwrap();
+        huge.set_len(1 << 40).unwrap();
+
+        let handlers = [StaticHandler::new("/assets", dir.path())];
+        for (path, length) in [("/assets/app.js", 15u64), ("/assets/huge.bin", 1 << 40)] {
+            let response = handle_static_files_with_method(&handlers, Method::HEAD, path, &HashMap::new())
+                .await
+                .unwrap();
+            let Some(ZapResponse::Custom(response)) = response else {
+                panic!("expected a buffered HEAD response for {}", path);
+            };
+            assert_eq!(response.status, StatusCode::OK);
+            assert_eq!(response.headers["Content-Length"], length.to_string());
+            assert!(response.headers.contains_key("ETag"));
+            assert!(response.headers.contains_key("Last-Modified"));
+            assert!(matches!(response.body, zap_core::ResponseBody::Empty));
+        }
+        let response = handlers[0].handle_head("/assets/app.js", &HashMap::new()).await.unwrap();
+        assert!(matches!(response, Some(ZapResponse::Custom(ref r)) if r.headers["Content-Type"].starts_with("text/javascript")));
+    }
+
+    #[tokio::test]
+    async fn test_head_with_strong_etag_matches_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.js");
+        std::fs::write(&path, b"console.log(1);").unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/assets",
+            dir.path(),
+            StaticOptions {
+                etag_strategy: ETagStrategy::Strong,
+                ..Default::default()
+            },
+        );
+        let head = |if_none_match: Option<&str>| {
+            let headers: HashMap<String, String> = if_none_match
+                .map(|tag| HashMap::from([("if-none-match".to_string(), tag.to_string())]))
+                .unwrap_or_default();
+            let handler = &handler;
+            async move {
+                match handler.handle_head("/assets/app.js", &headers).await.unwrap() {
+                    Some(ZapResponse::Custom(resp)) => resp,
+                    other => panic!("Expected custom response, got {:?}", other),
+                }
+            }
+        };
+        let hashes = || handler.metadata_cache.hashes.load(Ordering::Relaxed);
+
+        let etag = match handler.handle("/assets/app.js").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => resp.headers["ETag"].clone(),
+            other => panic!("Expected custom response, got {:?}", other),
+        };
+        assert!(etag.starts_with('"'));
+        assert_eq!(hashes(), 1);
+
+        // HEAD sends the GET validator, reusing the hash
+        let response = head(None).await;
+        assert_eq!(response.headers["ETag"], etag);
+        assert_eq!(hashes(), 1);
+
+        // ...so a tag from GET revalidates on HEAD
+        let response = head(Some(&etag)).await;
+        assert_eq!(response.status, StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers["ETag"], etag);
+
+        // A changed file is hashed again
+        std::fs::write(&path, b"console.log(22);").unwrap();
+        let response = head(Some(&etag)).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_ne!(response.headers["ETag"], etag);
+        assert_eq!(hashes(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_large_files_are_streamed() {
+        use http_body_util::BodyExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let contents: Vec<u8> = (0..STREAM_THRESHOLD as usize * 2 + 17).map(|i| (i % 253) as u8).collect();
+        std::fs::write(dir.path().join("video.mp4"), &contents).unwrap();
+        std::fs::write(dir.path().join("small.txt"), b"small").unwrap();
+
+        let handler = StaticHandler::new_with_options(
+            "/media",
+            dir.path(),
+            StaticOptions {
+                etag_strategy: ETagStrategy::Strong,
+                ..Default::default()
+            },
+        );
+
+        let response = handler.handle("/media/video.mp4").await.unwrap().unwrap();
+        assert!(matches!(response, ZapResponse::Streamed(_)));
+
+        let response = response.to_hyper_response();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-length"], contents.len().to_string());
+        assert_eq!(response.headers()["content-type"], "video/mp4");
+        assert!(response.headers()["etag"].to_str().unwrap().starts_with('"'));
+        assert_eq!(response.headers()["cache-control"], "public, max-age=3600");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), contents.as_slice());
+
+        // Small files are still served from memory
+        match handler.handle("/media/small.txt").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => {
+                assert!(matches!(resp.body, zap_core::ResponseBody::Bytes(ref b) if b == b"small"));
+            }
+            other => panic!("expected buffered response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_request_path() {
+        let root = Path::new("/srv/www");
+        assert_eq!(resolve_request_path(root, "/a/./b/../c.txt"), Some(root.join("a").join("c.txt")));
+        assert_eq!(resolve_request_path(root, "/hello%20world.txt"), Some(root.join("hello world.txt")));
+        assert_eq!(resolve_request_path(root, "/../etc/passwd"), None);
+        assert_eq!(resolve_request_path(root, "/a/%2e%2e/%2e%2e/etc"), None);
+        assert_eq!(resolve_request_path(root, "/bad%zz"), None);
+        assert_eq!(resolve_request_path(root, "/nul%00.txt"), None);
+    }
+
+    #[tokio::test]
+    async fn test_traversal_attempts_are_forbidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("public");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("hello world.txt"), b"hi").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), b"secret").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path(), root.join("escape")).unwrap();
+
+        let handler = StaticHandler::new("/assets", &root);
+
+        let mut attempts = vec![
+            "/assets/../../etc/passwd",
+            "/assets/../secret.txt",
+            "/assets/..%2f..%2fetc%2fpasswd",
+            "/assets/%2e%2e/secret.txt",
+            "/assets/%2E%2E%2Fsecret.txt",
+            "/assets/..\\secret.txt",
+            "/assets/..%5csecret.txt",
+        ];
+        if cfg!(unix) {
+            attempts.push("/assets/escape/secret.txt");
+        }
+
+        for attempt in attempts {
+            match handler.handle(attempt).await.unwrap() {
+                Some(ZapResponse::Custom(resp)) => {
+                    assert_eq!(resp.status, StatusCode::FORBIDDEN, "{} was not blocked", attempt);
+                }
+                other => panic!("{} was not blocked: {:?}", attempt, other),
+            }
+        }
+
+        // Encoded but harmless names still resolve
+        match handler.handle("/assets/hello%20world.txt").await.unwrap() {
+            Some(ZapResponse::Custom(resp)) => assert_eq!(resp.status, StatusCode::OK),
+            other => panic!("expected file, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Streaming return type for exported functions
+//!
+//! An `#[export]` function that returns `impl Stream<Item = T>` or [`ZapStream<T>`]
+//! is registered as a streaming export. Each item is sent to the caller as a
+//! separate Splice `StreamChunk` instead of a single `InvokeResult`.
+
+use futures::stream::{BoxStream, Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Boxed stream of serialized items produced by a streaming export wrapper
+pub type ValueStream = BoxStream<'static, Result<serde_json::Value, String>>;
+
+/// Named stream type for exported functions
+///
+/// Useful when `impl Stream` is inconvenient, e.g. when different branches
+/// return different stream types.
+///
+/// # Example
+/// ```ignore
+/// use zap_server::{export, ZapStream};
+///
+/// #[export]
+/// pub fn countdown(from: u32) -> ZapStream<u32> {
+///     ZapStream::iter((0..=from).rev())
+/// }
+/// ```
+pub struct ZapStream<T> {
+    inner: BoxStream<'static, T>,
+}
+
+impl<T> ZapStream<T> {
+    /// Wrap any sendable stream
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        Self {
+            inner: stream.boxed(),
+        }
+    }
+
+    /// Stream the items of an iterator
+    pub fn iter<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+        T: Send + 'static,
+    {
+        Self::new(futures::stream::iter(items))
+    }
+
+    /// Unwrap into the underlying boxed stream
+    pub fn into_inner(self) -> BoxStream<'static, T> {
+        self.inner
+    }
+}
+
+impl<T> Stream for ZapStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.inner.poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
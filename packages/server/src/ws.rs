@@ -0,0 +1,277 @@
+//! WebSocket broadcast hub
+//!
+//! Tracks connected clients and fans messages out to all of them, or to a
+//! single client by ID. Each client receives hub messages through a
+//! [`HubClient`]; [`BroadcastHub::serve_socket`] pumps them into a WebSocket.
+//!
+//! Both kinds of message are buffered per client, up to the hub's capacity.
+//! A client that falls behind skips its oldest broadcasts, while a direct
+//! send to a full buffer fails so the sender learns it was not delivered.
+//!
+//! ```ignore
+//! use zap_server::ws::BroadcastHub;
+//!
+//! #[export]
+//! pub fn announce(text: String) -> usize {
+//!     BroadcastHub::shared().broadcast(text)
+//! }
+//! ```
+
+use crate::error::{ZapError, ZapResult};
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::{tungstenite::Message as WsMessage, WebSocketStream};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Default number of broadcast and direct messages buffered per client
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Process-wide hub returned by [`BroadcastHub::shared`]
+static SHARED_HUB: OnceLock<BroadcastHub> = OnceLock::new();
+
+/// Pub/sub hub for WebSocket clients
+///
+/// Cloning is cheap; all clones share the same set of clients.
+#[derive(Clone)]
+pub struct BroadcastHub {
+    inner: Arc<HubInner>,
+}
+
+struct HubInner {
+    /// Fan-out channel for messages addressed to every client
+    broadcast_tx: broadcast::Sender<WsMessage>,
+    /// Direct channels for per-client sends (client_id -> sender)
+    clients: Mutex<HashMap<String, mpsc::Sender<WsMessage>>>,
+    /// Direct messages buffered per client
+    capacity: usize,
+}
+
+impl BroadcastHub {
+    /// Create a hub buffering up to `capacity` broadcast messages and
+    /// `capacity` direct messages per client
+    ///
+    /// Clients that fall further behind skip the oldest broadcasts, and
+    /// direct sends to them fail until they catch up.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (broadcast_tx, _) = broadcast::channel(capacity);
+        Self {
+            inner: Arc::new(HubInner {
+                broadcast_tx,
+                clients: Mutex::new(HashMap::new()),
+                capacity,
+            }),
+        }
+    }
+
+    /// Get the process-wide hub shared by handlers and exported functions
+    pub fn shared() -> BroadcastHub {
+        SHARED_HUB.get_or_init(BroadcastHub::default).clone()
+    }
+
+    /// Register a new client
+    pub fn join(&self) -> HubClient {
+        let id = Uuid::new_v4().to_string();
+        let (direct_tx, direct_rx) = mpsc::channel(self.inner.capacity);
+        self.lock_clients().insert(id.clone(), direct_tx);
+        debug!("WebSocket client {} joined hub", id);
+
+        HubClient {
+            id,
+            hub: self.clone(),
+            broadcast_rx: self.inner.broadcast_tx.subscribe(),
+            direct_rx,
+        }
+    }
+
+    /// Send a message to every connected client
+    ///
+    /// Returns the number of clients the message was queued for.
+    pub fn broadcast(&self, message: impl Into<WsMessage>) -> usize {
+        self.inner.broadcast_tx.send(message.into()).unwrap_or(0)
+    }
+
+    /// Send a message to a single client
+    ///
+    /// Fails without waiting if the client's buffer is full; the message is
+    /// dropped rather than letting a slow client hold up the sender.
+    pub fn send(&self, client_id: &str, message: impl Into<WsMessage>) -> ZapResult<()> {
+        let clients = self.lock_clients();
+        let sender = clients
+            .get(client_id)
+            .ok_or_else(|| ZapError::websocket(format!("Client {} not found", client_id)))?;
+        sender.try_send(message.into()).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => {
+                ZapError::websocket(format!("Client {} is not keeping up; message dropped", client_id))
+            }
+            mpsc::error::TrySendError::Closed(_) => {
+                ZapError::websocket(format!("Client {} disconnected", client_id))
+            }
+        })
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.lock_clients().len()
+    }
+
+    /// Forward hub messages to a WebSocket until either side closes
+    ///
+    /// Incoming frames other than `Close` are ignored; pings are answered by
+    /// the WebSocket implementation.
+    pub async fn serve_socket<S>(&self, socket: WebSocketStream<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let mut client = self.join();
+        let (mut sink, mut stream) = socket.split();
+
+        loop {
+            tokio::select! {
+                outgoing = client.recv() => {
+                    let Some(message) = outgoing else { break };
+                    if sink.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                incoming = stream.next() => match incoming {
+                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+
+        debug!("WebSocket client {} left hub", client.id());
+    }
+
+    fn lock_clients(&self) -> std::sync::MutexGuard<'_, HashMap<String, mpsc::Sender<WsMessage>>> {
+        // A panic while holding the lock can't leave the map half-updated
+        self.inner.clients.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for BroadcastHub {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl std::fmt::Debug for BroadcastHub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BroadcastHub")
+            .field("clients", &self.client_count())
+            .finish()
+    }
+}
+
+/// A client's subscription to a [`BroadcastHub`]
+///
+/// Dropping it removes the client from the hub.
+pub struct HubClient {
+    id: String,
+    hub: BroadcastHub,
+    broadcast_rx: broadcast::Receiver<WsMessage>,
+    direct_rx: mpsc::Receiver<WsMessage>,
+}
+
+impl HubClient {
+    /// Unique client ID, usable with [`BroadcastHub::send`]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Wait for the next broadcast or direct message
+    pub async fn recv(&mut self) -> Option<WsMessage> {
+        loop {
+            tokio::select! {
+                direct = self.direct_rx.recv() => return direct,
+                broadcast = self.broadcast_rx.recv() => match broadcast {
+                    Ok(message) => return Some(message),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client {} lagged, skipped {} messages", self.id, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+            }
+        }
+    }
+}
+
+impl Drop for HubClient {
+    fn drop(&mut self) {
+        self.hub.lock_clients().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    #[tokio::test]
+    async fn test_broadcast_and_direct_send() {
+        let hub = BroadcastHub::new(16);
+        let mut alice = hub.join();
+        let mut bob = hub.join();
+        assert_eq!(hub.client_count(), 2);
+
+        assert_eq!(hub.broadcast("hello all"), 2);
+        assert_eq!(alice.recv().await, Some(WsMessage::Text("hello all".into())));
+        assert_eq!(bob.recv().await, Some(WsMessage::Text("hello all".into())));
+
+        hub.send(bob.id(), "just bob").unwrap();
+        assert_eq!(bob.recv().await, Some(WsMessage::Text("just bob".into())));
+
+        let bob_id = bob.id().to_string();
+        drop(bob);
+        assert_eq!(hub.client_count(), 1);
+        assert!(hub.send(&bob_id, "gone").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_direct_send_to_full_buffer_fails() {
+        let hub = BroadcastHub::new(2);
+        let mut client = hub.join();
+
+        hub.send(client.id(), "one").unwrap();
+        hub.send(client.id(), "two").unwrap();
+        let error = hub.send(client.id(), "three").unwrap_err();
+        assert!(error.to_string().contains("not keeping up"), "{}", error);
+
+        // Queued messages are still delivered, and draining makes room again
+        assert_eq!(client.recv().await, Some(WsMessage::Text("one".into())));
+        hub.send(client.id(), "four").unwrap();
+        assert_eq!(client.recv().await, Some(WsMessage::Text("two".into())));
+        assert_eq!(client.recv().await, Some(WsMessage::Text("four".into())));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_connected_sockets() {
+        let hub = BroadcastHub::new(16);
+        let mut clients = Vec::new();
+
+        for _ in 0..2 {
+            let (server_io, client_io) = tokio::io::duplex(4096);
+            let server_ws = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+            let client_ws = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+
+            let hub = hub.clone();
+            tokio::spawn(async move { hub.serve_socket(server_ws).await });
+            clients.push(client_ws);
+        }
+
+        // Wait for both sockets to register with the hub
+        while hub.client_count() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        hub.broadcast("tick");
+        for client in &mut clients {
+            let message = client.next().await.unwrap().unwrap();
+            assert_eq!(message, WsMessage::Text("tick".into()));
+        }
+    }
+}
@@ -1,5 +1,5 @@
 // Integration test for RPC function registry
-use zap_server::export;
+use zap_server::{export, Context};
 use serde_json::json;
 
 // Test sync function
@@ -8,6 +8,12 @@ pub fn get_test_data(id: u64) -> String {
     format!("test_data_{}", id)
 }
 
+// Test context access: the caller's identity from the request's JWT
+#[export]
+pub fn whoami(ctx: &Context) -> String {
+    ctx.user_id().unwrap_or("anonymous").to_string()
+}
+
 // Test async function
 #[export]
 pub async fn async_greet(name: String) -> String {
@@ -148,7 +154,11 @@ async fn test_wrong_parameter_type() {
     );
     assert!(result.is_err(), "wrong parameter type should fail");
     let error_msg = result.unwrap_err();
-    assert!(error_msg.contains("deserialize"), "Error should mention deserialization failure");
+    let violations = zap_server::parse_validation_error(&error_msg)
+        .expect("wrong types should be reported as a validation error");
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].path, "id");
+    assert_eq!(violations[0].message, "expected integer, found string");
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -175,3 +185,110 @@ async fn test_non_object_params() {
     let error_msg = result.unwrap_err();
     assert!(error_msg.contains("must be an object"), "Error should mention params must be object");
 }
+
+async fn read_frame(stream: &mut tokio::net::UnixStream) -> Option<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf).await.ok()?;
+    let mut payload = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+    stream.read_exact(&mut payload).await.ok()?;
+    Some(payload)
+}
+
+async fn write_frame(stream: &mut tokio::net::UnixStream, payload: &[u8]) {
+    use tokio::io::AsyncWriteExt;
+
+    stream.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+    stream.write_all(payload).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_jwt_identity_reaches_exported_function() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use zap_core::{JwtAlgorithm, JwtClaims, JwtMiddleware};
+    use zap_server::{
+        GracefulShutdown, IpcEncoding, IpcMessage, ProxyHandler, RpcCallMessage, RpcResponseMessage,
+        RpcServerHandle, ShutdownConfig, Zap,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let ipc_socket = dir.path().join("zap.sock");
+    let ipc_path = ipc_socket.to_string_lossy().into_owned();
+    let rpc_socket = format!("{}.rpc", ipc_path);
+
+    let rpc = RpcServerHandle::new(ipc_path.clone(), zap_server::build_rpc_dispatcher())
+        .start()
+        .await
+        .unwrap();
+
+    // A TypeScript stand-in whose handler calls `whoami` with the request's identity
+    let listener = tokio::net::UnixListener::bind(&ipc_socket).unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let rpc_socket = rpc_socket.clone();
+            tokio::spawn(async move {
+                while let Some(payload) = read_frame(&mut stream).await {
+                    let Ok(IpcMessage::InvokeHandler { handler_id, request }) = zap_server::ipc::deserialize_message(&payload) else {
+                        return;
+                    };
+
+                    let call = RpcCallMessage {
+                        msg_type: "rpc_call".to_string(),
+                        function_name: "whoami".to_string(),
+                        params: json!({}),
+                        request_id: "rpc_whoami".to_string(),
+                        auth: request.auth,
+                    };
+                    let mut rpc = tokio::net::UnixStream::connect(&rpc_socket).await.unwrap();
+                    write_frame(&mut rpc, &serde_json::to_vec(&call).unwrap()).await;
+                    let reply = read_frame(&mut rpc).await.unwrap();
+                    let reply: RpcResponseMessage = serde_json::from_slice(&reply).unwrap();
+
+                    let response = IpcMessage::HandlerResponse {
+                        handler_id,
+                        status: 200,
+                        headers: Default::default(),
+                        body: reply.result.as_str().unwrap().to_string(),
+                    };
+                    let response = zap_server::ipc::serialize_message(&response, IpcEncoding::MessagePack).unwrap();
+                    write_frame(&mut stream, &response).await;
+                }
+            });
+        }
+    });
+
+    let jwt = JwtMiddleware::new("secret", JwtAlgorithm::HS256);
+    let token = jwt.sign(&JwtClaims {
+        sub: Some("user_42".to_string()),
+        ..Default::default()
+    });
+
+    let bound = Zap::new()
+        .hostname("127.0.0.1")
+        .port(0)
+        .use_middleware(jwt)
+        .get("/me", ProxyHandler::new("handler_0".to_string(), ipc_path))
+        .bind()
+        .await
+        .unwrap();
+    let port = bound.local_addr().port();
+    let shutdown = GracefulShutdown::new(ShutdownConfig::default().without_signal_handlers());
+    let server = tokio::spawn(bound.serve_with_coordinator(shutdown.clone()));
+
+    let mut client = tokio::net::TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!(
+        "GET /me HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\nConnection: close\r\n\r\n",
+        token
+    );
+    client.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    client.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200"), "unexpected response: {}", response);
+    assert!(response.ends_with("user_42"), "unexpected response: {}", response);
+
+    shutdown.trigger();
+    server.await.unwrap().unwrap();
+    rpc.shutdown().await;
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU32, Ordering};
-use zap_server::{export, Context};
+use zap_server::{export, Context, ZapStream};
 
 // Test 1: Simple sync function (no Context)
 #[export]
@@ -88,3 +88,168 @@ static VERSION_COUNTER: AtomicU32 = AtomicU32::new(1);
 pub fn get_version() -> u32 {
     VERSION_COUNTER.load(Ordering::SeqCst)
 }
+
+// Test 10: Function that bails out once the caller's deadline passes
+#[export]
+pub async fn deadline_aware_task(ctx: &Context, steps: u32, step_ms: u64) -> Result<u32, String> {
+    for completed in 0..steps {
+        if ctx.is_cancelled() {
+            return Err(format!("Deadline exceeded after {} steps", completed));
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(step_ms)).await;
+    }
+    Ok(steps)
+}
+
+// Test 11: Optional and defaulted parameters may be omitted by the caller
+#[export]
+pub fn paginate(offset: Option<u32>, #[default = 10] limit: u32) -> Vec<u32> {
+    let start = offset.unwrap_or(0);
+    (start..start + limit).collect()
+}
+
+// Test 12: Streaming function - each item is sent as a separate chunk
+#[export]
+pub fn count_up(to: u32) -> ZapStream<u32> {
+    ZapStream::iter(1..=to)
+}
+
+// Test 13: Streaming function whose items can fail
+#[export]
+pub async fn fail_after(ctx: &Context, ok_items: u32) -> impl futures::Stream<Item = Result<u32, String>> {
+    let trace_id = ctx.trace_id();
+    futures::stream::iter((0..=ok_items).map(move |i| {
+        if i < ok_items {
+            Ok(i)
+        } else {
+            Err(format!("failed after {} items (trace {})", ok_items, trace_id))
+        }
+    }))
+}
+
+// Test 14: Streams each step when the host negotiated streaming, otherwise
+// reports only the final step
+#[export]
+pub fn progress(ctx: &Context, steps: u32) -> ZapStream<u32> {
+    if ctx.supports_streaming() {
+        ZapStream::iter(1..=steps)
+    } else {
+        ZapStream::iter([steps])
+    }
+}
+
+// Test 15: Reports how long the request has been in flight
+#[export]
+pub async fn elapsed_micros(ctx: &Context, sleep_ms: u64) -> u64 {
+    tokio::time::sleep(tokio::time::Duration::from_millis(sleep_ms)).await;
+    ctx.elapsed().as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use zap_server::{find_export, RequestContext};
+
+    fn request_context() -> RequestContext {
+        RequestContext {
+            trace_id: 1,
+            span_id: 2,
+            headers: Vec::new(),
+            auth: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deadline_aware_task_bails_after_deadline() {
+        let ctx = Context::new(request_context()).with_deadline_ms(30);
+        assert!(ctx.deadline().is_some());
+        assert!(!ctx.is_cancelled());
+
+        let result = deadline_aware_task(&ctx, 100, 10).await;
+        assert!(result.unwrap_err().starts_with("Deadline exceeded"));
+        assert!(ctx.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_aware_task_without_deadline() {
+        let ctx = Context::new(request_context());
+        assert!(ctx.deadline().is_none());
+
+        assert_eq!(deadline_aware_task(&ctx, 3, 1).await, Ok(3));
+        assert!(!ctx.is_cancelled());
+    }
+
+    #[test]
+    fn test_paginate_wrapper_fills_missing_params() {
+        let mut params = std::collections::HashMap::new();
+        let all_default = __zap_wrapper_paginate(&params).unwrap();
+        assert_eq!(all_default, serde_json::json!((0..10).collect::<Vec<u32>>()));
+
+        params.insert("offset".to_string(), serde_json::json!(5));
+        params.insert("limit".to_string(), serde_json::json!(2));
+        assert_eq!(__zap_wrapper_paginate(&params).unwrap(), serde_json::json!([5, 6]));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_exports_register_stream_variant() {
+        let func = find_export("count_up").unwrap();
+        assert!(func.wrapper.is_streaming());
+        assert!(!find_export("add_numbers").unwrap().wrapper.is_streaming());
+
+        let mut params = std::collections::HashMap::new();
+        params.insert("to".to_string(), serde_json::json!(3));
+        let items: Vec<_> = func.wrapper.call_stream(None, &params).await.unwrap().collect().await;
+        assert_eq!(items, vec![Ok(serde_json::json!(1)), Ok(serde_json::json!(2)), Ok(serde_json::json!(3))]);
+
+        // The non-streaming call path collects items into an array
+        let collected = func.wrapper.call(None, &params).await.unwrap();
+        assert_eq!(collected, serde_json::json!([1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_item_error() {
+        let func = find_export("fail_after").unwrap();
+        let ctx = Context::new(request_context());
+        let mut params = std::collections::HashMap::new();
+        params.insert("ok_items".to_string(), serde_json::json!(2));
+
+        let items: Vec<_> = func.wrapper.call_stream(Some(&ctx), &params).await.unwrap().collect().await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1], Ok(serde_json::json!(1)));
+        assert!(items[2].as_ref().unwrap_err().starts_with("__TYPED_ERROR__:"));
+
+        assert!(func.wrapper.call_stream(None, &params).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_progress_branches_on_negotiated_streaming() {
+        use splice::protocol::{CAP_CANCELLATION, CAP_STREAMING};
+
+        let func = find_export("progress").unwrap();
+        let mut params = std::collections::HashMap::new();
+        params.insert("steps".to_string(), serde_json::json!(3));
+
+        let streaming = Context::new(request_context()).with_capabilities(CAP_STREAMING | CAP_CANCELLATION);
+        assert!(streaming.supports_streaming());
+        let items: Vec<_> = func.wrapper.call_stream(Some(&streaming), &params).await.unwrap().collect().await;
+        assert_eq!(items, vec![Ok(serde_json::json!(1)), Ok(serde_json::json!(2)), Ok(serde_json::json!(3))]);
+
+        // Without streaming the worker collects the items into one result
+        let single = Context::new(request_context()).with_capabilities(CAP_CANCELLATION);
+        assert!(!single.supports_streaming());
+        assert!(single.supports_cancellation());
+        let collected = func.wrapper.call(Some(&single), &params).await.unwrap();
+        assert_eq!(collected, serde_json::json!([3]));
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_micros_counts_from_receipt() {
+        let received = std::time::Instant::now() - std::time::Duration::from_millis(5);
+        let ctx = Context::new(request_context()).with_received_at(received);
+        assert_eq!(ctx.received_at(), received);
+
+        let elapsed = elapsed_micros(&ctx, 2).await;
+        assert!(elapsed >= 7_000, "elapsed {}us", elapsed);
+    }
+}